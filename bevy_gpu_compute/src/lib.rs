@@ -4,3 +4,7 @@ mod ram_limit;
 mod spawn_fallback_camera;
 mod system_params;
 mod task;
+
+// used by the code that `#[wgsl_library]` and `#[derive(WgslType)]` generate, so that depending on this crate is enough to use them
+#[doc(hidden)]
+pub use bevy_gpu_compute_macro::__resolve_wgsl_libraries;
@@ -1,12 +1,18 @@
 // Proc macros
+pub use bevy_gpu_compute_macro::WgslType;
 pub use bevy_gpu_compute_macro::wgsl_config;
+pub use bevy_gpu_compute_macro::wgsl_entry;
+pub use bevy_gpu_compute_macro::wgsl_extern;
 pub use bevy_gpu_compute_macro::wgsl_input_array;
+pub use bevy_gpu_compute_macro::wgsl_library;
 pub use bevy_gpu_compute_macro::wgsl_output_array;
 pub use bevy_gpu_compute_macro::wgsl_output_vec;
+pub use bevy_gpu_compute_macro::wgsl_raw;
 pub use bevy_gpu_compute_macro::wgsl_shader_module;
 
 //helpers when writing the shader module:
 pub use bevy_gpu_compute_core::MaxOutputLengths;
+pub use bevy_gpu_compute_core::wgsl::wgsl_type_def::WgslTypeDef;
 pub use bevy_gpu_compute_core::wgsl_helpers::*;
 
 pub use crate::plugin::BevyGpuComputePlugin;
@@ -80,15 +80,17 @@ impl GpuTaskRunner<'_, '_> {
                     should_recompute_memory = true;
                 }
                 GpuTaskCommand::Run => {
-                    dispatch_to_gpu(&mut task, &self.render_device, &self.render_queue);
-                    let output_counts =
-                        read_gpu_output_counts(&mut task, &self.render_device, &self.render_queue);
-                    read_gpu_outputs(
-                        output_counts,
-                        &mut task,
-                        &self.render_device,
-                        &self.render_queue,
-                    );
+                    let passes: Vec<String> = task
+                        .configuration()
+                        .shader()
+                        .entry_points()
+                        .iter()
+                        .map(|e| e.name().to_string())
+                        .collect();
+                    run_passes(&mut task, &passes, &self.render_device, &self.render_queue);
+                }
+                GpuTaskCommand::RunPasses(passes) => {
+                    run_passes(&mut task, &passes, &self.render_device, &self.render_queue);
                 }
             }
         }
@@ -98,3 +100,39 @@ impl GpuTaskRunner<'_, '_> {
         }
     }
 }
+
+/// the passes are submitted together, the outputs are read once they have all run. Passes that are not an entry point of the task are skipped
+fn run_passes(
+    task: &mut BevyGpuComputeTask,
+    passes: &[String],
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+) {
+    let shader = task.configuration().shader();
+    let passes: Vec<String> = passes
+        .iter()
+        .filter(|pass| {
+            let known = shader.entry_point(pass).is_some();
+            if !known {
+                log::error!(
+                    "Task {} has no entry point called {}, skipping the pass. Its entry points are {:?}",
+                    task.name(),
+                    pass,
+                    shader
+                        .entry_points()
+                        .iter()
+                        .map(|e| e.name())
+                        .collect::<Vec<_>>()
+                );
+            }
+            known
+        })
+        .cloned()
+        .collect();
+    if passes.is_empty() {
+        return;
+    }
+    dispatch_to_gpu(task, render_device, render_queue, &passes);
+    let output_counts = read_gpu_output_counts(task, render_device, render_queue);
+    read_gpu_outputs(output_counts, task, render_device, render_queue);
+}
@@ -17,7 +17,10 @@ pub enum GpuTaskCommand {
         iteration_space: Option<IterationSpace>,
         max_output_lengths: Option<MaxOutputLengths>,
     },
+    /// every entry point, in declaration order
     Run,
+    /// the given entry points, in this order
+    RunPasses(Vec<String>),
 }
 impl std::fmt::Display for GpuTaskCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -33,6 +36,7 @@ impl std::fmt::Display for GpuTaskCommand {
                 iteration_space, max_output_lengths
             ),
             GpuTaskCommand::Run => write!(f, "Run"),
+            GpuTaskCommand::RunPasses(passes) => write!(f, "RunPasses {:?}", passes),
         }
     }
 }
@@ -73,9 +77,22 @@ impl GpuTaskCommands {
         self
     }
 
-    /// This queues a run of the task. You still MUST call `GpuTaskRunner::run_commands` for this to take effect.
+    /// This queues a run of the task, which runs every entry point (`fn main` and the `#[wgsl_entry]` functions) in declaration order. You still MUST call `GpuTaskRunner::run_commands` for this to take effect.
     pub fn run(mut self) -> Self {
         self.commands.push(GpuTaskCommand::Run);
         self
     }
+
+    /// This queues a run of a single entry point of the task, by its function name. You still MUST call `GpuTaskRunner::run_commands` for this to take effect.
+    pub fn run_pass(self, name: &str) -> Self {
+        self.run_passes(&[name])
+    }
+
+    /// This queues a run of several entry points of the task, one after the other in a single GPU submission, so each pass sees what the previous ones wrote to the buffers. Passes can be repeated. You still MUST call `GpuTaskRunner::run_commands` for this to take effect.
+    pub fn run_passes(mut self, names: &[&str]) -> Self {
+        self.commands.push(GpuTaskCommand::RunPasses(
+            names.iter().map(|name| name.to_string()).collect(),
+        ));
+        self
+    }
 }
@@ -5,12 +5,21 @@ use super::lru_cache::LruCache;
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub struct PipelineKey {
     pub pipeline_consts_version: u64,
+    pub entry_point: String,
 }
 
 #[derive(Component)]
 pub struct PipelineLruCache {
     pub cache: LruCache<PipelineKey, ComputePipeline>,
 }
+impl PipelineLruCache {
+    /// keeps the pipelines of as many pipeline const versions as the default, for every entry point
+    pub fn for_entry_points(count: usize) -> Self {
+        Self {
+            cache: LruCache::new(10 * count.max(1)),
+        }
+    }
+}
 impl Default for PipelineLruCache {
     fn default() -> Self {
         Self {
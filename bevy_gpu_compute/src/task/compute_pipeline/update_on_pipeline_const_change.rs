@@ -10,18 +10,34 @@ pub fn update_compute_pipeline(task: &mut BevyGpuComputeTask, render_device: &Re
     if task.current_data().input_lengths().is_none() {
         return;
     }
-    log::trace!("Updating pipeline for task {}", task.name());
-    let key = PipelineKey {
-        pipeline_consts_version: task.configuration().version(),
-    };
-    if task
-        .runtime_state()
-        .pipeline_cache()
-        .cache
-        .contains_key(&key)
-    {
-    } else {
-        log::trace!("Creating new pipeline for task {}", task.name());
+    log::trace!("Updating pipelines for task {}", task.name());
+    // one pipeline per entry point, they all share the bind group layout of the task
+    let entry_points: Vec<String> = task
+        .configuration()
+        .shader()
+        .entry_points()
+        .iter()
+        .map(|e| e.name().to_string())
+        .collect();
+    let pipeline_consts = task.get_pipeline_consts();
+    for entry_point in entry_points {
+        let key = PipelineKey {
+            pipeline_consts_version: task.configuration().version(),
+            entry_point,
+        };
+        if task
+            .runtime_state()
+            .pipeline_cache()
+            .cache
+            .contains_key(&key)
+        {
+            continue;
+        }
+        log::trace!(
+            "Creating new pipeline for entry point {} of task {}",
+            key.entry_point,
+            task.name()
+        );
         log::trace!(
             "pipeline layout {:?}",
             task.runtime_state().pipeline_layout()
@@ -30,10 +46,10 @@ pub fn update_compute_pipeline(task: &mut BevyGpuComputeTask, render_device: &Re
             label: Some(task.name()),
             layout: Some(task.runtime_state().pipeline_layout()),
             module: task.configuration().shader().shader_module(),
-            entry_point: Some(task.configuration().shader().entry_point_function_name()),
+            entry_point: Some(key.entry_point.as_str()),
             // this is where we specify new values for pipeline constants...
             compilation_options: PipelineCompilationOptions {
-                constants: &task.get_pipeline_consts(),
+                constants: &pipeline_consts,
                 zero_initialize_workgroup_memory: Default::default(),
             },
             cache: None,
@@ -1,30 +1,87 @@
-use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::{
+    log,
+    render::renderer::{RenderDevice, RenderQueue},
+};
+use wgpu::ComputePassDescriptor;
 
-use crate::task::{compute_pipeline::pipeline_cache::PipelineKey, lib::BevyGpuComputeTask};
+use crate::task::{
+    compute_pipeline::pipeline_cache::PipelineKey,
+    lib::BevyGpuComputeTask,
+    task_components::runtime_state::{
+        gpu_workgroup_sizes::GpuWorkgroupSizes, gpu_workgroup_space::GpuWorkgroupSpace,
+    },
+};
+
+/// runs the given entry points one after the other, in a single submission. Each one is its own compute pass, so it sees what the previous ones wrote to the buffers
 pub fn dispatch_to_gpu(
     task: &mut BevyGpuComputeTask,
     render_device: &RenderDevice,
     render_queue: &RenderQueue,
+    passes: &[String],
 ) {
+    let Some(bind_group) = task.runtime_state().bind_group().clone() else {
+        log::error!(
+            "Task {} has no bind group yet, set its inputs before running it",
+            task.name()
+        );
+        return;
+    };
     let mut encoder = render_device.create_command_encoder(&Default::default());
-    {
-        let mut compute_pass = encoder.begin_compute_pass(&Default::default());
+    for pass in passes {
+        let workgroup_space = workgroup_space(task, pass);
         let key = PipelineKey {
             pipeline_consts_version: task.configuration().version(),
+            entry_point: pass.clone(),
         };
-        compute_pass.set_pipeline(
-            task.runtime_state_mut()
-                .pipeline_cache_mut()
-                .cache
-                .get(&key)
-                .unwrap(),
-        );
-        compute_pass.set_bind_group(0, task.runtime_state().bind_group().as_ref().unwrap(), &[]);
+        let Some(pipeline) = task
+            .runtime_state_mut()
+            .pipeline_cache_mut()
+            .cache
+            .get(&key)
+            .cloned()
+        else {
+            log::error!(
+                "Task {} has no pipeline for the entry point {}, set its inputs before running it. Skipping the pass",
+                task.name(),
+                pass
+            );
+            continue;
+        };
+        let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some(pass.as_str()),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
         compute_pass.dispatch_workgroups(
-            task.runtime_state().workgroup_space().x(),
-            task.runtime_state().workgroup_space().y(),
-            task.runtime_state().workgroup_space().z(),
+            workgroup_space.x(),
+            workgroup_space.y(),
+            workgroup_space.z(),
         );
     }
     render_queue.submit(std::iter::once(encoder.finish()));
 }
+
+/// entry points with their own workgroup size need a different number of workgroups to cover the iteration space
+fn workgroup_space(task: &BevyGpuComputeTask, pass: &str) -> GpuWorkgroupSpace {
+    let entry_point = task
+        .configuration()
+        .shader()
+        .entry_point(pass)
+        .unwrap_or_else(|| panic!("Task {} has no entry point called {}", task.name(), pass));
+    match entry_point.workgroup_size() {
+        Some([x, y, z]) => {
+            let iteration_space = task.configuration().iteration_space();
+            GpuWorkgroupSpace::from_iter_space_and_wrkgrp_sizes(
+                iteration_space,
+                &GpuWorkgroupSizes::custom_use_at_own_risk(
+                    x as usize,
+                    y as usize,
+                    z as usize,
+                    iteration_space.num_dimmensions().to_usize(),
+                ),
+            )
+        }
+        None => task.runtime_state().workgroup_space().clone(),
+    }
+}
@@ -12,8 +12,8 @@ use bevy_gpu_compute_core::{
 use super::task_components::{
     buffers::TaskBuffers,
     configuration::{
-        input_spec::InputSpec, iteration_space::IterationSpace, lib::TaskConfiguration,
-        output_spec::OutputSpec, wgsl_code::WgslCode,
+        entry_point::EntryPoint, input_spec::InputSpec, iteration_space::IterationSpace,
+        lib::TaskConfiguration, output_spec::OutputSpec, wgsl_code::WgslCode,
     },
     data::TaskData,
     runtime_state::{
@@ -86,7 +86,12 @@ impl BevyGpuComputeTask {
                 name,
                 render_device,
                 full_module.wgsl_code(iteration_space.num_dimmensions()),
-                "main".to_string(),
+                full_module
+                    .user_portion
+                    .entry_points
+                    .iter()
+                    .map(EntryPoint::from)
+                    .collect(),
             ),
         )
     }
@@ -0,0 +1,38 @@
+use bevy_gpu_compute_core::wgsl::shader_sections::WgslEntryPoint;
+
+/// a compute shader function of the task, each one is run as its own pass over the task's buffers
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryPoint {
+    name: String,
+    /// `None` uses the default workgroup size for the dimensions of the iteration space
+    workgroup_size: Option<[u32; 3]>,
+}
+
+impl EntryPoint {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            workgroup_size: None,
+        }
+    }
+    /// must match the `@workgroup_size` of the function in the shader
+    pub fn with_workgroup_size(mut self, workgroup_size: [u32; 3]) -> Self {
+        self.workgroup_size = Some(workgroup_size);
+        self
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn workgroup_size(&self) -> Option<[u32; 3]> {
+        self.workgroup_size
+    }
+}
+
+impl From<&WgslEntryPoint> for EntryPoint {
+    fn from(entry_point: &WgslEntryPoint) -> Self {
+        Self {
+            name: entry_point.name().to_string(),
+            workgroup_size: entry_point.workgroup_size,
+        }
+    }
+}
@@ -25,6 +25,21 @@ impl TaskConfiguration {
         inputs: InputSpec,
         outputs: OutputSpec,
     ) -> Self {
+        let num_dimmensions = iteration_space.num_dimmensions().to_usize();
+        for entry_point in shader.entry_points() {
+            if let Some(workgroup_size) = entry_point.workgroup_size() {
+                // a workgroup larger than 1 along a dimmension the iteration space does not have would run its invocations on the same positions
+                assert!(
+                    workgroup_size[num_dimmensions..]
+                        .iter()
+                        .all(|size| *size == 1),
+                    "The entry point {} has a workgroup size of {:?}, but the iteration space of the task only has {} dimmension(s), so the workgroup size has to be 1 in the other dimmensions. For example a workgroup size of (8, 8) needs an iteration space with x and y.",
+                    entry_point.name(),
+                    workgroup_size,
+                    num_dimmensions
+                );
+            }
+        }
         TaskConfiguration {
             shader,
             iteration_space,
@@ -1,3 +1,4 @@
+pub mod entry_point;
 pub mod input_spec;
 pub mod iteration_space;
 pub mod lib;
@@ -1,17 +1,21 @@
 use bevy::render::renderer::RenderDevice;
+use bevy_gpu_compute_core::wgsl::shader_module::source_map::WgslSourceMap;
 use wgpu::{ShaderModule, ShaderModuleDescriptor, ShaderSource};
 
+use super::entry_point::EntryPoint;
+
 #[derive(Debug)]
 pub struct WgslCode {
     code: String,
-    entry_point_function_name: String,
+    /// in the order they are run by `GpuTaskCommands::run`
+    entry_points: Vec<EntryPoint>,
     shader_module: Option<ShaderModule>,
 }
 impl Default for WgslCode {
     fn default() -> Self {
         Self {
             code: "".to_string(),
-            entry_point_function_name: "".to_string(),
+            entry_points: Vec::new(),
             shader_module: None,
         }
     }
@@ -22,11 +26,11 @@ impl WgslCode {
         label: &str,
         render_device: &RenderDevice,
         wgsl_code: String,
-        entry_point_function_name: String,
+        entry_points: Vec<EntryPoint>,
     ) -> Self {
         Self {
             code: wgsl_code.clone(),
-            entry_point_function_name,
+            entry_points,
             shader_module: Some(render_device.create_shader_module(ShaderModuleDescriptor {
                 label: Some(label),
                 source: ShaderSource::Wgsl(wgsl_code.into()),
@@ -37,16 +41,23 @@ impl WgslCode {
         label: &str,
         render_device: &RenderDevice,
         file_path: &str,
-        entry_point_function_name: String,
+        entry_points: Vec<EntryPoint>,
     ) -> Self {
         let code = std::fs::read_to_string(file_path).unwrap();
-        Self::from_string(label, render_device, code, entry_point_function_name)
+        Self::from_string(label, render_device, code, entry_points)
     }
     pub fn code(&self) -> &str {
         &self.code
     }
-    pub fn entry_point_function_name(&self) -> &str {
-        &self.entry_point_function_name
+    /// maps the line numbers of shader errors back to the rust source of the shader module
+    pub fn source_map(&self) -> WgslSourceMap {
+        WgslSourceMap::from_wgsl(&self.code)
+    }
+    pub fn entry_points(&self) -> &[EntryPoint] {
+        &self.entry_points
+    }
+    pub fn entry_point(&self, name: &str) -> Option<&EntryPoint> {
+        self.entry_points.iter().find(|e| e.name() == name)
     }
     pub fn shader_module(&self) -> &ShaderModule {
         assert!(
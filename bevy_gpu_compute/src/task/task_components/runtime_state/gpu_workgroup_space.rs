@@ -5,7 +5,7 @@ use super::gpu_workgroup_sizes::GpuWorkgroupSizes;
 /**
  * Dependent on IterationSpace and WorkgroupSizes
  */
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GpuWorkgroupSpace {
     x: u32,
     y: u32,
@@ -114,7 +114,9 @@ impl<'a> TaskRuntimeStateBuilder<'a> {
             self.task_configuration.outputs().max_lengths(),
             self.task_configuration.outputs().arrays(),
         );
-        let pipeline_cache = PipelineLruCache::default();
+        let pipeline_cache = PipelineLruCache::for_entry_points(
+            self.task_configuration.shader().entry_points().len(),
+        );
         let bind_group = None;
         let (bind_group_layout, pipeline_layout) = self.setup_static_runtime_state();
         TaskRuntimeState::new(
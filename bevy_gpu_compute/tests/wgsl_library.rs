@@ -0,0 +1,55 @@
+// only uses `bevy_gpu_compute`, a crate depending on nothing else has to be able to import libraries and shared types
+use bevy_gpu_compute::prelude::*;
+
+#[wgsl_library]
+pub mod shared_math {
+    use bevy_gpu_compute::prelude::*;
+    pub fn doubled(v: Vec2F32) -> Vec2F32 {
+        v * 2.0
+    }
+}
+
+#[derive(WgslType, Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct Particle {
+    pub position: Vec2F32,
+    pub radius: f32,
+    pub _padding: f32,
+}
+
+#[wgsl_shader_module]
+pub mod mover {
+    #[wgsl_input_array]
+    use crate::Particle;
+    use crate::shared_math::*;
+    use bevy_gpu_compute::prelude::*;
+    #[wgsl_output_array]
+    pub struct Moved {
+        pub position: Vec2F32,
+    }
+    fn main(iter_pos: WgslIterationPosition) {
+        let p = WgslVecInput::vec_val::<Particle>(iter_pos.x);
+        WgslOutput::set::<Moved>(
+            iter_pos.x,
+            Moved {
+                position: doubled(p.position),
+            },
+        );
+    }
+}
+
+#[test]
+fn test_library_and_type_imported_through_the_facade() {
+    let parsed = mover::parsed();
+    assert_eq!(parsed.input_arrays[0].item_type, Particle::wgsl_type());
+    assert!(
+        parsed
+            .helper_functions
+            .iter()
+            .any(|f| f.code.wgsl_code.contains("fn doubled"))
+    );
+    let moved = mover::Moved {
+        position: shared_math::doubled(Vec2F32::new(1.0, 2.0)),
+    };
+    assert_eq!(moved.position.y, 4.0);
+}
@@ -9,3 +9,6 @@ pub use rust::*;
 pub mod wgsl_helpers {
     pub use super::wgsl::user_facing_api::*;
 }
+pub mod wgsl_builtins {
+    pub use super::wgsl::user_facing_api::builtins::*;
+}
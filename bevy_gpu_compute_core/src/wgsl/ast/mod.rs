@@ -0,0 +1,309 @@
+/**
+ A typed representation of the WGSL that the shader module macro generates.
+
+ The macro lowers the rust code of the module into these nodes instead of building strings, and the `Display` implementations in `print.rs` are the only place where WGSL text is produced.
+ So every construct is always written the same way: one statement per line, four spaces of indentation, and parenthesis only where WGSL needs them.
+ Declarations and statements that know where they came from in the rust source end their first line with a `// src/foo.rs:LINE` comment, which `WgslSourceMap` reads back to report shader errors against the rust code.
+
+ Only the subset of WGSL that the macro generates is covered.
+*/
+mod print;
+
+/// a module level declaration
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Fn(Function),
+    Struct(Struct),
+    Alias(Alias),
+    Const(Const),
+}
+
+/// `@name` or `@name(arg, ...)`, like `@builtin(global_invocation_id)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    pub arguments: Vec<String>,
+}
+
+/// where a declaration or statement was written in the rust source, printed as `file:line`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// relative to the root of the crate that contains the shader module, like `src/shader.rs`
+    pub file: String,
+    /// starts at 1
+    pub line: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Function {
+    /// doc comments, printed as `//` comments before the declaration
+    pub docs: Vec<String>,
+    pub location: Option<SourceLocation>,
+    pub attributes: Vec<Attribute>,
+    pub name: String,
+    pub params: Vec<Param>,
+    pub return_type: Option<Type>,
+    pub body: Block,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    pub attributes: Vec<Attribute>,
+    pub name: String,
+    pub ty: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Struct {
+    pub docs: Vec<String>,
+    pub location: Option<SourceLocation>,
+    pub name: String,
+    pub members: Vec<Member>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    pub docs: Vec<String>,
+    pub name: String,
+    pub ty: Type,
+}
+
+/// `alias name = ty;`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alias {
+    pub docs: Vec<String>,
+    pub location: Option<SourceLocation>,
+    pub name: String,
+    pub ty: Type,
+}
+
+/// a `const`, at module level or inside of a function
+#[derive(Clone, Debug, PartialEq)]
+pub struct Const {
+    pub docs: Vec<String>,
+    pub location: Option<SourceLocation>,
+    pub name: String,
+    pub ty: Option<Type>,
+    pub value: Expr,
+}
+
+/// A name with optional template arguments, like `f32`, `vec3<f32>` or `array<u32, 4>`.
+/// Also used for the callee of a call, since WGSL calls functions and type constructors the same way
+#[derive(Clone, Debug, PartialEq)]
+pub struct Type {
+    pub name: String,
+    pub template_args: Vec<TemplateArg>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TemplateArg {
+    Type(Type),
+    /// like the length of an array
+    Expr(Expr),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// already written in WGSL syntax, like `1u`, `0.5` or `true`
+    Literal(String),
+    Ident(String),
+    Call {
+        callee: Type,
+        args: Vec<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Field {
+        base: Box<Expr>,
+        member: String,
+    },
+    Index {
+        base: Box<Expr>,
+        index: Box<Expr>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    Deref,
+    AddressOf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stmt {
+    Let {
+        name: String,
+        ty: Option<Type>,
+        value: Expr,
+    },
+    Var {
+        name: String,
+        ty: Option<Type>,
+        value: Option<Expr>,
+    },
+    Const(Const),
+    /// `target = value`, or `target op= value` for compound assignments. A target of `_` discards the value
+    Assign {
+        target: Expr,
+        op: Option<BinaryOp>,
+        value: Expr,
+    },
+    /// a function call whose result is not used
+    Expr(Expr),
+    Block(Block),
+    If(If),
+    Loop(Block),
+    While {
+        condition: Expr,
+        body: Block,
+    },
+    /// `for (init; condition; update) body`
+    For {
+        init: Option<Box<Stmt>>,
+        condition: Option<Expr>,
+        update: Option<Box<Stmt>>,
+        body: Block,
+    },
+    Switch {
+        selector: Expr,
+        clauses: Vec<SwitchClause>,
+    },
+    Break,
+    /// `break if condition;`, only allowed as the last statement of a `continuing` block
+    BreakIf(Expr),
+    Continue,
+    /// the `continuing` block of a `loop`, which `continue` jumps to. Must be the last statement of the loop body
+    Continuing(Block),
+    Return(Option<Expr>),
+    /// a comment on its own line, each line of the text becomes a `//` line
+    Comment(String),
+    /// hand-written WGSL, like the body of a `wgsl_raw!` function, printed as it is with only the indentation of its lines changed to that of the block
+    Raw(String),
+    /// a statement followed by the rust source location it was lowered from
+    Located {
+        location: SourceLocation,
+        stmt: Box<Stmt>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct If {
+    pub condition: Expr,
+    pub then_branch: Block,
+    pub else_branch: Option<Else>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Else {
+    If(Box<If>),
+    Block(Block),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwitchClause {
+    pub selectors: Vec<CaseSelector>,
+    pub body: Block,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CaseSelector {
+    Expr(Expr),
+    Default,
+}
+
+impl Type {
+    pub fn named(name: impl Into<String>) -> Self {
+        Type {
+            name: name.into(),
+            template_args: Vec::new(),
+        }
+    }
+}
+
+impl Attribute {
+    pub fn new(name: impl Into<String>, arguments: Vec<String>) -> Self {
+        Attribute {
+            name: name.into(),
+            arguments,
+        }
+    }
+}
+
+impl Expr {
+    pub fn ident(name: impl Into<String>) -> Self {
+        Expr::Ident(name.into())
+    }
+    pub fn literal(value: impl Into<String>) -> Self {
+        Expr::Literal(value.into())
+    }
+    pub fn call(callee: Type, args: Vec<Expr>) -> Self {
+        Expr::Call { callee, args }
+    }
+    pub fn unary(op: UnaryOp, operand: Expr) -> Self {
+        Expr::Unary {
+            op,
+            operand: Box::new(operand),
+        }
+    }
+    pub fn binary(op: BinaryOp, left: Expr, right: Expr) -> Self {
+        Expr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+    pub fn field(base: Expr, member: impl Into<String>) -> Self {
+        Expr::Field {
+            base: Box::new(base),
+            member: member.into(),
+        }
+    }
+    pub fn index(base: Expr, index: Expr) -> Self {
+        Expr::Index {
+            base: Box::new(base),
+            index: Box::new(index),
+        }
+    }
+}
+
+impl Block {
+    pub fn new(stmts: Vec<Stmt>) -> Self {
+        Block { stmts }
+    }
+}
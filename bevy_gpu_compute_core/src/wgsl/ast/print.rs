@@ -0,0 +1,894 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{
+    Alias, Attribute, BinaryOp, Block, CaseSelector, Const, Else, Expr, Function, If, Item, Member,
+    Param, SourceLocation, Stmt, Struct, SwitchClause, TemplateArg, Type, UnaryOp,
+};
+
+const INDENT: &str = "    ";
+
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Fn(function) => function.fmt(f),
+            Item::Struct(s) => s.fmt(f),
+            Item::Alias(alias) => alias.fmt(f),
+            Item::Const(c) => c.fmt(f),
+        }
+    }
+}
+
+impl Display for Attribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", self.name)?;
+        if !self.arguments.is_empty() {
+            write!(f, "({})", self.arguments.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_docs(f, &self.docs, 0)?;
+        if !self.attributes.is_empty() {
+            write_separated(f, &self.attributes, " ")?;
+            f.write_char('\n')?;
+        }
+        let declaration = Render(|f| {
+            write!(f, "fn {}(", self.name)?;
+            write_separated(f, &self.params, ", ")?;
+            f.write_char(')')?;
+            if let Some(return_type) = &self.return_type {
+                write!(f, " -> {}", return_type)?;
+            }
+            f.write_char(' ')?;
+            write_block(f, &self.body, 0)
+        });
+        write_located(f, self.location.as_ref(), declaration)
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for attribute in self.attributes.iter() {
+            write!(f, "{} ", attribute)?;
+        }
+        write!(f, "{}: {}", self.name, self.ty)
+    }
+}
+
+impl Display for Struct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_docs(f, &self.docs, 0)?;
+        let declaration = Render(|f| {
+            write!(f, "struct {} {{", self.name)?;
+            if self.members.is_empty() {
+                return f.write_char('}');
+            }
+            f.write_char('\n')?;
+            for member in self.members.iter() {
+                write_docs(f, &member.docs, 1)?;
+                writeln!(f, "{}{},", INDENT, member)?;
+            }
+            f.write_char('}')
+        });
+        write_located(f, self.location.as_ref(), declaration)
+    }
+}
+
+/// without its doc comments, which are written by the struct
+impl Display for Member {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.ty)
+    }
+}
+
+impl Display for Alias {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_docs(f, &self.docs, 0)?;
+        let declaration = Render(|f| write!(f, "alias {} = {};", self.name, self.ty));
+        write_located(f, self.location.as_ref(), declaration)
+    }
+}
+
+impl Display for Const {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_docs(f, &self.docs, 0)?;
+        let declaration = Render(|f| {
+            write!(f, "const {}", self.name)?;
+            if let Some(ty) = &self.ty {
+                write!(f, ": {}", ty)?;
+            }
+            write!(f, " = {};", self.value)
+        });
+        write_located(f, self.location.as_ref(), declaration)
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if !self.template_args.is_empty() {
+            f.write_char('<')?;
+            write_separated(f, &self.template_args, ", ")?;
+            f.write_char('>')?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TemplateArg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateArg::Type(t) => t.fmt(f),
+            TemplateArg::Expr(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_block(f, self, 0)
+    }
+}
+
+impl Display for Stmt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_stmt(f, self, 0)
+    }
+}
+
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Not => "!",
+            UnaryOp::Deref => "*",
+            UnaryOp::AddressOf => "&",
+        })
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+        })
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(value) => f.write_str(value),
+            Expr::Ident(name) => f.write_str(name),
+            Expr::Call { callee, args } => {
+                write!(f, "{}(", callee)?;
+                write_separated(f, args, ", ")?;
+                f.write_char(')')
+            }
+            Expr::Unary { op, operand } => {
+                // `- -x` must not become the decrement token `--`
+                let parenthesize = matches!(**operand, Expr::Binary { .. })
+                    || matches!(**operand, Expr::Unary { op: inner, .. } if inner == *op);
+                write!(f, "{}", op)?;
+                write_operand(f, operand, parenthesize)
+            }
+            Expr::Binary { op, left, right } => {
+                write_operand(f, left, operand_needs_parens(*op, left, false))?;
+                write!(f, " {} ", op)?;
+                write_operand(f, right, operand_needs_parens(*op, right, true))
+            }
+            Expr::Field { base, member } => {
+                write_operand(f, base, is_operator(base))?;
+                write!(f, ".{}", member)
+            }
+            Expr::Index { base, index } => {
+                write_operand(f, base, is_operator(base))?;
+                write!(f, "[{}]", index)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OpClass {
+    Multiplicative,
+    Additive,
+    Shift,
+    Relational,
+    Bitwise,
+    ShortCircuit,
+}
+
+impl BinaryOp {
+    fn class(self) -> OpClass {
+        match self {
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => OpClass::Multiplicative,
+            BinaryOp::Add | BinaryOp::Sub => OpClass::Additive,
+            BinaryOp::Shl | BinaryOp::Shr => OpClass::Shift,
+            BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => OpClass::Relational,
+            BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor => OpClass::Bitwise,
+            BinaryOp::And | BinaryOp::Or => OpClass::ShortCircuit,
+        }
+    }
+}
+
+/// WGSL is stricter than rust: shifts and bitwise operators cannot be mixed with other operators, and comparisons cannot be chained, without parenthesis
+fn operand_needs_parens(parent: BinaryOp, operand: &Expr, is_right: bool) -> bool {
+    let Expr::Binary { op, .. } = operand else {
+        return false;
+    };
+    let op = *op;
+    match parent.class() {
+        OpClass::Multiplicative => op.class() != OpClass::Multiplicative || is_right,
+        OpClass::Additive => match op.class() {
+            OpClass::Multiplicative => false,
+            OpClass::Additive => is_right,
+            _ => true,
+        },
+        OpClass::Shift => true,
+        OpClass::Relational => !matches!(
+            op.class(),
+            OpClass::Multiplicative | OpClass::Additive | OpClass::Shift
+        ),
+        OpClass::Bitwise => op != parent || is_right,
+        OpClass::ShortCircuit => match op.class() {
+            OpClass::ShortCircuit => op != parent || is_right,
+            OpClass::Bitwise => true,
+            _ => false,
+        },
+    }
+}
+
+fn is_operator(expr: &Expr) -> bool {
+    matches!(expr, Expr::Unary { .. } | Expr::Binary { .. })
+}
+
+fn write_operand(f: &mut Formatter<'_>, operand: &Expr, parenthesize: bool) -> fmt::Result {
+    if parenthesize {
+        write!(f, "({})", operand)
+    } else {
+        write!(f, "{}", operand)
+    }
+}
+
+fn write_separated<T: Display>(f: &mut Formatter<'_>, items: &[T], separator: &str) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// lets a part of the output be rendered to a string first
+struct Render<F: Fn(&mut Formatter<'_>) -> fmt::Result>(F);
+
+impl<F: Fn(&mut Formatter<'_>) -> fmt::Result> Display for Render<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
+    }
+}
+
+/// the location goes at the end of the first line, so that it stays next to the start of a multi line declaration or statement
+fn write_located(
+    f: &mut Formatter<'_>,
+    location: Option<&SourceLocation>,
+    content: impl Display,
+) -> fmt::Result {
+    let Some(location) = location else {
+        return write!(f, "{}", content);
+    };
+    let content = content.to_string();
+    match content.split_once('\n') {
+        Some((first_line, rest)) => write!(f, "{} // {}\n{}", first_line, location, rest),
+        None => write!(f, "{} // {}", content, location),
+    }
+}
+
+/// every line of the docs becomes a `//` line before the declaration
+fn write_docs(f: &mut Formatter<'_>, docs: &[String], indent: usize) -> fmt::Result {
+    for doc in docs.iter() {
+        write_indent(f, indent)?;
+        write_comment_lines(f, doc, indent, true)?;
+    }
+    Ok(())
+}
+
+/// the caller already wrote the indentation of the first line
+fn write_raw_lines(f: &mut Formatter<'_>, code: &str, indent: usize) -> fmt::Result {
+    let mut lines: Vec<&str> = code.lines().map(str::trim_end).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let first_line = lines.iter().position(|line| !line.is_empty()).unwrap_or(0);
+    let lines = &lines[first_line..];
+    // the indentation that all lines share, which comes from where the code was written in the rust source
+    let common_indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            f.write_char('\n')?;
+            if !line.is_empty() {
+                write_indent(f, indent)?;
+            }
+        }
+        f.write_str(line.get(common_indent..).unwrap_or(line.trim_start()))?;
+    }
+    Ok(())
+}
+
+fn write_comment_lines(
+    f: &mut Formatter<'_>,
+    text: &str,
+    indent: usize,
+    trailing_newline: bool,
+) -> fmt::Result {
+    // block doc comments start and end with an empty line
+    let lines: Vec<&str> = text
+        .trim_matches(|c| c == '\n' || c == '\r')
+        .lines()
+        .map(str::trim_end)
+        .collect();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            f.write_char('\n')?;
+            write_indent(f, indent)?;
+        }
+        match line.chars().next() {
+            None => f.write_str("//")?,
+            Some(c) if c.is_whitespace() => write!(f, "//{}", line)?,
+            Some(_) => write!(f, "// {}", line)?,
+        }
+    }
+    if trailing_newline {
+        f.write_char('\n')?;
+    }
+    Ok(())
+}
+
+fn write_indent(f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+    for _ in 0..indent {
+        f.write_str(INDENT)?;
+    }
+    Ok(())
+}
+
+fn write_block(f: &mut Formatter<'_>, block: &Block, indent: usize) -> fmt::Result {
+    if block.stmts.is_empty() {
+        return f.write_str("{}");
+    }
+    f.write_str("{\n")?;
+    for stmt in block.stmts.iter() {
+        write_indent(f, indent + 1)?;
+        write_stmt(f, stmt, indent + 1)?;
+        f.write_char('\n')?;
+    }
+    write_indent(f, indent)?;
+    f.write_char('}')
+}
+
+fn write_stmt(f: &mut Formatter<'_>, stmt: &Stmt, indent: usize) -> fmt::Result {
+    match stmt {
+        Stmt::Let { .. } | Stmt::Var { .. } | Stmt::Assign { .. } | Stmt::Expr(_) => {
+            write_simple_stmt(f, stmt)?;
+            f.write_char(';')
+        }
+        Stmt::Const(c) => c.fmt(f),
+        Stmt::Block(block) => write_block(f, block, indent),
+        Stmt::If(if_stmt) => write_if(f, if_stmt, indent),
+        Stmt::Loop(body) => {
+            f.write_str("loop ")?;
+            write_block(f, body, indent)
+        }
+        Stmt::While { condition, body } => {
+            write!(f, "while {} ", condition)?;
+            write_block(f, body, indent)
+        }
+        Stmt::For {
+            init,
+            condition,
+            update,
+            body,
+        } => {
+            f.write_str("for (")?;
+            if let Some(init) = init {
+                write_simple_stmt(f, init)?;
+            }
+            f.write_str("; ")?;
+            if let Some(condition) = condition {
+                write!(f, "{}", condition)?;
+            }
+            f.write_str("; ")?;
+            if let Some(update) = update {
+                write_simple_stmt(f, update)?;
+            }
+            f.write_str(") ")?;
+            write_block(f, body, indent)
+        }
+        Stmt::Switch { selector, clauses } => {
+            writeln!(f, "switch {} {{", selector)?;
+            for clause in clauses.iter() {
+                write_indent(f, indent + 1)?;
+                write_switch_clause(f, clause, indent + 1)?;
+                f.write_char('\n')?;
+            }
+            write_indent(f, indent)?;
+            f.write_char('}')
+        }
+        Stmt::Break => f.write_str("break;"),
+        Stmt::BreakIf(condition) => write!(f, "break if {};", condition),
+        Stmt::Continue => f.write_str("continue;"),
+        Stmt::Continuing(block) => {
+            f.write_str("continuing ")?;
+            write_block(f, block, indent)
+        }
+        Stmt::Return(None) => f.write_str("return;"),
+        Stmt::Return(Some(value)) => write!(f, "return {};", value),
+        Stmt::Comment(text) => write_comment_lines(f, text, indent, false),
+        Stmt::Raw(code) => write_raw_lines(f, code, indent),
+        Stmt::Located { location, stmt } => {
+            write_located(f, Some(location), Render(|f| write_stmt(f, stmt, indent)))
+        }
+    }
+}
+
+/// the statements that can also be used in the header of a `for` loop, without the `;`
+fn write_simple_stmt(f: &mut Formatter<'_>, stmt: &Stmt) -> fmt::Result {
+    match stmt {
+        Stmt::Let { name, ty, value } => {
+            write!(f, "let {}", name)?;
+            if let Some(ty) = ty {
+                write!(f, ": {}", ty)?;
+            }
+            write!(f, " = {}", value)
+        }
+        Stmt::Var { name, ty, value } => {
+            write!(f, "var {}", name)?;
+            if let Some(ty) = ty {
+                write!(f, ": {}", ty)?;
+            }
+            if let Some(value) = value {
+                write!(f, " = {}", value)?;
+            }
+            Ok(())
+        }
+        Stmt::Assign { target, op, value } => match op {
+            Some(op) => write!(f, "{} {}= {}", target, op, value),
+            None => write!(f, "{} = {}", target, value),
+        },
+        Stmt::Expr(expr) => write!(f, "{}", expr),
+        _ => write_stmt(f, stmt, 0),
+    }
+}
+
+fn write_if(f: &mut Formatter<'_>, if_stmt: &If, indent: usize) -> fmt::Result {
+    write!(f, "if {} ", if_stmt.condition)?;
+    write_block(f, &if_stmt.then_branch, indent)?;
+    match &if_stmt.else_branch {
+        Some(Else::If(else_if)) => {
+            f.write_str(" else ")?;
+            write_if(f, else_if, indent)
+        }
+        Some(Else::Block(block)) => {
+            f.write_str(" else ")?;
+            write_block(f, block, indent)
+        }
+        None => Ok(()),
+    }
+}
+
+fn write_switch_clause(f: &mut Formatter<'_>, clause: &SwitchClause, indent: usize) -> fmt::Result {
+    if let [CaseSelector::Default] = clause.selectors.as_slice() {
+        f.write_str("default: ")?;
+    } else {
+        f.write_str("case ")?;
+        for (i, selector) in clause.selectors.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match selector {
+                CaseSelector::Expr(e) => write!(f, "{}", e)?,
+                CaseSelector::Default => f.write_str("default")?,
+            }
+        }
+        f.write_str(": ")?;
+    }
+    write_block(f, &clause.body, indent)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn ident(name: &str) -> Expr {
+        Expr::ident(name)
+    }
+
+    fn literal(value: &str) -> Expr {
+        Expr::literal(value)
+    }
+
+    #[test]
+    fn test_function() {
+        let function = Function {
+            docs: vec![],
+            location: None,
+            attributes: vec![],
+            name: "main".to_string(),
+            params: vec![Param {
+                attributes: vec![Attribute::new(
+                    "builtin",
+                    vec!["global_invocation_id".to_string()],
+                )],
+                name: "iter_pos".to_string(),
+                ty: Type {
+                    name: "vec3".to_string(),
+                    template_args: vec![TemplateArg::Type(Type::named("u32"))],
+                },
+            }],
+            return_type: None,
+            body: Block::new(vec![
+                Stmt::Var {
+                    name: "total".to_string(),
+                    ty: Some(Type::named("u32")),
+                    value: Some(literal("0u")),
+                },
+                Stmt::For {
+                    init: Some(Box::new(Stmt::Var {
+                        name: "i".to_string(),
+                        ty: Some(Type::named("u32")),
+                        value: Some(literal("0")),
+                    })),
+                    condition: Some(Expr::binary(BinaryOp::Lt, ident("i"), literal("4"))),
+                    update: Some(Box::new(Stmt::Assign {
+                        target: ident("i"),
+                        op: Some(BinaryOp::Add),
+                        value: literal("1u"),
+                    })),
+                    body: Block::new(vec![Stmt::Assign {
+                        target: ident("total"),
+                        op: Some(BinaryOp::Add),
+                        value: Expr::field(ident("iter_pos"), "x"),
+                    }]),
+                },
+                Stmt::Return(None),
+            ]),
+        };
+        assert_eq!(
+            Item::Fn(function).to_string(),
+            "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) {
+    var total: u32 = 0u;
+    for (var i: u32 = 0; i < 4; i += 1u) {
+        total += iter_pos.x;
+    }
+    return;
+}"
+        );
+    }
+
+    #[test]
+    fn test_declarations() {
+        let array = Type {
+            name: "array".to_string(),
+            template_args: vec![
+                TemplateArg::Type(Type::named("f32")),
+                TemplateArg::Expr(literal("2")),
+            ],
+        };
+        let items = [
+            Item::Struct(Struct {
+                docs: vec![],
+                location: None,
+                name: "Particle".to_string(),
+                members: vec![
+                    Member {
+                        docs: vec![],
+                        name: "position".to_string(),
+                        ty: array.clone(),
+                    },
+                    Member {
+                        docs: vec![],
+                        name: "mass".to_string(),
+                        ty: Type::named("f32"),
+                    },
+                ],
+            }),
+            Item::Alias(Alias {
+                docs: vec![],
+                location: None,
+                name: "Position".to_string(),
+                ty: array.clone(),
+            }),
+            Item::Const(Const {
+                docs: vec![],
+                location: None,
+                name: "ORIGIN".to_string(),
+                ty: Some(array.clone()),
+                value: Expr::call(array, vec![]),
+            }),
+        ];
+        let printed: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            printed.join("\n"),
+            "struct Particle {
+    position: array<f32, 2>,
+    mass: f32,
+}
+alias Position = array<f32, 2>;
+const ORIGIN: array<f32, 2> = array<f32, 2>();"
+        );
+    }
+
+    #[test]
+    fn test_parenthesis_are_only_added_where_needed() {
+        let cases = [
+            (
+                // (a + b) * c
+                Expr::binary(
+                    BinaryOp::Mul,
+                    Expr::binary(BinaryOp::Add, ident("a"), ident("b")),
+                    ident("c"),
+                ),
+                "(a + b) * c",
+            ),
+            (
+                // a + b * c - d
+                Expr::binary(
+                    BinaryOp::Sub,
+                    Expr::binary(
+                        BinaryOp::Add,
+                        ident("a"),
+                        Expr::binary(BinaryOp::Mul, ident("b"), ident("c")),
+                    ),
+                    ident("d"),
+                ),
+                "a + b * c - d",
+            ),
+            (
+                // a - (b - c)
+                Expr::binary(
+                    BinaryOp::Sub,
+                    ident("a"),
+                    Expr::binary(BinaryOp::Sub, ident("b"), ident("c")),
+                ),
+                "a - (b - c)",
+            ),
+            (
+                // bitwise operators and shifts cannot be mixed with others in WGSL
+                Expr::binary(
+                    BinaryOp::BitOr,
+                    Expr::binary(BinaryOp::BitAnd, ident("a"), ident("b")),
+                    Expr::binary(BinaryOp::Shl, ident("c"), literal("1u")),
+                ),
+                "(a & b) | (c << 1u)",
+            ),
+            (
+                Expr::binary(
+                    BinaryOp::Or,
+                    Expr::binary(
+                        BinaryOp::And,
+                        Expr::binary(BinaryOp::Lt, ident("a"), ident("b")),
+                        ident("c"),
+                    ),
+                    Expr::binary(BinaryOp::Eq, ident("d"), literal("0u")),
+                ),
+                "(a < b && c) || d == 0u",
+            ),
+            (
+                Expr::field(Expr::unary(UnaryOp::Deref, ident("p")), "x"),
+                "(*p).x",
+            ),
+            (
+                Expr::unary(UnaryOp::Neg, Expr::unary(UnaryOp::Neg, ident("x"))),
+                "-(-x)",
+            ),
+            (
+                Expr::unary(
+                    UnaryOp::Not,
+                    Expr::binary(BinaryOp::Gt, ident("x"), literal("0")),
+                ),
+                "!(x > 0)",
+            ),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(expr.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_control_flow() {
+        let body = Block::new(vec![
+            Stmt::If(If {
+                condition: ident("a"),
+                then_branch: Block::new(vec![Stmt::Break]),
+                else_branch: Some(Else::If(Box::new(If {
+                    condition: ident("b"),
+                    then_branch: Block::new(vec![Stmt::Continue]),
+                    else_branch: Some(Else::Block(Block::default())),
+                }))),
+            }),
+            Stmt::Switch {
+                selector: ident("x"),
+                clauses: vec![
+                    SwitchClause {
+                        selectors: vec![
+                            CaseSelector::Expr(literal("1u")),
+                            CaseSelector::Expr(literal("2u")),
+                        ],
+                        body: Block::new(vec![Stmt::Expr(Expr::call(
+                            Type::named("f"),
+                            vec![ident("x")],
+                        ))]),
+                    },
+                    SwitchClause {
+                        selectors: vec![CaseSelector::Default],
+                        body: Block::default(),
+                    },
+                ],
+            },
+            Stmt::Continuing(Block::new(vec![
+                Stmt::Assign {
+                    target: ident("x"),
+                    op: Some(BinaryOp::Add),
+                    value: literal("1u"),
+                },
+                Stmt::BreakIf(Expr::binary(BinaryOp::Eq, ident("x"), ident("n"))),
+            ])),
+        ]);
+        assert_eq!(
+            Stmt::Loop(body).to_string(),
+            "loop {
+    if a {
+        break;
+    } else if b {
+        continue;
+    } else {}
+    switch x {
+        case 1u, 2u: {
+            f(x);
+        }
+        default: {}
+    }
+    continuing {
+        x += 1u;
+        break if x == n;
+    }
+}"
+        );
+    }
+
+    #[test]
+    fn test_comments_and_source_locations() {
+        let location = |line| SourceLocation {
+            file: "src/shader.rs".to_string(),
+            line,
+        };
+        let items = [
+            Item::Struct(Struct {
+                docs: vec![" A particle".to_string()],
+                location: Some(location(3)),
+                name: "Particle".to_string(),
+                members: vec![Member {
+                    docs: vec![" in kg".to_string()],
+                    name: "mass".to_string(),
+                    ty: Type::named("f32"),
+                }],
+            }),
+            Item::Fn(Function {
+                docs: vec!["\n Doubles the mass\n\n of a particle\n".to_string()],
+                location: Some(location(8)),
+                attributes: vec![],
+                name: "heavier".to_string(),
+                params: vec![Param {
+                    attributes: vec![],
+                    name: "p".to_string(),
+                    ty: Type::named("Particle"),
+                }],
+                return_type: Some(Type::named("f32")),
+                body: Block::new(vec![
+                    Stmt::Comment(" no overflow checks".to_string()),
+                    Stmt::Located {
+                        location: location(10),
+                        stmt: Box::new(Stmt::If(If {
+                            condition: Expr::field(ident("p"), "mass"),
+                            then_branch: Block::new(vec![Stmt::Located {
+                                location: location(11),
+                                stmt: Box::new(Stmt::Return(Some(literal("0.0")))),
+                            }]),
+                            else_branch: None,
+                        })),
+                    },
+                    Stmt::Located {
+                        location: location(13),
+                        stmt: Box::new(Stmt::Return(Some(Expr::binary(
+                            BinaryOp::Mul,
+                            Expr::field(ident("p"), "mass"),
+                            literal("2.0"),
+                        )))),
+                    },
+                ]),
+            }),
+        ];
+        let printed: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            printed.join("\n"),
+            "// A particle
+struct Particle { // src/shader.rs:3
+    // in kg
+    mass: f32,
+}
+// Doubles the mass
+//
+// of a particle
+fn heavier(p: Particle) -> f32 { // src/shader.rs:8
+    // no overflow checks
+    if p.mass { // src/shader.rs:10
+        return 0.0; // src/shader.rs:11
+    }
+    return p.mass * 2.0; // src/shader.rs:13
+}"
+        );
+    }
+
+    #[test]
+    fn test_raw_code() {
+        let function = Function {
+            docs: vec![],
+            location: None,
+            attributes: vec![],
+            name: "fract2".to_string(),
+            params: vec![Param {
+                attributes: vec![],
+                name: "p".to_string(),
+                ty: Type::named("f32"),
+            }],
+            return_type: Some(Type::named("f32")),
+            body: Block::new(vec![Stmt::Raw(
+                "\n            let f = fract(p);\n\n            return f * f;\n        "
+                    .to_string(),
+            )]),
+        };
+        assert_eq!(
+            function.to_string(),
+            "fn fract2(p: f32) -> f32 {\n    let f = fract(p);\n\n    return f * f;\n}"
+        );
+    }
+}
@@ -1,4 +1,6 @@
+pub mod ast;
 pub mod shader_custom_type_name;
 pub mod shader_module;
 pub mod shader_sections;
 pub mod user_facing_api;
+pub mod wgsl_type_def;
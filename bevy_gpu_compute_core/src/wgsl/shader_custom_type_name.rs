@@ -57,4 +57,8 @@ impl ShaderCustomTypeName {
     pub fn uniform(&self) -> String {
         self.uniform.clone()
     }
+    /// the padded type that an array config is stored as in its uniform buffer, see `WgslType::uniform_binding_type`
+    pub fn uniform_layout(&self) -> String {
+        format!("{}_uniform", self.name)
+    }
 }
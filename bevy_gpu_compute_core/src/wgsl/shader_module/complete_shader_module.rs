@@ -1,5 +1,6 @@
 use crate::IterSpaceDimmension;
 use crate::wgsl::shader_module::derived_portion::WgslShaderModuleDerivedPortion;
+use crate::wgsl::shader_module::source_map::WgslSourceMap;
 use crate::wgsl::shader_module::user_defined_portion::WgslShaderModuleUserPortion;
 pub struct WgslShaderModule {
     pub user_portion: WgslShaderModuleUserPortion,
@@ -57,26 +58,18 @@ impl WgslShaderModule {
         self.library_portion.helper_functions.iter().for_each(|f| {
             wgsl.push_str_w_newline(&f.code.wgsl_code.clone());
         });
-        // now add the main function
-        if iter_space_dimmensions == IterSpaceDimmension::OneD {
-            wgsl.push_str_w_newline("@compute @workgroup_size(64, 1, 1)");
-        } else if iter_space_dimmensions == IterSpaceDimmension::TwoD {
-            wgsl.push_str_w_newline("@compute @workgroup_size(8, 8, 1)");
-        } else {
-            wgsl.push_str_w_newline("@compute @workgroup_size(4, 4, 4)");
-        }
-        wgsl.push_str_w_newline(
-            &self
-                .user_portion
-                .main_function
-                .as_ref()
-                .unwrap()
-                .code
-                .wgsl_code
-                .clone(),
-        );
+        // now add the entry points, a `#[wgsl_library]` has none
+        self.user_portion.entry_points.iter().for_each(|e| {
+            let [x, y, z] = e.workgroup_size(iter_space_dimmensions);
+            wgsl.push_str_w_newline(&format!("@compute @workgroup_size({}, {}, {})", x, y, z));
+            wgsl.push_str_w_newline(&e.function.code.wgsl_code.clone());
+        });
         wgsl
     }
+    /// the rust locations of the lines of `wgsl_code`
+    pub fn source_map(&self, iter_space_dimmensions: IterSpaceDimmension) -> WgslSourceMap {
+        WgslSourceMap::from_wgsl(&self.wgsl_code(iter_space_dimmensions))
+    }
 }
 
 // implement push_str_w_newline for String
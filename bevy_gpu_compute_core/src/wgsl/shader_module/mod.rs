@@ -1,3 +1,4 @@
 pub mod complete_shader_module;
 pub mod derived_portion;
+pub mod source_map;
 pub mod user_defined_portion;
@@ -0,0 +1,85 @@
+use crate::wgsl::ast::SourceLocation;
+
+/**
+ Maps every line of a generated WGSL shader back to the rust code it was written in, so that errors that wgpu reports for a shader line can be reported against the rust source.
+
+ The shader module macro ends the first line of every declaration and statement with a `// src/foo.rs:LINE` comment. Lines without one, like the closing brace of a block, belong to the closest annotated line above them in the same declaration.
+ Code that the library adds, like the bindings, has no location.
+*/
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WgslSourceMap {
+    /// the rust location of each WGSL line, the first element is line 1
+    pub lines: Vec<Option<SourceLocation>>,
+}
+
+impl WgslSourceMap {
+    pub fn from_wgsl(wgsl: &str) -> Self {
+        let mut current = None;
+        let lines = wgsl
+            .lines()
+            .map(|line| {
+                if let Some(location) = source_annotation(line) {
+                    current = Some(location);
+                } else if line.starts_with(|c: char| !c.is_whitespace() && c != '}') {
+                    // the start of a declaration without a location
+                    current = None;
+                }
+                current.clone()
+            })
+            .collect();
+        WgslSourceMap { lines }
+    }
+
+    /// `wgsl_line` starts at 1, like the line numbers in wgpu's error messages
+    pub fn rust_location(&self, wgsl_line: usize) -> Option<&SourceLocation> {
+        self.lines.get(wgsl_line.checked_sub(1)?)?.as_ref()
+    }
+}
+
+/// the trailing `// file:line` comment of a line of code, comments on their own line are not annotations
+fn source_annotation(line: &str) -> Option<SourceLocation> {
+    let (code, comment) = line.rsplit_once(" // ")?;
+    if code.trim().is_empty() || code.trim_start().starts_with("//") {
+        return None;
+    }
+    let (file, line) = comment.trim_end().rsplit_once(':')?;
+    Some(SourceLocation {
+        file: file.to_string(),
+        line: line.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_are_mapped_to_the_closest_annotation() {
+        let wgsl = "override LEN: u32;
+// doubles it
+fn double(x: f32) -> f32 { // src/shader.rs:4
+    // a comment
+    if x > 0.0 { // src/shader.rs:5
+        return x * 2.0; // src/shader.rs:6
+    }
+    return 0.0; // src/shader.rs:8
+}
+@group(0) @binding(0) var<uniform> u: U;";
+        let source_map = WgslSourceMap::from_wgsl(wgsl);
+        let line_of = |wgsl_line| source_map.rust_location(wgsl_line).map(|l| l.line);
+        assert_eq!(source_map.lines.len(), 10);
+        assert_eq!(line_of(1), None);
+        assert_eq!(line_of(2), None);
+        assert_eq!(line_of(3), Some(4));
+        assert_eq!(line_of(4), Some(4));
+        assert_eq!(line_of(6), Some(6));
+        assert_eq!(line_of(7), Some(6));
+        assert_eq!(line_of(9), Some(8));
+        assert_eq!(line_of(10), None);
+        assert_eq!(line_of(0), None);
+        assert_eq!(
+            source_map.rust_location(5).unwrap().to_string(),
+            "src/shader.rs:5"
+        );
+    }
+}
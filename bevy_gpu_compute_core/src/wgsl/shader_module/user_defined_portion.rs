@@ -19,12 +19,12 @@ pub struct WgslShaderModuleUserPortion {
     pub input_arrays: Vec<WgslInputArray>,
     /// identified with a #[vec_output] attribute above them
     pub output_arrays: Vec<WgslOutputArray>,
-    /// any function that appears besides the one called "main"
+    /// any function that appears besides the entry points
     pub helper_functions: Vec<WgslFunction>,
-    /// the main function, identified by its name: "main"
-    /// MUST contain a single parameter called "global_id" of type "WgslGlobalId"
-    /// look for any attempt to ASSIGN to the value of "global_id.x", "global_id.y", or "global_id.z" or just "global_id" and throw an error
-    pub main_function: Option<WgslFunction>,
+    /// the function called "main" and the functions with a #[wgsl_entry] attribute, in declaration order
+    /// each MUST contain a single parameter called "iter_pos" of type "WgslIterationPosition"
+    /// a library has none
+    pub entry_points: Vec<WgslEntryPoint>,
     pub binding_numbers_by_variable_name: Option<HashMap<String, u32>>,
 }
 impl WgslShaderModuleUserPortion {
@@ -36,7 +36,7 @@ impl WgslShaderModuleUserPortion {
             input_arrays: vec![],
             output_arrays: vec![],
             helper_functions: vec![],
-            main_function: None,
+            entry_points: vec![],
             binding_numbers_by_variable_name: None,
         }
     }
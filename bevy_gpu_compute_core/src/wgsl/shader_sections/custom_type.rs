@@ -5,3 +5,18 @@ pub struct WgslType {
     pub name: ShaderCustomTypeName,
     pub code: WgslShaderModuleSectionCode,
 }
+
+impl WgslType {
+    /**
+     The type that the uniform binding of a config is declared with.
+     Arrays in uniform buffers need a stride that is a multiple of 16 bytes, so for array configs the macro also declares a type with padded elements, named by `ShaderCustomTypeName::uniform_layout`, which is used instead.
+    */
+    pub fn uniform_binding_type(&self) -> String {
+        let layout = self.name.uniform_layout();
+        if self.code.wgsl_code.contains(&format!("alias {} =", layout)) {
+            layout
+        } else {
+            self.name.name().clone()
+        }
+    }
+}
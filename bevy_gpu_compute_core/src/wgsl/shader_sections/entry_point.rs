@@ -0,0 +1,30 @@
+use crate::IterSpaceDimmension;
+
+use super::function::WgslFunction;
+
+/// a compute shader function, `fn main` or a `#[wgsl_entry]` function. Each one is run as its own pass of the task
+#[derive(Clone, Debug, PartialEq)]
+pub struct WgslEntryPoint {
+    pub function: WgslFunction,
+    /// from `#[wgsl_entry(workgroup_size = ...)]`, otherwise the default for the dimensions of the iteration space is used
+    pub workgroup_size: Option<[u32; 3]>,
+}
+
+impl WgslEntryPoint {
+    pub fn name(&self) -> &str {
+        &self.function.name
+    }
+    pub fn workgroup_size(&self, iter_space_dimmensions: IterSpaceDimmension) -> [u32; 3] {
+        self.workgroup_size
+            .unwrap_or_else(|| default_workgroup_size(iter_space_dimmensions))
+    }
+}
+
+/// Based on this resource: https://developer.arm.com/documentation/101897/0303/Compute-shading/Workgroup-sizes
+pub fn default_workgroup_size(iter_space_dimmensions: IterSpaceDimmension) -> [u32; 3] {
+    match iter_space_dimmensions {
+        IterSpaceDimmension::OneD => [64, 1, 1],
+        IterSpaceDimmension::TwoD => [8, 8, 1],
+        IterSpaceDimmension::ThreeD => [4, 4, 4],
+    }
+}
@@ -1,6 +1,7 @@
 mod code;
 mod const_assignment;
 mod custom_type;
+mod entry_point;
 mod function;
 mod input_array;
 mod output_array;
@@ -10,6 +11,7 @@ mod workgroup_declaration;
 pub use code::*;
 pub use const_assignment::*;
 pub use custom_type::*;
+pub use entry_point::*;
 pub use function::*;
 pub use input_array::*;
 pub use output_array::*;
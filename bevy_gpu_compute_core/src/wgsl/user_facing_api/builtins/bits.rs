@@ -0,0 +1,120 @@
+use super::component_wise::*;
+
+macro_rules! unary_bit_builtins {
+    ($($(#[$doc:meta])* $name:ident(|$bits:ident, $signed:pat_param| $body:expr);)+) => {
+        $(
+            $(#[$doc])*
+            pub fn $name<T>(e: T) -> T
+            where
+                T: WgslComponentWise,
+                T::Component: WgslIntegerScalar,
+            {
+                e.map(|c| {
+                    let $bits = c.to_bits();
+                    let $signed = T::Component::SIGNED;
+                    T::Component::from_bits($body)
+                })
+            }
+        )+
+    };
+}
+
+unary_bit_builtins! {
+    countLeadingZeros(|bits, _| bits.leading_zeros());
+    countOneBits(|bits, _| bits.count_ones());
+    countTrailingZeros(|bits, _| bits.trailing_zeros());
+    reverseBits(|bits, _| bits.reverse_bits());
+    /// the index of the most significant 1 bit, or for negative signed values the most significant 0 bit.
+    /// All bits set (so -1 for i32) if there is no such bit
+    firstLeadingBit(|bits, signed| {
+        let bits = if signed && (bits as i32) < 0 { !bits } else { bits };
+        if bits == 0 { u32::MAX } else { 31 - bits.leading_zeros() }
+    });
+    /// the index of the least significant 1 bit, all bits set (so -1 for i32) if `e` is 0
+    firstTrailingBit(|bits, _| if bits == 0 { u32::MAX } else { bits.trailing_zeros() });
+}
+
+/// reads `count` bits starting at bit `offset`, signed values are sign extended
+pub fn extractBits<T>(e: T, offset: u32, count: u32) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslIntegerScalar,
+{
+    let (offset, count) = clamp_bit_range(offset, count);
+    e.map(|c| {
+        if count == 0 {
+            return T::Component::from_bits(0);
+        }
+        let field = (c.to_bits() >> offset) & low_bits_mask(count);
+        let sign_bit = 1 << (count - 1);
+        if T::Component::SIGNED && field & sign_bit != 0 {
+            T::Component::from_bits(field | !low_bits_mask(count))
+        } else {
+            T::Component::from_bits(field)
+        }
+    })
+}
+
+/// replaces `count` bits of `e` starting at bit `offset` with the lowest `count` bits of `newbits`
+pub fn insertBits<T>(e: T, newbits: T, offset: u32, count: u32) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslIntegerScalar,
+{
+    let (offset, count) = clamp_bit_range(offset, count);
+    let mask = low_bits_mask(count).checked_shl(offset).unwrap_or(0);
+    e.zip(newbits, |c, new| {
+        let new = new.to_bits().checked_shl(offset).unwrap_or(0);
+        T::Component::from_bits((c.to_bits() & !mask) | (new & mask))
+    })
+}
+
+/// WGSL clamps the bit range to the 32 bits that exist
+fn clamp_bit_range(offset: u32, count: u32) -> (u32, u32) {
+    let offset = offset.min(32);
+    (offset, count.min(32 - offset))
+}
+
+fn low_bits_mask(count: u32) -> u32 {
+    if count >= 32 {
+        u32::MAX
+    } else {
+        (1 << count) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wgsl::user_facing_api::primitives::*;
+
+    #[test]
+    fn test_counting_bits() {
+        assert_eq!(countOneBits(Vec2U32::new(0b1011, 0)), Vec2U32::new(3, 0));
+        assert_eq!(countLeadingZeros(1_u32), 31);
+        assert_eq!(countTrailingZeros(-8_i32), 3);
+        assert_eq!(reverseBits(1_u32), 0x8000_0000);
+    }
+
+    #[test]
+    fn test_first_bits() {
+        assert_eq!(firstLeadingBit(0b0110_u32), 2);
+        assert_eq!(firstLeadingBit(0_u32), u32::MAX);
+        assert_eq!(
+            firstLeadingBit(Vec3I32::new(-1, 0, -8)),
+            Vec3I32::new(-1, -1, 2)
+        );
+        assert_eq!(firstTrailingBit(Vec2I32::new(0, 12)), Vec2I32::new(-1, 2));
+    }
+
+    #[test]
+    fn test_extract_and_insert_bits() {
+        assert_eq!(extractBits(0b1101_0000_u32, 4, 4), 0b1101);
+        // signed values are sign extended from the highest extracted bit
+        assert_eq!(extractBits(0b1101_0000_i32, 4, 4), -3);
+        assert_eq!(extractBits(u32::MAX, 0, 32), u32::MAX);
+        assert_eq!(extractBits(u32::MAX, 40, 4), 0);
+        assert_eq!(insertBits(0_u32, 0b111, 4, 2), 0b11_0000);
+        assert_eq!(insertBits(u32::MAX, 0, 0, 32), 0);
+    }
+}
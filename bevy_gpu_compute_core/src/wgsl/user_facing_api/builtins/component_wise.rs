@@ -0,0 +1,249 @@
+use crate::wgsl::user_facing_api::primitives::*;
+
+/// A scalar, or a vector of scalars. WGSL applies most builtins to a vector one component at a time, so this is what makes the builtins generic over scalars and vectors
+pub trait WgslComponentWise: Copy {
+    type Component: Copy;
+    const COMPONENTS: usize;
+
+    fn component(self, index: usize) -> Self::Component;
+    fn from_components(f: impl FnMut(usize) -> Self::Component) -> Self;
+
+    fn splat(value: Self::Component) -> Self {
+        Self::from_components(|_| value)
+    }
+    fn map(self, f: impl Fn(Self::Component) -> Self::Component) -> Self {
+        Self::from_components(|i| f(self.component(i)))
+    }
+    fn zip(
+        self,
+        other: Self,
+        f: impl Fn(Self::Component, Self::Component) -> Self::Component,
+    ) -> Self {
+        Self::from_components(|i| f(self.component(i), other.component(i)))
+    }
+    fn zip3(
+        self,
+        b: Self,
+        c: Self,
+        f: impl Fn(Self::Component, Self::Component, Self::Component) -> Self::Component,
+    ) -> Self {
+        Self::from_components(|i| f(self.component(i), b.component(i), c.component(i)))
+    }
+    fn fold<A>(self, init: A, f: impl Fn(A, Self::Component) -> A) -> A {
+        (0..Self::COMPONENTS).fold(init, |acc, i| f(acc, self.component(i)))
+    }
+}
+
+/// Only implemented for vectors, for the builtins WGSL does not define on scalars, like `dot` and `normalize`
+pub trait WgslVector: WgslComponentWise {}
+
+/// Only implemented for 3 component vectors, for `cross`
+pub trait WgslVector3: WgslVector {}
+
+/// f32 and f16. Float builtins are computed in f32, which is at least as precise as WGSL requires for f16
+pub trait WgslFloatScalar: Copy {
+    fn to_f32(self) -> f32;
+    fn from_f32(value: f32) -> Self;
+}
+
+/// u32 and i32, the bit manipulation builtins work on their bits
+pub trait WgslIntegerScalar: Copy {
+    const SIGNED: bool;
+    fn to_bits(self) -> u32;
+    fn from_bits(bits: u32) -> Self;
+}
+
+/// Every scalar type that supports arithmetic, so everything but bool
+pub trait WgslNumericScalar: WgslScalarArithmetic + PartialOrd {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn wgsl_abs(self) -> Self;
+}
+
+/// A value that can be passed where WGSL accepts either a `T` or a scalar that is broadcast to all components of `T`, like the last argument of `mix`
+pub trait WgslBroadcast<T> {
+    fn broadcast(self) -> T;
+}
+
+impl<T: WgslComponentWise> WgslBroadcast<T> for T {
+    fn broadcast(self) -> T {
+        self
+    }
+}
+
+impl WgslFloatScalar for f32 {
+    fn to_f32(self) -> f32 {
+        self
+    }
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+impl WgslFloatScalar for PodF16 {
+    fn to_f32(self) -> f32 {
+        self.into()
+    }
+    fn from_f32(value: f32) -> Self {
+        value.into()
+    }
+}
+
+impl WgslIntegerScalar for u32 {
+    const SIGNED: bool = false;
+    fn to_bits(self) -> u32 {
+        self
+    }
+    fn from_bits(bits: u32) -> Self {
+        bits
+    }
+}
+
+impl WgslIntegerScalar for i32 {
+    const SIGNED: bool = true;
+    fn to_bits(self) -> u32 {
+        self as u32
+    }
+    fn from_bits(bits: u32) -> Self {
+        bits as i32
+    }
+}
+
+impl WgslNumericScalar for u32 {
+    fn zero() -> Self {
+        0
+    }
+    fn one() -> Self {
+        1
+    }
+    fn wgsl_abs(self) -> Self {
+        self
+    }
+}
+
+impl WgslNumericScalar for i32 {
+    fn zero() -> Self {
+        0
+    }
+    fn one() -> Self {
+        1
+    }
+    // abs(i32::MIN) is i32::MIN in WGSL
+    fn wgsl_abs(self) -> Self {
+        self.wrapping_abs()
+    }
+}
+
+impl WgslNumericScalar for f32 {
+    fn zero() -> Self {
+        0.0
+    }
+    fn one() -> Self {
+        1.0
+    }
+    fn wgsl_abs(self) -> Self {
+        self.abs()
+    }
+}
+
+impl WgslNumericScalar for PodF16 {
+    fn zero() -> Self {
+        PodF16::from(0.0_f32)
+    }
+    fn one() -> Self {
+        PodF16::from(1.0_f32)
+    }
+    fn wgsl_abs(self) -> Self {
+        PodF16::new(self.value.abs())
+    }
+}
+
+macro_rules! impl_scalar_components {
+    ($($type:ty),+) => {
+        $(
+            impl WgslComponentWise for $type {
+                type Component = Self;
+                const COMPONENTS: usize = 1;
+
+                fn component(self, _index: usize) -> Self::Component {
+                    self
+                }
+                fn from_components(mut f: impl FnMut(usize) -> Self::Component) -> Self {
+                    f(0)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_vector_components {
+    ($name:ident, $type:ty, $($field:ident, $index:expr),+) => {
+        impl WgslComponentWise for $name {
+            type Component = $type;
+            const COMPONENTS: usize = [$($index),+].len();
+
+            fn component(self, index: usize) -> Self::Component {
+                match index {
+                    $($index => self.$field,)+
+                    _ => panic!("Index out of bounds"),
+                }
+            }
+            fn from_components(mut f: impl FnMut(usize) -> Self::Component) -> Self {
+                Self::new($(f($index)),+)
+            }
+        }
+
+        impl WgslVector for $name {}
+
+        impl WgslBroadcast<$name> for $type {
+            fn broadcast(self) -> $name {
+                $name::splat(self)
+            }
+        }
+    };
+}
+
+macro_rules! define_vector_components {
+    ($type:ty, $suffix:ident) => {
+        paste::paste! {
+            impl_vector_components!([<Vec2 $suffix>], $type, x, 0, y, 1);
+            impl_vector_components!([<Vec3 $suffix>], $type, x, 0, y, 1, z, 2);
+            impl_vector_components!([<Vec4 $suffix>], $type, x, 0, y, 1, z, 2, w, 3);
+
+            impl WgslVector3 for [<Vec3 $suffix>] {}
+        }
+    };
+}
+
+impl_scalar_components!(u32, i32, f32, PodF16);
+define_vector_components!(u32, U32);
+define_vector_components!(i32, I32);
+define_vector_components!(f32, F32);
+define_vector_components!(PodF16, F16);
+
+/// applies an f32 function to every component of a float scalar or vector
+pub(crate) fn map_f32<T>(e: T, f: impl Fn(f32) -> f32) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    e.map(|c| T::Component::from_f32(f(c.to_f32())))
+}
+
+pub(crate) fn zip_f32<T>(a: T, b: T, f: impl Fn(f32, f32) -> f32) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    a.zip(b, |a, b| T::Component::from_f32(f(a.to_f32(), b.to_f32())))
+}
+
+pub(crate) fn zip3_f32<T>(a: T, b: T, c: T, f: impl Fn(f32, f32, f32) -> f32) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    a.zip3(b, c, |a, b, c| {
+        T::Component::from_f32(f(a.to_f32(), b.to_f32(), c.to_f32()))
+    })
+}
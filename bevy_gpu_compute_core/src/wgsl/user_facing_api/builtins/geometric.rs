@@ -0,0 +1,139 @@
+use super::component_wise::*;
+use crate::wgsl::user_facing_api::primitives::WgslScalarArithmetic;
+
+/// the sum of the component-wise product, integer dot products wrap on overflow
+pub fn dot<T>(e1: T, e2: T) -> T::Component
+where
+    T: WgslVector,
+    T::Component: WgslNumericScalar,
+{
+    e1.zip(e2, WgslScalarArithmetic::wgsl_mul)
+        .fold(T::Component::zero(), WgslScalarArithmetic::wgsl_add)
+}
+
+pub fn cross<T>(e1: T, e2: T) -> T
+where
+    T: WgslVector3,
+    T::Component: WgslFloatScalar,
+{
+    let a = |i| e1.component(i).to_f32();
+    let b = |i| e2.component(i).to_f32();
+    T::from_components(|i| {
+        let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+        T::Component::from_f32(a(j) * b(k) - a(k) * b(j))
+    })
+}
+
+/// also defined for scalars, where it is the absolute value
+pub fn length<T>(e: T) -> T::Component
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    let squared_sum = e.fold(0.0, |sum, c| sum + c.to_f32() * c.to_f32());
+    T::Component::from_f32(squared_sum.sqrt())
+}
+
+pub fn distance<T>(e1: T, e2: T) -> T::Component
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    length(zip_f32(e1, e2, |a, b| a - b))
+}
+
+pub fn normalize<T>(e: T) -> T
+where
+    T: WgslVector,
+    T::Component: WgslFloatScalar,
+{
+    let length = length(e).to_f32();
+    map_f32(e, |c| c / length)
+}
+
+/// `e1` if `dot(e2, e3)` is negative, otherwise `-e1`
+pub fn faceForward<T>(e1: T, e2: T, e3: T) -> T
+where
+    T: WgslVector,
+    T::Component: WgslFloatScalar,
+{
+    if float_dot(e2, e3) < 0.0 {
+        e1
+    } else {
+        map_f32(e1, |c| -c)
+    }
+}
+
+/// the reflection of the incident vector `e1` on the surface with normal `e2`: `e1 - 2 * dot(e2, e1) * e2`
+pub fn reflect<T>(e1: T, e2: T) -> T
+where
+    T: WgslVector,
+    T::Component: WgslFloatScalar,
+{
+    let scale = 2.0 * float_dot(e2, e1);
+    zip_f32(e1, e2, |i, n| i - scale * n)
+}
+
+/// the refraction of the incident vector `e1` through the surface with normal `e2`, with `e3` the ratio of the indices of refraction.
+/// Total internal reflection gives the zero vector
+pub fn refract<T>(e1: T, e2: T, e3: T::Component) -> T
+where
+    T: WgslVector,
+    T::Component: WgslFloatScalar,
+{
+    let eta = e3.to_f32();
+    let n_dot_i = float_dot(e2, e1);
+    let k = 1.0 - eta * eta * (1.0 - n_dot_i * n_dot_i);
+    if k < 0.0 {
+        return map_f32(e1, |_| 0.0);
+    }
+    zip_f32(e1, e2, |i, n| eta * i - (eta * n_dot_i + k.sqrt()) * n)
+}
+
+fn float_dot<T>(e1: T, e2: T) -> f32
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    zip_f32(e1, e2, |a, b| a * b).fold(0.0, |sum, c| sum + c.to_f32())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wgsl::user_facing_api::primitives::*;
+
+    #[test]
+    fn test_dot_cross_and_length() {
+        let x = Vec3F32::new(1.0, 0.0, 0.0);
+        let y = Vec3F32::new(0.0, 1.0, 0.0);
+        assert_eq!(cross(x, y), Vec3F32::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            dot(Vec3F32::new(1.0, 2.0, 3.0), Vec3F32::new(4.0, 5.0, 6.0)),
+            32.0
+        );
+        assert_eq!(dot(Vec2U32::new(u32::MAX, 1), Vec2U32::new(2, 3)), 1);
+        assert_eq!(length(Vec2F32::new(3.0, 4.0)), 5.0);
+        assert_eq!(length(-2.0_f32), 2.0);
+        assert_eq!(
+            distance(Vec2F32::new(1.0, 1.0), Vec2F32::new(4.0, 5.0)),
+            5.0
+        );
+        assert_eq!(normalize(Vec2F32::new(0.0, 3.0)), Vec2F32::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn test_reflect_and_refract() {
+        let incident = Vec2F32::new(1.0, -1.0);
+        let normal = Vec2F32::new(0.0, 1.0);
+        assert_eq!(reflect(incident, normal), Vec2F32::new(1.0, 1.0));
+        assert_eq!(faceForward(normal, incident, normal), normal);
+        // with a ratio of 1 the ray passes straight through
+        assert_eq!(refract(incident, normal, 1.0), incident);
+        // total internal reflection
+        assert_eq!(
+            refract(Vec2F32::new(1.0, -0.1), normal, 2.0),
+            Vec2F32::new(0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use crate::wgsl::user_facing_api::primitives::*;
+
+/// bool and the bool vectors, for `all` and `any`
+pub trait WgslBoolComponents {
+    fn wgsl_all(&self) -> bool;
+    fn wgsl_any(&self) -> bool;
+}
+
+impl WgslBoolComponents for bool {
+    fn wgsl_all(&self) -> bool {
+        *self
+    }
+    fn wgsl_any(&self) -> bool {
+        *self
+    }
+}
+
+macro_rules! impl_bool_components {
+    ($name:ident, $($field:ident),+) => {
+        impl WgslBoolComponents for $name {
+            fn wgsl_all(&self) -> bool {
+                $(self.$field)&&+
+            }
+            fn wgsl_any(&self) -> bool {
+                $(self.$field)||+
+            }
+        }
+    };
+}
+
+impl_bool_components!(Vec2Bool, x, y);
+impl_bool_components!(Vec3Bool, x, y, z);
+impl_bool_components!(Vec4Bool, x, y, z, w);
+
+/// true if every component is true
+pub fn all(e: impl WgslBoolComponents) -> bool {
+    e.wgsl_all()
+}
+
+/// true if any component is true
+pub fn any(e: impl WgslBoolComponents) -> bool {
+    e.wgsl_any()
+}
+
+/// `t` when `cond` is true, otherwise `f`. Note the order of the arguments, which is the opposite of an `if`
+pub fn select<T>(f: T, t: T, cond: bool) -> T {
+    if cond { t } else { f }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logical_builtins() {
+        assert!(all(Vec3Bool::new(true, true, true)));
+        assert!(!all(Vec2Bool::new(true, false)));
+        assert!(any(Vec4Bool::new(false, false, true, false)));
+        assert!(!any(false));
+        assert_eq!(select(1.0_f32, 2.0, true), 2.0);
+        assert_eq!(
+            select(Vec2U32::new(0, 0), Vec2U32::new(1, 1), false),
+            Vec2U32::new(0, 0)
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use super::component_wise::*;
+use crate::wgsl::user_facing_api::primitives::*;
+
+/// the float matrices, WGSL has no integer or bool matrices
+pub trait WgslMatrix: Copy {
+    type Transposed;
+    fn wgsl_transpose(self) -> Self::Transposed;
+}
+
+/// square float matrices, for `determinant`
+pub trait WgslSquareMatrix: WgslMatrix {
+    type Scalar;
+    fn wgsl_determinant(self) -> Self::Scalar;
+}
+
+/// a `CxR` matrix becomes a `RxC` matrix, whose columns are the rows of `e`
+pub fn transpose<M: WgslMatrix>(e: M) -> M::Transposed {
+    e.wgsl_transpose()
+}
+
+pub fn determinant<M: WgslSquareMatrix>(e: M) -> M::Scalar {
+    e.wgsl_determinant()
+}
+
+macro_rules! impl_matrix_builtins {
+    ($suffix:ident, $columns:literal, $rows:literal, $column_fields:tt, [$($row:ident),+]) => {
+        paste::paste! {
+            impl WgslMatrix for [<Mat $columns x $rows $suffix>] {
+                type Transposed = [<Mat $rows x $columns $suffix>];
+
+                fn wgsl_transpose(self) -> Self::Transposed {
+                    let matrix = self;
+                    Self::Transposed::new($(
+                        impl_matrix_builtins!(@row matrix, [<Vec $columns $suffix>], $column_fields, $row)
+                    ),+)
+                }
+            }
+        }
+    };
+    (@row $matrix:ident, $vec_type:ty, [$($column:ident),+], $row:ident) => {
+        <$vec_type>::new($($matrix.$column.$row),+)
+    };
+    (@square $suffix:ident, $size:literal, [$($column:ident),+]) => {
+        paste::paste! {
+            impl WgslSquareMatrix for [<Mat $size x $size $suffix>] {
+                type Scalar = <[<Vec $size $suffix>] as WgslComponentWise>::Component;
+
+                fn wgsl_determinant(self) -> Self::Scalar {
+                    let columns = [$(self.$column.fold(Vec::new(), |mut column, c| {
+                        column.push(c.to_f32());
+                        column
+                    })),+];
+                    Self::Scalar::from_f32(cofactor_determinant(&columns))
+                }
+            }
+        }
+    };
+}
+
+macro_rules! define_matrix_builtins {
+    ($suffix:ident) => {
+        impl_matrix_builtins!($suffix, 2, 2, [x, y], [x, y]);
+        impl_matrix_builtins!($suffix, 2, 3, [x, y], [x, y, z]);
+        impl_matrix_builtins!($suffix, 2, 4, [x, y], [x, y, z, w]);
+        impl_matrix_builtins!($suffix, 3, 2, [x, y, z], [x, y]);
+        impl_matrix_builtins!($suffix, 3, 3, [x, y, z], [x, y, z]);
+        impl_matrix_builtins!($suffix, 3, 4, [x, y, z], [x, y, z, w]);
+        impl_matrix_builtins!($suffix, 4, 2, [x, y, z, w], [x, y]);
+        impl_matrix_builtins!($suffix, 4, 3, [x, y, z, w], [x, y, z]);
+        impl_matrix_builtins!($suffix, 4, 4, [x, y, z, w], [x, y, z, w]);
+        impl_matrix_builtins!(@square $suffix, 2, [x, y]);
+        impl_matrix_builtins!(@square $suffix, 3, [x, y, z]);
+        impl_matrix_builtins!(@square $suffix, 4, [x, y, z, w]);
+    };
+}
+
+define_matrix_builtins!(F32);
+define_matrix_builtins!(F16);
+
+/// Laplace expansion along the first column, which is exact for small integer valued matrices unlike elimination
+fn cofactor_determinant(columns: &[Vec<f32>]) -> f32 {
+    if columns.len() == 1 {
+        return columns[0][0];
+    }
+    (0..columns.len())
+        .map(|row| {
+            let minor: Vec<Vec<f32>> = columns[1..]
+                .iter()
+                .map(|column| {
+                    let mut column = column.clone();
+                    column.remove(row);
+                    column
+                })
+                .collect();
+            let sign = if row % 2 == 0 { 1.0 } else { -1.0 };
+            sign * columns[0][row] * cofactor_determinant(&minor)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transpose() {
+        let m = Mat2x3F32::new(Vec3F32::new(1.0, 2.0, 3.0), Vec3F32::new(4.0, 5.0, 6.0));
+        let t: Mat3x2F32 = transpose(m);
+        assert_eq!(
+            t,
+            Mat3x2F32::new(
+                Vec2F32::new(1.0, 4.0),
+                Vec2F32::new(2.0, 5.0),
+                Vec2F32::new(3.0, 6.0)
+            )
+        );
+        assert_eq!(transpose(t), m);
+    }
+
+    #[test]
+    fn test_determinant() {
+        let m = Mat2x2F32::new(Vec2F32::new(1.0, 2.0), Vec2F32::new(3.0, 4.0));
+        assert_eq!(determinant(m), -2.0);
+        let m = Mat3x3F32::new(
+            Vec3F32::new(2.0, 0.0, 1.0),
+            Vec3F32::new(1.0, 3.0, 2.0),
+            Vec3F32::new(1.0, 1.0, 2.0),
+        );
+        assert_eq!(determinant(m), 6.0);
+        let diagonal = Mat4x4F32::new(
+            Vec4F32::new(1.0, 0.0, 0.0, 0.0),
+            Vec4F32::new(0.0, 1.0, 0.0, 0.0),
+            Vec4F32::new(0.0, 0.0, 1.0, 0.0),
+            Vec4F32::new(0.0, 0.0, 0.0, 2.0),
+        );
+        assert_eq!(determinant(diagonal), 2.0);
+    }
+}
@@ -0,0 +1,246 @@
+use super::{
+    bits::*, component_wise::WgslBroadcast, geometric::*, logical::*, matrix::*, numeric::*,
+};
+use crate::wgsl::user_facing_api::primitives::*;
+
+// Method versions of the builtins, `v.length()` is the same as `length(v)`, and is turned into that by the macro.
+// Builtins with camelCase names in WGSL get snake_case method names, like `v.inverse_sqrt()` for `inverseSqrt(v)`
+
+macro_rules! impl_numeric_vector_methods {
+    ($name:ident, $type:ty) => {
+        impl $name {
+            pub fn abs(self) -> Self {
+                abs(self)
+            }
+            pub fn min(self, e2: Self) -> Self {
+                min(self, e2)
+            }
+            pub fn max(self, e2: Self) -> Self {
+                max(self, e2)
+            }
+            pub fn clamp(self, low: Self, high: Self) -> Self {
+                clamp(self, low, high)
+            }
+            pub fn dot(self, e2: Self) -> $type {
+                dot(self, e2)
+            }
+        }
+    };
+}
+
+macro_rules! impl_signed_vector_methods {
+    ($name:ident) => {
+        impl $name {
+            pub fn sign(self) -> Self {
+                sign(self)
+            }
+        }
+    };
+}
+
+macro_rules! impl_float_vector_methods {
+    ($name:ident, $type:ty) => {
+        impl_float_vector_methods!(@unary $name,
+            acos, acosh, asin, asinh, atan, atanh, ceil, cos, cosh, degrees, exp, exp2, floor, fract, log, log2,
+            radians, round, saturate, sin, sinh, sqrt, tan, tanh, trunc, normalize
+        );
+
+        impl $name {
+            pub fn inverse_sqrt(self) -> Self {
+                inverseSqrt(self)
+            }
+            pub fn quantize_to_f16(self) -> Self {
+                quantizeToF16(self)
+            }
+            pub fn atan2(self, x: Self) -> Self {
+                atan2(self, x)
+            }
+            pub fn pow(self, e2: Self) -> Self {
+                pow(self, e2)
+            }
+            pub fn step(self, x: Self) -> Self {
+                step(self, x)
+            }
+            pub fn smoothstep(self, high: Self, x: Self) -> Self {
+                smoothstep(self, high, x)
+            }
+            pub fn mix(self, e2: Self, e3: impl WgslBroadcast<Self>) -> Self {
+                mix(self, e2, e3)
+            }
+            pub fn fma(self, e2: Self, e3: Self) -> Self {
+                fma(self, e2, e3)
+            }
+            pub fn length(self) -> $type {
+                length(self)
+            }
+            pub fn distance(self, e2: Self) -> $type {
+                distance(self, e2)
+            }
+            pub fn face_forward(self, e2: Self, e3: Self) -> Self {
+                faceForward(self, e2, e3)
+            }
+            pub fn reflect(self, e2: Self) -> Self {
+                reflect(self, e2)
+            }
+            pub fn refract(self, e2: Self, e3: $type) -> Self {
+                refract(self, e2, e3)
+            }
+        }
+    };
+    (@unary $name:ident, $($function:ident),+) => {
+        impl $name {
+            $(
+                pub fn $function(self) -> Self {
+                    $function(self)
+                }
+            )+
+        }
+    };
+}
+
+macro_rules! impl_integer_vector_methods {
+    ($name:ident) => {
+        impl $name {
+            pub fn count_leading_zeros(self) -> Self {
+                countLeadingZeros(self)
+            }
+            pub fn count_one_bits(self) -> Self {
+                countOneBits(self)
+            }
+            pub fn count_trailing_zeros(self) -> Self {
+                countTrailingZeros(self)
+            }
+            pub fn reverse_bits(self) -> Self {
+                reverseBits(self)
+            }
+            pub fn first_leading_bit(self) -> Self {
+                firstLeadingBit(self)
+            }
+            pub fn first_trailing_bit(self) -> Self {
+                firstTrailingBit(self)
+            }
+            pub fn extract_bits(self, offset: u32, count: u32) -> Self {
+                extractBits(self, offset, count)
+            }
+            pub fn insert_bits(self, newbits: Self, offset: u32, count: u32) -> Self {
+                insertBits(self, newbits, offset, count)
+            }
+        }
+    };
+}
+
+macro_rules! impl_bool_vector_methods {
+    ($name:ident) => {
+        impl $name {
+            pub fn all(&self) -> bool {
+                self.wgsl_all()
+            }
+            pub fn any(&self) -> bool {
+                self.wgsl_any()
+            }
+        }
+    };
+}
+
+macro_rules! impl_matrix_methods {
+    ($suffix:ident, $($columns:literal x $rows:literal),+) => {
+        paste::paste! {
+            $(
+                impl [<Mat $columns x $rows $suffix>] {
+                    pub fn transpose(self) -> [<Mat $rows x $columns $suffix>] {
+                        transpose(self)
+                    }
+                }
+            )+
+        }
+    };
+    (@square $suffix:ident, $scalar_type:ty, $($size:literal),+) => {
+        paste::paste! {
+            $(
+                impl [<Mat $size x $size $suffix>] {
+                    pub fn determinant(self) -> $scalar_type {
+                        determinant(self)
+                    }
+                }
+            )+
+        }
+    };
+}
+
+macro_rules! define_all_vector_methods {
+    ($type:ty, $suffix:ident, numeric $(, $kind:ident)*) => {
+        paste::paste! {
+            impl_numeric_vector_methods!([<Vec2 $suffix>], $type);
+            impl_numeric_vector_methods!([<Vec3 $suffix>], $type);
+            impl_numeric_vector_methods!([<Vec4 $suffix>], $type);
+            $(
+                define_all_vector_methods!(@$kind $type, [<Vec2 $suffix>], [<Vec3 $suffix>], [<Vec4 $suffix>]);
+            )*
+        }
+    };
+    (@signed $type:ty, $($name:ident),+) => {
+        $(impl_signed_vector_methods!($name);)+
+    };
+    (@float $type:ty, $($name:ident),+) => {
+        $(impl_float_vector_methods!($name, $type);)+
+    };
+    (@integer $type:ty, $($name:ident),+) => {
+        $(impl_integer_vector_methods!($name);)+
+    };
+}
+
+define_all_vector_methods!(u32, U32, numeric, integer);
+define_all_vector_methods!(i32, I32, numeric, signed, integer);
+define_all_vector_methods!(f32, F32, numeric, signed, float);
+define_all_vector_methods!(PodF16, F16, numeric, signed, float);
+impl_bool_vector_methods!(Vec2Bool);
+impl_bool_vector_methods!(Vec3Bool);
+impl_bool_vector_methods!(Vec4Bool);
+
+impl Vec3F32 {
+    pub fn cross(self, e2: Self) -> Self {
+        cross(self, e2)
+    }
+}
+
+impl Vec3F16 {
+    pub fn cross(self, e2: Self) -> Self {
+        cross(self, e2)
+    }
+}
+
+impl_matrix_methods!(F32, 2 x 2, 2 x 3, 2 x 4, 3 x 2, 3 x 3, 3 x 4, 4 x 2, 4 x 3, 4 x 4);
+impl_matrix_methods!(F16, 2 x 2, 2 x 3, 2 x 4, 3 x 2, 3 x 3, 3 x 4, 4 x 2, 4 x 3, 4 x 4);
+impl_matrix_methods!(@square F32, f32, 2, 3, 4);
+impl_matrix_methods!(@square F16, PodF16, 2, 3, 4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_methods_match_the_builtins() {
+        let a = Vec3F32::new(3.0, 0.0, 4.0);
+        let b = Vec3F32::new(0.0, 1.0, 0.0);
+        assert_eq!(a.length(), 5.0);
+        assert_eq!(a.dot(b), dot(a, b));
+        assert_eq!(a.cross(b), cross(a, b));
+        assert_eq!(a.normalize(), Vec3F32::new(0.6, 0.0, 0.8));
+        assert_eq!(a.min(b).max(b), Vec3F32::new(0.0, 1.0, 0.0));
+        assert_eq!(a.mix(b, 0.5), Vec3F32::new(1.5, 0.5, 2.0));
+        assert_eq!(
+            Vec2F32::new(4.0, 16.0).inverse_sqrt(),
+            Vec2F32::new(0.5, 0.25)
+        );
+        assert_eq!(Vec2I32::new(-3, 3).abs().sign(), Vec2I32::new(1, 1));
+        assert_eq!(Vec2U32::new(0b111, 1).count_one_bits(), Vec2U32::new(3, 1));
+        assert!(Vec2Bool::new(true, false).any());
+
+        let m = Mat2x3F32::new(Vec3F32::new(1.0, 2.0, 3.0), Vec3F32::new(4.0, 5.0, 6.0));
+        assert_eq!(m.transpose(), transpose(m));
+        assert_eq!(
+            Mat2x2F32::new(Vec2F32::new(1.0, 2.0), Vec2F32::new(3.0, 4.0)).determinant(),
+            -2.0
+        );
+    }
+}
@@ -0,0 +1,25 @@
+/*!
+ CPU implementations of the WGSL builtin functions, so that shader modules can call them and still compile and run as rust.
+ They have the same names and argument orders as in WGSL, and the macro emits calls to them unchanged.
+ The vector and matrix types also have them as methods, `v.length()` is turned into `length(v)` by the macro.
+ Like in WGSL most of them work on scalars as well as vectors, in which case they are applied to each component.
+
+ Not included are the builtins that only make sense on the GPU (derivatives, textures, atomics, barriers), and `frexp` and `modf`, which return structs.
+*/
+#![allow(non_snake_case)]
+
+mod bits;
+mod component_wise;
+mod geometric;
+mod logical;
+mod matrix;
+mod methods;
+mod numeric;
+mod packing;
+pub use bits::*;
+pub use component_wise::*;
+pub use geometric::*;
+pub use logical::*;
+pub use matrix::*;
+pub use numeric::*;
+pub use packing::*;
@@ -0,0 +1,220 @@
+use super::component_wise::*;
+use crate::wgsl::user_facing_api::primitives::WgslScalarNegation;
+
+// float builtins that are applied to each component on its own
+macro_rules! unary_float_builtins {
+    ($($(#[$doc:meta])* $name:ident(|$e:ident| $body:expr);)+) => {
+        $(
+            $(#[$doc])*
+            pub fn $name<T>(e: T) -> T
+            where
+                T: WgslComponentWise,
+                T::Component: WgslFloatScalar,
+            {
+                map_f32(e, |$e: f32| $body)
+            }
+        )+
+    };
+}
+
+unary_float_builtins! {
+    acos(|e| e.acos());
+    acosh(|e| e.acosh());
+    asin(|e| e.asin());
+    asinh(|e| e.asinh());
+    atan(|e| e.atan());
+    atanh(|e| e.atanh());
+    ceil(|e| e.ceil());
+    cos(|e| e.cos());
+    cosh(|e| e.cosh());
+    /// radians to degrees
+    degrees(|e| e.to_degrees());
+    exp(|e| e.exp());
+    exp2(|e| e.exp2());
+    floor(|e| e.floor());
+    /// `e - floor(e)`, so the result is always positive
+    fract(|e| e - e.floor());
+    /// `1.0 / sqrt(e)`
+    inverseSqrt(|e| 1.0 / e.sqrt());
+    log(|e| e.ln());
+    log2(|e| e.log2());
+    /// rounds to the nearest f16 value, and returns it as the original type
+    quantizeToF16(|e| e as f16 as f32);
+    /// degrees to radians
+    radians(|e| e.to_radians());
+    /// rounds half way cases to the nearest even number, unlike rust's `f32::round`
+    round(|e| e.round_ties_even());
+    /// clamps to between 0.0 and 1.0
+    saturate(|e| e.clamp(0.0, 1.0));
+    sin(|e| e.sin());
+    sinh(|e| e.sinh());
+    sqrt(|e| e.sqrt());
+    tan(|e| e.tan());
+    tanh(|e| e.tanh());
+    trunc(|e| e.trunc());
+}
+
+/// the angle of the point (x, y), like rust's `f32::atan2`
+pub fn atan2<T>(y: T, x: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    zip_f32(y, x, f32::atan2)
+}
+
+pub fn pow<T>(e1: T, e2: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    zip_f32(e1, e2, f32::powf)
+}
+
+/// 1.0 where `edge <= x`, otherwise 0.0
+pub fn step<T>(edge: T, x: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    zip_f32(edge, x, |edge, x| if edge <= x { 1.0 } else { 0.0 })
+}
+
+/// `e1 * e2 + e3`
+pub fn fma<T>(e1: T, e2: T, e3: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    zip3_f32(e1, e2, e3, f32::mul_add)
+}
+
+/// smooth Hermite interpolation between 0.0 and 1.0, as `x` goes from `low` to `high`
+pub fn smoothstep<T>(low: T, high: T, x: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    zip3_f32(low, high, x, |low, high, x| {
+        let t = ((x - low) / (high - low)).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    })
+}
+
+/// linear blend of `e1` and `e2`, `e3` can be either the same type as them or a scalar
+pub fn mix<T>(e1: T, e2: T, e3: impl WgslBroadcast<T>) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslFloatScalar,
+{
+    zip3_f32(e1, e2, e3.broadcast(), |a, b, t| a * (1.0 - t) + b * t)
+}
+
+pub fn abs<T>(e: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslNumericScalar,
+{
+    e.map(WgslNumericScalar::wgsl_abs)
+}
+
+pub fn min<T>(e1: T, e2: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslNumericScalar,
+{
+    e1.zip(e2, |a, b| if b < a { b } else { a })
+}
+
+pub fn max<T>(e1: T, e2: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslNumericScalar,
+{
+    e1.zip(e2, |a, b| if b > a { b } else { a })
+}
+
+/// `min(max(e, low), high)`
+pub fn clamp<T>(e: T, low: T, high: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslNumericScalar,
+{
+    min(max(e, low), high)
+}
+
+/// -1, 0 or 1, only defined for signed types
+pub fn sign<T>(e: T) -> T
+where
+    T: WgslComponentWise,
+    T::Component: WgslNumericScalar + WgslScalarNegation,
+{
+    let zero = T::Component::zero();
+    let one = T::Component::one();
+    e.map(|c| {
+        if c > zero {
+            one
+        } else if c < zero {
+            one.wgsl_neg()
+        } else {
+            zero
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wgsl::user_facing_api::primitives::*;
+
+    #[test]
+    fn test_float_builtins_work_on_scalars_and_vectors() {
+        assert_eq!(sqrt(4.0_f32), 2.0);
+        assert_eq!(
+            sqrt(Vec3F32::new(1.0, 4.0, 9.0)),
+            Vec3F32::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(fract(-1.25_f32), 0.75);
+        // ties round to even
+        assert_eq!(
+            round(Vec4F32::new(0.5, 1.5, 2.5, -0.5)),
+            Vec4F32::new(0.0, 2.0, 2.0, -0.0)
+        );
+        assert_eq!(step(1.0_f32, 1.0), 1.0);
+        assert_eq!(smoothstep(0.0_f32, 2.0, 1.0), 0.5);
+        assert_eq!(sqrt(PodF16::from(16.0_f32)), PodF16::from(4.0_f32));
+    }
+
+    #[test]
+    fn test_mix_with_vector_or_scalar_factor() {
+        let a = Vec2F32::new(0.0, 10.0);
+        let b = Vec2F32::new(10.0, 20.0);
+        assert_eq!(mix(a, b, 0.5), Vec2F32::new(5.0, 15.0));
+        assert_eq!(mix(a, b, Vec2F32::new(0.0, 1.0)), Vec2F32::new(0.0, 20.0));
+        assert_eq!(mix(1.0_f32, 3.0, 0.25), 1.5);
+    }
+
+    #[test]
+    fn test_numeric_builtins() {
+        assert_eq!(abs(i32::MIN), i32::MIN);
+        assert_eq!(abs(Vec2I32::new(-3, 4)), Vec2I32::new(3, 4));
+        assert_eq!(
+            min(Vec2U32::new(1, 5), Vec2U32::new(3, 2)),
+            Vec2U32::new(1, 2)
+        );
+        assert_eq!(max(2.0_f32, -1.0), 2.0);
+        assert_eq!(
+            clamp(
+                Vec3I32::new(-5, 5, 15),
+                Vec3I32::splat(0),
+                Vec3I32::splat(10)
+            ),
+            Vec3I32::new(0, 5, 10)
+        );
+        assert_eq!(
+            sign(Vec3F32::new(-2.0, 0.0, 3.0)),
+            Vec3F32::new(-1.0, 0.0, 1.0)
+        );
+        assert_eq!(sign(-7_i32), -1);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::wgsl::user_facing_api::primitives::*;
+
+// In every packing builtin component `i` of the vector is stored in the `i`th lowest group of bits of the u32
+
+/// each component clamped to [-1, 1] and stored as an 8 bit signed integer
+pub fn pack4x8snorm(e: Vec4F32) -> u32 {
+    pack_bytes([e.x, e.y, e.z, e.w].map(|c| (c.clamp(-1.0, 1.0) * 127.0).round() as i8 as u8))
+}
+
+/// each component clamped to [0, 1] and stored as an 8 bit unsigned integer
+pub fn pack4x8unorm(e: Vec4F32) -> u32 {
+    pack_bytes([e.x, e.y, e.z, e.w].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+}
+
+/// each component clamped to [-1, 1] and stored as a 16 bit signed integer
+pub fn pack2x16snorm(e: Vec2F32) -> u32 {
+    pack_halves([e.x, e.y].map(|c| (c.clamp(-1.0, 1.0) * 32767.0).round() as i16 as u16))
+}
+
+/// each component clamped to [0, 1] and stored as a 16 bit unsigned integer
+pub fn pack2x16unorm(e: Vec2F32) -> u32 {
+    pack_halves([e.x, e.y].map(|c| (c.clamp(0.0, 1.0) * 65535.0).round() as u16))
+}
+
+/// each component converted to f16
+pub fn pack2x16float(e: Vec2F32) -> u32 {
+    pack_halves([e.x, e.y].map(|c| (c as f16).to_bits()))
+}
+
+/// each component stored as its lowest 8 bits
+pub fn pack4xI8(e: Vec4I32) -> u32 {
+    pack_bytes([e.x, e.y, e.z, e.w].map(|c| c as u8))
+}
+
+/// each component stored as its lowest 8 bits
+pub fn pack4xU8(e: Vec4U32) -> u32 {
+    pack_bytes([e.x, e.y, e.z, e.w].map(|c| c as u8))
+}
+
+/// each component clamped to [-128, 127] and stored as 8 bits
+pub fn pack4xI8Clamp(e: Vec4I32) -> u32 {
+    pack_bytes([e.x, e.y, e.z, e.w].map(|c| c.clamp(-128, 127) as u8))
+}
+
+/// each component clamped to [0, 255] and stored as 8 bits
+pub fn pack4xU8Clamp(e: Vec4U32) -> u32 {
+    pack_bytes([e.x, e.y, e.z, e.w].map(|c| c.min(255) as u8))
+}
+
+pub fn unpack4x8snorm(e: u32) -> Vec4F32 {
+    let [x, y, z, w] = e.to_le_bytes().map(|b| (b as i8 as f32 / 127.0).max(-1.0));
+    Vec4F32::new(x, y, z, w)
+}
+
+pub fn unpack4x8unorm(e: u32) -> Vec4F32 {
+    let [x, y, z, w] = e.to_le_bytes().map(|b| b as f32 / 255.0);
+    Vec4F32::new(x, y, z, w)
+}
+
+pub fn unpack2x16snorm(e: u32) -> Vec2F32 {
+    let [x, y] = unpack_halves(e).map(|h| (h as i16 as f32 / 32767.0).max(-1.0));
+    Vec2F32::new(x, y)
+}
+
+pub fn unpack2x16unorm(e: u32) -> Vec2F32 {
+    let [x, y] = unpack_halves(e).map(|h| h as f32 / 65535.0);
+    Vec2F32::new(x, y)
+}
+
+pub fn unpack2x16float(e: u32) -> Vec2F32 {
+    let [x, y] = unpack_halves(e).map(|h| f16::from_bits(h) as f32);
+    Vec2F32::new(x, y)
+}
+
+/// each byte sign extended
+pub fn unpack4xI8(e: u32) -> Vec4I32 {
+    let [x, y, z, w] = e.to_le_bytes().map(|b| b as i8 as i32);
+    Vec4I32::new(x, y, z, w)
+}
+
+pub fn unpack4xU8(e: u32) -> Vec4U32 {
+    let [x, y, z, w] = e.to_le_bytes().map(|b| b as u32);
+    Vec4U32::new(x, y, z, w)
+}
+
+fn pack_bytes(bytes: [u8; 4]) -> u32 {
+    u32::from_le_bytes(bytes)
+}
+
+fn pack_halves([low, high]: [u16; 2]) -> u32 {
+    low as u32 | (high as u32) << 16
+}
+
+fn unpack_halves(e: u32) -> [u16; 2] {
+    [e as u16, (e >> 16) as u16]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_and_unpack_norms() {
+        assert_eq!(pack4x8unorm(Vec4F32::new(1.0, 0.0, 2.0, -1.0)), 0x00ff_00ff);
+        assert_eq!(pack4x8snorm(Vec4F32::new(1.0, -1.0, 0.0, 0.0)), 0x0000_817f);
+        assert_eq!(
+            unpack4x8unorm(0x00ff_00ff),
+            Vec4F32::new(1.0, 0.0, 1.0, 0.0)
+        );
+        // -128 and -127 both unpack to -1
+        assert_eq!(
+            unpack4x8snorm(0x0000_8081),
+            Vec4F32::new(-1.0, -1.0, 0.0, 0.0)
+        );
+        let v = Vec2F32::new(1.0, 0.0);
+        assert_eq!(unpack2x16unorm(pack2x16unorm(v)), v);
+        assert_eq!(unpack2x16snorm(pack2x16snorm(-v)), -v);
+    }
+
+    #[test]
+    fn test_pack_and_unpack_floats_and_bytes() {
+        let v = Vec2F32::new(1.5, -0.25);
+        assert_eq!(pack2x16float(v), 0xb400_3e00);
+        assert_eq!(unpack2x16float(pack2x16float(v)), v);
+        assert_eq!(pack4xI8(Vec4I32::new(-1, 1, 256, 0)), 0x0000_01ff);
+        assert_eq!(pack4xI8Clamp(Vec4I32::new(-300, 300, 0, 0)), 0x0000_7f80);
+        assert_eq!(pack4xU8Clamp(Vec4U32::new(300, 1, 0, 0)), 0x0000_01ff);
+        assert_eq!(unpack4xI8(0x0000_01ff), Vec4I32::new(-1, 1, 0, 0));
+        assert_eq!(unpack4xU8(0x0000_01ff), Vec4U32::new(255, 1, 0, 0));
+    }
+}
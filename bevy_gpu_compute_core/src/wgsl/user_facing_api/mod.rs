@@ -1,3 +1,4 @@
+pub(crate) mod builtins;
 mod helpers;
 mod primitives;
 pub use helpers::*;
@@ -1,9 +1,10 @@
+use crate::wgsl::user_facing_api::primitives::pod_f16::*;
 use crate::wgsl::user_facing_api::primitives::vectors::*;
 
 macro_rules! impl_matrix {
     ($name:ident, $vec_type:ty, $($field:ident, $index:expr),+) => {
         #[repr(C)]
-        #[derive(Debug, Clone,Copy, bytemuck::Pod, bytemuck::Zeroable)]
+        #[derive(Debug, Clone,Copy, PartialEq, Default, bytemuck::Pod, bytemuck::Zeroable)]
         #[allow(clippy::manual_non_exhaustive)]
 // cannot use #[non_exhaustive] in a macro, and we want to force users even intra-crate to use the constructors for the matrix and vector types
         pub struct $name {
@@ -53,6 +54,7 @@ macro_rules! impl_matrix {
 macro_rules! impl_matrix_no_pod {
     ($name:ident, $vec_type:ty, $($field:ident, $index:expr),+) => {
         #[non_exhaustive]
+        #[derive(Debug, Clone, Copy, PartialEq, Default)]
         pub struct $name {
             $(pub $field: $vec_type,)+
         }
@@ -96,6 +98,128 @@ macro_rules! impl_matrix_no_pod {
     }
 }
 
+// The arithmetic WGSL allows on matrices: component-wise addition and subtraction, scaling by a scalar, and the column-major
+// matrix x vector, vector x matrix and matrix x matrix products. The fields of a matrix are its columns.
+macro_rules! impl_matrix_arithmetic {
+    ($suffix:ident, $scalar_type:ty, $columns:literal, $rows:literal, $($column:ident),+) => {
+        paste::paste! {
+            impl std::ops::Add for [<Mat $columns x $rows $suffix>] {
+                type Output = Self;
+
+                fn add(self, rhs: Self) -> Self::Output {
+                    Self::new($(self.$column + rhs.$column),+)
+                }
+            }
+
+            impl std::ops::Sub for [<Mat $columns x $rows $suffix>] {
+                type Output = Self;
+
+                fn sub(self, rhs: Self) -> Self::Output {
+                    Self::new($(self.$column - rhs.$column),+)
+                }
+            }
+
+            impl std::ops::Mul<$scalar_type> for [<Mat $columns x $rows $suffix>] {
+                type Output = Self;
+
+                fn mul(self, rhs: $scalar_type) -> Self::Output {
+                    Self::new($(self.$column * rhs),+)
+                }
+            }
+
+            impl std::ops::Mul<[<Mat $columns x $rows $suffix>]> for $scalar_type {
+                type Output = [<Mat $columns x $rows $suffix>];
+
+                fn mul(self, rhs: [<Mat $columns x $rows $suffix>]) -> Self::Output {
+                    rhs * self
+                }
+            }
+
+            impl std::ops::AddAssign for [<Mat $columns x $rows $suffix>] {
+                fn add_assign(&mut self, rhs: Self) {
+                    *self = *self + rhs;
+                }
+            }
+
+            impl std::ops::SubAssign for [<Mat $columns x $rows $suffix>] {
+                fn sub_assign(&mut self, rhs: Self) {
+                    *self = *self - rhs;
+                }
+            }
+
+            impl std::ops::MulAssign<$scalar_type> for [<Mat $columns x $rows $suffix>] {
+                fn mul_assign(&mut self, rhs: $scalar_type) {
+                    *self = *self * rhs;
+                }
+            }
+
+            // each column scaled by the matching component of the vector, then summed
+            impl std::ops::Mul<[<Vec $columns $suffix>]> for [<Mat $columns x $rows $suffix>] {
+                type Output = [<Vec $rows $suffix>];
+
+                fn mul(self, rhs: [<Vec $columns $suffix>]) -> Self::Output {
+                    [$(self.$column * rhs.$column),+]
+                        .into_iter()
+                        .reduce(|sum, scaled_column| sum + scaled_column)
+                        .unwrap()
+                }
+            }
+
+            // the dot product of the vector with each column
+            impl std::ops::Mul<[<Mat $columns x $rows $suffix>]> for [<Vec $rows $suffix>] {
+                type Output = [<Vec $columns $suffix>];
+
+                fn mul(self, rhs: [<Mat $columns x $rows $suffix>]) -> Self::Output {
+                    [<Vec $columns $suffix>]::new($((self * rhs.$column).component_sum()),+)
+                }
+            }
+
+            impl_matrix_arithmetic!(@product $suffix, $columns, $rows, 2, x, y);
+            impl_matrix_arithmetic!(@product $suffix, $columns, $rows, 3, x, y, z);
+            impl_matrix_arithmetic!(@product $suffix, $columns, $rows, 4, x, y, z, w);
+        }
+    };
+    // (columns x rows) * (rhs_columns x columns) = (rhs_columns x rows), each column of the rhs is multiplied by the lhs
+    (@product $suffix:ident, $columns:literal, $rows:literal, $rhs_columns:literal, $($rhs_column:ident),+) => {
+        paste::paste! {
+            impl std::ops::Mul<[<Mat $rhs_columns x $columns $suffix>]> for [<Mat $columns x $rows $suffix>] {
+                type Output = [<Mat $rhs_columns x $rows $suffix>];
+
+                fn mul(self, rhs: [<Mat $rhs_columns x $columns $suffix>]) -> Self::Output {
+                    [<Mat $rhs_columns x $rows $suffix>]::new($(self * rhs.$rhs_column),+)
+                }
+            }
+        }
+    };
+    // `m *= n` is only valid when the product has the same type as `m`, which is the case for square matrices
+    (@product_assign $suffix:ident, $size:literal) => {
+        paste::paste! {
+            impl std::ops::MulAssign for [<Mat $size x $size $suffix>] {
+                fn mul_assign(&mut self, rhs: Self) {
+                    *self = *self * rhs;
+                }
+            }
+        }
+    };
+}
+
+macro_rules! define_matrix_arithmetic {
+    ($scalar_type:ty, $suffix:ident) => {
+        impl_matrix_arithmetic!($suffix, $scalar_type, 2, 2, x, y);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 2, 3, x, y);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 2, 4, x, y);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 3, 2, x, y, z);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 3, 3, x, y, z);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 3, 4, x, y, z);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 4, 2, x, y, z, w);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 4, 3, x, y, z, w);
+        impl_matrix_arithmetic!($suffix, $scalar_type, 4, 4, x, y, z, w);
+        impl_matrix_arithmetic!(@product_assign $suffix, 2);
+        impl_matrix_arithmetic!(@product_assign $suffix, 3);
+        impl_matrix_arithmetic!(@product_assign $suffix, 4);
+    };
+}
+
 macro_rules! define_matrix_types {
     ($scalar_type:ty, $suffix:ident) => {
         paste::paste! {
@@ -141,8 +265,12 @@ macro_rules! define_matrix_types_no_pod {
 define_matrix_types!(u32, U32);
 define_matrix_types!(i32, I32);
 define_matrix_types!(f32, F32);
-define_matrix_types!(f16, F16);
+define_matrix_types!(PodF16, F16);
 define_matrix_types_no_pod!(bool, Bool);
+define_matrix_arithmetic!(u32, U32);
+define_matrix_arithmetic!(i32, I32);
+define_matrix_arithmetic!(f32, F32);
+define_matrix_arithmetic!(PodF16, F16);
 
 #[cfg(test)]
 mod tests {
@@ -212,4 +340,51 @@ mod tests {
         assert!(mat.x.y);
         assert!(mat[0][1]);
     }
+
+    #[test]
+    fn test_matrix_vector_products_are_column_major() {
+        let mat = Mat2x3F32::new(Vec3F32::new(1.0, 2.0, 3.0), Vec3F32::new(4.0, 5.0, 6.0));
+        assert_eq!(mat * Vec2F32::new(1.0, 2.0), Vec3F32::new(9.0, 12.0, 15.0));
+        assert_eq!(Vec3F32::new(1.0, 1.0, 1.0) * mat, Vec2F32::new(6.0, 15.0));
+    }
+
+    #[test]
+    fn test_matrix_products() {
+        let a = Mat2x2F32::new(Vec2F32::new(1.0, 2.0), Vec2F32::new(3.0, 4.0));
+        let b = Mat2x2F32::new(Vec2F32::new(5.0, 6.0), Vec2F32::new(7.0, 8.0));
+        let expected = Mat2x2F32::new(Vec2F32::new(23.0, 34.0), Vec2F32::new(31.0, 46.0));
+        assert_eq!(a * b, expected);
+        let mut c = a;
+        c *= b;
+        assert_eq!(c, expected);
+
+        // a 2 column, 3 row matrix times a 4 column, 2 row matrix is a 4 column, 3 row matrix
+        let tall = Mat2x3F32::new(Vec3F32::new(1.0, 0.0, 1.0), Vec3F32::new(0.0, 1.0, 1.0));
+        let wide = Mat4x2F32::new(
+            Vec2F32::new(1.0, 0.0),
+            Vec2F32::new(0.0, 1.0),
+            Vec2F32::new(1.0, 1.0),
+            Vec2F32::new(2.0, 3.0),
+        );
+        let product: Mat4x3F32 = tall * wide;
+        assert_eq!(product.w, Vec3F32::new(2.0, 3.0, 5.0));
+    }
+
+    #[test]
+    fn test_matrix_component_wise_and_scalar_arithmetic() {
+        let a = Mat2x2I32::new(Vec2I32::new(1, 2), Vec2I32::new(3, 4));
+        let b = Mat2x2I32::new(Vec2I32::new(1, 1), Vec2I32::new(1, 1));
+        assert_eq!(
+            a + b,
+            Mat2x2I32::new(Vec2I32::new(2, 3), Vec2I32::new(4, 5))
+        );
+        assert_eq!(
+            a - b,
+            Mat2x2I32::new(Vec2I32::new(0, 1), Vec2I32::new(2, 3))
+        );
+        assert_eq!(
+            2 * a,
+            Mat2x2I32::new(Vec2I32::new(2, 4), Vec2I32::new(6, 8))
+        );
+    }
 }
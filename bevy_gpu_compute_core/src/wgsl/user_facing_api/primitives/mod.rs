@@ -1,8 +1,13 @@
 mod iter_pos;
 mod matrices;
 mod pod_f16;
+mod scalar_arithmetic;
+mod swizzles;
 mod vectors;
+mod wgsl_bool;
 pub use iter_pos::*;
 pub use matrices::*;
 pub use pod_f16::*;
+pub use scalar_arithmetic::*;
 pub use vectors::*;
+pub use wgsl_bool::*;
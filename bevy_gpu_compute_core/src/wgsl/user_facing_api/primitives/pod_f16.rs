@@ -2,7 +2,7 @@ use bytemuck::{Pod, Zeroable};
 
 /// A 16-bit floating point number that implements Pod
 /// Includes padding to ensure 32-bit alignment
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default)]
 #[repr(C)] // Ensure consistent memory layout
 pub struct PodF16 {
     pub value: f16,
@@ -0,0 +1,100 @@
+use crate::wgsl::user_facing_api::primitives::pod_f16::*;
+
+/// Scalar arithmetic with the semantics WGSL gives it, so that vector and matrix operators produce the same results on the CPU as on the GPU:
+/// integer arithmetic wraps on overflow, integer division by zero returns the dividend, and integer remainder by zero returns zero
+pub trait WgslScalarArithmetic: Copy {
+    fn wgsl_add(self, rhs: Self) -> Self;
+    fn wgsl_sub(self, rhs: Self) -> Self;
+    fn wgsl_mul(self, rhs: Self) -> Self;
+    fn wgsl_div(self, rhs: Self) -> Self;
+    fn wgsl_rem(self, rhs: Self) -> Self;
+}
+
+/// negation is only defined in WGSL for signed scalars
+pub trait WgslScalarNegation: Copy {
+    fn wgsl_neg(self) -> Self;
+}
+
+macro_rules! impl_integer_arithmetic {
+    ($type:ty) => {
+        impl WgslScalarArithmetic for $type {
+            fn wgsl_add(self, rhs: Self) -> Self {
+                self.wrapping_add(rhs)
+            }
+            fn wgsl_sub(self, rhs: Self) -> Self {
+                self.wrapping_sub(rhs)
+            }
+            fn wgsl_mul(self, rhs: Self) -> Self {
+                self.wrapping_mul(rhs)
+            }
+            fn wgsl_div(self, rhs: Self) -> Self {
+                if rhs == 0 {
+                    self
+                } else {
+                    self.wrapping_div(rhs)
+                }
+            }
+            fn wgsl_rem(self, rhs: Self) -> Self {
+                if rhs == 0 { 0 } else { self.wrapping_rem(rhs) }
+            }
+        }
+    };
+}
+
+impl_integer_arithmetic!(u32);
+impl_integer_arithmetic!(i32);
+
+impl WgslScalarArithmetic for f32 {
+    fn wgsl_add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+    fn wgsl_sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
+    fn wgsl_mul(self, rhs: Self) -> Self {
+        self * rhs
+    }
+    fn wgsl_div(self, rhs: Self) -> Self {
+        self / rhs
+    }
+    // rust's float remainder truncates like wgsl's does
+    fn wgsl_rem(self, rhs: Self) -> Self {
+        self % rhs
+    }
+}
+
+impl WgslScalarArithmetic for PodF16 {
+    fn wgsl_add(self, rhs: Self) -> Self {
+        PodF16::new(self.value + rhs.value)
+    }
+    fn wgsl_sub(self, rhs: Self) -> Self {
+        PodF16::new(self.value - rhs.value)
+    }
+    fn wgsl_mul(self, rhs: Self) -> Self {
+        PodF16::new(self.value * rhs.value)
+    }
+    fn wgsl_div(self, rhs: Self) -> Self {
+        PodF16::new(self.value / rhs.value)
+    }
+    fn wgsl_rem(self, rhs: Self) -> Self {
+        PodF16::new(self.value % rhs.value)
+    }
+}
+
+impl WgslScalarNegation for i32 {
+    fn wgsl_neg(self) -> Self {
+        self.wrapping_neg()
+    }
+}
+
+impl WgslScalarNegation for f32 {
+    fn wgsl_neg(self) -> Self {
+        -self
+    }
+}
+
+impl WgslScalarNegation for PodF16 {
+    fn wgsl_neg(self) -> Self {
+        PodF16::new(-self.value)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::wgsl::user_facing_api::primitives::pod_f16::*;
+use crate::wgsl::user_facing_api::primitives::vectors::*;
+
+// WGSL style swizzles. `v.zyx()` reads the components into a new vector, like `v.zyx` in WGSL, and components can be repeated (`v.xxyy()`).
+// `v.set_xy(value)` writes them, so each component can only appear once. The `rgba` names work the same way as `xyzw`, but cannot be mixed with them.
+
+macro_rules! impl_swizzles {
+    // `$vectors` are the result types of 2, 3 and 4 component swizzles, `$components` pairs each swizzle name with the field it reads
+    ($name:ident, $vectors:tt, $components:tt) => {
+        impl_swizzles!(@first_getters $name, $vectors, $components, $components);
+        impl_swizzles!(@setters $name, [], $vectors, [x y z w], [], $components);
+    };
+    (@first_getters $name:ident, $vectors:tt, [$($component:tt)+], $all:tt) => {
+        $(impl_swizzles!(@getters $name, [$component], $vectors, $all, $all);)+
+    };
+    (@getters $name:ident, $prefix:tt, $vectors:tt, [$($component:tt)+], $all:tt) => {
+        $(impl_swizzles!(@getter $name, $prefix, $component, $vectors, $all);)+
+    };
+    (@getter $name:ident, $prefix:tt, $component:tt, [], $all:tt) => {};
+    (@getter $name:ident, [$(($n:ident $f:ident))+], ($cn:ident $cf:ident), [$vec:ident $($vectors:ident)*], $all:tt) => {
+        paste::paste! {
+            impl $name {
+                pub fn [<$($n)+ $cn>](&self) -> $vec {
+                    $vec::new($(self.$f,)+ self.$cf)
+                }
+            }
+        }
+        impl_swizzles!(@getters $name, [$(($n $f))+ ($cn $cf)], [$($vectors)*], $all, $all);
+    };
+    // every ordering of distinct components, `$before` and `$rest` are the components not in `$prefix`
+    (@setters $name:ident, $prefix:tt, $vectors:tt, $targets:tt, $before:tt, []) => {};
+    (@setters $name:ident, $prefix:tt, $vectors:tt, $targets:tt, [$($before:tt)*], [$component:tt $($rest:tt)*]) => {
+        impl_swizzles!(@setter $name, $prefix, $component, $vectors, $targets, [$($before)* $($rest)*]);
+        impl_swizzles!(@setters $name, $prefix, $vectors, $targets, [$($before)* $component], [$($rest)*]);
+    };
+    // the single component setters are generated with the vector types, or in `define_swizzles` for `rgba`
+    (@setter $name:ident, [], ($cn:ident $cf:ident), $vectors:tt, [$target:ident $($targets:ident)*], $remaining:tt) => {
+        impl_swizzles!(@setters $name, [($cn $cf $target)], $vectors, [$($targets)*], [], $remaining);
+    };
+    (@setter $name:ident, [$(($n:ident $f:ident $t:ident))+], ($cn:ident $cf:ident), [$vec:ident $($vectors:ident)*], [$target:ident $($targets:ident)*], $remaining:tt) => {
+        paste::paste! {
+            impl $name {
+                pub fn [<set_ $($n)+ $cn>](&mut self, value: $vec) {
+                    $(self.$f = value.$t;)+
+                    self.$cf = value.$target;
+                }
+            }
+        }
+        impl_swizzles!(@setters $name, [$(($n $f $t))+ ($cn $cf $target)], [$($vectors)*], [$($targets)*], [], $remaining);
+    };
+}
+
+macro_rules! define_swizzles {
+    ($type:ty, $suffix:ident) => {
+        paste::paste! {
+            define_swizzles!(@vector [<Vec2 $suffix>], $type, [[<Vec2 $suffix>] [<Vec3 $suffix>] [<Vec4 $suffix>]], [(x x) (y y)], [(r x) (g y)]);
+            define_swizzles!(@vector [<Vec3 $suffix>], $type, [[<Vec2 $suffix>] [<Vec3 $suffix>] [<Vec4 $suffix>]], [(x x) (y y) (z z)], [(r x) (g y) (b z)]);
+            define_swizzles!(@vector [<Vec4 $suffix>], $type, [[<Vec2 $suffix>] [<Vec3 $suffix>] [<Vec4 $suffix>]], [(x x) (y y) (z z) (w w)], [(r x) (g y) (b z) (a w)]);
+        }
+    };
+    (@vector $name:ident, $type:ty, $vectors:tt, $xyzw:tt, [$(($n:ident $f:ident))+]) => {
+        impl_swizzles!($name, $vectors, $xyzw);
+        impl_swizzles!($name, $vectors, [$(($n $f))+]);
+        paste::paste! {
+            impl $name {
+                $(
+                    pub fn [<set_ $n>](&mut self, value: $type) {
+                        self.$f = value;
+                    }
+                )+
+            }
+        }
+    };
+}
+
+define_swizzles!(u32, U32);
+define_swizzles!(i32, I32);
+define_swizzles!(f32, F32);
+define_swizzles!(PodF16, F16);
+define_swizzles!(bool, Bool);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swizzle_reads() {
+        let v = Vec4F32::new(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(v.xy(), Vec2F32::new(1.0, 2.0));
+        assert_eq!(v.zyx(), Vec3F32::new(3.0, 2.0, 1.0));
+        assert_eq!(v.wwxy(), Vec4F32::new(4.0, 4.0, 1.0, 2.0));
+        assert_eq!(v.rgb(), v.xyz());
+        assert_eq!(v.ab(), Vec2F32::new(3.0, 4.0).yx());
+        // smaller vectors can be swizzled into bigger ones
+        assert_eq!(Vec2I32::new(5, 6).xyxy(), Vec4I32::new(5, 6, 5, 6));
+        assert!(Vec3Bool::new(false, true, false).gr().x);
+    }
+
+    #[test]
+    fn test_swizzle_writes() {
+        let mut v = Vec4U32::new(1, 2, 3, 4);
+        v.set_xy(Vec2U32::new(10, 20));
+        assert_eq!(v, Vec4U32::new(10, 20, 3, 4));
+        v.set_wzx(Vec3U32::new(7, 8, 9));
+        assert_eq!(v, Vec4U32::new(9, 20, 8, 7));
+        v.set_bgra(Vec4U32::new(1, 2, 3, 4));
+        assert_eq!(v, Vec4U32::new(3, 2, 1, 4));
+        let mut color = Vec3F32::new(0.0, 0.0, 0.0);
+        color.set_gb(Vec2F32::new(0.5, 1.0));
+        assert_eq!(color, Vec3F32::new(0.0, 0.5, 1.0));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::wgsl::user_facing_api::primitives::pod_f16::*;
+use crate::wgsl::user_facing_api::primitives::scalar_arithmetic::*;
 
 macro_rules! impl_vector {
     ($name:ident, $type:ty, $($field:ident, $index:expr),+) => {
         #[repr(C)]
-        #[derive(Debug, Clone,Copy, bytemuck::Pod, bytemuck::Zeroable)]
+        #[derive(Debug, Clone,Copy, PartialEq, Default, bytemuck::Pod, bytemuck::Zeroable)]
         #[allow(clippy::manual_non_exhaustive)]
 // cannot use #[non_exhaustive] in a macro, and we want to force users even intra-crate to use the constructors for the matrix and vector types
         pub struct $name {
@@ -56,6 +57,7 @@ macro_rules! impl_vector {
 macro_rules! impl_vector_no_pod {
     ($name:ident, $type:ty, $($field:ident, $index:expr),+) => {
         #[non_exhaustive]
+        #[derive(Debug, Clone, Copy, PartialEq, Default)]
         pub struct $name {
             $(pub $field: $type,)+
         }
@@ -99,6 +101,77 @@ macro_rules! impl_vector_no_pod {
     }
 }
 
+// Component-wise arithmetic between vectors, and between a vector and a scalar (in either order), like in WGSL
+macro_rules! impl_vector_arithmetic {
+    ($name:ident, $type:ty, $($field:ident),+) => {
+        impl_vector_arithmetic!(@op $name, $type, Add, add, AddAssign, add_assign, wgsl_add, $($field),+);
+        impl_vector_arithmetic!(@op $name, $type, Sub, sub, SubAssign, sub_assign, wgsl_sub, $($field),+);
+        impl_vector_arithmetic!(@op $name, $type, Mul, mul, MulAssign, mul_assign, wgsl_mul, $($field),+);
+        impl_vector_arithmetic!(@op $name, $type, Div, div, DivAssign, div_assign, wgsl_div, $($field),+);
+        impl_vector_arithmetic!(@op $name, $type, Rem, rem, RemAssign, rem_assign, wgsl_rem, $($field),+);
+
+        impl $name {
+            /// sum of all components, used for the matrix products
+            pub(crate) fn component_sum(self) -> $type {
+                [$(self.$field),+]
+                    .into_iter()
+                    .reduce(WgslScalarArithmetic::wgsl_add)
+                    .unwrap()
+            }
+        }
+    };
+    (@op $name:ident, $type:ty, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $scalar_method:ident, $($field:ident),+) => {
+        impl std::ops::$trait for $name {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                Self::new($(self.$field.$scalar_method(rhs.$field)),+)
+            }
+        }
+
+        impl std::ops::$trait<$type> for $name {
+            type Output = Self;
+
+            fn $method(self, rhs: $type) -> Self::Output {
+                Self::new($(self.$field.$scalar_method(rhs)),+)
+            }
+        }
+
+        impl std::ops::$trait<$name> for $type {
+            type Output = $name;
+
+            fn $method(self, rhs: $name) -> Self::Output {
+                $name::new($(self.$scalar_method(rhs.$field)),+)
+            }
+        }
+
+        impl std::ops::$assign_trait for $name {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = std::ops::$trait::$method(*self, rhs);
+            }
+        }
+
+        impl std::ops::$assign_trait<$type> for $name {
+            fn $assign_method(&mut self, rhs: $type) {
+                *self = std::ops::$trait::$method(*self, rhs);
+            }
+        }
+    };
+}
+
+// WGSL only allows negating vectors of signed types
+macro_rules! impl_vector_negation {
+    ($name:ident, $($field:ident),+) => {
+        impl std::ops::Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                Self::new($(self.$field.wgsl_neg()),+)
+            }
+        }
+    };
+}
+
 macro_rules! define_vector_types {
     ($type:ty, $suffix:ident) => {
         paste::paste! {
@@ -106,7 +179,18 @@ macro_rules! define_vector_types {
             impl_vector!([<Vec3 $suffix>], $type, x, 0, y, 1, z, 2);
             impl_vector!([<Vec4 $suffix>], $type, x, 0, y, 1, z, 2, w, 3);
 
-
+            impl_vector_arithmetic!([<Vec2 $suffix>], $type, x, y);
+            impl_vector_arithmetic!([<Vec3 $suffix>], $type, x, y, z);
+            impl_vector_arithmetic!([<Vec4 $suffix>], $type, x, y, z, w);
+        }
+    };
+}
+macro_rules! define_vector_negation {
+    ($suffix:ident) => {
+        paste::paste! {
+            impl_vector_negation!([<Vec2 $suffix>], x, y);
+            impl_vector_negation!([<Vec3 $suffix>], x, y, z);
+            impl_vector_negation!([<Vec4 $suffix>], x, y, z, w);
         }
     };
 }
@@ -128,6 +212,9 @@ define_vector_types!(i32, I32);
 define_vector_types!(f32, F32);
 define_vector_types!(PodF16, F16);
 define_vector_types_no_pod!(bool, Bool);
+define_vector_negation!(I32);
+define_vector_negation!(F32);
+define_vector_negation!(F16);
 
 #[cfg(test)]
 mod tests {
@@ -164,4 +251,45 @@ mod tests {
         let vec4 = Vec4F32::new(1.0, 2.0, 3.0, 4.0);
         let _value = vec4[4]; // Should panic
     }
+
+    #[test]
+    fn test_vector_arithmetic() {
+        let a = Vec3F32::new(1.0, 2.0, 3.0);
+        let b = Vec3F32::new(4.0, 5.0, 6.0);
+        assert_eq!(a + b, Vec3F32::new(5.0, 7.0, 9.0));
+        assert_eq!(b - a, Vec3F32::new(3.0, 3.0, 3.0));
+        assert_eq!(a * b, Vec3F32::new(4.0, 10.0, 18.0));
+        assert_eq!(b / a, Vec3F32::new(4.0, 2.5, 2.0));
+        assert_eq!(b % a, Vec3F32::new(0.0, 1.0, 0.0));
+        assert_eq!(-a, Vec3F32::new(-1.0, -2.0, -3.0));
+        // scalars are broadcast to every component
+        assert_eq!(a * 2.0, Vec3F32::new(2.0, 4.0, 6.0));
+        assert_eq!(2.0 * a, Vec3F32::new(2.0, 4.0, 6.0));
+        assert_eq!(6.0 / a, Vec3F32::new(6.0, 3.0, 2.0));
+
+        let mut c = a;
+        c += b;
+        c *= 2.0;
+        assert_eq!(c, Vec3F32::new(10.0, 14.0, 18.0));
+    }
+
+    #[test]
+    fn test_integer_vector_arithmetic_matches_wgsl() {
+        // overflow wraps
+        assert_eq!(
+            Vec2U32::new(u32::MAX, 0) + Vec2U32::new(1, 1),
+            Vec2U32::new(0, 1)
+        );
+        assert_eq!(Vec2U32::new(0, 5) - 1, Vec2U32::new(u32::MAX, 4));
+        // division by zero returns the dividend, remainder by zero returns zero
+        assert_eq!(
+            Vec2I32::new(7, -7) / Vec2I32::new(0, 2),
+            Vec2I32::new(7, -3)
+        );
+        assert_eq!(
+            Vec2I32::new(7, -7) % Vec2I32::new(0, 2),
+            Vec2I32::new(0, -1)
+        );
+        assert_eq!(-Vec2I32::new(i32::MIN, 1), Vec2I32::new(i32::MIN, -1));
+    }
 }
@@ -0,0 +1,52 @@
+use bytemuck::{Pod, Zeroable};
+
+/// A bool that implements Pod, stored as a u32 like WGSL does for bools in buffers (0 = false, 1 = true)
+/// `bool` fields of the input, output and config types become this on the CPU side, use `.into()` to convert
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct WgslBool(pub u32);
+
+impl WgslBool {
+    pub fn new(value: bool) -> Self {
+        Self(value as u32)
+    }
+
+    pub fn get(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl From<bool> for WgslBool {
+    fn from(value: bool) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<WgslBool> for bool {
+    fn from(value: WgslBool) -> Self {
+        value.get()
+    }
+}
+
+impl PartialEq<bool> for WgslBool {
+    fn eq(&self, other: &bool) -> bool {
+        self.get() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wgsl_bool_pod() {
+        let values = [WgslBool::from(true), WgslBool::from(false)];
+        let bytes: &[u8] = bytemuck::cast_slice(&values);
+        assert_eq!(bytes, [1, 0, 0, 0, 0, 0, 0, 0]);
+        let restored: &[WgslBool] = bytemuck::cast_slice(bytes);
+        assert!(bool::from(restored[0]));
+        assert_eq!(restored[1], false);
+        // any value other than 0 is true, like in WGSL
+        assert!(WgslBool(7).get());
+    }
+}
@@ -0,0 +1,35 @@
+use super::shader_sections::WgslType;
+
+/// where a field of a `WgslTypeDef` type is in its rust layout, and its type in WGSL
+#[derive(Debug, Clone, PartialEq)]
+pub struct WgslFieldLayout {
+    pub name: String,
+    pub wgsl_type: String,
+    /// the byte offset of the field
+    pub offset: usize,
+    /// the size of the field in bytes
+    pub size: usize,
+}
+
+/**
+ A struct that is defined outside of the shader modules and translated to WGSL by `#[derive(WgslType)]`, so that the same rust type can be used by several shader modules and by normal game code.
+
+ A shader module uses the type by importing it with `use crate::path::to::MyType;`.
+*/
+pub trait WgslTypeDef: Sized {
+    /// the WGSL name and declaration of the type, like the helper types of a shader module
+    fn wgsl_type() -> WgslType;
+    /// the WGSL declaration of the type, `struct MyType { ... }`
+    fn wgsl_code() -> String {
+        Self::wgsl_type().code.wgsl_code
+    }
+    /// the fields in declaration order
+    fn fields() -> Vec<WgslFieldLayout>;
+    /// the size of one value in bytes, which is also its stride in a buffer
+    fn size() -> usize {
+        std::mem::size_of::<Self>()
+    }
+    fn alignment() -> usize {
+        std::mem::align_of::<Self>()
+    }
+}
@@ -1,8 +1,16 @@
 #![feature(allocator_api)]
 
-use pipeline::lib::CompilerPipeline;
+use pipeline::{
+    compilation_unit::ModuleKind,
+    entry_points::parse_workgroup_size,
+    lib::CompilerPipeline,
+    library_imports::LibraryResolution,
+    raw_functions::{expand_wgsl_extern, expand_wgsl_raw},
+    wgsl_type_def::derive_wgsl_type,
+};
 use proc_macro::TokenStream;
 use proc_macro_error::{proc_macro_error, set_dummy};
+use quote::ToTokens;
 use syn::parse_macro_input;
 mod pipeline;
 /**
@@ -27,7 +35,17 @@ let x = my_vec3[0];
 // invalid:
 let x = my_vec3.x();
 ```
-- "rgba" field access on vectors is not supported right now, use index or xyzw instead.
+- "rgba" field access on vectors is not supported, use index or xyzw instead. The `rgba` names can be used in swizzles though.
+- Swizzles are methods: `v.xy()`, `v.zyx()`, `v.rgb()` etc. read components into a smaller (or bigger) vector, and `v.set_xy(other)` writes them.
+- Enums without fields can be used, and become `u32` constants in WGSL. On the CPU they become a `u32` wrapper with a constant per variant, so they can be part of the input and output types, but a `match` on them needs a `_` arm, and they cannot be cast with `as`.
+- Methods and associated functions in `impl` blocks of your own types can be used, they become free functions named like `Position_dist` in WGSL. If the type of a variable cannot be worked out, annotate it so that the right method is called.
+- Generic helper functions can be used, a copy is made for each set of types they are called with, named like `lerp_f32` in WGSL. If a type parameter cannot be worked out from the arguments or from the declared type of the result, give it explicitly like `lerp::<f32>(...)`. Const generics are not supported.
+- Closures with explicit parameter types, like `let sq = |x: f32| x * x;`, can be stored with `let` and called. They become functions in WGSL, with the variables they capture as extra parameters, so they cannot change those variables or be passed to other functions.
+- The generated WGSL is checked with naga (the shader compiler used by wgpu) while your code compiles, so problems like recursive functions or names that are reserved words in WGSL (`target`, `meta`, ...) show up as errors starting with `Generated WGSL is invalid`, pointing at the rust code they came from. Shaders using `f16` are not checked, since naga does not support it yet.
+- Helper functions, consts and helper types can be shared between shader modules by putting them in a `#[wgsl_library]` module, and importing it with `use crate::path::to::library::*;`.
+- Besides `fn main`, functions marked `#[wgsl_entry]` are entry points of the shader, each run as its own pass over the same buffers, see `wgsl_entry`.
+- Functions that the macro cannot translate can be written in WGSL, with `#[wgsl_extern(path = "file.wgsl")]` or a `wgsl_raw!("...")` body.
+- A struct deriving `WgslType` can be used by several shader modules, and by the rest of your program, by importing it with `use crate::path::to::MyType;`. Put `#[wgsl_input_array]` etc. on the import to transfer it between the CPU and GPU.
 - Every Input/Output you want to transfer between the CPU and GPU must have its type defined within the shader module. Here's how you do that:
     - Input Vec/Array/Matrices: Define the inner-type, and put `#[vec_input]` above the type definition. Example: If you want to work with an input equivalent to `Vec<{x:f32, y:f32}>` in your module, then write
     ```ignore
@@ -52,7 +70,7 @@ let x = my_vec3.x();
     #use bevy_gpu_compute_corepu_compute_core::wgsl_in_rust_helpers::*;
     const MY_CONST: Vec3Bool = Vec3Bool::new(true, false, true);
     ```
-* If you see the error `the trait bound `bool: Pod` is not satisfied...` make sure you are not trying to use a `bool` in any input data or output data. The `bool` type CAN be used but only ON the GPU, it cannot be passed between the CPU and GPU.
+* `bool` can be used anywhere on the GPU. `bool` fields of the input, output and config types are stored as a `u32` in the buffers, so outside of the module they have the type `WgslBool`, use `true.into()` to create one and `bool::from(x)` to read it. Other types containing bools, like `Vec3Bool` or `[bool; 4]`, cannot be passed between the CPU and GPU.
  */
 #[proc_macro_attribute]
 #[proc_macro_error]
@@ -60,7 +78,162 @@ pub fn wgsl_shader_module(_attr: TokenStream, item: TokenStream) -> TokenStream
     set_dummy(item.clone().into());
     let module = parse_macro_input!(item as syn::ItemMod);
     let compiler_pipeline = CompilerPipeline::default();
-    compiler_pipeline.compile(module).into()
+    compiler_pipeline
+        .compile(LibraryResolution::new(module, ModuleKind::ShaderModule))
+        .into()
+}
+
+/**
+A module of helper functions, consts and helper types that can be shared between shader modules, instead of copying them into each one.
+
+Import it in a `#[wgsl_shader_module]`, or in another library, with a glob import of its full path starting with `crate`:
+```ignore
+#[wgsl_library]
+pub mod geometry {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    pub struct Circle {
+        pub center: Vec2F32,
+        pub radius: f32,
+    }
+    pub fn overlaps(a: Circle, b: Circle) -> bool {
+        let d = a.center - b.center;
+        d.x * d.x + d.y * d.y < (a.radius + b.radius) * (a.radius + b.radius)
+    }
+}
+
+#[wgsl_shader_module]
+pub mod collisions {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use crate::geometry::*;
+    // ...
+}
+```
+The `parsed()` of the importing module then contains the consts, helper types and helper functions of the library, and of the libraries it imports. An item of the importing module takes precedence over an item of a library with the same name, like with any glob import, and a library imported through several paths is only included once.
+
+A library cannot have a `main` function or input, output and config types, those belong to the shader module. The library has to be in the same crate as the modules importing it, and the import has to be a glob import, importing single items of a library is not supported.
+*/
+#[proc_macro_attribute]
+#[proc_macro_error]
+pub fn wgsl_library(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    set_dummy(item.clone().into());
+    let module = parse_macro_input!(item as syn::ItemMod);
+    let compiler_pipeline = CompilerPipeline::default();
+    compiler_pipeline
+        .compile(LibraryResolution::new(module, ModuleKind::Library))
+        .into()
+}
+
+/// Used by the macros that `#[wgsl_library]` modules and `#[derive(WgslType)]` structs export their source with, to collect the sources of the libraries and types that a module imports before compiling it
+#[doc(hidden)]
+#[proc_macro]
+#[proc_macro_error]
+pub fn __resolve_wgsl_libraries(input: TokenStream) -> TokenStream {
+    let resolution = parse_macro_input!(input as LibraryResolution);
+    set_dummy(resolution.module.to_token_stream());
+    let compiler_pipeline = CompilerPipeline::default();
+    compiler_pipeline.compile(resolution).into()
+}
+
+/**
+Translates a struct defined outside of the shader modules to WGSL, so that one rust type can be used by many shader modules and by normal game code, instead of redefining it in each module.
+
+The WGSL declaration and the layout of the fields are available through the `WgslTypeDef` trait:
+```ignore
+#[derive(WgslType, Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct Particle {
+    pub position: Vec2F32,
+    pub radius: f32,
+}
+assert_eq!(Particle::fields()[1].offset, 8);
+```
+A shader module uses the type by importing it by its full path. Put the attribute of an input, output or config type on the import to transfer it between the CPU and GPU:
+```ignore
+#[wgsl_shader_module]
+pub mod collisions {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    #[wgsl_input_array]
+    use crate::particles::Particle;
+    // ...
+}
+```
+The struct needs `#[repr(C)]`, and its fields can only be scalars, vectors, matrices and arrays of them, not bools or other structs. The fields have to be at the offsets WGSL gives them, which is checked at compile time: a `Vec3F32` is aligned to 16 bytes in WGSL but only to 4 in rust, so it can need padding fields before it and at the end of the struct. To be used in buffers it also needs to derive `Clone, Copy, bytemuck::Pod, bytemuck::Zeroable`. Its methods are only available on the CPU. Like a `#[wgsl_library]`, it can only be imported within the same crate.
+*/
+#[proc_macro_derive(WgslType)]
+#[proc_macro_error]
+pub fn wgsl_type(item: TokenStream) -> TokenStream {
+    let item_struct = parse_macro_input!(item as syn::ItemStruct);
+    derive_wgsl_type(&item_struct).into()
+}
+
+/**
+A helper function of a shader module that is written in a WGSL file, for what the macro cannot translate from rust:
+```ignore
+#[wgsl_extern(path = "noise.wgsl")]
+fn simplex(p: Vec2F32) -> f32;
+```
+The path is relative to the rust file, like with `include_str!`. The content of the file is added to the shader unchanged, so it can also declare other functions the extern function needs, and several extern functions can be declared with the same file.
+
+The function panics on the CPU, unless it is given a rust body, which is then only used on the CPU:
+```ignore
+#[wgsl_extern(path = "noise.wgsl")]
+fn simplex(p: Vec2F32) -> f32 {
+    noise::simplex(p.x, p.y)
+}
+```
+*/
+#[proc_macro_attribute]
+#[proc_macro_error]
+pub fn wgsl_extern(attr: TokenStream, item: TokenStream) -> TokenStream {
+    expand_wgsl_extern(attr.into(), item.into())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/**
+The body of a helper function of a shader module written in WGSL, which is added to the shader unchanged. The signature of the function is translated like for any other function:
+```ignore
+fn fade(t: f32) -> f32 {
+    wgsl_raw!("return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);")
+}
+```
+The function panics on the CPU.
+*/
+#[proc_macro]
+#[proc_macro_error]
+pub fn wgsl_raw(input: TokenStream) -> TokenStream {
+    expand_wgsl_raw(input.into())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/**
+Marks a function of a shader module as a compute shader entry point, in addition to `fn main`. Each entry point is run as its own pass over the same buffers, so an algorithm can be split into passes like clear, accumulate and compact:
+```ignore
+#[wgsl_entry]
+fn clear(iter_pos: WgslIterationPosition) {
+    // ...
+}
+#[wgsl_entry(workgroup_size = 256)]
+fn accumulate(iter_pos: WgslIterationPosition) {
+    // ...
+}
+```
+Entry points have the same signature as `fn main`. Without a `workgroup_size` (one to three dimensions, like `(8, 8)`) the default for the dimensions of the iteration space is used.
+
+`GpuTaskCommands::run` runs every entry point in declaration order, `run_pass("clear")` and `run_passes(&["clear", "accumulate"])` run the given ones, in a single submission.
+*/
+#[proc_macro_attribute]
+#[proc_macro_error]
+pub fn wgsl_entry(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match parse_workgroup_size(attr.into()) {
+        Ok(_) => item,
+        Err(e) => {
+            let mut tokens: proc_macro2::TokenStream = item.into();
+            tokens.extend(e.to_compile_error());
+            tokens.into()
+        }
+    }
 }
 
 /// used to help this library figure out what to do with user-defined types
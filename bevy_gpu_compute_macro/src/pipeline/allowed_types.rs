@@ -65,6 +65,169 @@ pub const WGSL_NATIVE_TYPES: [&str; 65] = [
     "u32",
     "bool",
 ];
+/// the functions in `bevy_gpu_compute_core::wgsl_builtins`, which have the same names in WGSL
+pub const WGSL_BUILTIN_FUNCTIONS: [&str; 75] = [
+    "acos",
+    "acosh",
+    "asin",
+    "asinh",
+    "atan",
+    "atanh",
+    "ceil",
+    "cos",
+    "cosh",
+    "degrees",
+    "exp",
+    "exp2",
+    "floor",
+    "fract",
+    "inverseSqrt",
+    "log",
+    "log2",
+    "quantizeToF16",
+    "radians",
+    "round",
+    "saturate",
+    "sin",
+    "sinh",
+    "sqrt",
+    "tan",
+    "tanh",
+    "trunc",
+    "atan2",
+    "pow",
+    "step",
+    "fma",
+    "smoothstep",
+    "mix",
+    "abs",
+    "min",
+    "max",
+    "clamp",
+    "sign",
+    "dot",
+    "cross",
+    "length",
+    "distance",
+    "normalize",
+    "faceForward",
+    "reflect",
+    "refract",
+    "countLeadingZeros",
+    "countOneBits",
+    "countTrailingZeros",
+    "reverseBits",
+    "firstLeadingBit",
+    "firstTrailingBit",
+    "extractBits",
+    "insertBits",
+    "pack4x8snorm",
+    "pack4x8unorm",
+    "pack2x16snorm",
+    "pack2x16unorm",
+    "pack2x16float",
+    "pack4xI8",
+    "pack4xU8",
+    "pack4xI8Clamp",
+    "pack4xU8Clamp",
+    "unpack4x8snorm",
+    "unpack4x8unorm",
+    "unpack2x16snorm",
+    "unpack2x16unorm",
+    "unpack2x16float",
+    "unpack4xI8",
+    "unpack4xU8",
+    "all",
+    "any",
+    "select",
+    "transpose",
+    "determinant",
+];
+/// methods of the vector, matrix and scalar types that are turned into a call to a WGSL builtin, `v.length()` becomes `length(v)`.
+/// Includes the rust names of the scalar methods that do the same thing as a builtin, like `powf`
+pub const WGSL_BUILTIN_METHODS: [(&str, &str); 66] = [
+    ("abs", "abs"),
+    ("min", "min"),
+    ("max", "max"),
+    ("clamp", "clamp"),
+    ("dot", "dot"),
+    ("sign", "sign"),
+    ("acos", "acos"),
+    ("acosh", "acosh"),
+    ("asin", "asin"),
+    ("asinh", "asinh"),
+    ("atan", "atan"),
+    ("atanh", "atanh"),
+    ("ceil", "ceil"),
+    ("cos", "cos"),
+    ("cosh", "cosh"),
+    ("degrees", "degrees"),
+    ("exp", "exp"),
+    ("exp2", "exp2"),
+    ("floor", "floor"),
+    ("fract", "fract"),
+    ("log", "log"),
+    ("log2", "log2"),
+    ("radians", "radians"),
+    ("round", "round"),
+    ("saturate", "saturate"),
+    ("sin", "sin"),
+    ("sinh", "sinh"),
+    ("sqrt", "sqrt"),
+    ("tan", "tan"),
+    ("tanh", "tanh"),
+    ("trunc", "trunc"),
+    ("normalize", "normalize"),
+    ("atan2", "atan2"),
+    ("pow", "pow"),
+    ("step", "step"),
+    ("smoothstep", "smoothstep"),
+    ("mix", "mix"),
+    ("fma", "fma"),
+    ("length", "length"),
+    ("distance", "distance"),
+    ("reflect", "reflect"),
+    ("refract", "refract"),
+    ("cross", "cross"),
+    ("transpose", "transpose"),
+    ("determinant", "determinant"),
+    ("all", "all"),
+    ("any", "any"),
+    ("inverse_sqrt", "inverseSqrt"),
+    ("quantize_to_f16", "quantizeToF16"),
+    ("face_forward", "faceForward"),
+    ("count_leading_zeros", "countLeadingZeros"),
+    ("count_one_bits", "countOneBits"),
+    ("count_trailing_zeros", "countTrailingZeros"),
+    ("reverse_bits", "reverseBits"),
+    ("first_leading_bit", "firstLeadingBit"),
+    ("first_trailing_bit", "firstTrailingBit"),
+    ("extract_bits", "extractBits"),
+    ("insert_bits", "insertBits"),
+    ("powf", "pow"),
+    ("ln", "log"),
+    ("to_degrees", "degrees"),
+    ("to_radians", "radians"),
+    ("mul_add", "fma"),
+    ("count_ones", "countOneBits"),
+    ("leading_zeros", "countLeadingZeros"),
+    ("trailing_zeros", "countTrailingZeros"),
+];
+/// methods of rust's f32 that do something different than the WGSL builtin of the same name, so they cannot be used on scalar floats
+pub const SCALAR_FLOAT_METHODS_DIFFERENT_FROM_WGSL: [&str; 3] = ["fract", "log", "round"];
+/// component names that vector swizzles like `v.xy()` and `v.set_rgb(c)` are made of, the two sets cannot be mixed
+pub const SWIZZLE_COMPONENT_SETS: [&str; 2] = ["xyzw", "rgba"];
+/// true if `components` is a swizzle of `min_len` to 4 components, for a swizzle assignment each component can only be written once
+pub fn is_swizzle(components: &str, min_len: usize, is_assignment: bool) -> bool {
+    (min_len..=4).contains(&components.len())
+        && SWIZZLE_COMPONENT_SETS
+            .iter()
+            .any(|set| components.chars().all(|c| set.contains(c)))
+        && (!is_assignment
+            || components
+                .char_indices()
+                .all(|(i, c)| !components[..i].contains(c)))
+}
 #[allow(dead_code)]
 const LIB_HELPER_TYPES: [&str; 5] = [
     "WgslScalar",
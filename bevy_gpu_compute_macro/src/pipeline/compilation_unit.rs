@@ -3,11 +3,27 @@ use proc_macro2::TokenStream;
 
 use super::{
     compilation_metadata::CompilationMetadata,
+    library_imports::{LibraryResolution, with_imported_items},
     phases::custom_type_collector::custom_type::CustomType,
+    raw_functions::with_extern_bodies,
 };
 
+/// what the module being compiled is used for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModuleKind {
+    /// a `#[wgsl_shader_module]`, with a main function and the buffers of a compute task
+    ShaderModule,
+    /// a `#[wgsl_library]`, only helper functions, consts and helper types that other modules import
+    Library,
+}
+
 pub struct CompilationUnit {
+    kind: ModuleKind,
     original_rust_module: syn::ItemMod,
+    /// the `#[wgsl_library]` modules imported by the module, and by those libraries
+    libraries: Vec<syn::ItemMod>,
+    /// the `#[derive(WgslType)]` structs imported by the module and its libraries, by their path
+    imported_types: Vec<(syn::Path, syn::ItemStruct)>,
     rust_module_for_cpu: Option<syn::ItemMod>,
     rust_module_for_gpu: Option<syn::ItemMod>,
     compiled_tokens: Option<TokenStream>,
@@ -15,9 +31,16 @@ pub struct CompilationUnit {
 }
 
 impl CompilationUnit {
-    pub fn new(original_rust_module: syn::ItemMod) -> Self {
+    pub fn new(resolution: LibraryResolution) -> Self {
         CompilationUnit {
-            original_rust_module,
+            kind: resolution.kind,
+            libraries: resolution
+                .library_modules()
+                .into_iter()
+                .map(with_extern_bodies)
+                .collect(),
+            imported_types: resolution.types,
+            original_rust_module: with_extern_bodies(resolution.module),
             rust_module_for_cpu: None,
             rust_module_for_gpu: None,
             compiled_tokens: None,
@@ -28,6 +51,9 @@ impl CompilationUnit {
             },
         }
     }
+    pub fn kind(&self) -> ModuleKind {
+        self.kind
+    }
     pub fn rust_module_for_gpu(&self) -> &syn::ItemMod {
         if self.rust_module_for_gpu.is_none() {
             panic!("rust_module_for_gpu is not set");
@@ -52,6 +78,14 @@ impl CompilationUnit {
     pub fn original_rust_module(&self) -> &syn::ItemMod {
         &self.original_rust_module
     }
+    /// the original module with the items of the imported libraries and types, which are needed to translate it to WGSL
+    pub fn original_rust_module_with_libraries(&self) -> syn::ItemMod {
+        with_imported_items(
+            &self.original_rust_module,
+            &self.libraries,
+            &self.imported_types,
+        )
+    }
     pub fn set_rust_module_for_gpu(&mut self, rust_module_for_gpu: syn::ItemMod) {
         self.rust_module_for_gpu = Some(rust_module_for_gpu);
     }
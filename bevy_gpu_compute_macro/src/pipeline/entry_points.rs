@@ -0,0 +1,102 @@
+use proc_macro2::TokenStream;
+use syn::{Attribute, Expr, ExprLit, ItemFn, Lit, Meta, MetaNameValue};
+
+/// the `#[wgsl_entry(...)]` attribute of a function
+pub fn entry_attribute(attrs: &[Attribute]) -> Option<&Attribute> {
+    attrs.iter().find(|attr| attr.path().is_ident("wgsl_entry"))
+}
+
+/// `fn main` and the `#[wgsl_entry]` functions are the compute shader entry points of a module
+pub fn is_entry_point(func: &ItemFn) -> bool {
+    func.sig.ident == "main" || entry_attribute(&func.attrs).is_some()
+}
+
+/// nothing, or `workgroup_size = 256`, `workgroup_size = (8, 8)` or `workgroup_size = (4, 4, 4)`. The missing dimensions are 1
+pub fn parse_workgroup_size(tokens: TokenStream) -> syn::Result<Option<[u32; 3]>> {
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+    let name_value: MetaNameValue = syn::parse2(tokens)?;
+    let expected = || {
+        syn::Error::new_spanned(
+            &name_value,
+            "expected `workgroup_size = 64`, `workgroup_size = (8, 8)` or `workgroup_size = (4, 4, 4)`",
+        )
+    };
+    if !name_value.path.is_ident("workgroup_size") {
+        return Err(expected());
+    }
+    let dimensions: Vec<&Expr> = match &name_value.value {
+        Expr::Tuple(tuple) => tuple.elems.iter().collect(),
+        Expr::Paren(paren) => vec![&paren.expr],
+        value => vec![value],
+    };
+    if dimensions.is_empty() || dimensions.len() > 3 {
+        return Err(expected());
+    }
+    let mut size = [1; 3];
+    for (i, dimension) in dimensions.into_iter().enumerate() {
+        let Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) = dimension
+        else {
+            return Err(expected());
+        };
+        size[i] = int.base10_parse::<u32>()?;
+        if size[i] == 0 {
+            return Err(syn::Error::new_spanned(
+                int,
+                "a workgroup size must be at least 1",
+            ));
+        }
+    }
+    Ok(Some(size))
+}
+
+/// the workgroup size of a `#[wgsl_entry]` attribute, `None` if the default for the iteration space should be used
+pub fn workgroup_size(attr: &Attribute) -> syn::Result<Option<[u32; 3]>> {
+    match &attr.meta {
+        Meta::Path(_) => Ok(None),
+        Meta::List(list) => parse_workgroup_size(list.tokens.clone()),
+        meta => Err(syn::Error::new_spanned(
+            meta,
+            "expected `#[wgsl_entry]` or `#[wgsl_entry(workgroup_size = ...)]`",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_workgroup_sizes() {
+        assert_eq!(parse_workgroup_size(quote!()).unwrap(), None);
+        assert_eq!(
+            parse_workgroup_size(quote!(workgroup_size = 256)).unwrap(),
+            Some([256, 1, 1])
+        );
+        assert_eq!(
+            parse_workgroup_size(quote!(workgroup_size = (8, 8))).unwrap(),
+            Some([8, 8, 1])
+        );
+        assert_eq!(
+            parse_workgroup_size(quote!(workgroup_size = (4, 4, 4))).unwrap(),
+            Some([4, 4, 4])
+        );
+        assert!(parse_workgroup_size(quote!(workgroup_size = 0)).is_err());
+        assert!(parse_workgroup_size(quote!(workgroup_size = (1, 1, 1, 1))).is_err());
+        assert!(parse_workgroup_size(quote!(size = 64)).is_err());
+        let func: ItemFn = parse_quote! {
+            #[wgsl_entry]
+            fn clear(iter_pos: WgslIterationPosition) {}
+        };
+        assert!(is_entry_point(&func));
+        assert_eq!(
+            workgroup_size(entry_attribute(&func.attrs).unwrap()).unwrap(),
+            None
+        );
+    }
+}
@@ -8,8 +8,9 @@ use super::phases::{
     non_mutating_tree_validation::compiler_phase::NonMutatingTreeValidation,
     typesafe_buffer_builders_generator::compiler_phase::TypesafeBufferBuildersGenerator,
     wgsl_helper_transformer::compiler_phase::WgslHelperTransformer,
+    wgsl_validation::compiler_phase::WgslValidation,
 };
-use crate::pipeline::compilation_unit::CompilationUnit;
+use crate::pipeline::{compilation_unit::CompilationUnit, library_imports::LibraryResolution};
 
 pub struct CompilerPipeline {
     phases: Vec<Box<dyn CompilerPhase>>,
@@ -26,13 +27,18 @@ impl Default for CompilerPipeline {
                 Box::new(GpuResourceMngmntAndWgslGenerator {}),
                 Box::new(ModuleForRustUsageCleaner {}),
                 Box::new(FinalStructureGenerator {}),
+                Box::new(WgslValidation {}),
             ],
         }
     }
 }
 impl CompilerPipeline {
-    pub fn compile(&self, module: syn::ItemMod) -> TokenStream {
-        let mut unit = CompilationUnit::new(module);
+    /// a module importing libraries or types is only compiled once their sources are collected, see `LibraryResolution`
+    pub fn compile(&self, resolution: LibraryResolution) -> TokenStream {
+        if let Some(request) = resolution.request_next_source() {
+            return request;
+        }
+        let mut unit = CompilationUnit::new(resolution);
         for phase in &self.phases {
             phase.execute(&mut unit);
         }
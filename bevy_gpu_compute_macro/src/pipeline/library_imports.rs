@@ -0,0 +1,513 @@
+use proc_macro_crate::{FoundCrate, crate_name};
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Ident, Item, ItemMod, ItemStruct, ItemUse, Path, PathSegment, Token, Type, UseTree, braced,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+};
+
+use super::compilation_unit::ModuleKind;
+
+/// the path of a `use crate::...` statement, and what it ends with
+fn crate_use_path(use_stmt: &ItemUse) -> Option<(Path, &UseTree)> {
+    let mut segments: Punctuated<PathSegment, Token![::]> = Punctuated::new();
+    let mut tree = &use_stmt.tree;
+    while let UseTree::Path(path) = tree {
+        segments.push(PathSegment::from(path.ident.clone()));
+        tree = &path.tree;
+    }
+    // the path is used from other modules while collecting the sources, so it has to be absolute
+    if use_stmt.leading_colon.is_some() || segments.is_empty() || segments[0].ident != "crate" {
+        return None;
+    }
+    Some((
+        Path {
+            leading_colon: None,
+            segments,
+        },
+        tree,
+    ))
+}
+
+/// `use crate::path::to::library::*;` imports the items of a `#[wgsl_library]` module.
+/// The path of the library, if the use statement is such an import
+pub fn library_import_path(use_stmt: &ItemUse) -> Option<Path> {
+    match crate_use_path(use_stmt)? {
+        (path, UseTree::Glob(_)) if path.segments.len() >= 2 => Some(path),
+        _ => None,
+    }
+}
+
+/// `use crate::path::to::MyType;` imports a struct deriving `WgslType`, which is told apart from the other items by its upper case name.
+/// The path of the type, if the use statement is such an import
+pub fn type_import_path(use_stmt: &ItemUse) -> Option<Path> {
+    match crate_use_path(use_stmt)? {
+        (mut path, UseTree::Name(name))
+            if name.ident.to_string().starts_with(char::is_uppercase) =>
+        {
+            path.segments.push(PathSegment::from(name.ident.clone()));
+            Some(path)
+        }
+        _ => None,
+    }
+}
+
+enum Import {
+    Library(Path),
+    Type(Path),
+}
+
+impl Import {
+    fn path(&self) -> &Path {
+        match self {
+            Import::Library(path) | Import::Type(path) => path,
+        }
+    }
+
+    /// a library has its source macro inside of it, a type next to it
+    fn source_macro_path(&self) -> Path {
+        let mut macro_path = self.path().clone();
+        let ident = macro_path.segments.last().unwrap().ident.clone();
+        match self {
+            Import::Library(_) => {
+                macro_path
+                    .segments
+                    .push(PathSegment::from(library_source_macro_ident(&ident)));
+            }
+            Import::Type(_) => {
+                macro_path.segments.pop();
+                macro_path
+                    .segments
+                    .push(PathSegment::from(type_source_macro_ident(&ident)));
+            }
+        }
+        macro_path
+    }
+}
+
+/// the libraries and types imported by the items of a module
+fn imports(module: &ItemMod) -> Vec<Import> {
+    module
+        .content
+        .as_ref()
+        .map(|(_, items)| {
+            items
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Use(use_stmt) => library_import_path(use_stmt)
+                        .map(Import::Library)
+                        .or_else(|| type_import_path(use_stmt).map(Import::Type)),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_token_stream().to_string()
+}
+
+/**
+ A proc macro only sees the module it is attached to, so the source of the `#[wgsl_library]` modules and `#[derive(WgslType)]` structs that a module imports is collected before the module is compiled:
+ 1. every library and type exports its own source with a `macro_rules!` macro, placed in the library module or next to the struct
+ 2. a module that imports libraries or types expands to a call of the macro of the first import it does not have the source of yet, passing along the module and the sources collected so far
+ 3. the macro appends its source and calls `__resolve_wgsl_libraries!`, which repeats this until the sources of everything imported by the module, and by the libraries, are collected
+ 4. the module is compiled, with the items of the libraries and the imported structs added to the module for the GPU. On the CPU they are used through the use statement like any rust items, so a library type or a derived type is the same rust type in every module importing it
+
+ In tokens: `shader_module { mod ... } crate::a::lib { mod lib ... } crate::b::MyType { struct MyType ... }`
+*/
+pub struct LibraryResolution {
+    pub kind: ModuleKind,
+    pub module: ItemMod,
+    pub libraries: Vec<(Path, ItemMod)>,
+    pub types: Vec<(Path, ItemStruct)>,
+}
+
+impl LibraryResolution {
+    pub fn new(module: ItemMod, kind: ModuleKind) -> Self {
+        LibraryResolution {
+            kind,
+            module,
+            libraries: Vec::new(),
+            types: Vec::new(),
+        }
+    }
+
+    /// the call of the source macro of the next library or type that is imported but not collected yet, `None` once the module can be compiled
+    pub fn request_next_source(&self) -> Option<TokenStream> {
+        let import = self.next_unresolved_import()?;
+        let macro_path = import.source_macro_path();
+        let path = import.path();
+        Some(quote! {
+            #macro_path! { #self #path }
+        })
+    }
+
+    fn next_unresolved_import(&self) -> Option<Import> {
+        let resolved: Vec<String> = self
+            .libraries
+            .iter()
+            .map(|(path, _)| path_key(path))
+            .chain(self.types.iter().map(|(path, _)| path_key(path)))
+            .collect();
+        imports(&self.module)
+            .into_iter()
+            .chain(
+                self.libraries
+                    .iter()
+                    .flat_map(|(_, library)| imports(library)),
+            )
+            .find(|import| !resolved.contains(&path_key(import.path())))
+    }
+
+    pub fn library_modules(&self) -> Vec<ItemMod> {
+        self.libraries
+            .iter()
+            .map(|(_, library)| library.clone())
+            .collect()
+    }
+}
+
+impl Parse for LibraryResolution {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind: Ident = input.parse()?;
+        let kind = match kind.to_string().as_str() {
+            "shader_module" => ModuleKind::ShaderModule,
+            "library" => ModuleKind::Library,
+            _ => return Err(syn::Error::new(kind.span(), "unknown module kind")),
+        };
+        let content;
+        braced!(content in input);
+        let module: ItemMod = content.parse()?;
+        let mut resolution = LibraryResolution::new(module, kind);
+        while !input.is_empty() {
+            let path = Path::parse_mod_style(input)?;
+            let content;
+            braced!(content in input);
+            match content.parse()? {
+                Item::Mod(library) => resolution.libraries.push((path, library)),
+                Item::Struct(item_struct) => resolution.types.push((path, item_struct)),
+                item => return Err(syn::Error::new_spanned(item, "expected a module or struct")),
+            }
+        }
+        Ok(resolution)
+    }
+}
+
+impl ToTokens for LibraryResolution {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let kind = match self.kind {
+            ModuleKind::ShaderModule => format_ident!("shader_module"),
+            ModuleKind::Library => format_ident!("library"),
+        };
+        let module = &self.module;
+        let libraries = self
+            .libraries
+            .iter()
+            .map(|(path, library)| quote!(#path { #library }));
+        let types = self
+            .types
+            .iter()
+            .map(|(path, item_struct)| quote!(#path { #item_struct }));
+        tokens.extend(quote! {
+            #kind { #module } #(#libraries)* #(#types)*
+        });
+    }
+}
+
+fn library_source_macro_ident(library_ident: &Ident) -> Ident {
+    format_ident!("__wgsl_library_source_{}", library_ident)
+}
+
+fn type_source_macro_ident(type_ident: &Ident) -> Ident {
+    format_ident!("__wgsl_type_source_{}", type_ident)
+}
+
+/// the path of `__resolve_wgsl_libraries`, through `bevy_gpu_compute` when the crate depends on it, so that a crate only depending on the facade crate can use libraries
+fn resolve_macro_path() -> TokenStream {
+    let found = crate_name("bevy_gpu_compute")
+        .map(|found| (found, "bevy_gpu_compute"))
+        .or_else(|_| {
+            crate_name("bevy_gpu_compute_macro").map(|found| (found, "bevy_gpu_compute_macro"))
+        });
+    let krate = match found {
+        Ok((FoundCrate::Name(name), _)) => format_ident!("{}", name),
+        // the tests and examples of the crate itself refer to it by its name
+        Ok((FoundCrate::Itself, name)) => format_ident!("{}", name),
+        Err(_) => format_ident!("bevy_gpu_compute_macro"),
+    };
+    quote!(::#krate::__resolve_wgsl_libraries)
+}
+
+/// a macro that appends `source` to the resolution it is called with
+fn source_macro(name: &Ident, source: TokenStream) -> TokenStream {
+    let resolve_macro = resolve_macro_path();
+    quote! {
+        #[doc(hidden)]
+        macro_rules! #name {
+            ($($resolution:tt)*) => {
+                #resolve_macro! { $($resolution)* { #source } }
+            };
+        }
+        #[doc(hidden)]
+        pub(crate) use #name;
+    }
+}
+
+/// the macro that a library exports its source with
+pub fn library_source_macro(library: &ItemMod) -> TokenStream {
+    source_macro(
+        &library_source_macro_ident(&library.ident),
+        library.to_token_stream(),
+    )
+}
+
+/// the macro that a `#[derive(WgslType)]` struct exports its source with, only its doc comments are kept from its attributes
+pub fn type_source_macro(item_struct: &ItemStruct) -> TokenStream {
+    let mut item_struct = item_struct.clone();
+    item_struct.attrs.retain(|attr| attr.path().is_ident("doc"));
+    source_macro(
+        &type_source_macro_ident(&item_struct.ident),
+        item_struct.to_token_stream(),
+    )
+}
+
+/// the structs imported by the use statements among the items, with the attributes of the use statement, like `#[wgsl_input_array]`
+fn imported_type_items(items: &[Item], types: &[(Path, ItemStruct)]) -> Vec<Item> {
+    items
+        .iter()
+        .filter_map(|item| {
+            let Item::Use(use_stmt) = item else {
+                return None;
+            };
+            let path = path_key(&type_import_path(use_stmt)?);
+            let (_, item_struct) = types.iter().find(|(p, _)| path_key(p) == path)?;
+            let mut item_struct = item_struct.clone();
+            item_struct.attrs.extend(use_stmt.attrs.iter().cloned());
+            Some(Item::Struct(item_struct))
+        })
+        .collect()
+}
+
+/// The module with the items of the libraries and the imported structs added, for the GPU.
+/// An item of the module takes precedence over a library item with the same name like with a rust glob import, and of two libraries declaring the same name the first one imported is used.
+pub fn with_imported_items(
+    module: &ItemMod,
+    libraries: &[ItemMod],
+    types: &[(Path, ItemStruct)],
+) -> ItemMod {
+    let mut module = module.clone();
+    let Some((_, items)) = &mut module.content else {
+        return module;
+    };
+    let own_types = imported_type_items(items, types);
+    items.extend(own_types);
+    let mut declared: Vec<String> = items.iter().filter_map(declared_name).collect();
+    for library in libraries {
+        let Some((_, library_items)) = &library.content else {
+            continue;
+        };
+        let mut library_items = library_items.clone();
+        library_items.extend(imported_type_items(&library_items, types));
+        let added: Vec<String> = library_items
+            .iter()
+            .filter_map(declared_name)
+            .filter(|name| !declared.contains(name))
+            .collect();
+        items.extend(library_items.into_iter().filter(|item| {
+            // impl blocks go with the type they are for
+            let name = match item {
+                Item::Impl(impl_block) => impl_type_name(&impl_block.self_ty),
+                item => declared_name(item),
+            };
+            name.is_some_and(|name| added.contains(&name))
+        }));
+        declared.extend(added);
+    }
+    module
+}
+
+fn declared_name(item: &Item) -> Option<String> {
+    match item {
+        Item::Fn(f) => Some(f.sig.ident.to_string()),
+        Item::Struct(s) => Some(s.ident.to_string()),
+        Item::Enum(e) => Some(e.ident.to_string()),
+        Item::Type(t) => Some(t.ident.to_string()),
+        Item::Const(c) => Some(c.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn impl_type_name(self_ty: &Type) -> Option<String> {
+    match self_ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_library_import_path() {
+        let path = |use_stmt: ItemUse| {
+            library_import_path(&use_stmt).map(|p| p.to_token_stream().to_string())
+        };
+        assert_eq!(
+            path(parse_quote!(
+                use crate::shaders::common::*;
+            )),
+            Some("crate :: shaders :: common".to_string())
+        );
+        assert_eq!(
+            path(parse_quote!(
+                use crate::shaders::common::double;
+            )),
+            None
+        );
+        assert_eq!(
+            path(parse_quote!(
+                use super::common::*;
+            )),
+            None
+        );
+        assert_eq!(
+            path(parse_quote!(
+                use bevy_gpu_compute_core::wgsl_helpers::*;
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_type_import_path() {
+        let path = |use_stmt: ItemUse| {
+            type_import_path(&use_stmt).map(|p| p.to_token_stream().to_string())
+        };
+        assert_eq!(
+            path(parse_quote!(
+                use crate::particles::Particle;
+            )),
+            Some("crate :: particles :: Particle".to_string())
+        );
+        assert_eq!(
+            path(parse_quote!(
+                use crate::particles::step;
+            )),
+            None
+        );
+        assert_eq!(
+            path(parse_quote!(
+                use bevy_gpu_compute_core::wgsl_helpers::Vec3F32;
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolution_requests_transitive_imports_once() {
+        let module: ItemMod = parse_quote! {
+            mod shader {
+                use crate::a::lib_a::*;
+                use crate::b::lib_b::*;
+            }
+        };
+        let lib_a: ItemMod = parse_quote! {
+            mod lib_a {
+                use crate::b::lib_b::*;
+                use crate::c::lib_c::*;
+            }
+        };
+        let mut resolution = LibraryResolution::new(module, ModuleKind::ShaderModule);
+        assert_eq!(
+            resolution.request_next_source().unwrap().to_string(),
+            "crate :: a :: lib_a :: __wgsl_library_source_lib_a ! { shader_module { mod shader { use crate :: a :: lib_a :: * ; use crate :: b :: lib_b :: * ; } } crate :: a :: lib_a }"
+        );
+        resolution
+            .libraries
+            .push((parse_quote!(crate::a::lib_a), lib_a));
+        resolution.libraries.push((
+            parse_quote!(crate::b::lib_b),
+            parse_quote!(
+                mod lib_b {}
+            ),
+        ));
+        assert_eq!(
+            resolution
+                .next_unresolved_import()
+                .map(|import| import.path().to_token_stream().to_string()),
+            Some("crate :: c :: lib_c".to_string())
+        );
+        // round trip through the tokens passed between the macros
+        let parsed: LibraryResolution = syn::parse2(resolution.to_token_stream()).unwrap();
+        assert_eq!(parsed.libraries.len(), 2);
+        assert_eq!(parsed.kind, ModuleKind::ShaderModule);
+    }
+
+    #[test]
+    fn test_resolution_requests_imported_types() {
+        let module: ItemMod = parse_quote! {
+            mod shader {
+                #[wgsl_input_array]
+                use crate::particles::Particle;
+            }
+        };
+        let mut resolution = LibraryResolution::new(module.clone(), ModuleKind::ShaderModule);
+        assert_eq!(
+            resolution.request_next_source().unwrap().to_string(),
+            "crate :: particles :: __wgsl_type_source_Particle ! { shader_module { mod shader { # [wgsl_input_array] use crate :: particles :: Particle ; } } crate :: particles :: Particle }"
+        );
+        resolution.types.push((
+            parse_quote!(crate::particles::Particle),
+            parse_quote!(
+                struct Particle {
+                    x: f32,
+                }
+            ),
+        ));
+        assert!(resolution.request_next_source().is_none());
+        let parsed: LibraryResolution = syn::parse2(resolution.to_token_stream()).unwrap();
+        assert_eq!(parsed.types.len(), 1);
+        // the struct takes the attributes of the use statement
+        let merged = with_imported_items(&module, &[], &parsed.types);
+        let Item::Struct(particle) = merged.content.unwrap().1.pop().unwrap() else {
+            panic!("expected the imported struct");
+        };
+        assert!(particle.attrs[0].path().is_ident("wgsl_input_array"));
+    }
+
+    #[test]
+    fn test_module_items_take_precedence() {
+        let module: ItemMod = parse_quote! {
+            mod shader {
+                fn scaled(x: f32) -> f32 { x * 3.0 }
+            }
+        };
+        let library: ItemMod = parse_quote! {
+            mod lib {
+                use bevy_gpu_compute_core::wgsl_helpers::*;
+                pub fn scaled(x: f32) -> f32 { x * 2.0 }
+                pub struct Circle { pub radius: f32 }
+                impl Circle {
+                    pub fn area(&self) -> f32 { self.radius * self.radius }
+                }
+            }
+        };
+        let merged = with_imported_items(&module, &[library.clone(), library], &[]);
+        let names: Vec<String> = merged
+            .content
+            .unwrap()
+            .1
+            .iter()
+            .map(|item| match item {
+                Item::Impl(i) => format!("impl {}", impl_type_name(&i.self_ty).unwrap()),
+                item => declared_name(item).unwrap(),
+            })
+            .collect();
+        assert_eq!(names, vec!["scaled", "Circle", "impl Circle"]);
+    }
+}
@@ -1,5 +1,9 @@
 mod allowed_types;
 mod compilation_metadata;
-mod compilation_unit;
+pub mod compilation_unit;
+pub mod entry_points;
 pub mod lib;
+pub mod library_imports;
 mod phases;
+pub mod raw_functions;
+pub mod wgsl_type_def;
@@ -1,14 +1,17 @@
 // find all user declared types, and make a list of them
 
-// ItemStruct.ident or  ItemType.ident
+// ItemStruct.ident, ItemType.ident or ItemEnum.ident
 
+use proc_macro2::TokenStream;
 use quote::ToTokens;
-use syn::visit::Visit;
+use syn::{Ident, visit::Visit};
 
 use super::custom_type::{CustomType, CustomTypeKind};
 
 struct CustomTypesCollector {
     custom_types: Vec<CustomType>,
+    /// `impl Default for ...` blocks, by the name of the type they are for
+    default_impls: Vec<(Ident, TokenStream)>,
 }
 
 impl<'ast> Visit<'ast> for CustomTypesCollector {
@@ -30,12 +33,36 @@ impl<'ast> Visit<'ast> for CustomTypesCollector {
             i.to_token_stream(),
         ));
     }
+
+    fn visit_item_enum(&mut self, i: &'ast syn::ItemEnum) {
+        syn::visit::visit_item_enum(self, i);
+        self.custom_types.push(CustomType::new(
+            &i.ident,
+            CustomTypeKind::Enum,
+            i.to_token_stream(),
+        ));
+    }
+
+    fn visit_item_impl(&mut self, i: &'ast syn::ItemImpl) {
+        syn::visit::visit_item_impl(self, i);
+        let is_default_impl = i
+            .trait_
+            .as_ref()
+            .is_some_and(|(_, path, _)| path.is_ident("Default"));
+        if let (true, syn::Type::Path(self_type)) = (is_default_impl, &*i.self_ty) {
+            if let Some(ident) = self_type.path.get_ident() {
+                self.default_impls
+                    .push((ident.clone(), i.to_token_stream()));
+            }
+        }
+    }
 }
 
 impl CustomTypesCollector {
     pub fn new() -> Self {
         CustomTypesCollector {
             custom_types: vec![],
+            default_impls: vec![],
         }
     }
 }
@@ -43,5 +70,11 @@ impl CustomTypesCollector {
 pub fn collect_custom_types(original_rust_module: &syn::ItemMod) -> Vec<CustomType> {
     let mut types_collector = CustomTypesCollector::new();
     types_collector.visit_item_mod(original_rust_module);
-    types_collector.custom_types
+    let mut custom_types = types_collector.custom_types;
+    for (ident, default_impl) in types_collector.default_impls {
+        if let Some(custom_type) = custom_types.iter_mut().find(|c| c.name.eq(&ident)) {
+            custom_type.default_impl = Some(default_impl);
+        }
+    }
+    custom_types
 }
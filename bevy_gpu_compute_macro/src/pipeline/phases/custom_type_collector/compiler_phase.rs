@@ -5,7 +5,7 @@ pub struct CustomTypeCollector;
 
 impl CompilerPhase for CustomTypeCollector {
     fn execute(&self, input: &mut CompilationUnit) {
-        let custom_types = collect_custom_types(input.original_rust_module());
+        let custom_types = collect_custom_types(&input.original_rust_module_with_libraries());
         input.set_custom_types(custom_types);
     }
 }
@@ -1,10 +1,16 @@
 use std::alloc::Global;
 
-use bevy_gpu_compute_core::wgsl::shader_sections::{WgslShaderModuleSectionCode, WgslType};
+use bevy_gpu_compute_core::wgsl::{
+    ast,
+    shader_sections::{WgslShaderModuleSectionCode, WgslType},
+};
 use proc_macro2::TokenStream;
 use syn::{Attribute, Ident};
 
-use crate::pipeline::phases::gpu_resource_mngmnt_and_wgsl_generator::to_wgsl_syntax::convert_file_to_wgsl;
+use crate::pipeline::phases::gpu_resource_mngmnt_and_wgsl_generator::{
+    array_configs::{array_config_element, padded_array_config_wgsl},
+    to_wgsl_syntax::lower_file_to_wgsl,
+};
 
 use super::custom_type_idents::CustomTypeIdents;
 
@@ -16,6 +22,8 @@ pub enum CustomTypeKind {
     OutputArray,
     OutputVec,
     ArrayLengthVariable,
+    /// a field-less enum, which becomes `u32` constants in WGSL
+    Enum,
 }
 
 impl From<&Vec<Attribute, Global>> for CustomTypeKind {
@@ -39,6 +47,8 @@ pub struct CustomType {
     pub name: CustomTypeIdents,
     pub kind: CustomTypeKind,
     pub rust_code: TokenStream,
+    /// the `impl Default for ...` of the type in the module, if there is one
+    pub default_impl: Option<TokenStream>,
 }
 impl CustomType {
     pub fn new(name: &Ident, kind: CustomTypeKind, type_def_code: TokenStream) -> Self {
@@ -46,17 +56,25 @@ impl CustomType {
             name: CustomTypeIdents::new(name),
             kind,
             rust_code: type_def_code,
+            default_impl: None,
         }
     }
     pub fn into_wgsl_type(self, custom_types: &Vec<CustomType>) -> WgslType {
+        let items = lower_file_to_wgsl(
+            self.rust_code.clone(),
+            custom_types,
+            "custom_type".to_string(),
+        );
+        let mut wgsl_code: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        if array_config_element(&self).is_some() {
+            if let Some(ast::Item::Alias(alias)) = items.first() {
+                wgsl_code.push(padded_array_config_wgsl(alias));
+            }
+        }
         WgslType {
             name: self.name.into(),
             code: WgslShaderModuleSectionCode {
-                wgsl_code: convert_file_to_wgsl(
-                    self.rust_code,
-                    custom_types,
-                    "custom_type".to_string(),
-                ),
+                wgsl_code: wgsl_code.join("\n"),
             },
         }
     }
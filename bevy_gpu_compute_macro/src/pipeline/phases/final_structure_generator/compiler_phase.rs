@@ -10,11 +10,16 @@ impl CompilerPhase for FinalStructureGenerator {
     fn execute(&self, input: &mut CompilationUnit) {
         let unaltered_module_to_ensure_complete_rust_compiler_checks =
             generate_unaltered_module(input.original_rust_module());
+        let mut wgsl_module_user_portion = input.wgsl_module_user_portion().clone();
         let user_facing_module = generate_user_facing_module(
-            &mut input.wgsl_module_user_portion().clone(),
+            &mut wgsl_module_user_portion,
             input.rust_module_for_cpu(),
             input.typesafe_buffer_builders(),
+            input.kind(),
+            input.original_rust_module(),
         );
+        // keep the binding numbers so that the complete shader can be assembled by later phases
+        input.set_wgsl_module_user_portion(wgsl_module_user_portion);
         input.set_compiled_tokens(quote! {
                 #unaltered_module_to_ensure_complete_rust_compiler_checks
 
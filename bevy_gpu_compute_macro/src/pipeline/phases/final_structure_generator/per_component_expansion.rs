@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use bevy_gpu_compute_core::{
     wgsl::shader_custom_type_name::ShaderCustomTypeName,
     wgsl::shader_sections::{
-        WgslConstAssignment, WgslFunction, WgslInputArray, WgslOutputArray,
+        WgslConstAssignment, WgslEntryPoint, WgslFunction, WgslInputArray, WgslOutputArray,
         WgslShaderModuleSectionCode, WgslType,
     },
 };
@@ -51,6 +51,19 @@ impl ToStructInitializer {
         )
     }
 
+    pub fn wgsl_entry_point(c: &WgslEntryPoint) -> TokenStream {
+        let f = ToStructInitializer::wgsl_function(&c.function);
+        let w = c
+            .workgroup_size
+            .map_or(quote!(None), |[x, y, z]| quote!(Some([#x, #y, #z])));
+        quote!(
+            WgslEntryPoint {
+                function: #f,
+                workgroup_size: #w,
+            }
+        )
+    }
+
     pub fn wgsl_const_assignment(c: &WgslConstAssignment) -> TokenStream {
         let c = ToStructInitializer::wgsl_shader_module_component(&c.code);
         quote!(
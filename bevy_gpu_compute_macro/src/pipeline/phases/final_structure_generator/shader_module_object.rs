@@ -61,14 +61,14 @@ pub fn generate_shader_module_object(
         })
         .collect();
 
-    let main_function: TokenStream =
-        wgsl_shader_module
-            .main_function
-            .as_ref()
-            .map_or(quote!(None), |func| {
-                let ts = ToStructInitializer::wgsl_function(func);
-                quote!(Some(#ts))
-            });
+    let entry_points: TokenStream = wgsl_shader_module
+        .entry_points
+        .iter()
+        .map(|entry_point| {
+            let ts = ToStructInitializer::wgsl_entry_point(entry_point);
+            quote!(#ts,)
+        })
+        .collect();
     let bindings_map: TokenStream = ToStructInitializer::hash_map(
         wgsl_shader_module
             .binding_numbers_by_variable_name
@@ -102,7 +102,10 @@ pub fn generate_shader_module_object(
                     #helper_functions
                     ]
                 .into(),
-                main_function: #main_function,
+                entry_points: [
+                    #entry_points
+                    ]
+                .into(),
                 binding_numbers_by_variable_name: Some(#bindings_map),
             }
         }
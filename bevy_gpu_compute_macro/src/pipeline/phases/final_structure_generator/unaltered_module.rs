@@ -2,8 +2,16 @@ use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
 use syn::ItemMod;
 
+use crate::pipeline::phases::module_for_rust_usage_cleaner::{
+    make_array_indices_usize::make_array_indices_usize, make_enums_pod::add_struct_derives_to_enums,
+};
+
 /// make a module that is not intended to be viewed or accessed just to allow the rust compiler to run and find any potential errors in the original code that might be missed elsewhere in our macro if we remove or alter parts of the original code.
+/// Array indices are still cast to `usize` like in the module for the CPU, since the `u32` indices that WGSL uses would not compile, and enums get the derives their CPU structs always have
 pub fn generate_unaltered_module(original_module: &ItemMod) -> TokenStream {
+    let mut original_module = original_module.clone();
+    make_array_indices_usize(&mut original_module);
+    add_struct_derives_to_enums(&mut original_module);
     let original_ident = &original_module.ident;
     let content: Vec<TokenStream> = if let Some(content) = &original_module.content {
         content
@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+
 use bevy_gpu_compute_core::wgsl::shader_module::user_defined_portion::WgslShaderModuleUserPortion;
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 use syn::ItemMod;
 
-use crate::pipeline::phases::final_structure_generator::generate_required_imports::generate_required_imports;
+use crate::pipeline::{
+    compilation_unit::ModuleKind, library_imports::library_source_macro,
+    phases::final_structure_generator::generate_required_imports::generate_required_imports,
+};
 
 use super::{
     shader_module_object::generate_shader_module_object,
@@ -14,8 +19,19 @@ pub fn generate_user_facing_module(
     wgsl_shader_module: &mut WgslShaderModuleUserPortion,
     rust_module_for_cpu: &ItemMod,
     builders: &TokenStream,
+    kind: ModuleKind,
+    original_module: &ItemMod,
 ) -> TokenStream {
-    let generated_types = define_types_for_use_in_rust_and_set_binding_numbers(wgsl_shader_module);
+    let generated_types = match kind {
+        ModuleKind::ShaderModule => {
+            define_types_for_use_in_rust_and_set_binding_numbers(wgsl_shader_module)
+        }
+        // a library has no buffers to bind, instead it exports its source for the modules importing it
+        ModuleKind::Library => {
+            wgsl_shader_module.binding_numbers_by_variable_name = Some(HashMap::new());
+            library_source_macro(original_module)
+        }
+    };
     let generated_shader_module_object = generate_shader_module_object(wgsl_shader_module);
     let required_imports = generate_required_imports();
     let user_module_content: TokenStream = rust_module_for_cpu
@@ -0,0 +1,62 @@
+use bevy_gpu_compute_core::wgsl::{ast, shader_custom_type_name::ShaderCustomTypeName};
+use proc_macro_error::abort;
+use quote::format_ident;
+use syn::{Ident, ItemType, Type, parse2, spanned::Spanned};
+
+use crate::pipeline::phases::custom_type_collector::custom_type::{CustomType, CustomTypeKind};
+
+/**
+ Arrays in uniform buffers need a stride that is a multiple of 16 bytes, which an array config like `type MyPosition = [f32; 2];` does not have, so on the GPU it is stored with padded elements:
+ ```ignore
+ struct MyPosition_uniform_element {
+     @align(16) value: f32,
+ }
+ alias MyPosition_uniform = array<MyPosition_uniform_element, 2>;
+ fn MyPosition_from_uniform(padded: MyPosition_uniform) -> MyPosition { ... }
+ ```
+ `WgslConfigInput::get::<MyPosition>()` becomes `MyPosition_from_uniform(myposition)`, and `ConfigInputDataBuilder::set_my_position` puts every element at the start of its 16 byte slot.
+
+ Returns the element type if `custom_type` is an array config.
+*/
+pub fn array_config_element(custom_type: &CustomType) -> Option<Type> {
+    if custom_type.kind != CustomTypeKind::Uniform {
+        return None;
+    }
+    let item = parse2::<ItemType>(custom_type.rust_code.clone()).ok()?;
+    let Type::Array(array) = &*item.ty else {
+        return None;
+    };
+    if let Type::Array(element) = &*array.elem {
+        abort!(
+            element.span(),
+            "Arrays of arrays cannot be used as a config, since every array element in a uniform buffer has to start at a multiple of 16 bytes. Use an array of a struct instead"
+        );
+    }
+    Some(*array.elem.clone())
+}
+
+/// the WGSL function that turns the padded uniform of an array config back into the array
+pub fn from_uniform_function(config_name: &Ident) -> Ident {
+    format_ident!("{}_from_uniform", config_name)
+}
+
+/// the padded layout of an array config, `alias` is the WGSL of the config type itself, like `alias MyPosition = array<f32, 2>;`
+pub fn padded_array_config_wgsl(alias: &ast::Alias) -> String {
+    let [ast::TemplateArg::Type(element), ast::TemplateArg::Expr(len)] =
+        alias.ty.template_args.as_slice()
+    else {
+        abort!(
+            proc_macro2::Span::call_site(),
+            "Expected the config `{}` to be an array",
+            alias.name
+        );
+    };
+    let name = &alias.name;
+    let layout = ShaderCustomTypeName::new(name).uniform_layout();
+    let from_uniform = format_ident!("{}_from_uniform", name);
+    format!(
+        "struct {name}_uniform_element {{\n    @align(16) value: {element},\n}}\n\
+        alias {layout} = array<{name}_uniform_element, {len}>;\n\
+        fn {from_uniform}(padded: {layout}) -> {name} {{\n    var value: {name};\n    for (var i = 0u; i < {len}; i += 1u) {{\n        value[i] = padded[i].value;\n    }}\n    return value;\n}}"
+    )
+}
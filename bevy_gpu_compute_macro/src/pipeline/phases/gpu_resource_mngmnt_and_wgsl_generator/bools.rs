@@ -0,0 +1,378 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro_error::abort;
+use quote::ToTokens;
+use syn::{
+    BinOp, Expr, ExprField, Fields, FnArg, Item, ItemMod, ItemStruct, Member, Pat, Type,
+    parse_quote, parse2, spanned::Spanned, visit::Visit, visit_mut::VisitMut,
+};
+
+use super::type_inference::ModuleTypes;
+use crate::pipeline::phases::custom_type_collector::custom_type::{CustomType, CustomTypeKind};
+
+/**
+ WGSL does not allow `bool` in buffers, and `bool` is not a "Pod" for bytemuck, so the `bool` fields of the input, output and config types are stored as a `u32` on the GPU and as a `WgslBool` on the CPU, while they are still used as bools in the rest of the module:
+ - `config.enabled` => `(config.enabled != 0u32)` on the GPU, `bool::from(config.enabled)` on the CPU
+ - `item.enabled = x` => `item.enabled = x as u32` on the GPU, `item.enabled = WgslBool::from(x)` on the CPU
+ - `Item { enabled: x }` => `Item { enabled: x as u32 }` on the GPU, `Item { enabled: WgslBool::from(x) }` on the CPU
+
+ Types used only on the GPU keep their bools.
+*/
+#[derive(Clone, Copy, PartialEq)]
+pub enum BoolStorage {
+    /// the GPU side
+    U32,
+    /// the CPU side
+    WgslBool,
+}
+
+/// the module and custom types for the GPU, with the `bool` fields of the transferred types stored as `u32`
+pub fn lower_transferred_bools(
+    module: &ItemMod,
+    custom_types: &[CustomType],
+) -> (ItemMod, Vec<CustomType>) {
+    let mut module = module.clone();
+    store_transferred_bools(&mut module, custom_types, BoolStorage::U32);
+    let custom_types = custom_types
+        .iter()
+        .map(|custom_type| {
+            if !is_transferred(custom_type) {
+                return custom_type.clone();
+            }
+            let mut custom_type = custom_type.clone();
+            if let Ok(mut item_struct) = parse2::<ItemStruct>(custom_type.rust_code.clone()) {
+                replace_bool_fields(&mut item_struct, BoolStorage::U32);
+                custom_type.rust_code = item_struct.to_token_stream();
+            }
+            custom_type
+        })
+        .collect();
+    (module, custom_types)
+}
+
+/// changes the definitions of the transferred types in the module, and every use of their `bool` fields
+pub fn store_transferred_bools(
+    module: &mut ItemMod,
+    custom_types: &[CustomType],
+    storage: BoolStorage,
+) {
+    let Some((_, items)) = &mut module.content else {
+        return;
+    };
+    let transferred: HashSet<String> = custom_types
+        .iter()
+        .filter(|c| is_transferred(c))
+        .map(|c| c.name.name.to_string())
+        .collect();
+    let mut bool_fields: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut module_types = ModuleTypes::default();
+    for item in items.iter_mut() {
+        module_types.add(item);
+        match item {
+            Item::Struct(item_struct) if transferred.contains(&item_struct.ident.to_string()) => {
+                let fields = replace_bool_fields(item_struct, storage);
+                if !fields.is_empty() {
+                    bool_fields.insert(item_struct.ident.to_string(), fields);
+                }
+            }
+            Item::Type(alias) if transferred.contains(&alias.ident.to_string()) => {
+                check_no_bools(&alias.ty);
+            }
+            _ => {}
+        }
+    }
+    if bool_fields.is_empty() {
+        return;
+    }
+    BoolFieldMarshaller {
+        storage,
+        bool_fields,
+        module_types,
+        var_types: HashMap::new(),
+        self_type: None,
+    }
+    .visit_item_mod_mut(module);
+}
+
+fn is_transferred(custom_type: &CustomType) -> bool {
+    matches!(
+        custom_type.kind,
+        CustomTypeKind::Uniform
+            | CustomTypeKind::InputArray
+            | CustomTypeKind::OutputArray
+            | CustomTypeKind::OutputVec
+    )
+}
+
+/// returns the names of the fields that were changed
+fn replace_bool_fields(item_struct: &mut ItemStruct, storage: BoolStorage) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let Fields::Named(fields) = &mut item_struct.fields else {
+        return names;
+    };
+    for field in fields.named.iter_mut() {
+        if matches!(&field.ty, Type::Path(p) if p.path.is_ident("bool")) {
+            field.ty = match storage {
+                BoolStorage::U32 => parse_quote!(u32),
+                BoolStorage::WgslBool => {
+                    parse_quote!(bevy_gpu_compute_core::wgsl_helpers::WgslBool)
+                }
+            };
+            names.insert(field.ident.as_ref().unwrap().to_string());
+        } else {
+            check_no_bools(&field.ty);
+        }
+    }
+    names
+}
+
+/// bools that are not directly a field cannot be stored as a `u32`
+fn check_no_bools(t: &Type) {
+    struct BoolFinder;
+    impl<'ast> Visit<'ast> for BoolFinder {
+        fn visit_ident(&mut self, ident: &'ast proc_macro2::Ident) {
+            let name = ident.to_string();
+            if name == "bool" || (name.ends_with("Bool") && name != "WgslBool") {
+                abort!(
+                    ident.span(),
+                    "Only `bool` fields can be used in the input, output and config types, since WGSL cannot put bools in buffers. Use a `u32` based type instead of `{}` (0 = false, 1 = true)",
+                    name
+                );
+            }
+        }
+    }
+    BoolFinder.visit_type(t);
+}
+
+struct BoolFieldMarshaller {
+    storage: BoolStorage,
+    /// the `bool` fields of each transferred type
+    bool_fields: HashMap<String, HashSet<String>>,
+    module_types: ModuleTypes,
+    var_types: HashMap<String, Type>,
+    /// the type of `self` and `Self` in impl blocks
+    self_type: Option<Type>,
+}
+
+impl BoolFieldMarshaller {
+    fn read(&self, field: ExprField) -> Expr {
+        match self.storage {
+            BoolStorage::U32 => parse_quote!((#field != 0u32)),
+            BoolStorage::WgslBool => parse_quote!(bool::from(#field)),
+        }
+    }
+    fn write(&self, value: &Expr) -> Expr {
+        match self.storage {
+            BoolStorage::U32 => match value {
+                Expr::Path(_)
+                | Expr::Lit(_)
+                | Expr::Paren(_)
+                | Expr::Call(_)
+                | Expr::MethodCall(_)
+                | Expr::Field(_) => parse_quote!(#value as u32),
+                value => parse_quote!((#value) as u32),
+            },
+            BoolStorage::WgslBool => {
+                parse_quote!(bevy_gpu_compute_core::wgsl_helpers::WgslBool::from(#value))
+            }
+        }
+    }
+    fn struct_name(&self, t: &Type) -> Option<String> {
+        match t {
+            Type::Reference(reference) => self.struct_name(&reference.elem),
+            Type::Paren(paren) => self.struct_name(&paren.elem),
+            Type::Path(path) if path.path.is_ident("Self") => {
+                self.struct_name(self.self_type.as_ref()?)
+            }
+            Type::Path(path) => Some(path.path.segments.last()?.ident.to_string()),
+            _ => None,
+        }
+    }
+    /// whether this is a `bool` field of a transferred type
+    fn is_stored_bool(&self, field: &ExprField) -> bool {
+        let Member::Named(member) = &field.member else {
+            return false;
+        };
+        let member = member.to_string();
+        if !self.bool_fields.values().any(|f| f.contains(&member)) {
+            return false;
+        }
+        if let Some(base) = self.struct_name_of(&field.base) {
+            return self
+                .bool_fields
+                .get(&base)
+                .is_some_and(|f| f.contains(&member));
+        }
+        // the field name alone has to be enough
+        let other_uses = self
+            .module_types
+            .struct_fields
+            .iter()
+            .filter(|(name, fields)| {
+                fields.contains_key(&member)
+                    && !self
+                        .bool_fields
+                        .get(*name)
+                        .is_some_and(|f| f.contains(&member))
+            })
+            .count();
+        if other_uses > 0 {
+            abort!(
+                field.span(),
+                "`{}` is a bool field of an input, output or config type, which is stored as a u32 in WGSL, but another type has a field with the same name. Give the variable an explicit type so that the right one can be used",
+                member
+            );
+        }
+        true
+    }
+    fn struct_name_of(&self, expr: &Expr) -> Option<String> {
+        if let Expr::Path(path) = expr {
+            if path.path.is_ident("self") {
+                return self.struct_name(self.self_type.as_ref()?);
+            }
+        }
+        let t = self.module_types.type_of(expr, &self.var_types)?;
+        self.struct_name(&t)
+    }
+    fn set_params(&mut self, inputs: &syn::punctuated::Punctuated<FnArg, syn::token::Comma>) {
+        self.var_types.clear();
+        for input in inputs.iter() {
+            if let FnArg::Typed(pat_type) = input {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    self.var_types
+                        .insert(pat_ident.ident.to_string(), *pat_type.ty.clone());
+                }
+            }
+        }
+    }
+}
+
+impl VisitMut for BoolFieldMarshaller {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut syn::ItemFn) {
+        self.set_params(&item_fn.sig.inputs);
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+    }
+    fn visit_item_impl_mut(&mut self, item_impl: &mut syn::ItemImpl) {
+        self.self_type = Some(*item_impl.self_ty.clone());
+        syn::visit_mut::visit_item_impl_mut(self, item_impl);
+        self.self_type = None;
+    }
+    fn visit_impl_item_fn_mut(&mut self, method: &mut syn::ImplItemFn) {
+        self.set_params(&method.sig.inputs);
+        syn::visit_mut::visit_impl_item_fn_mut(self, method);
+    }
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        syn::visit_mut::visit_local_mut(self, local);
+        self.module_types.add_local(local, &mut self.var_types);
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Field(field) if self.is_stored_bool(field) => {
+                self.visit_expr_mut(&mut field.base);
+                *expr = self.read(field.clone());
+            }
+            Expr::Assign(assign) => match &mut *assign.left {
+                Expr::Field(field) if self.is_stored_bool(field) => {
+                    self.visit_expr_mut(&mut field.base);
+                    self.visit_expr_mut(&mut assign.right);
+                    *assign.right = self.write(&assign.right);
+                }
+                _ => syn::visit_mut::visit_expr_assign_mut(self, assign),
+            },
+            Expr::Binary(binary)
+                if matches!(
+                    binary.op,
+                    BinOp::BitAndAssign(_) | BinOp::BitOrAssign(_) | BinOp::BitXorAssign(_)
+                ) && matches!(&*binary.left, Expr::Field(field) if self.is_stored_bool(field)) =>
+            {
+                // `a.enabled &= x` => `a.enabled = (a.enabled & x)`, with the conversions
+                let Expr::Field(mut field) = *binary.left.clone() else {
+                    unreachable!()
+                };
+                self.visit_expr_mut(&mut field.base);
+                self.visit_expr_mut(&mut binary.right);
+                let current = self.read(field.clone());
+                let right = &binary.right;
+                let value: Expr = match binary.op {
+                    BinOp::BitAndAssign(_) => parse_quote!(#current & #right),
+                    BinOp::BitOrAssign(_) => parse_quote!(#current | #right),
+                    _ => parse_quote!(#current ^ #right),
+                };
+                let value = self.write(&value);
+                *expr = parse_quote!(#field = #value);
+            }
+            Expr::Struct(expr_struct) => {
+                syn::visit_mut::visit_expr_struct_mut(self, expr_struct);
+                let Some(fields) = expr_struct
+                    .path
+                    .segments
+                    .last()
+                    .map(|s| -> Type {
+                        let ident = &s.ident;
+                        parse_quote!(#ident)
+                    })
+                    .and_then(|t| self.struct_name(&t))
+                    .and_then(|name| self.bool_fields.get(&name))
+                    .cloned()
+                else {
+                    return;
+                };
+                for field_value in expr_struct.fields.iter_mut() {
+                    let Member::Named(member) = &field_value.member else {
+                        continue;
+                    };
+                    if fields.contains(&member.to_string()) {
+                        field_value.expr = self.write(&field_value.expr);
+                        // `Item { enabled }` needs the `enabled:` now
+                        field_value.colon_token = Some(Default::default());
+                    }
+                }
+            }
+            _ => syn::visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn test_bool_fields_are_stored_as_u32() {
+        let mut module: ItemMod = parse_quote! {
+            mod m {
+                struct Item { enabled: bool, value: f32 }
+                struct Helper { on: bool }
+                fn flip(item: Item, h: Helper) -> Item {
+                    let mut out = Item { enabled: !item.enabled && h.on, value: item.value };
+                    out.enabled = true;
+                    out.enabled |= false;
+                    let enabled = out.enabled;
+                    Item { enabled, ..out }
+                }
+            }
+        };
+        let custom_types = [CustomType::new(
+            &parse_quote!(Item),
+            CustomTypeKind::InputArray,
+            quote!(
+                struct Item {
+                    enabled: bool,
+                    value: f32,
+                }
+            ),
+        )];
+        store_transferred_bools(&mut module, &custom_types, BoolStorage::U32);
+        assert_eq!(
+            module.to_token_stream().to_string(),
+            "mod m { struct Item { enabled : u32 , value : f32 } struct Helper { on : bool } \
+            fn flip (item : Item , h : Helper) -> Item { \
+            let mut out = Item { enabled : (! (item . enabled != 0u32) && h . on) as u32 , value : item . value } ; \
+            out . enabled = true as u32 ; \
+            out . enabled = ((out . enabled != 0u32) | false) as u32 ; \
+            let enabled = (out . enabled != 0u32) ; \
+            Item { enabled : enabled as u32 , .. out } } }"
+        );
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro_error::abort;
+use quote::format_ident;
+use syn::{
+    BinOp, Block, Expr, ExprClosure, FnArg, Ident, Item, ItemFn, ItemMod, Pat, ReturnType, Stmt,
+    Type, parse_quote, spanned::Spanned, visit::Visit, visit_mut::VisitMut,
+};
+
+use super::type_inference::ModuleTypes;
+
+/**
+ WGSL has no closures, so closures are lifted into functions of the module, with the variables they capture as extra parameters:
+ ```ignore
+ fn main() {
+     let scale = 2.0;
+     let scaled_sq = |x: f32| x * x * scale;
+     let y = scaled_sq(3.0);
+ }
+ ```
+ becomes
+ ```ignore
+ fn main() {
+     let scale = 2.0;
+     let y = main_scaled_sq(3.0, scale);
+ }
+ fn main_scaled_sq(x: f32, scale: f32) -> f32 { x * x * scale }
+ ```
+ If a captured variable is declared again after the closure, like `let scale = 3.0;`, the closure keeps seeing the old value, so that value is first copied into a hidden variable, `let scaled_sq_captured_scale = scale;`, which is passed instead.
+
+ This only works for closures that are stored with `let` and then only called, that have explicit parameter types, and that do not change the variables they capture.
+ Only the module for the GPU is changed.
+*/
+pub fn lift_closures(module: &ItemMod) -> ItemMod {
+    let mut module = module.clone();
+    let Some((_, items)) = &mut module.content else {
+        return module;
+    };
+    let mut module_types = ModuleTypes::default();
+    let mut used_names = HashSet::new();
+    for item in items.iter() {
+        module_types.add(item);
+        if let Item::Fn(item_fn) = item {
+            used_names.insert(item_fn.sig.ident.to_string());
+        }
+    }
+    let mut lifted = Vec::new();
+    for item in items.iter_mut() {
+        if let Item::Fn(item_fn) = item {
+            let mut lifter = ClosureLifter::new(item_fn, &module_types, &mut used_names);
+            lifter.visit_block_mut(&mut item_fn.block);
+            lifted.extend(lifter.lifted.into_iter().map(Item::Fn));
+        }
+    }
+    items.extend(lifted);
+    module
+}
+
+struct LiftedClosure {
+    name: Ident,
+    /// the values passed for the captured variables, which are the variables themselves unless they were copied
+    capture_args: Vec<Ident>,
+}
+
+/// what is known about the body of the closure that is being lifted
+struct CaptureState {
+    /// variables of the enclosing function that the closure can see
+    outer: HashSet<String>,
+    /// outer variables that are shadowed by parameters or variables of the closure
+    shadowed: HashSet<String>,
+    captured: Vec<Ident>,
+}
+
+struct ClosureLifter<'a> {
+    module_types: &'a ModuleTypes,
+    used_names: &'a mut HashSet<String>,
+    fn_name: Ident,
+    var_types: HashMap<String, Type>,
+    /// parameters and local variables of the enclosing function
+    scope: HashSet<String>,
+    mutable_locals: HashSet<String>,
+    closures: HashMap<String, LiftedClosure>,
+    capture: Option<CaptureState>,
+    lifted: Vec<ItemFn>,
+}
+
+impl<'a> ClosureLifter<'a> {
+    fn new(
+        item_fn: &ItemFn,
+        module_types: &'a ModuleTypes,
+        used_names: &'a mut HashSet<String>,
+    ) -> Self {
+        let mut var_types = HashMap::new();
+        let mut scope = HashSet::new();
+        for input in item_fn.sig.inputs.iter() {
+            if let FnArg::Typed(pat_type) = input {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    let name = pat_ident.ident.to_string();
+                    var_types.insert(name.clone(), *pat_type.ty.clone());
+                    scope.insert(name);
+                }
+            }
+        }
+        ClosureLifter {
+            module_types,
+            used_names,
+            fn_name: item_fn.sig.ident.clone(),
+            var_types,
+            scope,
+            mutable_locals: HashSet::new(),
+            closures: HashMap::new(),
+            capture: None,
+            lifted: Vec::new(),
+        }
+    }
+
+    /// lifts the closure, and returns the statements that copy the captured variables in `redeclared` which are declared again later
+    fn lift(
+        &mut self,
+        name: &Ident,
+        closure: &ExprClosure,
+        redeclared: &HashSet<String>,
+    ) -> Vec<Stmt> {
+        if self.capture.is_some() {
+            abort!(
+                closure.span(),
+                "Closures cannot be defined inside other closures in WGSL"
+            );
+        }
+        let mut var_types = self.var_types.clone();
+        let mut shadowed = HashSet::new();
+        let mut params: Vec<FnArg> = closure
+            .inputs
+            .iter()
+            .map(|input| {
+                let Pat::Type(pat_type) = input else {
+                    abort!(
+                        input.span(),
+                        "Closure parameters need an explicit type in WGSL, like `|x: f32|`"
+                    );
+                };
+                let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                    abort!(
+                        pat_type.pat.span(),
+                        "Closure parameters must be simple names in WGSL"
+                    );
+                };
+                let param_name = pat_ident.ident.to_string();
+                var_types.insert(param_name.clone(), *pat_type.ty.clone());
+                shadowed.insert(param_name);
+                parse_quote!(#pat_type)
+            })
+            .collect();
+        let mut body = *closure.body.clone();
+        let outer_var_types = std::mem::replace(&mut self.var_types, var_types);
+        self.capture = Some(CaptureState {
+            outer: self.scope.clone(),
+            shadowed,
+            captured: Vec::new(),
+        });
+        self.visit_expr_mut(&mut body);
+        let capture = self.capture.take().unwrap();
+        let output = match &closure.output {
+            ReturnType::Type(..) => closure.output.clone(),
+            ReturnType::Default => match self.returned_expr(&body) {
+                None => ReturnType::Default,
+                Some(returned) => {
+                    let t = self
+                        .module_types
+                        .type_of(returned, &self.var_types)
+                        .unwrap_or_else(|| {
+                            abort!(
+                                returned.span(),
+                                "Cannot work out what this closure returns, add the return type like `|x: f32| -> f32 {{ ... }}`"
+                            )
+                        });
+                    parse_quote!(-> #t)
+                }
+            },
+        };
+        self.var_types = outer_var_types;
+        let captures = capture.captured;
+        let capture_params = captures.iter().map(|captured| {
+            if closure.capture.is_some() && self.mutable_locals.contains(&captured.to_string()) {
+                abort!(
+                    captured.span(),
+                    "A `move` closure keeps the value `{}` had when the closure was created, which WGSL cannot do for variables that change. Copy it into a variable without `mut` first",
+                    captured
+                );
+            }
+            let t = self
+                .var_types
+                .get(&captured.to_string())
+                .unwrap_or_else(|| {
+                    abort!(
+                        captured.span(),
+                        "Cannot work out the type of `{}`, which is captured by a closure. Give it an explicit type where it is declared",
+                        captured
+                    )
+                });
+            let param: FnArg = parse_quote!(#captured: #t);
+            param
+        });
+        params.extend(capture_params);
+        let block: Block = match body {
+            Expr::Block(block) if block.label.is_none() => block.block,
+            body => parse_quote!({ #body }),
+        };
+        let lifted_name = self.unused_name(name);
+        self.lifted.push(parse_quote! {
+            fn #lifted_name(#(#params),*) #output #block
+        });
+        let mut copies = Vec::new();
+        let capture_args = captures
+            .into_iter()
+            .map(|captured| {
+                if !redeclared.contains(&captured.to_string()) {
+                    return captured;
+                }
+                let copy = format_ident!("{}_captured_{}", name, captured);
+                copies.push(parse_quote!(let #copy = #captured;));
+                if let Some(t) = self.var_types.get(&captured.to_string()).cloned() {
+                    self.var_types.insert(copy.to_string(), t);
+                }
+                self.scope.insert(copy.to_string());
+                copy
+            })
+            .collect();
+        self.closures.insert(
+            name.to_string(),
+            LiftedClosure {
+                name: lifted_name,
+                capture_args,
+            },
+        );
+        copies
+    }
+
+    fn returned_expr<'e>(&self, body: &'e Expr) -> Option<&'e Expr> {
+        match body {
+            Expr::Block(block) => match block.block.stmts.last() {
+                Some(Stmt::Expr(expr, None)) => Some(expr),
+                _ => None,
+            },
+            body => Some(body),
+        }
+    }
+
+    fn unused_name(&mut self, closure_name: &Ident) -> Ident {
+        let mut name = format_ident!("{}_{}", self.fn_name, closure_name);
+        let mut n: u32 = 1;
+        while self.used_names.contains(&name.to_string()) {
+            n += 1;
+            name = format_ident!("{}_{}_{}", self.fn_name, closure_name, n);
+        }
+        self.used_names.insert(name.to_string());
+        name
+    }
+
+    /// the variable of the enclosing function that this expression refers to, if it is captured by the closure being lifted
+    fn captured_ident<'e>(&self, expr: &'e Expr) -> Option<&'e Ident> {
+        let capture = self.capture.as_ref()?;
+        let ident = match expr {
+            Expr::Path(path) => path.path.get_ident()?,
+            Expr::Field(field) => return self.captured_ident(&field.base),
+            Expr::Index(index) => return self.captured_ident(&index.expr),
+            Expr::Paren(paren) => return self.captured_ident(&paren.expr),
+            Expr::Unary(unary) => return self.captured_ident(&unary.expr),
+            _ => return None,
+        };
+        let name = ident.to_string();
+        (capture.outer.contains(&name) && !capture.shadowed.contains(&name)).then_some(ident)
+    }
+
+    fn check_not_captured(&self, target: &Expr) {
+        if let Some(ident) = self.captured_ident(target) {
+            abort!(
+                target.span(),
+                "Closures cannot change the variables they capture in WGSL, `{}` is captured here. Pass it in and return the new value instead",
+                ident
+            );
+        }
+    }
+}
+
+/// the names of all variables declared in `stmts`, including nested blocks
+fn declared_names(stmts: &[Stmt]) -> HashSet<String> {
+    struct DeclaredNames(HashSet<String>);
+    impl Visit<'_> for DeclaredNames {
+        fn visit_pat_ident(&mut self, pat_ident: &syn::PatIdent) {
+            self.0.insert(pat_ident.ident.to_string());
+            syn::visit::visit_pat_ident(self, pat_ident);
+        }
+    }
+    let mut names = DeclaredNames(HashSet::new());
+    for stmt in stmts {
+        names.visit_stmt(stmt);
+    }
+    names.0
+}
+
+fn closure_binding(local: &syn::Local) -> Option<(&Ident, &ExprClosure)> {
+    let Pat::Ident(pat_ident) = &local.pat else {
+        return None;
+    };
+    match &*local.init.as_ref()?.expr {
+        Expr::Closure(closure) => Some((&pat_ident.ident, closure)),
+        _ => None,
+    }
+}
+
+impl VisitMut for ClosureLifter<'_> {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let stmts = std::mem::take(&mut block.stmts);
+        for (i, stmt) in stmts.iter().enumerate() {
+            if let Stmt::Local(local) = stmt {
+                if let Some((name, closure)) = closure_binding(local) {
+                    let redeclared = declared_names(&stmts[i + 1..]);
+                    let copies = self.lift(name, closure, &redeclared);
+                    block.stmts.extend(copies);
+                    continue;
+                }
+            }
+            let mut stmt = stmt.clone();
+            self.visit_stmt_mut(&mut stmt);
+            block.stmts.push(stmt);
+        }
+    }
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        syn::visit_mut::visit_local_mut(self, local);
+        self.module_types.add_local(local, &mut self.var_types);
+        let pat = match &local.pat {
+            Pat::Type(pat_type) => &*pat_type.pat,
+            pat => pat,
+        };
+        if let Pat::Ident(pat_ident) = pat {
+            let name = pat_ident.ident.to_string();
+            self.closures.remove(&name);
+            match &mut self.capture {
+                Some(capture) => {
+                    capture.shadowed.insert(name);
+                }
+                None => {
+                    if pat_ident.mutability.is_some() {
+                        self.mutable_locals.insert(name.clone());
+                    } else {
+                        self.mutable_locals.remove(&name);
+                    }
+                    self.scope.insert(name);
+                }
+            }
+        }
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Closure(closure) => abort!(
+                closure.span(),
+                "Closures can only be stored in a variable with `let` and then called in WGSL, like `let sq = |x: f32| x * x; sq(a)`"
+            ),
+            Expr::Call(call) => {
+                for arg in call.args.iter_mut() {
+                    self.visit_expr_mut(arg);
+                }
+                let Expr::Path(func) = &*call.func else {
+                    self.visit_expr_mut(&mut call.func);
+                    return;
+                };
+                let Some(lifted) = func
+                    .path
+                    .get_ident()
+                    .and_then(|ident| self.closures.get(&ident.to_string()))
+                else {
+                    self.visit_expr_mut(&mut call.func);
+                    return;
+                };
+                let name = &lifted.name;
+                *call.func = parse_quote!(#name);
+                for captured in lifted.capture_args.clone() {
+                    let mut arg: Expr = parse_quote!(#captured);
+                    // the captured variables are captured by the closure being lifted as well
+                    self.visit_expr_mut(&mut arg);
+                    call.args.push(arg);
+                }
+            }
+            Expr::Path(path) => {
+                if let Some(ident) = path.path.get_ident() {
+                    if self.closures.contains_key(&ident.to_string()) {
+                        abort!(
+                            ident.span(),
+                            "`{}` is a closure, which can only be called in WGSL, not passed on or stored",
+                            ident
+                        );
+                    }
+                    if let Some(ident) = self.captured_ident(expr).cloned() {
+                        let captured = &mut self.capture.as_mut().unwrap().captured;
+                        if !captured.contains(&ident) {
+                            captured.push(ident);
+                        }
+                    }
+                }
+            }
+            Expr::Assign(assign) => {
+                self.check_not_captured(&assign.left);
+                syn::visit_mut::visit_expr_mut(self, expr);
+            }
+            Expr::Binary(binary)
+                if matches!(
+                    binary.op,
+                    BinOp::AddAssign(_)
+                        | BinOp::SubAssign(_)
+                        | BinOp::MulAssign(_)
+                        | BinOp::DivAssign(_)
+                        | BinOp::RemAssign(_)
+                        | BinOp::BitXorAssign(_)
+                        | BinOp::BitAndAssign(_)
+                        | BinOp::BitOrAssign(_)
+                        | BinOp::ShlAssign(_)
+                        | BinOp::ShrAssign(_)
+                ) =>
+            {
+                self.check_not_captured(&binary.left);
+                syn::visit_mut::visit_expr_mut(self, expr);
+            }
+            Expr::Reference(reference) if reference.mutability.is_some() => {
+                self.check_not_captured(&reference.expr);
+                syn::visit_mut::visit_expr_mut(self, expr);
+            }
+            _ => syn::visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    #[test]
+    fn test_closures_are_lifted() {
+        let module: ItemMod = parse_quote! {
+            mod m {
+                fn main(v: Vec3F32) {
+                    let scale = 2.0;
+                    let sq = |x: f32| x * x;
+                    let scaled = |x: f32| -> f32 { sq(x) * scale };
+                    let a = sq(v.x) + scaled(v.y);
+                }
+            }
+        };
+        let output = lift_closures(&module).to_token_stream().to_string();
+        assert_eq!(
+            output,
+            "mod m { fn main (v : Vec3F32) { let scale = 2.0 ; let a = main_sq (v . x) + main_scaled (v . y , scale) ; } \
+            fn main_sq (x : f32) -> f32 { x * x } \
+            fn main_scaled (x : f32 , scale : f32) -> f32 { main_sq (x) * scale } }"
+        );
+    }
+
+    #[test]
+    fn test_redeclared_capture_is_copied() {
+        let module: ItemMod = parse_quote! {
+            mod m {
+                fn main() -> f32 {
+                    let s: f32 = 2.0;
+                    let f = |x: f32| x * s;
+                    let s = 5.0;
+                    f(1.0) + s
+                }
+            }
+        };
+        let output = lift_closures(&module).to_token_stream().to_string();
+        assert_eq!(
+            output,
+            "mod m { fn main () -> f32 { let s : f32 = 2.0 ; let f_captured_s = s ; let s = 5.0 ; main_f (1.0 , f_captured_s) + s } \
+            fn main_f (x : f32 , s : f32) -> f32 { x * s } }"
+        );
+    }
+}
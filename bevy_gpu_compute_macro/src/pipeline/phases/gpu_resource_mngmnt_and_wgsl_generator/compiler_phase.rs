@@ -6,8 +6,11 @@ pub struct GpuResourceMngmntAndWgslGenerator;
 
 impl CompilerPhase for GpuResourceMngmntAndWgslGenerator {
     fn execute(&self, input: &mut CompilationUnit) {
-        let (shader_module, custom_types) =
-            parse_shader_module_for_gpu(input.rust_module_for_gpu(), input.custom_types());
+        let (shader_module, custom_types) = parse_shader_module_for_gpu(
+            input.rust_module_for_gpu(),
+            input.custom_types(),
+            input.kind(),
+        );
         input.set_wgsl_module_user_portion(shader_module);
         input.set_custom_types(custom_types);
     }
@@ -6,6 +6,8 @@ use bevy_gpu_compute_core::wgsl::{
 use crate::pipeline::phases::custom_type_collector::custom_type::{CustomType, CustomTypeKind};
 use quote::quote;
 
+use super::enums::enum_to_wgsl_consts;
+
 pub fn generate_helper_types_inputs_and_outputs_for_wgsl_module_def(
     custom_types: &Vec<CustomType>,
     wgsl_module_def: &mut WgslShaderModuleUserPortion,
@@ -58,6 +60,11 @@ pub fn generate_helper_types_inputs_and_outputs_for_wgsl_module_def(
             CustomTypeKind::ArrayLengthVariable => {
                 // do nothing
             }
+            CustomTypeKind::Enum => {
+                wgsl_module_def
+                    .static_consts
+                    .extend(enum_to_wgsl_consts(custom_type, custom_types));
+            }
         }
     }
 
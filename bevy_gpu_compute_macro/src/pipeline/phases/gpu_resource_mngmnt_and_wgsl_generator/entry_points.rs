@@ -0,0 +1,143 @@
+use super::to_wgsl_syntax::lower_file_to_wgsl;
+use crate::pipeline::{
+    entry_points::{entry_attribute, is_entry_point, workgroup_size},
+    phases::custom_type_collector::custom_type::CustomType,
+};
+use bevy_gpu_compute_core::wgsl::{
+    ast,
+    shader_sections::{WgslEntryPoint, WgslFunction, WgslShaderModuleSectionCode},
+};
+use proc_macro_error::{abort, abort_if_dirty, emit_error};
+use quote::ToTokens;
+use syn::{ItemFn, ItemMod, spanned::Spanned, visit::Visit};
+
+/// `fn main` and the `#[wgsl_entry]` functions, in declaration order
+pub fn parse_entry_points(
+    rust_module_transformed_for_gpu: &ItemMod,
+    custom_types: &Vec<CustomType>,
+) -> Vec<WgslEntryPoint> {
+    let mut extractor = EntryPointsExtractor::new(custom_types);
+    extractor.visit_item_mod(rust_module_transformed_for_gpu);
+
+    if extractor.results.is_empty() {
+        abort!(
+            rust_module_transformed_for_gpu.ident.span(),
+            "No main function found, add `fn main(iter_pos: WgslIterationPosition)` or a `#[wgsl_entry]` function to this module"
+        );
+    }
+    extractor.results
+}
+
+struct EntryPointsExtractor<'a> {
+    custom_types: &'a Vec<CustomType>,
+    results: Vec<WgslEntryPoint>,
+}
+
+impl<'ast> Visit<'ast> for EntryPointsExtractor<'ast> {
+    fn visit_item_fn(&mut self, c: &'ast syn::ItemFn) {
+        syn::visit::visit_item_fn(self, c);
+        if !is_entry_point(c) {
+            return;
+        }
+        self.results.push(parse_entry_point(c, self.custom_types));
+    }
+}
+
+impl<'ast> EntryPointsExtractor<'ast> {
+    pub fn new(custom_types: &'ast Vec<CustomType>) -> Self {
+        EntryPointsExtractor {
+            custom_types,
+            results: Vec::new(),
+        }
+    }
+}
+
+fn parse_entry_point(func: &ItemFn, custom_types: &Vec<CustomType>) -> WgslEntryPoint {
+    let workgroup_size = entry_attribute(&func.attrs).map_or(Ok(None), workgroup_size);
+    let workgroup_size = workgroup_size.unwrap_or_else(|e| abort!(e.span(), e));
+    let mut func = func.clone();
+    func.attrs
+        .retain(|attr| !attr.path().is_ident("wgsl_entry"));
+    WgslEntryPoint {
+        function: parse_entry_fn(&func, custom_types),
+        workgroup_size,
+    }
+}
+
+fn parse_entry_fn(func: &ItemFn, custom_types: &Vec<CustomType>) -> WgslFunction {
+    validate_entry_function(func);
+    let items = lower_file_to_wgsl(func.to_token_stream(), custom_types, "main".to_string());
+    let Some(ast::Item::Fn(mut main)) = items.into_iter().next() else {
+        abort!(
+            func.sig.ident.span(),
+            "Failed to convert the entry point function"
+        );
+    };
+    // the iteration position is the builtin global invocation id of the compute shader
+    for param in main.params.iter_mut() {
+        if param.ty.name == "WgslIterationPosition" {
+            param.attributes = vec![ast::Attribute::new(
+                "builtin",
+                vec!["global_invocation_id".to_string()],
+            )];
+            param.ty = ast::Type {
+                name: "vec3".to_string(),
+                template_args: vec![ast::TemplateArg::Type(ast::Type::named("u32"))],
+            };
+        }
+    }
+    WgslFunction {
+        code: WgslShaderModuleSectionCode {
+            wgsl_code: main.to_string(),
+        },
+        name: func.sig.ident.to_string(),
+    }
+}
+
+/// reports every problem with the signature before aborting, since the wgsl conversion relies on it
+fn validate_entry_function(function: &ItemFn) {
+    let kind = if function.sig.ident == "main" {
+        "Main function".to_string()
+    } else {
+        format!("Entry point `{}`", function.sig.ident)
+    };
+    // Check that main has exactly one parameter
+    if function.sig.inputs.len() != 1 {
+        emit_error!(
+            function.sig.span(),
+            "{} must have exactly one parameter of type WgslIterationPosition",
+            kind
+        );
+    }
+    // Validate the parameter type is WgslIterationPosition called "iter_pos"
+    if let Some(syn::FnArg::Typed(pat_type)) = function.sig.inputs.first() {
+        match &*pat_type.pat {
+            syn::Pat::Ident(pat_ident) if pat_ident.ident == "iter_pos" => {}
+            _ => emit_error!(
+                pat_type.pat.span(),
+                "{} parameter must be called 'iter_pos'",
+                kind
+            ),
+        }
+        if let syn::Type::Path(type_path) = &*pat_type.ty {
+            if let Some(segment) = type_path.path.segments.last() {
+                if segment.ident != "WgslIterationPosition" {
+                    emit_error!(
+                        pat_type.ty.span(),
+                        "{} parameter must be of type WgslIterationPosition",
+                        kind
+                    );
+                }
+            }
+        }
+    }
+    // Check return type (should be void/unit)
+    if let syn::ReturnType::Type(_, _) = &function.sig.output {
+        emit_error!(
+            function.sig.output.span(),
+            "{} cannot have a return type",
+            kind
+        );
+    }
+    abort_if_dirty();
+}
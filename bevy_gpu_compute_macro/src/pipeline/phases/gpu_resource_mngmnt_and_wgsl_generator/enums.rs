@@ -0,0 +1,104 @@
+use bevy_gpu_compute_core::wgsl::shader_sections::{
+    WgslConstAssignment, WgslShaderModuleSectionCode,
+};
+use proc_macro_error::abort;
+use proc_macro2::Literal;
+use quote::{format_ident, quote};
+use syn::{Expr, Fields, Ident, ItemEnum, Lit, Path, parse2, spanned::Spanned};
+
+use super::to_wgsl_syntax::convert_file_to_wgsl;
+use crate::pipeline::phases::custom_type_collector::custom_type::{CustomType, CustomTypeKind};
+
+/**
+ Field-less enums become one `u32` constant per variant, numbered like rust numbers them:
+ `enum Shape { Circle, Square = 5, Triangle }` =>
+ ```ignore
+ const Shape_Circle: u32 = 0;
+ const Shape_Square: u32 = 5;
+ const Shape_Triangle: u32 = 6;
+ ```
+ The enum type itself becomes `u32`, and `Shape::Square` becomes `Shape_Square` in expressions and match patterns.
+*/
+pub fn enum_to_wgsl_consts(
+    custom_type: &CustomType,
+    custom_types: &Vec<CustomType>,
+) -> Vec<WgslConstAssignment> {
+    let item_enum = parse2::<ItemEnum>(custom_type.rust_code.clone()).unwrap();
+    let mut next_value: u32 = 0;
+    item_enum
+        .variants
+        .iter()
+        .map(|variant| {
+            if !matches!(variant.fields, Fields::Unit) {
+                abort!(
+                    variant.fields.span(),
+                    "Only enums without fields are supported in WGSL, since they become u32 constants"
+                );
+            }
+            if let Some((_, discriminant)) = &variant.discriminant {
+                next_value = match discriminant {
+                    Expr::Lit(syn::ExprLit {
+                        lit: Lit::Int(int), ..
+                    }) => int.base10_parse::<u32>().unwrap_or_else(|_| {
+                        abort!(int.span(), "Enum discriminants must fit in a u32 in WGSL")
+                    }),
+                    _ => abort!(
+                        discriminant.span(),
+                        "Enum discriminants must be integer literals in WGSL"
+                    ),
+                };
+            }
+            let name = variant_const_name(&item_enum.ident, &variant.ident);
+            let value = Literal::u32_unsuffixed(next_value);
+            next_value = next_value.wrapping_add(1);
+            WgslConstAssignment {
+                code: WgslShaderModuleSectionCode {
+                    wgsl_code: convert_file_to_wgsl(
+                        quote!(const #name: u32 = #value;),
+                        custom_types,
+                        "enum".to_string(),
+                    ),
+                },
+            }
+        })
+        .collect()
+}
+
+fn variant_const_name(enum_name: &Ident, variant: &Ident) -> Ident {
+    format_ident!("{}_{}", enum_name, variant)
+}
+
+/// `Shape::Square` => `Shape_Square`, if `Shape` is an enum of the module
+pub fn enum_variant_to_wgsl(path: &Path, custom_types: &[CustomType]) -> Option<Ident> {
+    let [enum_name, variant] = path.segments.iter().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    custom_types
+        .iter()
+        .any(|c| c.kind == CustomTypeKind::Enum && c.name.eq(&enum_name.ident))
+        .then(|| variant_const_name(&enum_name.ident, &variant.ident))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_enum_variant_paths() {
+        let item_enum: ItemEnum = parse_quote! {
+            enum Shape { Circle, Square = 5, Triangle }
+        };
+        let custom_types = [CustomType::new(
+            &item_enum.ident,
+            CustomTypeKind::Enum,
+            quote!(#item_enum),
+        )];
+        assert_eq!(
+            enum_variant_to_wgsl(&parse_quote!(Shape::Square), &custom_types).unwrap(),
+            "Shape_Square"
+        );
+        assert!(enum_variant_to_wgsl(&parse_quote!(Vec2F32::new), &custom_types).is_none());
+        assert!(enum_variant_to_wgsl(&parse_quote!(Shape), &custom_types).is_none());
+    }
+}
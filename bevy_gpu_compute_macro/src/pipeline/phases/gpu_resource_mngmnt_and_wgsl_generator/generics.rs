@@ -0,0 +1,417 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use proc_macro_error::abort;
+use quote::{ToTokens, format_ident};
+use syn::{
+    Expr, FnArg, GenericArgument, GenericParam, Ident, Item, ItemFn, ItemMod, Lit, Pat,
+    PathArguments, ReturnType, Type, parse_quote, spanned::Spanned, visit_mut::VisitMut,
+};
+
+use super::tuples::type_name_for_ident;
+use super::type_inference::ModuleTypes;
+
+/**
+ WGSL has no generics, so generic functions are copied once per set of type arguments they are called with, named after the types:
+ `fn lerp<T>(a: T, b: T, t: f32) -> T` called as `lerp(a, b, 0.5)` with `a: Vec3F32` => `fn lerp_Vec3F32(a: Vec3F32, b: Vec3F32, t: f32) -> Vec3F32`, and the call becomes `lerp_Vec3F32(a, b, 0.5)`
+
+ The type arguments come from a turbofish like `lerp::<f32>(...)`, or from the types of the arguments, which are known for parameters, variables with a declared type, literals, struct literals, constructors like `Vec3F32::new`, casts, fields, and the results of other functions.
+ If that is not enough, the declared type of the variable that the result is assigned to is used.
+ Generic functions that are never called are left out, and only the module for the GPU is changed.
+*/
+pub fn monomorphise_generic_functions(module: &ItemMod) -> ItemMod {
+    let mut module = module.clone();
+    let Some((_, items)) = &mut module.content else {
+        return module;
+    };
+    let mut generic_fns = HashMap::new();
+    let mut fn_names = HashSet::new();
+    let mut module_types = ModuleTypes::default();
+    for item in std::mem::take(items) {
+        match item {
+            Item::Fn(item_fn) if !item_fn.sig.generics.params.is_empty() => {
+                let type_params = generic_type_params(&item_fn);
+                generic_fns.insert(item_fn.sig.ident.to_string(), (item_fn, type_params));
+            }
+            item => {
+                if let Item::Fn(item_fn) = &item {
+                    fn_names.insert(item_fn.sig.ident.to_string());
+                }
+                module_types.add(&item);
+                items.push(item);
+            }
+        }
+    }
+    if generic_fns.is_empty() {
+        return module;
+    }
+    let mut instantiator = Instantiator {
+        generic_fns,
+        fn_names,
+        module_types,
+        instances: HashMap::new(),
+        queue: VecDeque::new(),
+    };
+    for item in items.iter_mut() {
+        if let Item::Fn(item_fn) = item {
+            instantiator.rewrite_calls(item_fn);
+        }
+    }
+    // instances can call other generic functions, which adds more instances
+    let mut instances = Vec::new();
+    while let Some(mut instance) = instantiator.queue.pop_front() {
+        instantiator.rewrite_calls(&mut instance);
+        instances.push(Item::Fn(instance));
+    }
+    items.extend(instances);
+    module
+}
+
+fn generic_type_params(item_fn: &ItemFn) -> Vec<Ident> {
+    item_fn
+        .sig
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(t) => Some(t.ident.clone()),
+            GenericParam::Lifetime(_) => None,
+            GenericParam::Const(c) => abort!(
+                c.span(),
+                "Const generics are not supported in WGSL, use a module level `const` instead"
+            ),
+        })
+        .collect()
+}
+
+struct Instantiator {
+    /// the generic functions and their type parameters, by name
+    generic_fns: HashMap<String, (ItemFn, Vec<Ident>)>,
+    /// names of the other functions of the module, which the instances must not reuse
+    fn_names: HashSet<String>,
+    module_types: ModuleTypes,
+    /// names of the instances that were already created, by generic function and type arguments
+    instances: HashMap<String, Ident>,
+    /// instances whose bodies still have to be searched for calls
+    queue: VecDeque<ItemFn>,
+}
+
+impl Instantiator {
+    fn rewrite_calls(&mut self, item_fn: &mut ItemFn) {
+        let mut var_types = HashMap::new();
+        for input in item_fn.sig.inputs.iter() {
+            if let FnArg::Typed(pat_type) = input {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    var_types.insert(pat_ident.ident.to_string(), *pat_type.ty.clone());
+                }
+            }
+        }
+        CallRewriter {
+            instantiator: self,
+            var_types,
+        }
+        .visit_block_mut(&mut item_fn.block);
+    }
+
+    /// the name of the instance for these type arguments, which is created if it does not exist yet
+    fn instance(&mut self, name: &str, type_args: &[Type]) -> Ident {
+        let key = format!(
+            "{}<{}>",
+            name,
+            type_args
+                .iter()
+                .map(|t| t.to_token_stream().to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        if let Some(instance) = self.instances.get(&key) {
+            return instance.clone();
+        }
+        let (generic_fn, type_params) = &self.generic_fns[name];
+        let mut instance = generic_fn.clone();
+        let type_names: Vec<String> = type_args.iter().map(type_name_for_ident).collect();
+        let instance_name = format_ident!("{}_{}", name, type_names.join("_"));
+        if self.fn_names.contains(&instance_name.to_string())
+            || self.instances.values().any(|other| *other == instance_name)
+        {
+            abort!(
+                generic_fn.sig.ident.span(),
+                "The copy of `{}` for the type arguments `{}` would be named `{}`, which is already the name of another function in the shader module, rename one of them",
+                name,
+                key,
+                instance_name
+            );
+        }
+        instance.sig.ident = instance_name.clone();
+        instance.sig.generics = Default::default();
+        TypeSubstitution {
+            substitutions: type_params
+                .iter()
+                .map(|p| p.to_string())
+                .zip(type_args.iter().cloned())
+                .collect(),
+        }
+        .visit_item_fn_mut(&mut instance);
+        if let ReturnType::Type(_, t) = &instance.sig.output {
+            self.module_types
+                .fn_returns
+                .insert(instance_name.to_string(), *t.clone());
+        }
+        self.instances.insert(key, instance_name.clone());
+        self.queue.push_back(instance);
+        instance_name
+    }
+}
+
+/// replaces the type parameters with the type arguments, in types and in paths like `T::new`
+struct TypeSubstitution {
+    substitutions: HashMap<String, Type>,
+}
+
+impl VisitMut for TypeSubstitution {
+    fn visit_type_mut(&mut self, t: &mut Type) {
+        if let Type::Path(p) = t {
+            if let Some(substitute) = p
+                .path
+                .get_ident()
+                .and_then(|ident| self.substitutions.get(&ident.to_string()))
+            {
+                *t = substitute.clone();
+                return;
+            }
+        }
+        syn::visit_mut::visit_type_mut(self, t);
+    }
+    fn visit_expr_path_mut(&mut self, path: &mut syn::ExprPath) {
+        if path.path.segments.len() > 1 {
+            let first = &path.path.segments[0].ident;
+            if let Some(Type::Path(substitute)) = self.substitutions.get(&first.to_string()) {
+                if let Some(ident) = substitute.path.get_ident() {
+                    path.path.segments[0].ident = ident.clone();
+                }
+            }
+        }
+        syn::visit_mut::visit_expr_path_mut(self, path);
+    }
+}
+
+struct CallRewriter<'a> {
+    instantiator: &'a mut Instantiator,
+    /// declared or inferred types of the parameters and local variables of the current function
+    var_types: HashMap<String, Type>,
+}
+
+impl VisitMut for CallRewriter<'_> {
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        let declared = match &local.pat {
+            Pat::Type(pat_type) => Some(*pat_type.ty.clone()),
+            _ => None,
+        };
+        match (&mut local.init, &declared) {
+            // the declared type can give the type arguments that only appear in the return type
+            (Some(init), Some(declared)) if self.is_generic_call(&init.expr) => {
+                let Expr::Call(call) = &mut *init.expr else {
+                    unreachable!()
+                };
+                call.args
+                    .iter_mut()
+                    .for_each(|arg| self.visit_expr_mut(arg));
+                self.rewrite_call(call, Some(declared));
+            }
+            _ => syn::visit_mut::visit_local_mut(self, local),
+        }
+        self.instantiator
+            .module_types
+            .add_local(local, &mut self.var_types);
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Call(call) = expr {
+            if self.is_generic_call(&call.func) || self.is_generic_call_expr(call) {
+                self.rewrite_call(call, None);
+            }
+        }
+    }
+}
+
+impl CallRewriter<'_> {
+    fn generic_name(&self, func: &Expr) -> Option<String> {
+        let Expr::Path(path) = func else {
+            return None;
+        };
+        let [segment] = path.path.segments.iter().collect::<Vec<_>>()[..] else {
+            return None;
+        };
+        let name = segment.ident.to_string();
+        self.instantiator
+            .generic_fns
+            .contains_key(&name)
+            .then_some(name)
+    }
+    fn is_generic_call_expr(&self, call: &syn::ExprCall) -> bool {
+        self.generic_name(&call.func).is_some()
+    }
+    fn is_generic_call(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::Call(call) if self.is_generic_call_expr(call))
+    }
+    fn rewrite_call(&mut self, call: &mut syn::ExprCall, expected_return: Option<&Type>) {
+        let Some(name) = self.generic_name(&call.func) else {
+            return;
+        };
+        let (generic_fn, type_params) = &self.instantiator.generic_fns[&name];
+        let mut bindings: HashMap<String, Type> = HashMap::new();
+        let Expr::Path(path) = &*call.func else {
+            unreachable!()
+        };
+        // `lerp::<f32>(...)`
+        if let PathArguments::AngleBracketed(args) = &path.path.segments[0].arguments {
+            let types = args.args.iter().filter_map(|arg| match arg {
+                GenericArgument::Type(t) => Some(t.clone()),
+                _ => None,
+            });
+            for (param, t) in type_params.iter().zip(types) {
+                if !matches!(t, Type::Infer(_)) {
+                    bindings.insert(param.to_string(), t);
+                }
+            }
+        }
+        let params: Vec<Type> = generic_fn
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(pat_type) => Some(*pat_type.ty.clone()),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        for (param, arg) in params.iter().zip(call.args.iter()) {
+            if is_unsuffixed_literal(arg) {
+                continue;
+            }
+            if let Some(arg_type) = self.type_of(arg) {
+                unify(param, &arg_type, type_params, &mut bindings);
+            }
+        }
+        if let (Some(expected), ReturnType::Type(_, returned)) =
+            (expected_return, &generic_fn.sig.output)
+        {
+            unify(returned, expected, type_params, &mut bindings);
+        }
+        // unsuffixed literals could be any type of number, so they only decide what nothing else does
+        let mut literal_bindings = HashMap::new();
+        for (param, arg) in params.iter().zip(call.args.iter()) {
+            if !is_unsuffixed_literal(arg) {
+                continue;
+            }
+            if let Some(arg_type) = self.type_of(arg) {
+                unify(param, &arg_type, type_params, &mut literal_bindings);
+            }
+        }
+        for (param, t) in literal_bindings {
+            bindings.entry(param).or_insert(t);
+        }
+        let type_args: Vec<Type> = type_params
+            .iter()
+            .map(|param| {
+                bindings.get(&param.to_string()).cloned().unwrap_or_else(|| {
+                    abort!(
+                        call.span(),
+                        "Cannot work out the type of `{}` for this call to `{}`, WGSL has no generics so it must be known when the shader is generated. Add it explicitly, like `{}::<f32>(...)`",
+                        param,
+                        name,
+                        name
+                    )
+                })
+            })
+            .collect();
+        let instance = self.instantiator.instance(&name, &type_args);
+        *call.func = parse_quote!(#instance);
+    }
+
+    fn type_of(&self, expr: &Expr) -> Option<Type> {
+        self.instantiator
+            .module_types
+            .type_of(expr, &self.var_types)
+    }
+}
+
+/// binds the type parameters in `param` to the matching parts of `arg`
+fn unify(param: &Type, arg: &Type, type_params: &[Ident], bindings: &mut HashMap<String, Type>) {
+    match (param, arg) {
+        (Type::Path(p), _) if p.path.get_ident().is_some_and(|i| type_params.contains(i)) => {
+            let name = p.path.get_ident().unwrap().to_string();
+            if let Some(bound) = bindings.get(&name) {
+                if bound.to_token_stream().to_string() != arg.to_token_stream().to_string() {
+                    abort!(
+                        param.span(),
+                        "`{}` is used as both `{}` and `{}` in the same call",
+                        name,
+                        bound.to_token_stream(),
+                        arg.to_token_stream()
+                    );
+                }
+                return;
+            }
+            bindings.insert(name, arg.clone());
+        }
+        (Type::Array(p), Type::Array(a)) => unify(&p.elem, &a.elem, type_params, bindings),
+        (Type::Reference(p), Type::Reference(a)) => unify(&p.elem, &a.elem, type_params, bindings),
+        (Type::Paren(p), _) => unify(&p.elem, arg, type_params, bindings),
+        (Type::Tuple(p), Type::Tuple(a)) if p.elems.len() == a.elems.len() => {
+            for (p, a) in p.elems.iter().zip(a.elems.iter()) {
+                unify(p, a, type_params, bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_unsuffixed_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Float(f) => f.suffix().is_empty(),
+            Lit::Int(i) => i.suffix().is_empty(),
+            _ => false,
+        },
+        Expr::Unary(unary) => is_unsuffixed_literal(&unary.expr),
+        Expr::Paren(paren) => is_unsuffixed_literal(&paren.expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_functions_are_monomorphised() {
+        let module: ItemMod = parse_quote! {
+            mod m {
+                fn lerp<T>(a: T, b: T, t: f32) -> T { a * (1.0 - t) + b * t }
+                fn twice<T>(a: T) -> T { lerp(a, a, 0.5) + a }
+                fn zero<T>() -> T { T::new() }
+                fn unused<T>(a: T) {}
+                fn main(v: Vec3F32) {
+                    let a = lerp(v, v * 2.0, 0.5);
+                    let b = lerp(1.0, 2.0, 0.5);
+                    let c = twice::<u32>(3);
+                    let d: Vec2F32 = zero();
+                }
+            }
+        };
+        let output = monomorphise_generic_functions(&module)
+            .to_token_stream()
+            .to_string();
+        assert!(output.contains("let a = lerp_Vec3F32 (v , v * 2.0 , 0.5) ;"));
+        assert!(output.contains("let b = lerp_f32 (1.0 , 2.0 , 0.5) ;"));
+        assert!(output.contains("let c = twice_u32 (3) ;"));
+        assert!(output.contains("let d : Vec2F32 = zero_Vec2F32 () ;"));
+        assert!(output.contains(
+            "fn lerp_Vec3F32 (a : Vec3F32 , b : Vec3F32 , t : f32) -> Vec3F32 { a * (1.0 - t) + b * t }"
+        ));
+        assert!(output.contains("fn twice_u32 (a : u32) -> u32 { lerp_u32 (a , a , 0.5) + a }"));
+        assert!(output.contains("fn lerp_u32 (a : u32 , b : u32 , t : f32) -> u32"));
+        assert!(output.contains("fn zero_Vec2F32 () -> Vec2F32 { Vec2F32 :: new () }"));
+        assert!(!output.contains("unused"));
+        assert!(!output.contains('<'));
+    }
+}
@@ -1,15 +1,37 @@
-use super::to_wgsl_syntax::convert_file_to_wgsl;
-use bevy_gpu_compute_core::wgsl::shader_sections::{WgslFunction, WgslShaderModuleSectionCode};
+use std::path::PathBuf;
+
+use super::to_wgsl_syntax::{convert_file_to_wgsl, lower_file_to_wgsl};
+use bevy_gpu_compute_core::wgsl::{
+    ast,
+    shader_sections::{WgslFunction, WgslShaderModuleSectionCode},
+};
+use proc_macro_error::abort;
 use quote::ToTokens;
-use syn::{ItemFn, ItemMod, visit::Visit};
+use regex::Regex;
+use syn::{Attribute, Item, ItemFn, ItemMod, LitStr, Macro, visit::Visit};
 
-use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
+use crate::pipeline::{
+    entry_points::is_entry_point,
+    phases::custom_type_collector::custom_type::CustomType,
+    raw_functions::{extern_attribute, extern_path, raw_body},
+};
 
 pub fn extract_helper_functions(
     rust_module_transformed_for_gpu: &ItemMod,
     custom_types: &Vec<CustomType>,
 ) -> Vec<WgslFunction> {
     let mut extractor = HelperFunctionsExtractor::new(custom_types);
+    extractor.module_functions = rust_module_transformed_for_gpu
+        .content
+        .iter()
+        .flat_map(|(_, items)| items)
+        .filter_map(|item| match item {
+            Item::Fn(func) if extern_attribute(&func.attrs).is_none() => {
+                Some(func.sig.ident.to_string())
+            }
+            _ => None,
+        })
+        .collect();
     extractor.visit_item_mod(rust_module_transformed_for_gpu);
     extractor.results
 }
@@ -17,12 +39,30 @@ pub fn extract_helper_functions(
 struct HelperFunctionsExtractor<'a> {
     custom_types: &'a Vec<CustomType>,
     results: Vec<WgslFunction>,
+    /// the functions of the module that are not from a WGSL file
+    module_functions: Vec<String>,
+    /// the files of the `#[wgsl_extern]` functions that were added already, with the path they were given with and the functions they declare
+    extern_files: Vec<(PathBuf, String, Vec<String>)>,
 }
 
 impl<'ast> Visit<'ast> for HelperFunctionsExtractor<'ast> {
     fn visit_item_fn(&mut self, c: &'ast syn::ItemFn) {
         syn::visit::visit_item_fn(self, c);
-        if c.sig.ident == "main" {
+        if is_entry_point(c) {
+            return;
+        }
+        if let Some(attr) = extern_attribute(&c.attrs) {
+            self.results.extend(extern_function(
+                c,
+                attr,
+                &self.module_functions,
+                &mut self.extern_files,
+            ));
+            return;
+        }
+        if let Some(mac) = raw_body(&c.block) {
+            self.results
+                .push(raw_body_function(c, mac, self.custom_types));
             return;
         }
         // ident from string
@@ -36,6 +76,8 @@ impl<'ast> HelperFunctionsExtractor<'ast> {
         HelperFunctionsExtractor {
             custom_types,
             results: Vec::new(),
+            module_functions: Vec::new(),
+            extern_files: Vec::new(),
         }
     }
 }
@@ -52,3 +94,92 @@ fn parse_fn(func: &ItemFn, custom_types: &Vec<CustomType>) -> WgslFunction {
         name: func.sig.ident.to_string(),
     }
 }
+
+/// the content of the file of a `#[wgsl_extern]` function, unchanged. A file declaring several functions is only added once, and its functions cannot have the names of the other functions of the module
+fn extern_function(
+    func: &ItemFn,
+    attr: &Attribute,
+    module_functions: &[String],
+    extern_files: &mut Vec<(PathBuf, String, Vec<String>)>,
+) -> Option<WgslFunction> {
+    let path = extern_path(attr).unwrap_or_else(|e| abort!(e.span(), e));
+    // relative to the rust file, like with `include_str!`
+    let Some(file) = path
+        .span()
+        .local_file()
+        .and_then(|rust_file| Some(rust_file.parent()?.join(path.value())))
+    else {
+        abort!(path, "Could not find the rust file of `{}`", path.value());
+    };
+    let code = std::fs::read_to_string(&file)
+        .unwrap_or_else(|e| abort!(path, "Could not read `{}`: {}", file.display(), e));
+    let name = func.sig.ident.to_string();
+    let declaration = Regex::new(&format!(r"\bfn\s+{}\s*\(", regex::escape(&name))).unwrap();
+    if !declaration.is_match(&code) {
+        abort!(
+            func.sig.ident,
+            "`{}` has no function `{}`",
+            path.value(),
+            name
+        );
+    }
+    if extern_files.iter().any(|(f, _, _)| *f == file) {
+        return None;
+    }
+    let declared: Vec<String> = Regex::new(r"\bfn\s+(\w+)\s*\(")
+        .unwrap()
+        .captures_iter(&code)
+        .map(|captures| captures[1].to_string())
+        .collect();
+    for declared_name in &declared {
+        if module_functions.contains(declared_name) {
+            abort!(
+                path,
+                "`{}` declares a function `{}`, which the module also has. Rename one of them, WGSL functions need unique names",
+                path.value(),
+                declared_name
+            );
+        }
+        if let Some((_, other_path, _)) = extern_files
+            .iter()
+            .find(|(_, _, names)| names.contains(declared_name))
+        {
+            abort!(
+                path,
+                "`{}` and `{}` both declare a function `{}`. Rename one of them, WGSL functions need unique names",
+                path.value(),
+                other_path,
+                declared_name
+            );
+        }
+    }
+    extern_files.push((file, path.value(), declared));
+    Some(WgslFunction {
+        name,
+        code: WgslShaderModuleSectionCode {
+            wgsl_code: code.trim_end().to_string(),
+        },
+    })
+}
+
+/// the signature is translated like for any function, the body is the WGSL code of the `wgsl_raw!`, unchanged
+fn raw_body_function(func: &ItemFn, mac: &Macro, custom_types: &Vec<CustomType>) -> WgslFunction {
+    let code: LitStr = mac.parse_body().unwrap_or_else(|e| abort!(e.span(), e));
+    let mut signature = func.clone();
+    signature.block.stmts.clear();
+    let Some(ast::Item::Fn(mut function)) = lower_file_to_wgsl(
+        signature.to_token_stream(),
+        custom_types,
+        "wgsl_raw fn".to_string(),
+    )
+    .pop() else {
+        abort!(func.sig.ident, "Failed to translate the function signature");
+    };
+    function.body = ast::Block::new(vec![ast::Stmt::Raw(code.value())]);
+    WgslFunction {
+        code: WgslShaderModuleSectionCode {
+            wgsl_code: ast::Item::Fn(function).to_string(),
+        },
+        name: func.sig.ident.to_string(),
+    }
+}
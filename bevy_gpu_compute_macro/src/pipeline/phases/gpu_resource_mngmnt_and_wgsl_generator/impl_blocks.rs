@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use proc_macro_error::abort;
+use quote::format_ident;
+use syn::{
+    Expr, Fields, FnArg, Ident, ImplItem, Item, ItemFn, ItemMod, ItemStruct, Pat, ReturnType, Type,
+    parse_quote, parse2, spanned::Spanned, visit_mut::VisitMut,
+};
+
+use crate::pipeline::{
+    allowed_types::{WGSL_BUILTIN_METHODS, is_swizzle},
+    phases::custom_type_collector::custom_type::CustomType,
+};
+
+/**
+ WGSL has no methods, so inherent `impl` blocks on the types of the module become free functions, named `Type_method`:
+ - `impl Position { fn dist(&self, o: Position) -> f32 { ... } }` => `fn Position_dist(self_: Position, o: Position) -> f32 { ... }`
+ - `&mut self` becomes `self_: &mut Position`, which is then lowered to a pointer like any other `&mut` parameter, see `pointer.rs`
+ - `p.dist(o)` => `Position_dist(p, o)`, and `p.translate(v)` => `Position_translate(&mut p, v)` for `&mut self` methods
+ - `Position::new(x, y)` and `Self::new(x, y)` => `Position_new(x, y)`
+
+ Which type a method call is on is found from the declared types of parameters and variables, struct fields, and the return types of functions and methods.
+ If it is unknown, the method name must only be defined for one type.
+
+ Trait impls, like `impl Default`, are left alone. Only the module for the GPU is changed, the CPU version of the module keeps the original `impl` blocks.
+*/
+pub fn lower_impl_blocks(module: &ItemMod, custom_types: &[CustomType]) -> ItemMod {
+    let mut module = module.clone();
+    let Some((_, items)) = &mut module.content else {
+        return module;
+    };
+    let mut methods = Vec::new();
+    let mut lowered_items = Vec::new();
+    for item in std::mem::take(items) {
+        match item {
+            Item::Impl(item_impl) if item_impl.trait_.is_none() => {
+                let type_name = match &*item_impl.self_ty {
+                    Type::Path(p) => p.path.get_ident().cloned(),
+                    _ => None,
+                };
+                let Some(type_name) =
+                    type_name.filter(|t| custom_types.iter().any(|c| c.name.eq(t)))
+                else {
+                    abort!(
+                        item_impl.self_ty.span(),
+                        "`impl` blocks are only supported for structs and enums defined in the shader module"
+                    );
+                };
+                for impl_item in item_impl.items {
+                    let ImplItem::Fn(method) = impl_item else {
+                        abort!(
+                            impl_item.span(),
+                            "Only functions are supported in `impl` blocks in WGSL, use a module level `const` instead of associated constants"
+                        );
+                    };
+                    let (function, method) = method_to_function(&type_name, method);
+                    lowered_items.push(Item::Fn(function));
+                    methods.push(method);
+                }
+            }
+            item => items.push(item),
+        }
+    }
+    items.extend(lowered_items);
+    if methods.is_empty() {
+        return module;
+    }
+    let mut fn_returns = HashMap::new();
+    for item in items.iter() {
+        if let Item::Fn(item_fn) = item {
+            if let Some(return_type) = return_type_name(&item_fn.sig.output) {
+                fn_returns.insert(item_fn.sig.ident.to_string(), return_type);
+            }
+        }
+    }
+    MethodCallTransformer {
+        methods,
+        fn_returns,
+        struct_fields: struct_fields(custom_types),
+        var_types: HashMap::new(),
+    }
+    .visit_item_mod_mut(&mut module);
+    module
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Receiver {
+    /// associated functions, like `new`
+    None,
+    Value,
+    MutRef,
+}
+
+struct Method {
+    type_name: Ident,
+    name: Ident,
+    receiver: Receiver,
+}
+
+impl Method {
+    fn function_name(&self) -> Ident {
+        mangled_name(&self.type_name, &self.name)
+    }
+}
+
+fn mangled_name(type_name: &Ident, method: &Ident) -> Ident {
+    format_ident!("{}_{}", type_name, method)
+}
+
+fn return_type_name(output: &ReturnType) -> Option<Ident> {
+    match output {
+        ReturnType::Type(_, t) => match &**t {
+            Type::Path(p) => p.path.get_ident().cloned(),
+            _ => None,
+        },
+        ReturnType::Default => None,
+    }
+}
+
+fn method_to_function(type_name: &Ident, method: syn::ImplItemFn) -> (ItemFn, Method) {
+    let mut function = ItemFn {
+        attrs: method.attrs,
+        vis: method.vis,
+        sig: method.sig,
+        block: Box::new(method.block),
+    };
+    let name = function.sig.ident.clone();
+    let mut receiver = Receiver::None;
+    for input in function.sig.inputs.iter_mut() {
+        if let FnArg::Receiver(r) = input {
+            if r.reference.is_none() && r.mutability.is_some() {
+                abort!(
+                    r.span(),
+                    "`mut self` is not supported in WGSL, since parameters cannot be changed, use `&mut self` or copy `self` into a local variable instead"
+                );
+            }
+            receiver = match (&r.reference, &r.mutability) {
+                (Some(_), Some(_)) => Receiver::MutRef,
+                _ => Receiver::Value,
+            };
+            *input = match receiver {
+                Receiver::MutRef => parse_quote!(self_: &mut #type_name),
+                _ => parse_quote!(self_: #type_name),
+            };
+        }
+    }
+    function.sig.ident = mangled_name(type_name, &name);
+    SelfReplacer { type_name }.visit_item_fn_mut(&mut function);
+    let method = Method {
+        type_name: type_name.clone(),
+        name,
+        receiver,
+    };
+    (function, method)
+}
+
+/// `Self` => the type name, and `self` => `self_`, since `self` is reserved in WGSL
+struct SelfReplacer<'a> {
+    type_name: &'a Ident,
+}
+
+impl VisitMut for SelfReplacer<'_> {
+    fn visit_path_mut(&mut self, path: &mut syn::Path) {
+        let is_single = path.segments.len() == 1;
+        if let Some(first) = path.segments.first_mut() {
+            if first.ident == "Self" {
+                first.ident = self.type_name.clone();
+            } else if first.ident == "self" && is_single {
+                first.ident = format_ident!("self_");
+            }
+        }
+        syn::visit_mut::visit_path_mut(self, path);
+    }
+}
+
+/// the field types of the structs of the module, by struct name and field name
+fn struct_fields(custom_types: &[CustomType]) -> HashMap<String, HashMap<String, Ident>> {
+    custom_types
+        .iter()
+        .filter_map(|c| {
+            let item_struct = parse2::<ItemStruct>(c.rust_code.clone()).ok()?;
+            let Fields::Named(fields) = item_struct.fields else {
+                return None;
+            };
+            let fields = fields
+                .named
+                .iter()
+                .filter_map(|f| {
+                    let Type::Path(p) = &f.ty else {
+                        return None;
+                    };
+                    Some((f.ident.as_ref()?.to_string(), p.path.get_ident()?.clone()))
+                })
+                .collect();
+            Some((item_struct.ident.to_string(), fields))
+        })
+        .collect()
+}
+
+struct MethodCallTransformer {
+    methods: Vec<Method>,
+    /// return types of the functions of the module, including the lowered methods
+    fn_returns: HashMap<String, Ident>,
+    struct_fields: HashMap<String, HashMap<String, Ident>>,
+    /// types of the parameters and local variables of the current function, and whether they are `&mut` pointers
+    var_types: HashMap<String, (Ident, bool)>,
+}
+
+impl VisitMut for MethodCallTransformer {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        self.var_types.clear();
+        for input in item_fn.sig.inputs.iter() {
+            let FnArg::Typed(pat_type) = input else {
+                continue;
+            };
+            let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                continue;
+            };
+            let (t, is_pointer) = match &*pat_type.ty {
+                Type::Reference(r) if r.mutability.is_some() => (&*r.elem, true),
+                t => (t, false),
+            };
+            if let Type::Path(p) = t {
+                if let Some(ident) = p.path.get_ident() {
+                    self.var_types
+                        .insert(pat_ident.ident.to_string(), (ident.clone(), is_pointer));
+                }
+            }
+        }
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+    }
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        syn::visit_mut::visit_local_mut(self, local);
+        let (pat, declared) = match &local.pat {
+            Pat::Type(pat_type) => (
+                &*pat_type.pat,
+                match &*pat_type.ty {
+                    Type::Path(p) => p.path.get_ident().cloned(),
+                    _ => None,
+                },
+            ),
+            pat => (pat, None),
+        };
+        let Pat::Ident(pat_ident) = pat else {
+            return;
+        };
+        let name = pat_ident.ident.to_string();
+        let inferred = declared.or_else(|| {
+            local
+                .init
+                .as_ref()
+                .and_then(|init| self.type_of(&init.expr))
+        });
+        match inferred {
+            Some(t) => {
+                self.var_types.insert(name, (t, false));
+            }
+            None => {
+                self.var_types.remove(&name);
+            }
+        }
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        match expr {
+            Expr::Path(path) if path.path.segments.len() == 2 => {
+                let segments = &path.path.segments;
+                if let Some(method) = self.method(&segments[0].ident, &segments[1].ident) {
+                    let function = method.function_name();
+                    *expr = parse_quote!(#function);
+                }
+            }
+            Expr::MethodCall(call) => {
+                let Some(method) = self.resolve_method_call(call) else {
+                    return;
+                };
+                let function = method.function_name();
+                let receiver = &call.receiver;
+                let is_pointer = self.is_pointer(receiver);
+                let receiver: Expr = match (method.receiver, is_pointer) {
+                    (Receiver::MutRef, true) => parse_quote!(#receiver),
+                    (Receiver::MutRef, false) => {
+                        if !matches!(&**receiver, Expr::Path(p) if p.path.get_ident().is_some()) {
+                            abort!(
+                                receiver.span(),
+                                "`{}` takes `&mut self`, so in WGSL it can only be called on a local variable declared with `let mut`",
+                                method.name
+                            );
+                        }
+                        parse_quote!(&mut #receiver)
+                    }
+                    (_, true) => parse_quote!(*#receiver),
+                    (_, false) => parse_quote!(#receiver),
+                };
+                let args = std::iter::once(receiver).chain(call.args.iter().cloned());
+                *expr = parse_quote!(#function(#(#args),*));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl MethodCallTransformer {
+    fn method(&self, type_name: &Ident, name: &Ident) -> Option<&Method> {
+        self.methods
+            .iter()
+            .find(|m| &m.type_name == type_name && &m.name == name)
+    }
+    fn resolve_method_call(&self, call: &syn::ExprMethodCall) -> Option<&Method> {
+        if let Some(t) = self.type_of(&call.receiver) {
+            return self.method(&t, &call.method);
+        }
+        let candidates: Vec<&Method> = self
+            .methods
+            .iter()
+            .filter(|m| m.name == call.method && m.receiver != Receiver::None)
+            .collect();
+        let method_name = call.method.to_string();
+        let is_builtin = WGSL_BUILTIN_METHODS.iter().any(|(m, _)| *m == method_name)
+            || is_swizzle(&method_name, 2, false)
+            || method_name.starts_with("set_");
+        match (candidates.as_slice(), is_builtin) {
+            ([], _) => None,
+            ([method], false) => Some(method),
+            _ => abort!(
+                call.receiver.span(),
+                "The type of this value is unknown, so it is not clear which `{}` method is called, declare the type of the variable",
+                call.method
+            ),
+        }
+    }
+    fn is_pointer(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .and_then(|ident| self.var_types.get(&ident.to_string()))
+                .is_some_and(|(_, is_pointer)| *is_pointer),
+            _ => false,
+        }
+    }
+    /// the name of the type of the expression, if it is known
+    fn type_of(&self, expr: &Expr) -> Option<Ident> {
+        match expr {
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .and_then(|ident| self.var_types.get(&ident.to_string()))
+                .map(|(t, _)| t.clone()),
+            Expr::Paren(paren) => self.type_of(&paren.expr),
+            Expr::Group(group) => self.type_of(&group.expr),
+            Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Deref(_)) => {
+                self.type_of(&unary.expr)
+            }
+            Expr::Struct(s) => s.path.segments.last().map(|s| s.ident.clone()),
+            Expr::Field(field) => {
+                let syn::Member::Named(member) = &field.member else {
+                    return None;
+                };
+                let base = self.type_of(&field.base)?;
+                self.struct_fields
+                    .get(&base.to_string())?
+                    .get(&member.to_string())
+                    .cloned()
+            }
+            Expr::Call(call) => match &*call.func {
+                Expr::Path(path) => path
+                    .path
+                    .get_ident()
+                    .and_then(|f| self.fn_returns.get(&f.to_string()))
+                    .cloned(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::phases::custom_type_collector::custom_type::CustomTypeKind;
+    use quote::{ToTokens, quote};
+
+    #[test]
+    fn test_methods_become_functions() {
+        let position = quote!(
+            struct Position {
+                x: f32,
+                y: f32,
+            }
+        );
+        let custom_types = [CustomType::new(
+            &format_ident!("Position"),
+            CustomTypeKind::GpuOnlyHelperType,
+            position.clone(),
+        )];
+        let module: ItemMod = parse_quote! {
+            mod m {
+                #position
+                impl Position {
+                    fn new(x: f32, y: f32) -> Self { Self { x, y } }
+                    fn dist(&self, o: Position) -> f32 { (self.x - o.x) + self.other() }
+                    fn other(self) -> f32 { self.y }
+                    fn translate(&mut self, by: f32) { self.x += by; let d = self.dist(*self); }
+                }
+                fn main() {
+                    let mut p = Position::new(1.0, 2.0);
+                    p.translate(p.dist(Position::new(0.0, 0.0)));
+                }
+            }
+        };
+        let output = lower_impl_blocks(&module, &custom_types)
+            .to_token_stream()
+            .to_string();
+        assert!(!output.contains("impl"));
+        assert!(
+            output
+                .contains("fn Position_new (x : f32 , y : f32) -> Position { Position { x , y } }")
+        );
+        assert!(output.contains(
+            "fn Position_dist (self_ : Position , o : Position) -> f32 { (self_ . x - o . x) + Position_other (self_) }"
+        ));
+        assert!(output.contains(
+            "fn Position_translate (self_ : & mut Position , by : f32) { self_ . x += by ; let d = Position_dist (* self_ , * self_) ; }"
+        ));
+        assert!(output.contains(
+            "let mut p = Position_new (1.0 , 2.0) ; Position_translate (& mut p , Position_dist (p , Position_new (0.0 , 0.0))) ;"
+        ));
+    }
+}
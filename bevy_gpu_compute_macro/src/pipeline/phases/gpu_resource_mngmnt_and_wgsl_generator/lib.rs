@@ -1,26 +1,45 @@
 use bevy_gpu_compute_core::wgsl::shader_module::user_defined_portion::WgslShaderModuleUserPortion;
 
+use crate::pipeline::compilation_unit::ModuleKind;
 use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
 
+use super::bools::lower_transferred_bools;
+use super::closures::lift_closures;
 use super::constants::extract_constants;
 use super::divide_custom_types::generate_helper_types_inputs_and_outputs_for_wgsl_module_def;
+use super::entry_points::parse_entry_points;
+use super::generics::monomorphise_generic_functions;
 use super::helper_functions::extract_helper_functions;
-use super::main_function::parse_main_function;
+use super::impl_blocks::lower_impl_blocks;
+use super::tuples::lower_tuples;
 
 /// This will also change custom_types
 pub fn parse_shader_module_for_gpu(
     rust_module_transformed_for_gpu: &syn::ItemMod,
-    custom_types: &Vec<CustomType>,
+    custom_types: &[CustomType],
+    kind: ModuleKind,
 ) -> (WgslShaderModuleUserPortion, Vec<CustomType>) {
+    let rust_module_transformed_for_gpu =
+        lower_impl_blocks(rust_module_transformed_for_gpu, custom_types);
+    let rust_module_transformed_for_gpu =
+        monomorphise_generic_functions(&rust_module_transformed_for_gpu);
+    let rust_module_transformed_for_gpu = lift_closures(&rust_module_transformed_for_gpu);
+    let (rust_module_transformed_for_gpu, custom_types) =
+        lower_transferred_bools(&rust_module_transformed_for_gpu, custom_types);
+    let (rust_module_transformed_for_gpu, custom_types) =
+        lower_tuples(&rust_module_transformed_for_gpu, &custom_types);
     let mut out_module: WgslShaderModuleUserPortion = WgslShaderModuleUserPortion::empty();
-    out_module.main_function = Some(parse_main_function(
-        rust_module_transformed_for_gpu,
-        custom_types,
-    ));
-    out_module.static_consts = extract_constants(rust_module_transformed_for_gpu, custom_types);
+    // a library has no entry points, it is part of the modules importing it
+    if kind == ModuleKind::ShaderModule {
+        out_module.entry_points =
+            parse_entry_points(&rust_module_transformed_for_gpu, &custom_types);
+    }
+    out_module.static_consts = extract_constants(&rust_module_transformed_for_gpu, &custom_types);
     out_module.helper_functions =
-        extract_helper_functions(rust_module_transformed_for_gpu, custom_types);
-    let new_custom_types =
-        generate_helper_types_inputs_and_outputs_for_wgsl_module_def(custom_types, &mut out_module);
+        extract_helper_functions(&rust_module_transformed_for_gpu, &custom_types);
+    let new_custom_types = generate_helper_types_inputs_and_outputs_for_wgsl_module_def(
+        &custom_types,
+        &mut out_module,
+    );
     (out_module, new_custom_types)
 }
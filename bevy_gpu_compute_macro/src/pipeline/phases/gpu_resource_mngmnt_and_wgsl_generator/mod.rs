@@ -1,7 +1,15 @@
+pub mod array_configs;
+pub mod bools;
+mod closures;
 pub mod compiler_phase;
 mod constants;
 mod divide_custom_types;
+mod entry_points;
+mod enums;
+mod generics;
 mod helper_functions;
+mod impl_blocks;
 mod lib;
-mod main_function;
 pub mod to_wgsl_syntax;
+mod tuples;
+pub mod type_inference;
@@ -1,39 +1,146 @@
+use bevy_gpu_compute_core::wgsl::ast;
 use proc_macro_error::abort;
-use syn::{parse_quote, spanned::Spanned, visit_mut::VisitMut};
+use syn::{
+    Expr, GenericArgument, Lit, PathArguments, Type, parse_quote, spanned::Spanned,
+    visit_mut::VisitMut,
+};
 
+use super::lowering::WgslLowering;
+
+/// `[T; N]` becomes `array<T, N>` wherever it appears, in type aliases, struct fields, parameters, return types, local variables and constants
 pub struct ArrayToWgslTransformer {}
 
 impl VisitMut for ArrayToWgslTransformer {
-    fn visit_item_type_mut(&mut self, t: &mut syn::ItemType) {
-        syn::visit_mut::visit_item_type_mut(self, t);
-        if let syn::Type::Array(arr) = *t.ty.clone() {
-            let type_path = array_to_wgsl(&arr);
-            *t.ty = syn::Type::Path(type_path);
-        }
-    }
-    fn visit_pat_type_mut(&mut self, t: &mut syn::PatType) {
-        syn::visit_mut::visit_pat_type_mut(self, t);
-        if let syn::Type::Array(arr) = *t.ty.clone() {
-            let type_path = array_to_wgsl(&arr);
-            *t.ty = syn::Type::Path(type_path);
+    fn visit_type_mut(&mut self, t: &mut Type) {
+        // nested arrays are converted from the inside out
+        syn::visit_mut::visit_type_mut(self, t);
+        if let Type::Array(arr) = t {
+            *t = Type::Path(array_to_wgsl(arr));
         }
     }
 }
 
 pub fn array_to_wgsl(arr: &syn::TypeArray) -> syn::TypePath {
-    let ident = match *arr.elem.clone() {
-        syn::Type::Path(p) => {
-            if let Some(f) = p.path.segments.first() {
-                f.ident.clone()
-            } else {
-                abort!(arr.elem.span(), "Array element type is not a path")
-            }
-        }
+    let elem = match *arr.elem.clone() {
+        Type::Path(p) => p,
         _ => abort!(arr.elem.span(), "Array element type is not a path"),
     };
     let len = arr.len.clone();
 
-    parse_quote!(array<#ident,#len>)
+    parse_quote!(array<#elem,#len>)
+}
+
+/**
+ Array literals and repeat expressions become WGSL array constructors:
+ - `[a, b, c]` => `array<T, 3>(a, b, c)`
+ - `[a; 3]` => `array<T, 3>(a, a, a)`, `ControlFlowTransformer` already moved an `a` with side effects into a variable, so it is only evaluated once
+ - `[0.0; 3]` => `array<T, 3>()`, the zero value constructor
+
+ The array type comes from `declared_type` (the annotated type of the variable or constant that the literal is assigned to, which may also be an alias) or from the suffix of the first element, like `[0u32; 4]`.
+ Otherwise the type is left for WGSL to infer, as `array(a, b, c)`.
+*/
+pub fn array_literal_to_wgsl(
+    expr: &Expr,
+    declared_type: Option<&Type>,
+    lowering: &mut WgslLowering,
+) -> ast::Expr {
+    let (elements, len) = match expr {
+        Expr::Array(array) => (
+            array.elems.iter().cloned().collect::<Vec<_>>(),
+            array.elems.len(),
+        ),
+        Expr::Repeat(repeat) => {
+            let len = match &*repeat.len {
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Int(len), ..
+                }) => len.base10_parse::<usize>().unwrap_or_else(|_| {
+                    abort!(len.span(), "Array length is too large");
+                }),
+                _ => abort!(
+                    repeat.len.span(),
+                    "The length of a repeat expression like `[0.0; 4]` must be an integer literal in WGSL"
+                ),
+            };
+            (vec![*repeat.expr.clone(); len], len)
+        }
+        _ => abort!(expr.span(), "Expected an array literal"),
+    };
+    let array_type = match declared_type {
+        Some(declared_type) => Some(lowering.lower_type(declared_type)),
+        None => elements
+            .first()
+            .and_then(literal_suffix_type)
+            .map(|element_type| ast::Type {
+                name: "array".to_string(),
+                template_args: vec![
+                    ast::TemplateArg::Type(element_type),
+                    ast::TemplateArg::Expr(ast::Expr::literal(len.to_string())),
+                ],
+            }),
+    };
+    if let (Some(array_type), Expr::Repeat(repeat)) = (&array_type, expr) {
+        if is_zero_literal(&repeat.expr) {
+            return ast::Expr::call(array_type.clone(), Vec::new());
+        }
+    }
+    let element_type = declared_type.and_then(wgsl_array_element_type);
+    let elements = elements
+        .iter()
+        .map(|element| {
+            if matches!(element, Expr::Array(_) | Expr::Repeat(_)) {
+                array_literal_to_wgsl(element, element_type.as_ref(), lowering)
+            } else {
+                lowering.lower_expr(element)
+            }
+        })
+        .collect();
+    let constructor = array_type.unwrap_or_else(|| ast::Type::named("array"));
+    ast::Expr::call(constructor, elements)
+}
+
+/// the `T` of an `array<T, N>` type, which rust arrays were already converted to by `ArrayToWgslTransformer`
+fn wgsl_array_element_type(t: &Type) -> Option<Type> {
+    let Type::Path(p) = t else {
+        return None;
+    };
+    let last = p.path.segments.last()?;
+    if last.ident != "array" {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(element_type) => Some(element_type.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn literal_suffix_type(expr: &Expr) -> Option<ast::Type> {
+    let Expr::Lit(lit) = expr else {
+        return None;
+    };
+    let suffix = match &lit.lit {
+        Lit::Int(i) => i.suffix(),
+        Lit::Float(f) => f.suffix(),
+        _ => return None,
+    };
+    match suffix {
+        "u32" | "i32" | "f32" => Some(ast::Type::named(suffix)),
+        _ => None,
+    }
+}
+
+fn is_zero_literal(expr: &Expr) -> bool {
+    let Expr::Lit(lit) = expr else {
+        return false;
+    };
+    match &lit.lit {
+        Lit::Int(i) => i.base10_parse::<u64>().is_ok_and(|v| v == 0),
+        Lit::Float(f) => f.base10_parse::<f64>().is_ok_and(|v| v == 0.0),
+        Lit::Bool(b) => !b.value,
+        _ => false,
+    }
 }
 
 #[cfg(test)]
@@ -48,4 +155,37 @@ mod tests {
         let output = array_to_wgsl(&input);
         assert_eq!(output.to_token_stream().to_string(), "array < f32 , 4 >");
     }
+
+    #[test]
+    fn test_nested_array_types() {
+        let mut input: syn::ItemStruct = parse_quote! {
+            struct Grid { cells: [[u32; 4]; 2], weights: [Vec2F32; 3] }
+        };
+        ArrayToWgslTransformer {}.visit_item_struct_mut(&mut input);
+        assert_eq!(
+            input.to_token_stream().to_string(),
+            "struct Grid { cells : array < array < u32 , 4 > , 2 > , weights : array < Vec2F32 , 3 > }"
+        );
+    }
+
+    #[test]
+    fn test_array_literals() {
+        let custom_types = Vec::new();
+        let mut lowering = WgslLowering::new(&custom_types);
+        let declared: Type = parse_quote!(array<array<u32, 2>, 2>);
+        let cases: [(Expr, Option<&Type>, &str); 4] = [
+            (parse_quote!([1.0, 2.0]), None, "array(1.0, 2.0)"),
+            (parse_quote!([x; 3]), None, "array(x, x, x)"),
+            (parse_quote!([0i32; 8]), None, "array<i32, 8>()"),
+            (
+                parse_quote!([[1, 2], [3, 4]]),
+                Some(&declared),
+                "array<array<u32, 2>, 2>(array<u32, 2>(1, 2), array<u32, 2>(3, 4))",
+            ),
+        ];
+        for (input, declared_type, expected) in cases {
+            let output = array_literal_to_wgsl(&input, declared_type, &mut lowering).to_string();
+            assert_eq!(output, expected);
+        }
+    }
 }
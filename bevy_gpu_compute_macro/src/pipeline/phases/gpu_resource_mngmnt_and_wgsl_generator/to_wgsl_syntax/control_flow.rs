@@ -0,0 +1,762 @@
+use proc_macro_error::abort;
+use proc_macro2::Span;
+use quote::format_ident;
+use syn::{
+    BinOp, Block, Expr, ExprBreak, ExprContinue, ExprIf, Ident, ReturnType, Stmt, Type,
+    parse_quote,
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+};
+
+/**
+ WGSL has `loop {}`, `switch`, `break` and `continue`, but no loop labels and no statements that produce a value, so before the expressions are converted:
+ - `break 'outer;` inside a nested loop => `_break_outer = true; break;`, followed by `if _break_outer { break; }` after every loop between it and `'outer`
+ - `continue 'outer;` inside a nested loop => `_continue_outer = true; break;`, followed by `if _continue_outer { continue; }` right after the nested loop in the body of `'outer` (`break` at the levels in between). The flag is reset at the start of every iteration of `'outer`
+ - `break;` inside a `match` arm is handled like a labeled break, since in WGSL it would only leave the `switch` the `match` becomes
+ - `let x: T = loop { break value; };` => `let mut _loop_value_0: T; loop { _loop_value_0 = value; break; } let x: T = _loop_value_0;`
+ - `let x: T = match y { 0 => a, _ => b };` => `let mut _match_value_0: T; match y { 0 => { _match_value_0 = a; } _ => { _match_value_0 = b; } } let x: T = _match_value_0;`
+ - `let x = if c { a } else { b };` => `let x = select(b, a, c);` when `a` and `b` are simple expressions without side effects, otherwise it is lowered like a `match`, and so are blocks like `let x: T = { ...; a };`
+ - `[f(x); 3]` => `let _repeat_value_0 = f(x); [_repeat_value_0; 3]` when the repeated value is not a simple expression, since it is evaluated once in rust but would be once per element in the WGSL array constructor
+
+   the type of the hidden variable comes from the `let` annotation, or from the function return type when the value is returned
+*/
+pub struct ControlFlowTransformer {
+    /// the loops and matches enclosing the expression currently being visited, innermost last
+    frame_stack: Vec<Frame>,
+    /// statements that have to be inserted before the statement currently being visited
+    hoisted: Vec<Stmt>,
+    /// labeled breaks and continues of loops in the statement currently being visited that still have to be forwarded to an enclosing loop
+    exited: Vec<Escape>,
+    /// how the value of the next visited expression is used
+    position: Position,
+    /// type of the trailing expression of the next visited block, if it is known
+    tail_type: Option<Type>,
+    return_type: Option<Type>,
+    /// used to give the hidden variables of a function unique names
+    hidden_var_count: usize,
+}
+
+#[derive(Clone)]
+enum Position {
+    /// the value of the expression is discarded
+    Statement,
+    /// the value of the expression is used, its type is known if `Some`
+    Value(Option<Box<Type>>),
+    /// the value of the expression is returned from the function, `WgslLowering` inserts the `return`s into the branches of `if`s and blocks in this position
+    Tail(Box<Type>),
+}
+
+impl Position {
+    fn is_value(&self) -> bool {
+        !matches!(self, Position::Statement)
+    }
+    fn value_type(self) -> Option<Box<Type>> {
+        match self {
+            Position::Statement => None,
+            Position::Value(t) => t,
+            Position::Tail(t) => Some(t),
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum FrameKind {
+    Loop,
+    Match,
+}
+
+struct Frame {
+    kind: FrameKind,
+    label: Option<Ident>,
+    break_flag: Option<Ident>,
+    continue_flag: Option<Ident>,
+    value_var: Option<Ident>,
+    /// labeled breaks and continues inside this frame that target an enclosing loop
+    escaping: Vec<Escape>,
+}
+
+#[derive(Clone, PartialEq)]
+enum Escape {
+    /// index of the targeted loop in the frame stack, and its flag
+    Break(usize, Ident),
+    Continue(usize, Ident),
+}
+
+impl ControlFlowTransformer {
+    pub fn new() -> Self {
+        ControlFlowTransformer {
+            frame_stack: Vec::new(),
+            hoisted: Vec::new(),
+            exited: Vec::new(),
+            position: Position::Value(None),
+            tail_type: None,
+            return_type: None,
+            hidden_var_count: 0,
+        }
+    }
+
+    fn hidden_var(&mut self, prefix: &str) -> Ident {
+        let ident = format_ident!("{}_{}", prefix, self.hidden_var_count);
+        self.hidden_var_count += 1;
+        ident
+    }
+
+    fn push_frame(&mut self, kind: FrameKind, label: Option<Ident>) {
+        self.frame_stack.push(Frame {
+            kind,
+            label,
+            break_flag: None,
+            continue_flag: None,
+            value_var: None,
+            escaping: Vec::new(),
+        });
+    }
+
+    /// pops the innermost frame, queueing the flag declarations it needs in front of its statement
+    fn pop_frame(&mut self) -> Frame {
+        let frame = self.frame_stack.pop().unwrap();
+        if let Some(flag) = &frame.break_flag {
+            self.hoisted.push(parse_quote!(let mut #flag = false;));
+        }
+        if let Some(flag) = &frame.continue_flag {
+            self.hoisted.push(parse_quote!(let mut #flag = false;));
+        }
+        for escape in frame.escaping.iter() {
+            if !self.exited.contains(escape) {
+                self.exited.push(escape.clone());
+            }
+        }
+        frame
+    }
+
+    fn visit_loop_body(&mut self, label: Option<Ident>, body: &mut Block) -> Frame {
+        self.push_frame(FrameKind::Loop, label);
+        self.visit_block_mut(body);
+        let frame = self.pop_frame();
+        if let Some(flag) = &frame.continue_flag {
+            body.stmts.insert(0, parse_quote!(#flag = false;));
+        }
+        frame
+    }
+
+    /// replaces a value producing expression with its hidden variable, moving the expression itself in front of the current statement
+    fn hoist_value(&mut self, expr: &mut Expr, value_var: Ident, value_type: Option<Box<Type>>) {
+        let Some(value_type) = value_type else {
+            abort!(
+                expr.span(),
+                "Loops, matches, ifs and blocks that produce a value need a known type in WGSL, bind the value with a type annotation like `let x: u32 = ...;`"
+            );
+        };
+        self.hoisted
+            .push(parse_quote!(let mut #value_var: #value_type;));
+        self.hoisted.push(Stmt::Expr(expr.clone(), None));
+        *expr = parse_quote!(#value_var);
+    }
+
+    /// lowers a value producing block, `if` or `match` into assignments to a hidden variable in each of its branches
+    fn hoist_branches(&mut self, expr: &mut Expr, prefix: &str, position: Position) {
+        let value_var = self.hidden_var(prefix);
+        *expr = assign_value_to_branches(expr.clone(), &value_var);
+        self.position = Position::Statement;
+        self.visit_expr_mut(expr);
+        self.hoist_value(expr, value_var, position.value_type());
+    }
+
+    /// index in the frame stack of the loop targeted by a `break` or `continue`
+    fn target_loop(&self, label: &Option<syn::Lifetime>, span: proc_macro2::Span) -> usize {
+        match label {
+            Some(lifetime) => self
+                .frame_stack
+                .iter()
+                .rposition(|frame| frame.label.as_ref() == Some(&lifetime.ident))
+                .unwrap_or_else(|| {
+                    abort!(
+                        lifetime.span(),
+                        "Labels are only supported on loops in WGSL, not on blocks"
+                    )
+                }),
+            None => self
+                .frame_stack
+                .iter()
+                .rposition(|frame| frame.kind == FrameKind::Loop)
+                .unwrap_or_else(|| abort!(span, "`break` and `continue` must be inside a loop")),
+        }
+    }
+
+    /// the flag of the targeted loop, creating it if this is the first escape to that loop
+    fn escape_flag(&mut self, target: usize, is_break: bool) -> Ident {
+        let existing = if is_break {
+            &self.frame_stack[target].break_flag
+        } else {
+            &self.frame_stack[target].continue_flag
+        };
+        if let Some(flag) = existing {
+            return flag.clone();
+        }
+        let prefix = if is_break { "_break" } else { "_continue" };
+        let flag = match &self.frame_stack[target].label {
+            // a hidden variable, so it must not be located at the label in the WGSL output
+            Some(label) => format_ident!("{}_{}", prefix, label, span = Span::call_site()),
+            None => self.hidden_var(prefix),
+        };
+        if is_break {
+            self.frame_stack[target].break_flag = Some(flag.clone());
+        } else {
+            self.frame_stack[target].continue_flag = Some(flag.clone());
+        }
+        flag
+    }
+
+    fn break_to_stmts(&mut self, break_expr: &mut ExprBreak) -> Vec<Stmt> {
+        let target = self.target_loop(&break_expr.label, break_expr.span());
+        let mut stmts = Vec::new();
+        if let Some(value) = break_expr.expr.as_mut() {
+            self.visit_expr_mut(value);
+            let value_var = match &self.frame_stack[target].value_var {
+                Some(value_var) => value_var.clone(),
+                None => {
+                    let value_var = self.hidden_var("_loop_value");
+                    self.frame_stack[target].value_var = Some(value_var.clone());
+                    value_var
+                }
+            };
+            stmts.push(parse_quote!(#value_var = #value;));
+        }
+        if target != self.frame_stack.len() - 1 {
+            let flag = self.escape_flag(target, true);
+            self.escape(target, Escape::Break(target, flag.clone()));
+            stmts.push(parse_quote!(#flag = true;));
+        }
+        stmts.push(parse_quote!(break;));
+        stmts
+    }
+
+    fn continue_to_stmts(&mut self, continue_expr: &ExprContinue) -> Vec<Stmt> {
+        let target = self.target_loop(&continue_expr.label, continue_expr.span());
+        // a continue inside a switch still applies to the enclosing loop in WGSL
+        let crosses_loop = self.frame_stack[target + 1..]
+            .iter()
+            .any(|frame| frame.kind == FrameKind::Loop);
+        if !crosses_loop {
+            return vec![parse_quote!(continue;)];
+        }
+        let flag = self.escape_flag(target, false);
+        self.escape(target, Escape::Continue(target, flag.clone()));
+        vec![parse_quote!(#flag = true;), parse_quote!(break;)]
+    }
+
+    /// records the escape in every frame between the current one and the targeted one
+    fn escape(&mut self, target: usize, escape: Escape) {
+        for frame in self.frame_stack[target + 1..].iter_mut() {
+            if !frame.escaping.contains(&escape) {
+                frame.escaping.push(escape.clone());
+            }
+        }
+    }
+
+    /// statements that forward labeled breaks and continues after a nested loop or match has been exited
+    fn escape_checks(&self, exited: &[Escape]) -> Vec<Stmt> {
+        let current = self.frame_stack.len().checked_sub(1);
+        exited
+            .iter()
+            .map(|escape| match escape {
+                Escape::Continue(target, flag) if Some(*target) == current => {
+                    parse_quote!(if #flag { continue; })
+                }
+                Escape::Break(_, flag) | Escape::Continue(_, flag) => {
+                    parse_quote!(if #flag { break; })
+                }
+            })
+            .collect()
+    }
+}
+
+/// turns an expression whose value is used into a block that assigns that value to `value_var`
+fn assign_value(expr: Expr, value_var: &Ident) -> Block {
+    match expr {
+        Expr::Block(block_expr) if block_expr.label.is_none() => {
+            let mut block = block_expr.block;
+            if let Some(Stmt::Expr(tail, None)) = block
+                .stmts
+                .pop_if(|stmt| matches!(stmt, Stmt::Expr(_, None)))
+            {
+                block.stmts.push(assign_value_stmt(tail, value_var));
+            }
+            block
+        }
+        _ => Block {
+            brace_token: Default::default(),
+            stmts: vec![assign_value_stmt(expr, value_var)],
+        },
+    }
+}
+
+fn assign_value_stmt(expr: Expr, value_var: &Ident) -> Stmt {
+    match expr {
+        Expr::Block(_) | Expr::If(_) | Expr::Match(_) => {
+            Stmt::Expr(assign_value_to_branches(expr, value_var), None)
+        }
+        Expr::Break(_) | Expr::Continue(_) | Expr::Return(_) => parse_quote!(#expr;),
+        _ => parse_quote!(#value_var = #expr;),
+    }
+}
+
+/// moves the assignment of the value of a block, `if` or `match` into each of its branches
+fn assign_value_to_branches(expr: Expr, value_var: &Ident) -> Expr {
+    match expr {
+        Expr::Block(ref block_expr) if block_expr.label.is_none() => {
+            let block = assign_value(expr, value_var);
+            parse_quote!(#block)
+        }
+        Expr::If(mut if_expr) => {
+            let then_branch = &if_expr.then_branch;
+            if_expr.then_branch = assign_value(parse_quote!(#then_branch), value_var);
+            if let Some((_, else_branch)) = if_expr.else_branch.as_mut() {
+                **else_branch = assign_value_to_branches(*else_branch.clone(), value_var);
+            }
+            Expr::If(if_expr)
+        }
+        Expr::Match(mut match_expr) => {
+            for arm in match_expr.arms.iter_mut() {
+                let body = assign_value(*arm.body.clone(), value_var);
+                *arm.body = parse_quote!(#body);
+            }
+            Expr::Match(match_expr)
+        }
+        _ => expr,
+    }
+}
+
+/// whether a block or `if` ends in a value, rather than only containing statements
+fn produces_value(expr: &Expr) -> bool {
+    match expr {
+        Expr::Block(block_expr) => block_produces_value(&block_expr.block),
+        Expr::If(if_expr) => match &if_expr.else_branch {
+            Some((_, else_branch)) => {
+                block_produces_value(&if_expr.then_branch) || produces_value(else_branch)
+            }
+            None => false,
+        },
+        _ => true,
+    }
+}
+
+fn block_produces_value(block: &Block) -> bool {
+    match block.stmts.last() {
+        Some(Stmt::Expr(Expr::Break(_) | Expr::Continue(_) | Expr::Return(_), None)) => false,
+        Some(Stmt::Expr(tail, None)) => produces_value(tail),
+        _ => false,
+    }
+}
+
+/// `if c { a } else { b }` => `select(b, a, c)`, if `a` and `b` are cheap and side-effect free, since `select` always evaluates both
+fn if_to_select(if_expr: &ExprIf) -> Option<Expr> {
+    let then_value = simple_block_value(&if_expr.then_branch)?;
+    let else_value = match &*if_expr.else_branch.as_ref()?.1 {
+        Expr::Block(block_expr) if block_expr.label.is_none() => {
+            simple_block_value(&block_expr.block)?
+        }
+        Expr::If(else_if) => if_to_select(else_if)?,
+        _ => return None,
+    };
+    let cond = &if_expr.cond;
+    Some(parse_quote!(select(#else_value, #then_value, #cond)))
+}
+
+fn simple_block_value(block: &Block) -> Option<Expr> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(value, None)] if is_simple(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// literals, variables, fields, indexing and arithmetic on those
+fn is_simple(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Path(_) => true,
+        Expr::Field(field) => is_simple(&field.base),
+        Expr::Index(index) => is_simple(&index.expr) && is_simple(&index.index),
+        Expr::Unary(unary) => is_simple(&unary.expr),
+        Expr::Binary(binary) => {
+            !is_compound_assignment(&binary.op)
+                && is_simple(&binary.left)
+                && is_simple(&binary.right)
+        }
+        Expr::Paren(paren) => is_simple(&paren.expr),
+        Expr::Group(group) => is_simple(&group.expr),
+        Expr::Cast(cast) => is_simple(&cast.expr),
+        _ => false,
+    }
+}
+
+pub(super) fn is_compound_assignment(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
+
+impl VisitMut for ControlFlowTransformer {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut syn::ItemFn) {
+        self.hidden_var_count = 0;
+        self.return_type = match &item_fn.sig.output {
+            ReturnType::Default => None,
+            ReturnType::Type(_, t) => Some(*t.clone()),
+        };
+        self.tail_type = self.return_type.clone();
+        self.visit_block_mut(&mut item_fn.block);
+    }
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let tail_type = self.tail_type.take();
+        let stmt_count = block.stmts.len();
+        let mut stmts = Vec::with_capacity(stmt_count);
+        for (i, mut stmt) in std::mem::take(&mut block.stmts).into_iter().enumerate() {
+            let outer_hoisted = std::mem::take(&mut self.hoisted);
+            let outer_exited = std::mem::take(&mut self.exited);
+            match &mut stmt {
+                // expanded in place, so that no extra block is needed
+                Stmt::Expr(Expr::Break(break_expr), _) => {
+                    let break_stmts = self.break_to_stmts(break_expr);
+                    stmts.append(&mut self.hoisted);
+                    stmts.extend(break_stmts);
+                }
+                Stmt::Expr(Expr::Continue(continue_expr), _) => {
+                    stmts.extend(self.continue_to_stmts(continue_expr));
+                }
+                _ => {
+                    if let Stmt::Expr(_, semi) = &stmt {
+                        self.position = match &tail_type {
+                            Some(t) if i == stmt_count - 1 && semi.is_none() => {
+                                Position::Tail(Box::new(t.clone()))
+                            }
+                            _ => Position::Statement,
+                        };
+                    }
+                    self.visit_stmt_mut(&mut stmt);
+                    stmts.append(&mut self.hoisted);
+                    stmts.push(stmt);
+                    let exited = std::mem::take(&mut self.exited);
+                    stmts.extend(self.escape_checks(&exited));
+                }
+            }
+            self.hoisted = outer_hoisted;
+            self.exited = outer_exited;
+        }
+        block.stmts = stmts;
+    }
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        if let Some(init) = local.init.as_mut() {
+            if let syn::Pat::Type(pat_type) = &local.pat {
+                self.position = Position::Value(Some(pat_type.ty.clone()));
+            }
+            self.visit_expr_mut(&mut init.expr);
+            if let Some((_, diverge)) = init.diverge.as_mut() {
+                self.visit_expr_mut(diverge);
+            }
+        }
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let position = std::mem::replace(&mut self.position, Position::Value(None));
+        let is_value = position.is_value() && produces_value(expr);
+        match expr {
+            Expr::Loop(loop_expr) => {
+                let label = loop_expr.label.take().map(|l| l.name.ident);
+                let frame = self.visit_loop_body(label, &mut loop_expr.body);
+                if let Some(value_var) = frame.value_var {
+                    self.hoist_value(expr, value_var, position.value_type());
+                }
+            }
+            Expr::ForLoop(for_loop) => {
+                self.visit_expr_mut(&mut for_loop.expr);
+                let label = for_loop.label.take().map(|l| l.name.ident);
+                self.visit_loop_body(label, &mut for_loop.body);
+            }
+            Expr::While(while_expr) => {
+                self.visit_expr_mut(&mut while_expr.cond);
+                let label = while_expr.label.take().map(|l| l.name.ident);
+                self.visit_loop_body(label, &mut while_expr.body);
+            }
+            Expr::Block(block_expr) if block_expr.label.is_some() => {
+                abort!(
+                    block_expr.label.span(),
+                    "Labels are only supported on loops in WGSL, not on blocks"
+                );
+            }
+            Expr::Match(_) if position.is_value() => {
+                self.hoist_branches(expr, "_match_value", position);
+            }
+            Expr::Match(match_expr) => {
+                self.visit_expr_mut(&mut match_expr.expr);
+                self.push_frame(FrameKind::Match, None);
+                for arm in match_expr.arms.iter_mut() {
+                    self.position = Position::Statement;
+                    self.visit_expr_mut(&mut arm.body);
+                }
+                self.pop_frame();
+            }
+            Expr::If(if_expr) if is_value && matches!(position, Position::Tail(_)) => {
+                let return_type = *position.value_type().unwrap();
+                self.visit_expr_mut(&mut if_expr.cond);
+                self.tail_type = Some(return_type.clone());
+                self.visit_block_mut(&mut if_expr.then_branch);
+                if let Some((_, else_branch)) = if_expr.else_branch.as_mut() {
+                    self.position = Position::Tail(Box::new(return_type));
+                    self.visit_expr_mut(else_branch);
+                }
+            }
+            Expr::Block(block_expr) if is_value && matches!(position, Position::Tail(_)) => {
+                self.tail_type = position.value_type().map(|t| *t);
+                self.visit_block_mut(&mut block_expr.block);
+            }
+            Expr::If(if_expr) if is_value => {
+                if let Some(select) = if_to_select(if_expr) {
+                    *expr = select;
+                    visit_mut::visit_expr_mut(self, expr);
+                } else {
+                    self.hoist_branches(expr, "_if_value", position);
+                }
+            }
+            Expr::Block(block_expr) if is_value => {
+                if let [Stmt::Expr(value, None)] = block_expr.block.stmts.as_slice() {
+                    // nothing to hoist if the block only holds its value
+                    *expr = parse_quote!((#value));
+                    self.position = position;
+                    self.visit_expr_mut(expr);
+                } else {
+                    self.hoist_branches(expr, "_block_value", position);
+                }
+            }
+            Expr::Break(break_expr) => {
+                let stmts = self.break_to_stmts(break_expr);
+                *expr = parse_quote!({ #(#stmts)* });
+            }
+            Expr::Continue(continue_expr) => {
+                let stmts = self.continue_to_stmts(continue_expr);
+                *expr = parse_quote!({ #(#stmts)* });
+            }
+            Expr::Return(return_expr) => {
+                if let Some(returned) = return_expr.expr.as_mut() {
+                    self.position = Position::Value(self.return_type.clone().map(Box::new));
+                    self.visit_expr_mut(returned);
+                }
+            }
+            Expr::Repeat(repeat)
+                if !is_simple(&repeat.expr)
+                    && !matches!(&*repeat.expr, Expr::Array(_) | Expr::Repeat(_)) =>
+            {
+                self.visit_expr_mut(&mut repeat.expr);
+                let value_var = self.hidden_var("_repeat_value");
+                let value = &repeat.expr;
+                self.hoisted.push(parse_quote!(let #value_var = #value;));
+                *repeat.expr = parse_quote!(#value_var);
+            }
+            // closures have their own loops and return type
+            Expr::Closure(_) => {}
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+    use syn::ItemFn;
+
+    fn lower(mut item_fn: ItemFn) -> String {
+        ControlFlowTransformer::new().visit_item_fn_mut(&mut item_fn);
+        item_fn.block.to_token_stream().to_string()
+    }
+
+    #[test]
+    fn test_unlabeled_loop_is_unchanged() {
+        let input: ItemFn = parse_quote! {
+            fn f() {
+                loop {
+                    if x > 3 {
+                        break;
+                    }
+                    x += 1;
+                }
+            }
+        };
+        assert_eq!(lower(input), "{ loop { if x > 3 { break ; } x += 1 ; } }");
+    }
+
+    #[test]
+    fn test_labeled_break() {
+        let input: ItemFn = parse_quote! {
+            fn f() {
+                'outer: loop {
+                    loop {
+                        break 'outer;
+                    }
+                }
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let mut _break_outer = false ; loop { loop { _break_outer = true ; break ; } if _break_outer { break ; } } }"
+        );
+    }
+
+    #[test]
+    fn test_labeled_continue_through_two_levels() {
+        let input: ItemFn = parse_quote! {
+            fn f() {
+                'outer: for i in 0..4 {
+                    for j in 0..4 {
+                        while c {
+                            continue 'outer;
+                        }
+                    }
+                }
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let mut _continue_outer = false ; for i in 0 .. 4 { _continue_outer = false ; for j in 0 .. 4 { while c { _continue_outer = true ; break ; } if _continue_outer { break ; } } if _continue_outer { continue ; } } }"
+        );
+    }
+
+    #[test]
+    fn test_break_value() {
+        let input: ItemFn = parse_quote! {
+            fn f() {
+                let x: u32 = loop {
+                    break 5;
+                };
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let mut _loop_value_0 : u32 ; loop { _loop_value_0 = 5 ; break ; } let x : u32 = _loop_value_0 ; }"
+        );
+    }
+
+    #[test]
+    fn test_returned_break_value() {
+        let input: ItemFn = parse_quote! {
+            fn f() -> i32 {
+                loop {
+                    if c {
+                        break 1
+                    }
+                }
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let mut _loop_value_0 : i32 ; loop { if c { _loop_value_0 = 1 ; break ; } } _loop_value_0 }"
+        );
+    }
+
+    #[test]
+    fn test_match_value() {
+        let input: ItemFn = parse_quote! {
+            fn f(x: u32) -> f32 {
+                match x {
+                    0 => 1.0,
+                    _ => {
+                        let y = 2.0;
+                        y * 3.0
+                    }
+                }
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let mut _match_value_0 : f32 ; match x { 0 => { _match_value_0 = 1.0 ; } , _ => { let y = 2.0 ; _match_value_0 = y * 3.0 ; } } _match_value_0 }"
+        );
+    }
+
+    #[test]
+    fn test_break_inside_match() {
+        let input: ItemFn = parse_quote! {
+            fn f() {
+                loop {
+                    match x {
+                        0 => break,
+                        1 => continue,
+                        _ => {}
+                    }
+                }
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let mut _break_0 = false ; loop { match x { 0 => { _break_0 = true ; break ; } , 1 => { continue ; } , _ => { } } if _break_0 { break ; } } }"
+        );
+    }
+
+    #[test]
+    fn test_simple_if_value_becomes_select() {
+        let input: ItemFn = parse_quote! {
+            fn f(d: f32) {
+                let r = if d > 0.0 { d } else if d < -1.0 { -1.0 } else { 0.0 };
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let r = select (select (0.0 , - 1.0 , d < - 1.0) , d , d > 0.0) ; }"
+        );
+    }
+
+    #[test]
+    fn test_if_value_with_side_effects_is_hoisted() {
+        let input: ItemFn = parse_quote! {
+            fn f(d: f32) {
+                let r: f32 = if d > 0.0 {
+                    let s = d * 2.0;
+                    s
+                } else {
+                    helper(d)
+                };
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let mut _if_value_0 : f32 ; if d > 0.0 { let s = d * 2.0 ; _if_value_0 = s ; } else { _if_value_0 = helper (d) ; } let r : f32 = _if_value_0 ; }"
+        );
+    }
+
+    #[test]
+    fn test_block_value() {
+        let input: ItemFn = parse_quote! {
+            fn f() {
+                let a = { 3.0 };
+                let b: f32 = {
+                    let t = 2.0;
+                    t * a
+                };
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let a = (3.0) ; let mut _block_value_0 : f32 ; { let t = 2.0 ; _block_value_0 = t * a ; } let b : f32 = _block_value_0 ; }"
+        );
+    }
+
+    #[test]
+    fn test_repeated_value_is_evaluated_once() {
+        let input: ItemFn = parse_quote! {
+            fn f(d: f32) {
+                let a = [next(d); 3];
+                let b = [d; 2];
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "{ let _repeat_value_0 = next (d) ; let a = [_repeat_value_0 ; 3] ; let b = [d ; 2] ; }"
+        );
+    }
+}
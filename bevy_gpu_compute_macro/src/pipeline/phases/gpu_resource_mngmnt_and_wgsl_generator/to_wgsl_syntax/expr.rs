@@ -1,246 +1,360 @@
+use bevy_gpu_compute_core::wgsl::ast;
 use proc_macro_error::abort;
 use quote::ToTokens;
-use syn::{Expr, ExprCall, LitFloat, parse_quote, parse2, spanned::Spanned, visit_mut::VisitMut};
+use syn::{
+    BinOp, Expr, ExprLit, ExprPath, Lit, LitFloat, LitInt, Member, Type, UnOp, parse_quote,
+    spanned::Spanned,
+};
 
-use crate::pipeline::allowed_types::WGSL_NATIVE_TYPES;
+use super::{
+    array::array_literal_to_wgsl,
+    lowering::{WgslLowering, compound_assignment_op},
+    method_call::method_call_to_wgsl,
+    struct_literal::struct_literal_to_wgsl,
+    r#type::convert_path_segment,
+};
+use crate::pipeline::{
+    allowed_types::{WGSL_BUILTIN_FUNCTIONS, WGSL_NATIVE_TYPES},
+    phases::custom_type_collector::custom_type::CustomTypeKind,
+    phases::gpu_resource_mngmnt_and_wgsl_generator::enums::enum_variant_to_wgsl,
+};
 
-pub struct ExprToWgslTransformer {}
-
-impl VisitMut for ExprToWgslTransformer {
-    fn visit_expr_mut(&mut self, expr: &mut syn::Expr) {
-        // First visit nested expressions
-        syn::visit_mut::visit_expr_mut(self, expr);
-        if let Some(new_expr) = expr_to_wgsl(expr) {
-            *expr = new_expr;
-        }
-    }
-}
-
-/// if none then no mutation is needed
-pub fn expr_to_wgsl(expr: &syn::Expr) -> Option<Expr> {
-    #[allow(unused_variables)]
-    match expr {
-        syn::Expr::Lit(lit) => match &lit.lit {
-            // to handle things like 3.4_f32
-            // if the suffix is u32 then write it as a type cast like u32(digits), same for f32, and i32 and f16
-            syn::Lit::Float(l) => {
-                // must either have no suffix, in which case we do nothing, or have f32 as the suffix
-                let suffix = l.suffix();
-                if suffix.is_empty() || suffix == "f" {
-                    None
-                } else if suffix == "f32" {
-                    let value = l.base10_digits();
-                    let value = LitFloat::new(value, l.span());
-                    return Some(parse_quote!(f32(#value)));
-                } else {
-                    abort!(
-                        l.span(),
-                        "Unsupported float suffix in WGSL: ".to_owned() + suffix
-                    );
-                }
+impl WgslLowering<'_> {
+    /// an expression whose value is used
+    pub fn lower_expr(&mut self, expr: &Expr) -> ast::Expr {
+        match expr {
+            Expr::Lit(lit) => literal_to_wgsl(lit),
+            Expr::Path(path) => self.lower_path(path),
+            Expr::Call(call) => ast::Expr::call(
+                self.lower_callee(&call.func),
+                call.args.iter().map(|arg| self.lower_expr(arg)).collect(),
+            ),
+            // `x as f32` => `f32(x)`
+            Expr::Cast(cast) => {
+                ast::Expr::call(self.lower_type(&cast.ty), vec![self.lower_expr(&cast.expr)])
             }
-            syn::Lit::Int(l) => {
-                let suffix = l.suffix();
-                if suffix.is_empty() || suffix == "u" || suffix == "i" {
-                    None
-                } else if suffix == "u32" {
-                    let value = l.base10_digits();
-                    let value = LitFloat::new(value, l.span());
-                    return Some(parse_quote!(u32(#value)));
-                } else if suffix == "i32" {
-                    let value = l.base10_digits();
-                    let value = LitFloat::new(value, l.span());
-                    return Some(parse_quote!(i32(#value)));
-                } else {
+            Expr::MethodCall(method_call) => {
+                let lowered = method_call_to_wgsl(method_call);
+                self.lower_expr(&lowered)
+            }
+            // struct literals are reordered before their fields are lowered, since default values may be added
+            Expr::Struct(struct_expr) => {
+                let lowered = struct_literal_to_wgsl(struct_expr, self.custom_types);
+                self.lower_expr(&lowered)
+            }
+            Expr::Array(_) | Expr::Repeat(_) => array_literal_to_wgsl(expr, None, self),
+            Expr::Binary(binary) => {
+                if compound_assignment_op(&binary.op).is_some() {
                     abort!(
-                        l.span(),
-                        "Unsupported integer suffix in WGSL: ".to_owned() + suffix
+                        binary.span(),
+                        "Compound assignments can only be used as statements in WGSL"
                     );
                 }
+                ast::Expr::binary(
+                    binary_op(&binary.op),
+                    self.lower_expr(&binary.left),
+                    self.lower_expr(&binary.right),
+                )
             }
-            _ => None,
-        },
-        syn::Expr::Array(array) => {
-            abort!(array.span(), "Array literals are not supported in WGSL")
-        }
-        syn::Expr::Assign(assign) => None,
-        syn::Expr::Async(async_expr) => {
-            abort!(
-                async_expr.span(),
-                "Async expressions are not supported in WGSL"
-            )
-        }
-        syn::Expr::Await(await_expr) => {
-            abort!(
-                await_expr.span(),
-                "Await expressions are not supported in WGSL"
-            )
-        }
-        syn::Expr::Binary(bin) => None,
-        syn::Expr::Block(block) => None,
-        syn::Expr::Break(break_expr) => None,
-        syn::Expr::Call(call) => None,
-        syn::Expr::Cast(cast) => {
-            let cast_type = cast.ty.clone();
-            let cast_expr = cast.expr.clone();
-            match *cast_type {
-                syn::Type::Path(path) => {
-                    let ident = path.path.segments.first().unwrap().ident.clone();
-                    Some(parse_quote! (#ident(#cast_expr)))
-                }
-                _ => None,
+            Expr::Unary(unary) => {
+                let op = match unary.op {
+                    UnOp::Deref(_) => ast::UnaryOp::Deref,
+                    UnOp::Not(_) => ast::UnaryOp::Not,
+                    UnOp::Neg(_) => ast::UnaryOp::Neg,
+                    _ => abort!(unary.span(), "Unsupported unary operator in WGSL"),
+                };
+                ast::Expr::unary(op, self.lower_expr(&unary.expr))
             }
-        }
-        syn::Expr::Closure(closure) => {
-            abort!(
-                closure.span(),
-                "Closure expressions are not supported in WGSL"
-            )
-        }
-        syn::Expr::Const(const_expr) => {
-            abort!(const_expr.span(), "Const blocks are not supported in WGSL")
-        }
-        syn::Expr::Continue(continue_expr) => None,
-        syn::Expr::Field(field) => None,
-        syn::Expr::ForLoop(for_loop) => {
-            abort!(
-                for_loop.span(),
-                "For loops to wgsl syntax conversion not yet implemented"
-            );
-            //todo Convert to wgsl style for loop from rust
-            // let s = format!(
-            // "for ({init}; {cond}; {update}) {{ {body} }}",
-            // init = &for_loop.init,
-            // cond = &for_loop.cond,
-            // update = &for_loop.update,
-            // body = &for_loop.body
-            // );
-            // Some(parse_quote!(#s))
-        }
-        syn::Expr::Group(group) => None,
-        syn::Expr::If(if_expr) => None,
-        syn::Expr::Index(index) => None,
-        syn::Expr::Infer(_) => {
-            abort!(
+            // `&mut x` was already turned into `&x` by `PointerToWgslTransformer`
+            Expr::Reference(reference) => {
+                ast::Expr::unary(ast::UnaryOp::AddressOf, self.lower_expr(&reference.expr))
+            }
+            Expr::Field(field) => {
+                let member = match &field.member {
+                    Member::Named(ident) => ident.to_string(),
+                    Member::Unnamed(index) => index.index.to_string(),
+                };
+                ast::Expr::field(self.lower_expr(&field.base), member)
+            }
+            Expr::Index(index) => {
+                ast::Expr::index(self.lower_expr(&index.expr), self.lower_expr(&index.index))
+            }
+            // the printer adds the parenthesis that WGSL needs
+            Expr::Paren(paren) => self.lower_expr(&paren.expr),
+            Expr::Group(group) => self.lower_expr(&group.expr),
+            Expr::Assign(assign) => abort!(
+                assign.span(),
+                "Assignments can only be used as statements in WGSL"
+            ),
+            // value producing ones were already lowered into statements by `ControlFlowTransformer`
+            Expr::Block(_)
+            | Expr::If(_)
+            | Expr::Match(_)
+            | Expr::Loop(_)
+            | Expr::ForLoop(_)
+            | Expr::While(_)
+            | Expr::Break(_)
+            | Expr::Continue(_)
+            | Expr::Return(_) => abort!(
                 expr.span(),
-                "Type inference expressions are not supported in WGSL"
-            )
-        }
-        syn::Expr::Let(let_expr) => None,
-        syn::Expr::Loop(loop_expr) => {
-            abort!(
-                loop_expr.span(),
-                "Loop expression conversion to wgsl not yet implemented"
-            )
-            //todo format!("for (;;) {{ {} }}", format_block(&loop_expr.body))
-        }
-        syn::Expr::Macro(macro_expr) => {
-            abort!(
-                macro_expr.span(),
-                "Macro invocations are not supported in WGSL"
-            )
-        }
-        syn::Expr::Match(match_expr) => {
-            abort!(
-                match_expr.span(),
-                "Match expressions are not supported in WGSL"
-            )
-        }
-        syn::Expr::MethodCall(method_call) => {
-            abort!(
-                method_call.span(),
-                "Method calls are not supported in WGSL, use standalone functions instead"
-            )
-        }
-        syn::Expr::Paren(paren) => None,
-        syn::Expr::Path(path) => {
-            if path.path.segments.len() > 1 {
-                if path.path.segments.len() == 2 {
-                    let matched = WGSL_NATIVE_TYPES
-                        .iter()
-                        .find(|t| t == &&path.path.segments[0].ident.to_string());
-                    if let Some(m) = matched {
-                        if path.path.segments.last().unwrap().ident == "new" {
-                            // will be handled at a later stage
-                            return None;
-                        }
-                    }
-                }
-
+                "This expression can only be used as a statement in WGSL"
+            ),
+            Expr::Async(async_expr) => {
                 abort!(
-                    path.span(),
-                    "Complex paths are not supported in WGSL, only simple identifiers are allowed"
+                    async_expr.span(),
+                    "Async expressions are not supported in WGSL"
                 )
             }
-            None
-        }
-        syn::Expr::Range(range) => {
-            abort!(range.span(), "Range expressions are not supported in WGSL")
-        }
-        syn::Expr::Reference(reference) => {
-            if reference.mutability.is_some() {
+            Expr::Await(await_expr) => {
+                abort!(
+                    await_expr.span(),
+                    "Await expressions are not supported in WGSL"
+                )
+            }
+            Expr::Closure(closure) => {
+                abort!(
+                    closure.span(),
+                    "Closure expressions are not supported in WGSL"
+                )
+            }
+            Expr::Const(const_expr) => {
+                abort!(const_expr.span(), "Const blocks are not supported in WGSL")
+            }
+            Expr::Infer(_) => {
+                abort!(
+                    expr.span(),
+                    "Type inference expressions are not supported in WGSL"
+                )
+            }
+            Expr::Let(let_expr) => {
                 abort!(
-                    reference.span(),
-                    "Mutable references are not supported in WGSL yet"
+                    let_expr.span(),
+                    "`let` expressions like `if let` are not supported in WGSL"
                 )
             }
-            None
-            // format!("&{}", expr_to_wgsl(&reference.expr))
-            // todo still some work to do around converting pointers correctly
+            Expr::Macro(macro_expr) => {
+                abort!(
+                    macro_expr.span(),
+                    "Macro invocations are not supported in WGSL"
+                )
+            }
+            Expr::Range(range) => {
+                abort!(range.span(), "Range expressions are not supported in WGSL")
+            }
+            Expr::Try(try_expr) => {
+                abort!(try_expr.span(), "Try expressions are not supported in WGSL")
+            }
+            Expr::TryBlock(try_block) => {
+                abort!(try_block.span(), "Try blocks are not supported in WGSL")
+            }
+            Expr::Tuple(tuple) => {
+                abort!(tuple.span(), "Tuple expressions are not supported in WGSL")
+            }
+            Expr::Unsafe(unsafe_expr) => {
+                abort!(
+                    unsafe_expr.span(),
+                    "Unsafe blocks are not supported in WGSL"
+                )
+            }
+            Expr::Yield(yield_expr) => {
+                abort!(
+                    yield_expr.span(),
+                    "Yield expressions are not supported in WGSL"
+                )
+            }
+            _ => {
+                let message = format!(
+                    "Unsupported expression type in WGSL: {}",
+                    expr.to_token_stream()
+                );
+                abort!(expr.span(), message)
+            }
         }
-        syn::Expr::Return(ret) => None,
-        // initialization field order must match the struct definition field order, because we are not able right now to reference the original struct definition to reorder the fields for wgsl
-        syn::Expr::Struct(struct_expr) => {
-            // Some(parse_quote!(Somethin2gnn ( x: 3 )))
-            let fields = struct_expr
-                .fields
-                .iter()
-                .map(|f| f.expr.to_token_stream().to_string())
-                .collect::<Vec<_>>()
-                .join(", ");
-            let struct_type_name = if let Some(lp) = struct_expr.path.segments.last() {
-                lp
-            } else {
-                abort!(struct_expr.span(), "Struct path is empty")
-            };
-            let s = format!("{}({})", &struct_type_name.ident.to_string(), fields);
-            let expr: syn::ExprCall = parse2::<ExprCall>(s.parse().unwrap()).unwrap();
-            // Some(syn::Expr::Verbatim(quote!(#s)))
-            Some(Expr::Call(expr))
+    }
+
+    fn lower_path(&mut self, path: &ExprPath) -> ast::Expr {
+        if let Some(variant) = enum_variant_to_wgsl(&path.path, self.custom_types) {
+            return ast::Expr::ident(variant.to_string());
         }
-        syn::Expr::Try(try_expr) => {
-            abort!(try_expr.span(), "Try expressions are not supported in WGSL")
+        if let Some(ident) = path.path.get_ident() {
+            return ast::Expr::ident(ident.to_string());
         }
-        syn::Expr::TryBlock(try_block) => {
-            abort!(try_block.span(), "Try blocks are not supported in WGSL")
+        if let Some(function) = wgsl_builtin_function(path) {
+            return ast::Expr::ident(function);
         }
-        syn::Expr::Tuple(tuple) => {
-            abort!(tuple.span(), "Tuple expressions are not supported in WGSL")
+        abort!(
+            path.span(),
+            "Complex paths are not supported in WGSL, only simple identifiers are allowed"
+        )
+    }
+
+    /// functions are called by name, and the helper types by their WGSL type, `Vec3F32::new(..)` => `vec3<f32>(..)`
+    fn lower_callee(&mut self, func: &Expr) -> ast::Type {
+        let Expr::Path(path) = func else {
+            abort!(func.span(), "Only functions can be called in WGSL");
+        };
+        let segments = &path.path.segments;
+        if segments.len() == 1 {
+            return self.lower_path_segment(&segments[0]);
         }
-        syn::Expr::Unary(unary) => None,
-        syn::Expr::Unsafe(unsafe_expr) => {
-            abort!(
-                unsafe_expr.span(),
-                "Unsafe blocks are not supported in WGSL"
-            )
+        if segments.len() == 2
+            && segments[1].ident == "new"
+            && WGSL_NATIVE_TYPES.contains(&segments[0].ident.to_string().as_str())
+        {
+            let wgsl_type = convert_path_segment(segments[0].clone(), self.custom_types);
+            return self.lower_path_segment(&wgsl_type);
         }
-        syn::Expr::Verbatim(tokens) => {
-            //todo: Emit warning about uninterpreted tokens
-            None
+        if let Some(function) = wgsl_builtin_function(path) {
+            return ast::Type::named(function);
         }
-        syn::Expr::While(while_expr) => None,
-        syn::Expr::Yield(yield_expr) => {
-            abort!(
-                yield_expr.span(),
-                "Yield expressions are not supported in WGSL"
-            )
+        abort!(
+            path.span(),
+            "Complex paths are not supported in WGSL, only simple identifiers are allowed"
+        )
+    }
+
+    /// the type of a variable, cast or suffixed literal, if it was declared. Enums count as `u32`
+    pub fn declared_type_of(&self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Path(path) => {
+                let t = self
+                    .local_var_types
+                    .get(&path.path.get_ident()?.to_string())?;
+                let is_enum = self.custom_types.iter().any(|c| {
+                    c.kind == CustomTypeKind::Enum
+                        && matches!(t, Type::Path(p) if p.path.is_ident(&c.name.name))
+                });
+                if is_enum {
+                    Some(parse_quote!(u32))
+                } else {
+                    Some(t.clone())
+                }
+            }
+            Expr::Cast(cast) => Some(*cast.ty.clone()),
+            Expr::Paren(paren) => self.declared_type_of(&paren.expr),
+            Expr::Lit(lit) => match &lit.lit {
+                Lit::Int(int) if int.suffix() == "u32" || int.suffix() == "i32" => {
+                    let suffix = syn::Ident::new(int.suffix(), int.span());
+                    Some(parse_quote!(#suffix))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// the type of the right hand side of `a += b` and the like, shift amounts are always `u32`
+    pub fn compound_assignment_type(&self, binary: &syn::ExprBinary) -> Option<Type> {
+        match binary.op {
+            BinOp::ShlAssign(_) | BinOp::ShrAssign(_) => Some(parse_quote!(u32)),
+            BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::BitXorAssign(_) => self.declared_type_of(&binary.left),
+            _ => None,
+        }
+    }
+}
+
+/// `wgsl_builtins::sqrt` is just `sqrt` in wgsl
+fn wgsl_builtin_function(path: &ExprPath) -> Option<String> {
+    let segments = &path.path.segments;
+    if segments.len() < 2 {
+        return None;
+    }
+    let function = segments.last().unwrap().ident.to_string();
+    (segments[segments.len() - 2].ident == "wgsl_builtins"
+        && WGSL_BUILTIN_FUNCTIONS.contains(&function.as_str()))
+    .then_some(function)
+}
+
+/// naga, the WGSL compiler used by wgpu, treats an unsuffixed integer literal in a compound assignment or switch case as an `i32` instead of converting it to the type it is used with.
+/// So `1` is written as `1u` where a `u32` is expected and as `1.0` where an `f32` is expected
+pub fn typed_int_literal(expr: &Expr, t: &Type) -> Option<Expr> {
+    let Expr::Lit(lit) = expr else {
+        return None;
+    };
+    let Lit::Int(int) = &lit.lit else {
+        return None;
+    };
+    if !int.suffix().is_empty() {
+        return None;
+    }
+    let Type::Path(path) = t else {
+        return None;
+    };
+    let digits = int.base10_digits();
+    if path.path.is_ident("u32") {
+        let value = LitInt::new(&format!("{}u", digits), int.span());
+        Some(parse_quote!(#value))
+    } else if path.path.is_ident("f32") {
+        let value = LitFloat::new(&format!("{}.0", digits), int.span());
+        Some(parse_quote!(#value))
+    } else {
+        None
+    }
+}
+
+/// rust suffixes become WGSL suffixes, like `5u32` => `5u` and `2.5f32` => `2.5f`
+pub fn literal_to_wgsl(lit: &ExprLit) -> ast::Expr {
+    let value = match &lit.lit {
+        Lit::Int(l) => {
+            let digits = l.base10_digits();
+            match l.suffix() {
+                "" => digits.to_string(),
+                "u" | "u32" => format!("{}u", digits),
+                "i" | "i32" => format!("{}i", digits),
+                "f" | "f32" => format!("{}f", digits),
+                suffix => abort!(
+                    l.span(),
+                    "Unsupported integer suffix in WGSL: ".to_owned() + suffix
+                ),
+            }
         }
-        _ => {
-            let message = format!(
-                "Unsupported expression type in WGSL: {}",
-                expr.to_token_stream()
-            );
-            abort!(expr.span(), message)
+        Lit::Float(l) => {
+            let digits = l.base10_digits();
+            match l.suffix() {
+                "" => digits.to_string(),
+                "f" | "f32" => format!("{}f", digits),
+                suffix => abort!(
+                    l.span(),
+                    "Unsupported float suffix in WGSL: ".to_owned() + suffix
+                ),
+            }
         }
+        Lit::Bool(b) => b.value.to_string(),
+        _ => abort!(
+            lit.span(),
+            "Only number and bool literals are supported in WGSL"
+        ),
+    };
+    ast::Expr::literal(value)
+}
+
+fn binary_op(op: &BinOp) -> ast::BinaryOp {
+    match op {
+        BinOp::Add(_) => ast::BinaryOp::Add,
+        BinOp::Sub(_) => ast::BinaryOp::Sub,
+        BinOp::Mul(_) => ast::BinaryOp::Mul,
+        BinOp::Div(_) => ast::BinaryOp::Div,
+        BinOp::Rem(_) => ast::BinaryOp::Rem,
+        BinOp::And(_) => ast::BinaryOp::And,
+        BinOp::Or(_) => ast::BinaryOp::Or,
+        BinOp::BitAnd(_) => ast::BinaryOp::BitAnd,
+        BinOp::BitOr(_) => ast::BinaryOp::BitOr,
+        BinOp::BitXor(_) => ast::BinaryOp::BitXor,
+        BinOp::Shl(_) => ast::BinaryOp::Shl,
+        BinOp::Shr(_) => ast::BinaryOp::Shr,
+        BinOp::Eq(_) => ast::BinaryOp::Eq,
+        BinOp::Ne(_) => ast::BinaryOp::Ne,
+        BinOp::Lt(_) => ast::BinaryOp::Lt,
+        BinOp::Le(_) => ast::BinaryOp::Le,
+        BinOp::Gt(_) => ast::BinaryOp::Gt,
+        BinOp::Ge(_) => ast::BinaryOp::Ge,
+        _ => abort!(op.span(), "Unsupported binary operator in WGSL"),
     }
 }
@@ -0,0 +1,603 @@
+use std::collections::HashSet;
+
+use bevy_gpu_compute_core::wgsl::ast;
+use proc_macro_error::abort;
+use proc_macro2::Literal;
+use quote::format_ident;
+use syn::{
+    Block, Expr, ExprAssign, ExprBinary, ExprForLoop, ExprReference, GenericArgument, Ident,
+    ItemType, Pat, PathArguments, RangeLimits, Type, parse_quote, parse2, spanned::Spanned,
+    visit::Visit,
+};
+
+use super::{
+    control_flow::is_compound_assignment, expr::typed_int_literal, lowering::WgslLowering,
+};
+use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
+
+/**
+ Rust `for` loops become WGSL's javascript style `for` loops:
+ - `for i in a..b` => `for (var i: u32 = a; i < b; i += 1u) {}`
+ - `for i in (a..b).step_by(n)` => `for (var i: u32 = a; i < b; i += min(n, b - i)) {}`
+
+   the step never goes past `b`, so that unsigned loops ending near `u32::MAX` cannot wrap around
+ - `for i in (a..b).rev()` => `for (var _rev_i: u32 = b; _rev_i > a; _rev_i -= 1u) { let i = _rev_i - 1; }`
+
+   the hidden counter stays one above the loop variable so that unsigned loops ending at 0 cannot underflow
+ - `for i in a..=b` => `{ var i: u32 = a; loop { if i > b { break; } continuing { let _last_i = i == b; i += 1u; break if _last_i; } } }`
+
+   the last value is checked before stepping past it, so that ranges ending at `u32::MAX` terminate. `(a..=b).rev()` counts down from `b` the same way
+ - `for x in arr` => `for (var _idx_x: u32 = 0; _idx_x < N; _idx_x += 1u) { let x = arr[_idx_x]; }`
+
+   `N` comes from the declared type of `arr`, so `arr` must be a function parameter, an annotated local variable, or an array literal
+
+ Bounds and steps with function calls, or using variables the loop body assigns to, are evaluated once into a hidden `let _end_i = ...;` (`_start_i`, `_step_i`) before the loop, since Rust evaluates the range only once.
+
+ The loop variable type is taken from a literal suffix (`0u32..n`), a cast (`a as i32..b`) or the declared type of a variable used as one of the bounds. If none of those are present it defaults to `u32`.
+*/
+pub fn for_loop_to_wgsl(for_loop: &ExprForLoop, lowering: &mut WgslLowering) -> ast::Stmt {
+    let loop_var = loop_variable_ident(&for_loop.pat).to_string();
+    let iterable = ForLoopIterable::parse(&for_loop.expr, lowering);
+    match iterable {
+        ForLoopIterable::Range {
+            start,
+            end,
+            inclusive,
+            reversed,
+            step,
+        } => {
+            let var_type = loop_variable_type(&start, &end, lowering);
+            let wgsl_type = lowering.lower_type(&var_type);
+            let step = step.map(|s| typed_int_literal(&s, &var_type).unwrap_or(*s));
+            // rust evaluates the range once, while wgsl re-evaluates the loop condition and update on every iteration
+            let assigned = assigned_variables(&for_loop.body);
+            let mut hoisted = Vec::new();
+            let mut evaluate_once = |role: &str, original: &Expr, value: ast::Expr| {
+                if is_loop_invariant(original, &assigned) {
+                    return value;
+                }
+                let name = format!("_{}_{}", role, loop_var);
+                hoisted.push(ast::Stmt::Let {
+                    name: name.clone(),
+                    ty: Some(wgsl_type.clone()),
+                    value,
+                });
+                ast::Expr::ident(&name)
+            };
+            let start = evaluate_once("start", &start, lowering.lower_expr(&start));
+            let end = evaluate_once("end", &end, lowering.lower_expr(&end));
+            let step = step.map(|s| evaluate_once("step", &s, lowering.lower_expr(&s)));
+            lowering
+                .local_var_types
+                .insert(loop_var.clone(), var_type.clone());
+            let mut body = lowering.lower_block(&for_loop.body);
+            let one = one(&var_type, lowering);
+            if inclusive {
+                // checked after the body, since `i <= b` is always true when `b` is the largest value of the type
+                let (first, last, op, past_last) = if reversed {
+                    (end, start, ast::BinaryOp::Sub, ast::BinaryOp::Lt)
+                } else {
+                    (start, end, ast::BinaryOp::Add, ast::BinaryOp::Gt)
+                };
+                let counter = || ast::Expr::ident(&loop_var);
+                let is_last = match &step {
+                    // the distance left to the last value is smaller than the step
+                    Some(s) => {
+                        let distance = if reversed {
+                            ast::Expr::binary(ast::BinaryOp::Sub, counter(), last.clone())
+                        } else {
+                            ast::Expr::binary(ast::BinaryOp::Sub, last.clone(), counter())
+                        };
+                        ast::Expr::binary(ast::BinaryOp::Lt, distance, s.clone())
+                    }
+                    None => ast::Expr::binary(ast::BinaryOp::Eq, counter(), last.clone()),
+                };
+                let last_flag = format!("_last_{}", loop_var);
+                body.stmts.insert(
+                    0,
+                    ast::Stmt::If(ast::If {
+                        condition: ast::Expr::binary(past_last, counter(), last),
+                        then_branch: ast::Block::new(vec![ast::Stmt::Break]),
+                        else_branch: None,
+                    }),
+                );
+                body.stmts.push(ast::Stmt::Continuing(ast::Block::new(vec![
+                    ast::Stmt::Let {
+                        name: last_flag.clone(),
+                        ty: None,
+                        value: is_last,
+                    },
+                    ast::Stmt::Assign {
+                        target: counter(),
+                        op: Some(op),
+                        value: step.unwrap_or(one),
+                    },
+                    ast::Stmt::BreakIf(ast::Expr::ident(&last_flag)),
+                ])));
+                hoisted.push(ast::Stmt::Var {
+                    name: loop_var,
+                    ty: Some(wgsl_type),
+                    value: Some(first),
+                });
+                hoisted.push(ast::Stmt::Loop(body));
+                return ast::Stmt::Block(ast::Block::new(hoisted));
+            }
+            let counter = if reversed {
+                format!("_rev_{}", loop_var)
+            } else {
+                loop_var.clone()
+            };
+            let (init, bound, condition, op) = if reversed {
+                (end, start, ast::BinaryOp::Gt, ast::BinaryOp::Sub)
+            } else {
+                (start, end, ast::BinaryOp::Lt, ast::BinaryOp::Add)
+            };
+            let step = match step {
+                Some(s) => {
+                    let distance = if reversed {
+                        ast::Expr::binary(
+                            ast::BinaryOp::Sub,
+                            ast::Expr::ident(&counter),
+                            bound.clone(),
+                        )
+                    } else {
+                        ast::Expr::binary(
+                            ast::BinaryOp::Sub,
+                            bound.clone(),
+                            ast::Expr::ident(&counter),
+                        )
+                    };
+                    ast::Expr::call(ast::Type::named("min"), vec![s, distance])
+                }
+                None => one,
+            };
+            if reversed {
+                body.stmts.insert(
+                    0,
+                    ast::Stmt::Let {
+                        name: loop_var,
+                        ty: None,
+                        value: ast::Expr::binary(
+                            ast::BinaryOp::Sub,
+                            ast::Expr::ident(&counter),
+                            ast::Expr::literal("1"),
+                        ),
+                    },
+                );
+            }
+            let for_loop = counting_loop(
+                &counter,
+                wgsl_type,
+                init,
+                ast::Expr::binary(condition, ast::Expr::ident(&counter), bound),
+                op,
+                step,
+                body,
+            );
+            if hoisted.is_empty() {
+                for_loop
+            } else {
+                hoisted.push(for_loop);
+                ast::Stmt::Block(ast::Block::new(hoisted))
+            }
+        }
+        ForLoopIterable::Array { array, len } => {
+            let array = lowering.lower_expr(&array);
+            let len = lowering.lower_expr(&len);
+            let mut body = lowering.lower_block(&for_loop.body);
+            let index = format!("_idx_{}", loop_var);
+            body.stmts.insert(
+                0,
+                ast::Stmt::Let {
+                    name: loop_var,
+                    ty: None,
+                    value: ast::Expr::index(array, ast::Expr::ident(&index)),
+                },
+            );
+            counting_loop(
+                &index,
+                ast::Type::named("u32"),
+                ast::Expr::literal("0"),
+                ast::Expr::binary(ast::BinaryOp::Lt, ast::Expr::ident(&index), len),
+                ast::BinaryOp::Add,
+                ast::Expr::literal("1u"),
+                body,
+            )
+        }
+    }
+}
+
+/// `for (var counter: counter_type = init; condition; counter op= step) body`
+fn counting_loop(
+    counter: &str,
+    counter_type: ast::Type,
+    init: ast::Expr,
+    condition: ast::Expr,
+    op: ast::BinaryOp,
+    step: ast::Expr,
+    body: ast::Block,
+) -> ast::Stmt {
+    ast::Stmt::For {
+        init: Some(Box::new(ast::Stmt::Var {
+            name: counter.to_string(),
+            ty: Some(counter_type),
+            value: Some(init),
+        })),
+        condition: Some(condition),
+        update: Some(Box::new(ast::Stmt::Assign {
+            target: ast::Expr::ident(counter),
+            op: Some(op),
+            value: step,
+        })),
+        body,
+    }
+}
+
+fn one(var_type: &Type, lowering: &mut WgslLowering) -> ast::Expr {
+    let one: Expr = parse_quote!(1);
+    lowering.lower_expr(&typed_int_literal(&one, var_type).unwrap_or(one))
+}
+
+/// a bound or step without calls, over variables the loop body never assigns to, gives the same value on every iteration and can stay inline
+fn is_loop_invariant(expr: &Expr, assigned: &HashSet<String>) -> bool {
+    match expr {
+        Expr::Lit(_) => true,
+        Expr::Path(path) => path
+            .path
+            .get_ident()
+            .is_none_or(|ident| !assigned.contains(&ident.to_string())),
+        Expr::Field(field) => is_loop_invariant(&field.base, assigned),
+        Expr::Index(index) => {
+            is_loop_invariant(&index.expr, assigned) && is_loop_invariant(&index.index, assigned)
+        }
+        Expr::Unary(unary) => is_loop_invariant(&unary.expr, assigned),
+        Expr::Binary(binary) => {
+            is_loop_invariant(&binary.left, assigned) && is_loop_invariant(&binary.right, assigned)
+        }
+        Expr::Paren(paren) => is_loop_invariant(&paren.expr, assigned),
+        Expr::Group(group) => is_loop_invariant(&group.expr, assigned),
+        Expr::Cast(cast) => is_loop_invariant(&cast.expr, assigned),
+        _ => false,
+    }
+}
+
+/// the variables assigned to anywhere in `block`, including through fields, indices and `&mut` references
+fn assigned_variables(block: &Block) -> HashSet<String> {
+    struct AssignedVariables(HashSet<String>);
+    impl AssignedVariables {
+        fn add_root(&mut self, target: &Expr) {
+            match target {
+                Expr::Path(path) => {
+                    if let Some(ident) = path.path.get_ident() {
+                        self.0.insert(ident.to_string());
+                    }
+                }
+                Expr::Field(field) => self.add_root(&field.base),
+                Expr::Index(index) => self.add_root(&index.expr),
+                Expr::Paren(paren) => self.add_root(&paren.expr),
+                Expr::Group(group) => self.add_root(&group.expr),
+                Expr::Unary(unary) => self.add_root(&unary.expr),
+                _ => {}
+            }
+        }
+    }
+    impl<'ast> Visit<'ast> for AssignedVariables {
+        fn visit_expr_assign(&mut self, assign: &'ast ExprAssign) {
+            self.add_root(&assign.left);
+            syn::visit::visit_expr_assign(self, assign);
+        }
+        fn visit_expr_binary(&mut self, binary: &'ast ExprBinary) {
+            if is_compound_assignment(&binary.op) {
+                self.add_root(&binary.left);
+            }
+            syn::visit::visit_expr_binary(self, binary);
+        }
+        fn visit_expr_reference(&mut self, reference: &'ast ExprReference) {
+            if reference.mutability.is_some() {
+                self.add_root(&reference.expr);
+            }
+            syn::visit::visit_expr_reference(self, reference);
+        }
+    }
+    let mut finder = AssignedVariables(HashSet::new());
+    finder.visit_block(block);
+    finder.0
+}
+
+enum ForLoopIterable {
+    Range {
+        start: Box<Expr>,
+        end: Box<Expr>,
+        inclusive: bool,
+        reversed: bool,
+        step: Option<Box<Expr>>,
+    },
+    Array {
+        array: Box<Expr>,
+        len: Box<Expr>,
+    },
+}
+
+impl ForLoopIterable {
+    fn parse(expr: &Expr, lowering: &WgslLowering) -> Self {
+        match expr {
+            Expr::Paren(p) => Self::parse(&p.expr, lowering),
+            Expr::Group(g) => Self::parse(&g.expr, lowering),
+            Expr::MethodCall(call) if call.method == "rev" && call.args.is_empty() => {
+                match Self::parse(&call.receiver, lowering) {
+                    Self::Range {
+                        start,
+                        end,
+                        inclusive,
+                        reversed: false,
+                        step: None,
+                    } => Self::Range {
+                        start,
+                        end,
+                        inclusive,
+                        reversed: true,
+                        step: None,
+                    },
+                    _ => abort!(
+                        call.span(),
+                        "`.rev()` is only supported directly on a range in WGSL for loops, for example `(a..b).rev()` or `(a..b).rev().step_by(n)`"
+                    ),
+                }
+            }
+            Expr::MethodCall(call) if call.method == "step_by" && call.args.len() == 1 => {
+                match Self::parse(&call.receiver, lowering) {
+                    Self::Range {
+                        start,
+                        end,
+                        inclusive,
+                        reversed,
+                        step: None,
+                    } => Self::Range {
+                        start,
+                        end,
+                        inclusive,
+                        reversed,
+                        step: call.args.first().cloned().map(Box::new),
+                    },
+                    _ => abort!(
+                        call.span(),
+                        "`.step_by(n)` is only supported on a range in WGSL for loops, for example `(a..b).step_by(n)`"
+                    ),
+                }
+            }
+            Expr::Range(range) => {
+                let (Some(start), Some(end)) = (&range.start, &range.end) else {
+                    abort!(
+                        range.span(),
+                        "For loops in WGSL need a range with both a start and an end, like `a..b`"
+                    );
+                };
+                Self::Range {
+                    start: start.clone(),
+                    end: end.clone(),
+                    inclusive: matches!(range.limits, RangeLimits::Closed(_)),
+                    reversed: false,
+                    step: None,
+                }
+            }
+            Expr::Path(_) | Expr::Array(_) | Expr::Repeat(_) => Self::Array {
+                array: Box::new(expr.clone()),
+                len: Box::new(array_length(expr, lowering)),
+            },
+            _ => abort!(
+                expr.span(),
+                "For loops in WGSL can only iterate over integer ranges (`a..b`, `a..=b`, optionally with `.rev()` or `.step_by(n)`) or fixed-size arrays"
+            ),
+        }
+    }
+}
+
+fn loop_variable_ident(pat: &Pat) -> Ident {
+    match pat {
+        Pat::Ident(pat_ident) => {
+            if pat_ident.mutability.is_some() {
+                abort!(
+                    pat_ident.span(),
+                    "Mutable for loop variables are not supported in WGSL, copy the value into a `let mut` variable inside the loop instead"
+                );
+            }
+            pat_ident.ident.clone()
+        }
+        Pat::Wild(_) => format_ident!("_i"),
+        _ => abort!(
+            pat.span(),
+            "Only simple identifiers are supported as for loop variables in WGSL"
+        ),
+    }
+}
+
+fn loop_variable_type(start: &Expr, end: &Expr, lowering: &WgslLowering) -> Type {
+    let found = integer_type_of(start, lowering).or_else(|| integer_type_of(end, lowering));
+    match found {
+        Some(t) => {
+            let is_integer =
+                matches!(&t, Type::Path(p) if p.path.is_ident("u32") || p.path.is_ident("i32"));
+            if !is_integer {
+                abort!(
+                    start.span(),
+                    "For loop ranges in WGSL must be over u32 or i32 values"
+                );
+            }
+            t
+        }
+        None => parse_quote!(u32),
+    }
+}
+
+fn integer_type_of(expr: &Expr, lowering: &WgslLowering) -> Option<Type> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Int(i) if !i.suffix().is_empty() => {
+                let suffix = format_ident!("{}", i.suffix());
+                Some(parse_quote!(#suffix))
+            }
+            _ => None,
+        },
+        Expr::Cast(cast) => Some(*cast.ty.clone()),
+        Expr::Path(path) => path
+            .path
+            .get_ident()
+            .and_then(|ident| lowering.local_var_types.get(&ident.to_string()))
+            .cloned(),
+        Expr::Paren(p) => integer_type_of(&p.expr, lowering),
+        Expr::Group(g) => integer_type_of(&g.expr, lowering),
+        Expr::Unary(u) => integer_type_of(&u.expr, lowering),
+        Expr::Binary(b) => {
+            integer_type_of(&b.left, lowering).or_else(|| integer_type_of(&b.right, lowering))
+        }
+        _ => None,
+    }
+}
+
+fn array_length(expr: &Expr, lowering: &WgslLowering) -> Expr {
+    let len = match expr {
+        Expr::Array(array) => {
+            let len = Literal::usize_unsuffixed(array.elems.len());
+            Some(parse_quote!(#len))
+        }
+        Expr::Repeat(repeat) => Some(*repeat.len.clone()),
+        Expr::Path(path) => path
+            .path
+            .get_ident()
+            .and_then(|ident| lowering.local_var_types.get(&ident.to_string()))
+            .and_then(|t| array_length_of_type(t, lowering.custom_types)),
+        _ => None,
+    };
+    if let Some(l) = len {
+        l
+    } else {
+        abort!(
+            expr.span(),
+            "Unable to determine the length of this array, for loops over arrays in WGSL need an array literal, a function parameter, or a local variable with an explicitly declared array type like `let a: [f32; 4] = ...`"
+        );
+    }
+}
+
+/// handles both rust arrays `[T; N]` and arrays that were already converted to wgsl `array<T, N>`, as well as type aliases of either
+pub fn array_length_of_type(t: &Type, custom_types: &[CustomType]) -> Option<Expr> {
+    match t {
+        Type::Array(arr) => Some(arr.len.clone()),
+        Type::Paren(p) => array_length_of_type(&p.elem, custom_types),
+        Type::Path(p) => {
+            let last = p.path.segments.last()?;
+            if last.ident == "array" {
+                if let PathArguments::AngleBracketed(args) = &last.arguments {
+                    return match args.args.iter().nth(1)? {
+                        GenericArgument::Const(len) => Some(len.clone()),
+                        GenericArgument::Type(len) => Some(parse_quote!(#len)),
+                        _ => None,
+                    };
+                }
+                return None;
+            }
+            let alias = custom_types.iter().find(|c| c.name.eq(&last.ident))?;
+            let item_type = parse2::<ItemType>(alias.rust_code.clone()).ok()?;
+            array_length_of_type(&item_type.ty, custom_types)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn lower(for_loop: ExprForLoop) -> String {
+        let custom_types = vec![];
+        for_loop_to_wgsl(&for_loop, &mut WgslLowering::new(&custom_types)).to_string()
+    }
+
+    #[test]
+    fn test_half_open_range() {
+        let input: ExprForLoop = parse_quote! { for i in 0..10 { x = x + i; } };
+        assert_eq!(
+            lower(input),
+            "for (var i: u32 = 0; i < 10; i += 1u) {\n    x = x + i;\n}"
+        );
+    }
+
+    #[test]
+    fn test_inclusive_range_with_typed_bound() {
+        let input: ExprForLoop = parse_quote! { for i in -5i32..=n { x = x + i } };
+        assert_eq!(
+            lower(input),
+            "{\n    var i: i32 = -5i;\n    loop {\n        if i > n {\n            break;\n        }\n        x = x + i;\n        continuing {\n            let _last_i = i == n;\n            i += 1;\n            break if _last_i;\n        }\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_step_by() {
+        let input: ExprForLoop = parse_quote! { for i in (2..n).step_by(3) { x = x + i; } };
+        assert_eq!(
+            lower(input),
+            "for (var i: u32 = 2; i < n; i += min(3u, n - i)) {\n    x = x + i;\n}"
+        );
+    }
+
+    #[test]
+    fn test_calls_in_bounds_and_steps_are_evaluated_once() {
+        let input: ExprForLoop =
+            parse_quote! { for i in (0..count()).step_by(stride()) { x = x + i; } };
+        assert_eq!(
+            lower(input),
+            "{\n    let _end_i: u32 = count();\n    let _step_i: u32 = stride();\n    for (var i: u32 = 0; i < _end_i; i += min(_step_i, _end_i - i)) {\n        x = x + i;\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_bound_assigned_in_the_body_is_evaluated_once() {
+        let input: ExprForLoop = parse_quote! { for i in (a..=n).rev() { n = n + 1; } };
+        assert_eq!(
+            lower(input),
+            "{\n    let _end_i: u32 = n;\n    var i: u32 = _end_i;\n    loop {\n        if i < a {\n            break;\n        }\n        n = n + 1;\n        continuing {\n            let _last_i = i == a;\n            i -= 1u;\n            break if _last_i;\n        }\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_rev() {
+        let input: ExprForLoop = parse_quote! { for i in (a + 1..n).rev() { x = x + i; } };
+        assert_eq!(
+            lower(input),
+            "for (var _rev_i: u32 = n; _rev_i > a + 1; _rev_i -= 1u) {\n    let i = _rev_i - 1;\n    x = x + i;\n}"
+        );
+    }
+
+    #[test]
+    fn test_rev_step_by() {
+        let input: ExprForLoop = parse_quote! { for i in (0..=n).rev().step_by(2) { x = x + i; } };
+        assert_eq!(
+            lower(input),
+            "{\n    var i: u32 = n;\n    loop {\n        if i < 0 {\n            break;\n        }\n        x = x + i;\n        continuing {\n            let _last_i = i - 0 < 2u;\n            i -= 2u;\n            break if _last_i;\n        }\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_rev_inclusive_range() {
+        let input: ExprForLoop = parse_quote! { for i in (a..=n).rev() { x = x + i; } };
+        assert_eq!(
+            lower(input),
+            "{\n    var i: u32 = n;\n    loop {\n        if i < a {\n            break;\n        }\n        x = x + i;\n        continuing {\n            let _last_i = i == a;\n            i -= 1u;\n            break if _last_i;\n        }\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_array_variable() {
+        let input: ExprForLoop = parse_quote! { for v in values { x = x + v; } };
+        let custom_types = vec![];
+        let mut lowering = WgslLowering::new(&custom_types);
+        lowering.local_var_types =
+            HashMap::from([("values".to_string(), parse_quote!(array<f32, 4>))]);
+        let output = for_loop_to_wgsl(&input, &mut lowering).to_string();
+        assert_eq!(
+            output,
+            "for (var _idx_v: u32 = 0; _idx_v < 4; _idx_v += 1u) {\n    let v = values[_idx_v];\n    x = x + v;\n}"
+        );
+    }
+}
@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+use bevy_gpu_compute_core::wgsl::ast;
+use proc_macro_error::abort;
+use quote::ToTokens;
+use syn::{
+    Block, Expr, ExprIf, Fields, FnArg, GenericArgument, Item, ItemConst, ItemFn, ItemStruct,
+    Local, Pat, PathArguments, PathSegment, ReturnType, Stmt, Type, spanned::Spanned,
+};
+
+use super::{
+    array::array_literal_to_wgsl,
+    expr::typed_int_literal,
+    for_loop::for_loop_to_wgsl,
+    match_expr::match_to_wgsl,
+    method_call::method_call_to_wgsl,
+    source::{SourceFiles, doc_comments, first_code_location, last_line, source_location},
+    type_def::type_def_to_wgsl,
+};
+use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
+
+/**
+ Lowers the rust items, after the syntax transformations in this module, into the WGSL AST of `bevy_gpu_compute_core::wgsl::ast`.
+ - `let x = a;` => `let x = a;`, and `let mut x = a;` or a `let` without a value => `var x = a;`
+ - `let _ = f();` => `_ = f();`
+ - `type A = T;` => `alias A = T;`
+ - the trailing expression of a function with a return type becomes a `return`, also inside of the branches of a trailing `if` or block
+ - doc comments, and `//` comments between statements, become WGSL comments, and declarations and statements written by the user are annotated with their rust source location, see `source.rs`
+ - other attributes and visibility are dropped
+*/
+pub struct WgslLowering<'a> {
+    pub custom_types: &'a Vec<CustomType>,
+    /// declared types of the parameters and annotated local variables of the function currently being lowered, keyed by variable name
+    pub local_var_types: HashMap<String, Type>,
+    source_files: SourceFiles,
+}
+
+impl<'a> WgslLowering<'a> {
+    pub fn new(custom_types: &'a Vec<CustomType>) -> Self {
+        WgslLowering {
+            custom_types,
+            local_var_types: HashMap::new(),
+            source_files: SourceFiles::default(),
+        }
+    }
+
+    pub fn lower_item(&mut self, item: &Item) -> ast::Item {
+        match item {
+            Item::Fn(item_fn) => ast::Item::Fn(self.lower_fn(item_fn)),
+            Item::Struct(item_struct) => ast::Item::Struct(self.lower_struct(item_struct)),
+            Item::Type(item_type) => ast::Item::Alias(type_def_to_wgsl(item_type, self)),
+            Item::Const(item_const) => ast::Item::Const(ast::Const {
+                docs: doc_comments(&item_const.attrs),
+                location: source_location(item_const.const_token.span),
+                ..self.lower_const(item_const)
+            }),
+            _ => abort!(
+                item.span(),
+                "Only functions, structs, type aliases and constants are supported in WGSL"
+            ),
+        }
+    }
+
+    fn lower_fn(&mut self, item_fn: &ItemFn) -> ast::Function {
+        self.local_var_types.clear();
+        let params = item_fn
+            .sig
+            .inputs
+            .iter()
+            .map(|input| {
+                let FnArg::Typed(pat_type) = input else {
+                    abort!(input.span(), "`self` parameters are not supported in WGSL");
+                };
+                let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                    abort!(
+                        pat_type.pat.span(),
+                        "Only simple identifiers are supported as function parameters in WGSL"
+                    );
+                };
+                self.local_var_types
+                    .insert(pat_ident.ident.to_string(), *pat_type.ty.clone());
+                ast::Param {
+                    attributes: Vec::new(),
+                    name: pat_ident.ident.to_string(),
+                    ty: self.lower_type(&pat_type.ty),
+                }
+            })
+            .collect();
+        let return_type = match &item_fn.sig.output {
+            ReturnType::Default => None,
+            ReturnType::Type(_, t) => Some(self.lower_type(t)),
+        };
+        let body = self.lower_block_returning(&item_fn.block, return_type.is_some());
+        ast::Function {
+            docs: doc_comments(&item_fn.attrs),
+            location: source_location(item_fn.sig.fn_token.span),
+            attributes: Vec::new(),
+            name: item_fn.sig.ident.to_string(),
+            params,
+            return_type,
+            body,
+        }
+    }
+
+    fn lower_struct(&mut self, item_struct: &ItemStruct) -> ast::Struct {
+        let Fields::Named(fields) = &item_struct.fields else {
+            abort!(
+                item_struct.fields.span(),
+                "Only structs with named fields are supported in WGSL"
+            );
+        };
+        ast::Struct {
+            docs: doc_comments(&item_struct.attrs),
+            location: source_location(item_struct.struct_token.span),
+            name: item_struct.ident.to_string(),
+            members: fields
+                .named
+                .iter()
+                .map(|field| ast::Member {
+                    docs: doc_comments(&field.attrs),
+                    name: field.ident.as_ref().unwrap().to_string(),
+                    ty: self.lower_type(&field.ty),
+                })
+                .collect(),
+        }
+    }
+
+    fn lower_const(&mut self, item_const: &ItemConst) -> ast::Const {
+        let value = match &*item_const.expr {
+            Expr::Array(_) | Expr::Repeat(_) => {
+                array_literal_to_wgsl(&item_const.expr, Some(&item_const.ty), self)
+            }
+            value => self.lower_expr(value),
+        };
+        ast::Const {
+            docs: Vec::new(),
+            location: None,
+            name: item_const.ident.to_string(),
+            ty: Some(self.lower_type(&item_const.ty)),
+            value,
+        }
+    }
+
+    /// types were already converted to their WGSL names by `TypeToWgslTransformer` and `ArrayToWgslTransformer`
+    pub fn lower_type(&mut self, t: &Type) -> ast::Type {
+        match t {
+            Type::Path(type_path) if type_path.qself.is_none() => {
+                let segments = &type_path.path.segments;
+                if segments.len() != 1 {
+                    abort!(
+                        t.span(),
+                        "Only types defined in the shader module or the WGSL helper types can be used in WGSL"
+                    );
+                }
+                self.lower_path_segment(&segments[0])
+            }
+            Type::Paren(paren) => self.lower_type(&paren.elem),
+            Type::Group(group) => self.lower_type(&group.elem),
+            Type::Reference(reference) => abort!(
+                reference.span(),
+                "Only `&mut` references can be used as function parameters in WGSL"
+            ),
+            _ => abort!(
+                t.span(),
+                "Unsupported type in WGSL: {}",
+                t.to_token_stream()
+            ),
+        }
+    }
+
+    /// a name with its template arguments, like `vec3<f32>` or `array<u32, 4>`
+    pub fn lower_path_segment(&mut self, segment: &PathSegment) -> ast::Type {
+        let template_args = match &segment.arguments {
+            PathArguments::None => Vec::new(),
+            PathArguments::AngleBracketed(args) => args
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    GenericArgument::Type(t) => ast::TemplateArg::Type(self.lower_type(t)),
+                    GenericArgument::Const(e) => ast::TemplateArg::Expr(self.lower_expr(e)),
+                    _ => abort!(arg.span(), "Unsupported template argument in WGSL"),
+                })
+                .collect(),
+            PathArguments::Parenthesized(args) => {
+                abort!(args.span(), "Function types are not supported in WGSL")
+            }
+        };
+        ast::Type {
+            name: segment.ident.to_string(),
+            template_args,
+        }
+    }
+
+    /// the trailing expression of the block is lowered as a statement, since its value is not used
+    pub fn lower_block(&mut self, block: &Block) -> ast::Block {
+        self.lower_block_returning(block, false)
+    }
+
+    /// if `returns_tail` is set the trailing expression is the value of the function, so it is returned
+    fn lower_block_returning(&mut self, block: &Block, returns_tail: bool) -> ast::Block {
+        let last = block.stmts.len().checked_sub(1);
+        let mut stmts = Vec::new();
+        // the last line of user written code, comments are only taken from between two of those
+        let mut previous_line =
+            source_location(block.brace_token.span.open()).map(|location| location.line);
+        for (i, stmt) in block.stmts.iter().enumerate() {
+            let located = first_code_location(stmt);
+            if let (Some(after_line), Some((start, location))) = (previous_line, &located) {
+                let comments =
+                    self.source_files
+                        .comments_between(*start, after_line, location.line);
+                stmts.extend(comments.into_iter().map(ast::Stmt::Comment));
+            }
+            stmts.extend(
+                doc_comments(stmt_attributes(stmt))
+                    .into_iter()
+                    .map(ast::Stmt::Comment),
+            );
+            let lowered = match stmt {
+                Stmt::Expr(expr, None) if returns_tail && Some(i) == last => {
+                    self.lower_tail_expr(expr)
+                }
+                _ => self.lower_stmt(stmt),
+            };
+            stmts.push(match located {
+                Some((_, location)) => {
+                    previous_line = Some(location.line.max(last_line(stmt)));
+                    ast::Stmt::Located {
+                        location,
+                        stmt: Box::new(lowered),
+                    }
+                }
+                None => lowered,
+            });
+        }
+        let close = block.brace_token.span.close();
+        if let (Some(after_line), Some(location)) = (previous_line, source_location(close)) {
+            let comments = self
+                .source_files
+                .comments_between(close, after_line, location.line);
+            stmts.extend(comments.into_iter().map(ast::Stmt::Comment));
+        }
+        ast::Block::new(stmts)
+    }
+
+    /// `ControlFlowTransformer` already lowered value producing expressions other than `if`s and blocks in this position
+    fn lower_tail_expr(&mut self, expr: &Expr) -> ast::Stmt {
+        match expr {
+            Expr::If(if_expr) => ast::Stmt::If(self.lower_if(if_expr, true)),
+            Expr::Block(block_expr) => {
+                ast::Stmt::Block(self.lower_block_returning(&block_expr.block, true))
+            }
+            Expr::Match(_)
+            | Expr::Loop(_)
+            | Expr::ForLoop(_)
+            | Expr::While(_)
+            | Expr::Break(_)
+            | Expr::Continue(_)
+            | Expr::Return(_) => self.lower_expr_stmt(expr),
+            _ => ast::Stmt::Return(Some(self.lower_expr(expr))),
+        }
+    }
+
+    fn lower_stmt(&mut self, stmt: &Stmt) -> ast::Stmt {
+        match stmt {
+            Stmt::Local(local) => self.lower_local(local),
+            Stmt::Expr(expr, _) => self.lower_expr_stmt(expr),
+            Stmt::Item(Item::Const(item_const)) => ast::Stmt::Const(self.lower_const(item_const)),
+            Stmt::Item(item) => abort!(
+                item.span(),
+                "Only constants can be declared inside of functions in WGSL"
+            ),
+            Stmt::Macro(stmt_macro) => abort!(
+                stmt_macro.span(),
+                "Macro invocations are not supported in WGSL"
+            ),
+        }
+    }
+
+    fn lower_local(&mut self, local: &Local) -> ast::Stmt {
+        let (pat, declared_type) = match &local.pat {
+            Pat::Type(pat_type) => (&*pat_type.pat, Some(&*pat_type.ty)),
+            pat => (pat, None),
+        };
+        let value = local.init.as_ref().map(|init| {
+            if let Some((else_token, _)) = &init.diverge {
+                abort!(else_token.span(), "`let ... else` is not supported in WGSL");
+            }
+            match &*init.expr {
+                // the declared type of the variable is also the type of an array literal assigned to it
+                Expr::Array(_) | Expr::Repeat(_) => {
+                    array_literal_to_wgsl(&init.expr, declared_type, self)
+                }
+                value => self.lower_expr(value),
+            }
+        });
+        match pat {
+            Pat::Ident(pat_ident) => {
+                let name = pat_ident.ident.to_string();
+                let ty = declared_type.map(|t| self.lower_type(t));
+                if let Some(t) = declared_type {
+                    self.local_var_types.insert(name.clone(), t.clone());
+                }
+                match value {
+                    Some(value) if pat_ident.mutability.is_none() => {
+                        ast::Stmt::Let { name, ty, value }
+                    }
+                    value => ast::Stmt::Var { name, ty, value },
+                }
+            }
+            Pat::Wild(_) => match value {
+                Some(value) => ast::Stmt::Assign {
+                    target: ast::Expr::ident("_"),
+                    op: None,
+                    value,
+                },
+                None => abort!(local.span(), "`let _;` does nothing"),
+            },
+            _ => abort!(
+                pat.span(),
+                "Only simple identifiers are supported in let statements in WGSL"
+            ),
+        }
+    }
+
+    /// an expression whose value is not used
+    pub fn lower_expr_stmt(&mut self, expr: &Expr) -> ast::Stmt {
+        match expr {
+            Expr::ForLoop(for_loop) => for_loop_to_wgsl(for_loop, self),
+            Expr::Match(match_expr) => match_to_wgsl(match_expr, self),
+            Expr::If(if_expr) => ast::Stmt::If(self.lower_if(if_expr, false)),
+            Expr::While(while_expr) => ast::Stmt::While {
+                condition: self.lower_expr(&while_expr.cond),
+                body: self.lower_block(&while_expr.body),
+            },
+            Expr::Loop(loop_expr) => ast::Stmt::Loop(self.lower_block(&loop_expr.body)),
+            Expr::Block(block_expr) => ast::Stmt::Block(self.lower_block(&block_expr.block)),
+            Expr::Assign(assign) => {
+                // like the values of matches, ifs and loops assigned to their hidden variable
+                let value = self
+                    .declared_type_of(&assign.left)
+                    .and_then(|t| typed_int_literal(&assign.right, &t))
+                    .unwrap_or(*assign.right.clone());
+                ast::Stmt::Assign {
+                    target: self.lower_expr(&assign.left),
+                    op: None,
+                    value: self.lower_expr(&value),
+                }
+            }
+            Expr::Binary(binary) if compound_assignment_op(&binary.op).is_some() => {
+                let value = match self.compound_assignment_type(binary) {
+                    Some(t) => {
+                        typed_int_literal(&binary.right, &t).unwrap_or(*binary.right.clone())
+                    }
+                    None => *binary.right.clone(),
+                };
+                ast::Stmt::Assign {
+                    target: self.lower_expr(&binary.left),
+                    op: compound_assignment_op(&binary.op),
+                    value: self.lower_expr(&value),
+                }
+            }
+            // labels and break values were already lowered by `ControlFlowTransformer`
+            Expr::Break(_) => ast::Stmt::Break,
+            Expr::Continue(_) => ast::Stmt::Continue,
+            Expr::Return(ret) => {
+                ast::Stmt::Return(ret.expr.as_ref().map(|value| self.lower_expr(value)))
+            }
+            // swizzle setters become assignments
+            Expr::MethodCall(method_call) => {
+                let lowered = method_call_to_wgsl(method_call);
+                self.lower_expr_stmt(&lowered)
+            }
+            Expr::Paren(paren) => self.lower_expr_stmt(&paren.expr),
+            Expr::Group(group) => self.lower_expr_stmt(&group.expr),
+            _ => ast::Stmt::Expr(self.lower_expr(expr)),
+        }
+    }
+
+    fn lower_if(&mut self, if_expr: &ExprIf, returns_tail: bool) -> ast::If {
+        let condition = self.lower_expr(&if_expr.cond);
+        let then_branch = self.lower_block_returning(&if_expr.then_branch, returns_tail);
+        let else_branch =
+            if_expr
+                .else_branch
+                .as_ref()
+                .map(|(_, else_branch)| match &**else_branch {
+                    Expr::If(else_if) => {
+                        ast::Else::If(Box::new(self.lower_if(else_if, returns_tail)))
+                    }
+                    Expr::Block(block_expr) => ast::Else::Block(
+                        self.lower_block_returning(&block_expr.block, returns_tail),
+                    ),
+                    other => abort!(other.span(), "Expected a block after `else`"),
+                });
+        ast::If {
+            condition,
+            then_branch,
+            else_branch,
+        }
+    }
+}
+
+fn stmt_attributes(stmt: &Stmt) -> &[syn::Attribute] {
+    match stmt {
+        Stmt::Local(local) => &local.attrs,
+        Stmt::Item(Item::Const(item_const)) => &item_const.attrs,
+        _ => &[],
+    }
+}
+
+/// the operator of `a += b` and the like
+pub fn compound_assignment_op(op: &syn::BinOp) -> Option<ast::BinaryOp> {
+    match op {
+        syn::BinOp::AddAssign(_) => Some(ast::BinaryOp::Add),
+        syn::BinOp::SubAssign(_) => Some(ast::BinaryOp::Sub),
+        syn::BinOp::MulAssign(_) => Some(ast::BinaryOp::Mul),
+        syn::BinOp::DivAssign(_) => Some(ast::BinaryOp::Div),
+        syn::BinOp::RemAssign(_) => Some(ast::BinaryOp::Rem),
+        syn::BinOp::BitAndAssign(_) => Some(ast::BinaryOp::BitAnd),
+        syn::BinOp::BitOrAssign(_) => Some(ast::BinaryOp::BitOr),
+        syn::BinOp::BitXorAssign(_) => Some(ast::BinaryOp::BitXor),
+        syn::BinOp::ShlAssign(_) => Some(ast::BinaryOp::Shl),
+        syn::BinOp::ShrAssign(_) => Some(ast::BinaryOp::Shr),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn lower(item: Item) -> String {
+        let custom_types = Vec::new();
+        WgslLowering::new(&custom_types)
+            .lower_item(&item)
+            .to_string()
+    }
+
+    #[test]
+    fn test_local_variables() {
+        let input: Item = parse_quote! {
+            fn main() {
+                let a = 1;
+                let mut b: f32 = 2.0;
+                let c;
+                let _ = f(a);
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "fn main() {\n    let a = 1;\n    var b: f32 = 2.0;\n    var c;\n    _ = f(a);\n}"
+        );
+    }
+
+    #[test]
+    fn test_implicit_returns() {
+        let input: Item = parse_quote! {
+            pub fn pick(a: f32, b: f32) -> f32 {
+                if a > b {
+                    a
+                } else {
+                    { b }
+                }
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "fn pick(a: f32, b: f32) -> f32 {\n    if a > b {\n        return a;\n    } else {\n        {\n            return b;\n        }\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_helper_type_constructors() {
+        let input: Item = parse_quote! {
+            const M: mat2x2<f32> = Mat2x2F32::new(Vec2F32::new(1.0, 2.0), Vec2F32::new(3.0, 4.0));
+        };
+        assert_eq!(
+            lower(input),
+            "const M: mat2x2<f32> = mat2x2<f32>(vec2<f32>(1.0, 2.0), vec2<f32>(3.0, 4.0));"
+        );
+    }
+
+    #[test]
+    fn test_literals_casts_and_parenthesis() {
+        let input: Item = parse_quote! {
+            fn f(a: u32, b: u32) -> f32 {
+                let c = (a + b) * 2u32 + (a * b);
+                -((c as f32) * 2.5f32)
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "fn f(a: u32, b: u32) -> f32 {\n    let c = (a + b) * 2u + a * b;\n    return -(f32(c) * 2.5f);\n}"
+        );
+    }
+
+    #[test]
+    fn test_type_alias_and_struct() {
+        assert_eq!(
+            lower(parse_quote!(
+                pub type Pair = array<u32, 2>;
+            )),
+            "alias Pair = array<u32, 2>;"
+        );
+        assert_eq!(
+            lower(parse_quote!(
+                #[derive(Clone)]
+                pub struct Body {
+                    pub pair: Pair,
+                    mass: f32,
+                }
+            )),
+            "struct Body {\n    pair: Pair,\n    mass: f32,\n}"
+        );
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let input: Item = parse_quote! {
+            /// the larger of the two
+            fn larger(a: f32, b: f32) -> f32 {
+                /// compared once
+                let bigger = a > b;
+                select(b, a, bigger)
+            }
+        };
+        assert_eq!(
+            lower(input),
+            "// the larger of the two\nfn larger(a: f32, b: f32) -> f32 {\n    // compared once\n    let bigger = a > b;\n    return select(b, a, bigger);\n}"
+        );
+        assert_eq!(
+            lower(parse_quote!(
+                /// a body
+                /// with mass
+                struct Body {
+                    /// in kg
+                    mass: f32,
+                }
+            )),
+            "// a body\n// with mass\nstruct Body {\n    // in kg\n    mass: f32,\n}"
+        );
+    }
+}
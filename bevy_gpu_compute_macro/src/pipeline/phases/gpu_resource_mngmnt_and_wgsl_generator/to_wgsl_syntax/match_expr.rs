@@ -0,0 +1,262 @@
+use bevy_gpu_compute_core::wgsl::ast;
+use proc_macro_error::abort;
+use syn::{Block, Expr, ExprMatch, Lit, Pat, Type, parse_quote, spanned::Spanned};
+
+use super::lowering::WgslLowering;
+use crate::pipeline::phases::{
+    custom_type_collector::custom_type::CustomType,
+    gpu_resource_mngmnt_and_wgsl_generator::enums::enum_variant_to_wgsl,
+};
+
+const HOISTED_SCRUTINEE: &str = "_match_scrutinee";
+
+/**
+ Rust `match` on a `u32` or `i32` becomes a WGSL `switch`:
+ - `0 => a,` => `case 0: { a; }`, or `case 0u:` when matching on a `u32`
+ - `1 | 2 => b,` => `case 1, 2: { b; }`
+ - `_ => c,` => `default: { c; }`
+ - `n => d,` => `default: { let n = x; d; }`, where a scrutinee other than a variable is first stored in a hidden `let` so that it is evaluated only once
+ - arms after the first `_` or binding arm are left out, since they can never match
+
+ Matches that produce a value, and `break`s inside of match arms, were already lowered into plain statements by `ControlFlowTransformer`, and the scrutinee type was already checked by `validate_match_scrutinees`.
+ Constants written in SCREAMING_CASE and enum variants like `Shape::Circle` are used as case selectors, any other identifier pattern is treated as a binding.
+*/
+pub fn match_to_wgsl(match_expr: &ExprMatch, lowering: &mut WgslLowering) -> ast::Stmt {
+    let unsigned = lowering
+        .declared_type_of(&match_expr.expr)
+        .is_some_and(|t| matches!(&t, Type::Path(p) if p.path.is_ident("u32")));
+    let mut patterns: Vec<(Vec<ast::CaseSelector>, Option<String>)> = match_expr
+        .arms
+        .iter()
+        .map(|arm| {
+            if let Some((if_token, _)) = &arm.guard {
+                abort!(
+                    if_token.span(),
+                    "Match guards are not supported in WGSL, move the condition into the body of the match arm"
+                );
+            }
+            let mut selectors = Vec::new();
+            let mut binding = None;
+            collect_selectors(
+                &arm.pat,
+                lowering.custom_types,
+                unsigned,
+                &mut selectors,
+                &mut binding,
+            );
+            (selectors, binding)
+        })
+        .collect();
+    // the arms after a `_` or binding arm are unreachable, rust only warns about them but WGSL does not allow a second default clause
+    if let Some(first_default) = patterns
+        .iter()
+        .position(|(selectors, _)| selectors.contains(&ast::CaseSelector::Default))
+    {
+        patterns.truncate(first_default + 1);
+    }
+    let mut scrutinee = lowering.lower_expr(&match_expr.expr);
+    // a binding arm reads the scrutinee again, so anything other than a variable is only evaluated once, before the switch
+    let hoisted = if patterns.iter().any(|(_, binding)| binding.is_some())
+        && !matches!(&*match_expr.expr, Expr::Path(path) if path.path.get_ident().is_some())
+    {
+        let value = std::mem::replace(&mut scrutinee, ast::Expr::ident(HOISTED_SCRUTINEE));
+        Some(ast::Stmt::Let {
+            name: HOISTED_SCRUTINEE.to_string(),
+            ty: None,
+            value,
+        })
+    } else {
+        None
+    };
+    let mut clauses: Vec<ast::SwitchClause> = match_expr
+        .arms
+        .iter()
+        .zip(patterns)
+        .map(|(arm, (selectors, binding))| {
+            let mut body = arm_body(&arm.body, lowering);
+            if let Some(name) = binding {
+                body.stmts.insert(
+                    0,
+                    ast::Stmt::Let {
+                        name,
+                        ty: None,
+                        value: scrutinee.clone(),
+                    },
+                );
+            }
+            ast::SwitchClause { selectors, body }
+        })
+        .collect();
+    // rust checks that matches are exhaustive, but wgsl always needs a default clause
+    if !clauses
+        .iter()
+        .any(|c| c.selectors.contains(&ast::CaseSelector::Default))
+    {
+        clauses.push(ast::SwitchClause {
+            selectors: vec![ast::CaseSelector::Default],
+            body: ast::Block::default(),
+        });
+    }
+    let switch = ast::Stmt::Switch {
+        selector: scrutinee,
+        clauses,
+    };
+    match hoisted {
+        Some(scrutinee) => ast::Stmt::Block(ast::Block::new(vec![scrutinee, switch])),
+        None => switch,
+    }
+}
+
+/// the case selectors of a pattern, and the name of the variable it binds if any
+fn collect_selectors(
+    pat: &Pat,
+    custom_types: &[CustomType],
+    unsigned: bool,
+    selectors: &mut Vec<ast::CaseSelector>,
+    binding: &mut Option<String>,
+) {
+    match pat {
+        Pat::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => {
+                let digits = int.base10_digits();
+                let selector = match int.suffix() {
+                    "u32" => format!("{}u", digits),
+                    "i32" => format!("{}i", digits),
+                    // naga reads an unsuffixed case selector as an i32, which does not match a u32 scrutinee
+                    _ if unsigned => format!("{}u", digits),
+                    _ => digits.to_string(),
+                };
+                selectors.push(ast::CaseSelector::Expr(ast::Expr::literal(selector)));
+            }
+            _ => abort!(
+                lit.span(),
+                "Match expressions in WGSL can only match on u32 or i32 values, so only integer literals can be used as patterns"
+            ),
+        },
+        Pat::Or(or) => {
+            for case in or.cases.iter() {
+                collect_selectors(case, custom_types, unsigned, selectors, binding);
+            }
+        }
+        Pat::Paren(paren) => {
+            collect_selectors(&paren.pat, custom_types, unsigned, selectors, binding)
+        }
+        Pat::Wild(_) => selectors.push(ast::CaseSelector::Default),
+        Pat::Ident(pat_ident) if pat_ident.subpat.is_none() => {
+            let name = pat_ident.ident.to_string();
+            if name
+                .chars()
+                .all(|c| c.is_uppercase() || c.is_numeric() || c == '_')
+            {
+                selectors.push(ast::CaseSelector::Expr(ast::Expr::ident(name)));
+            } else {
+                selectors.push(ast::CaseSelector::Default);
+                *binding = Some(name);
+            }
+        }
+        Pat::Path(path) if path.path.get_ident().is_some() => {
+            let ident = path.path.get_ident().unwrap();
+            selectors.push(ast::CaseSelector::Expr(ast::Expr::ident(ident.to_string())));
+        }
+        Pat::Path(path) if enum_variant_to_wgsl(&path.path, custom_types).is_some() => {
+            let variant = enum_variant_to_wgsl(&path.path, custom_types).unwrap();
+            selectors.push(ast::CaseSelector::Expr(ast::Expr::ident(
+                variant.to_string(),
+            )));
+        }
+        Pat::Range(range) => abort!(
+            range.span(),
+            "Range patterns are not supported in WGSL, list the values with `|` instead"
+        ),
+        _ => abort!(
+            pat.span(),
+            "Only integer literals, constants, enum variants, `|`, `_` and plain bindings are supported as match patterns in WGSL"
+        ),
+    }
+}
+
+fn arm_body(body: &Expr, lowering: &mut WgslLowering) -> ast::Block {
+    let block: Block = match body {
+        Expr::Block(block_expr) if block_expr.label.is_none() => block_expr.block.clone(),
+        Expr::Tuple(tuple) if tuple.elems.is_empty() => return ast::Block::default(),
+        _ => parse_quote!({ #body; }),
+    };
+    lowering.lower_block(&block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arms_after_the_default_are_dropped() {
+        let custom_types = Vec::new();
+        let mut lowering = WgslLowering::new(&custom_types);
+        let input: ExprMatch = parse_quote! {
+            match id {
+                0 => x = 1,
+                n => x = n,
+                _ => x = 3,
+            }
+        };
+        assert_eq!(
+            match_to_wgsl(&input, &mut lowering).to_string(),
+            "switch id {\n    case 0: {\n        x = 1;\n    }\n    default: {\n        let n = id;\n        x = n;\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_literal_or_and_wildcard_arms() {
+        let custom_types = Vec::new();
+        let mut lowering = WgslLowering::new(&custom_types);
+        let input: ExprMatch = parse_quote! {
+            match id {
+                0 => x = 1,
+                1 | 2u32 => {
+                    x = 2;
+                }
+                _ => {}
+            }
+        };
+        assert_eq!(
+            match_to_wgsl(&input, &mut lowering).to_string(),
+            "switch id {\n    case 0: {\n        x = 1;\n    }\n    case 1, 2u: {\n        x = 2;\n    }\n    default: {}\n}"
+        );
+    }
+
+    #[test]
+    fn test_binding_arm_becomes_default() {
+        let custom_types = Vec::new();
+        let mut lowering = WgslLowering::new(&custom_types);
+        let input: ExprMatch = parse_quote! {
+            match state {
+                STATE_IDLE => {}
+                other => {
+                    x = other;
+                }
+            }
+        };
+        assert_eq!(
+            match_to_wgsl(&input, &mut lowering).to_string(),
+            "switch state {\n    case STATE_IDLE: {}\n    default: {\n        let other = state;\n        x = other;\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn test_scrutinee_evaluated_once_for_binding_arm() {
+        let custom_types = Vec::new();
+        let mut lowering = WgslLowering::new(&custom_types);
+        let input: ExprMatch = parse_quote! {
+            match next_id(state) {
+                0 => {}
+                other => {
+                    x = other;
+                }
+            }
+        };
+        assert_eq!(
+            match_to_wgsl(&input, &mut lowering).to_string(),
+            "{\n    let _match_scrutinee = next_id(state);\n    switch _match_scrutinee {\n        case 0: {}\n        default: {\n            let other = _match_scrutinee;\n            x = other;\n        }\n    }\n}"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use proc_macro_error::abort;
+use quote::format_ident;
+use syn::{Expr, ExprMethodCall, parse_quote, spanned::Spanned};
+
+use crate::pipeline::allowed_types::{WGSL_BUILTIN_METHODS, is_swizzle};
+
+/**
+ Method calls on the vector, matrix and scalar types become calls to the WGSL builtin with the receiver as the first argument:
+ - `a.dot(b)` => `dot(a, b)`
+ - `v.inverse_sqrt()` => `inverseSqrt(v)`
+ - `x.powf(y)` => `pow(x, y)`
+
+ Swizzles become the native WGSL swizzles, except for assignments since WGSL can only assign one component at a time:
+ - `v.zyx()` => `v.zyx`
+ - `v.set_x(a)` => `v.x = a`
+ - `v.set_xy(a)` => `{ let swizzle_value = a; v.x = swizzle_value.x; v.y = swizzle_value.y; }`
+ - `f(v)[i].set_xy(a)` => `{ let swizzle_target = &f(v)[i]; let swizzle_value = a; (*swizzle_target).x = swizzle_value.x; (*swizzle_target).y = swizzle_value.y; }`, so that a receiver that is not a plain variable, field or element is only evaluated once
+
+ Unknown methods were already rejected by `validate_method_calls`.
+*/
+pub fn method_call_to_wgsl(method_call: &ExprMethodCall) -> Expr {
+    let method = method_call.method.to_string();
+    if let Some(swizzle) = swizzle_to_wgsl(method_call) {
+        return swizzle;
+    }
+    let Some((_, builtin)) = WGSL_BUILTIN_METHODS
+        .iter()
+        .find(|(name, _)| *name == method.as_str())
+    else {
+        abort!(
+            method_call.span(),
+            "Unknown method `{}`, only methods that have a WGSL builtin equivalent can be used",
+            method
+        );
+    };
+    let builtin = format_ident!("{}", builtin);
+    let receiver = &method_call.receiver;
+    let args = method_call.args.iter();
+    parse_quote!(#builtin(#receiver #(, #args)*))
+}
+
+fn swizzle_to_wgsl(method_call: &ExprMethodCall) -> Option<Expr> {
+    let method = method_call.method.to_string();
+    let receiver = &method_call.receiver;
+    if method_call.args.is_empty() && is_swizzle(&method, 2, false) {
+        let swizzle = &method_call.method;
+        return Some(parse_quote!(#receiver.#swizzle));
+    }
+    let components = method.strip_prefix("set_")?;
+    if method_call.args.len() != 1 || !is_swizzle(components, 1, true) {
+        return None;
+    }
+    let value = &method_call.args[0];
+    let components: Vec<_> = components.chars().map(|c| format_ident!("{}", c)).collect();
+    if let [component] = components.as_slice() {
+        return Some(parse_quote!(#receiver.#component = #value));
+    }
+    let value_components = [
+        format_ident!("x"),
+        format_ident!("y"),
+        format_ident!("z"),
+        format_ident!("w"),
+    ];
+    let value_components = &value_components[..components.len()];
+    if is_plain_place(receiver) {
+        return Some(parse_quote!({
+            let swizzle_value = #value;
+            #(#receiver.#components = swizzle_value.#value_components;)*
+        }));
+    }
+    // rust evaluates the receiver before the argument
+    Some(parse_quote!({
+        let swizzle_target = &mut #receiver;
+        let swizzle_value = #value;
+        #((*swizzle_target).#components = swizzle_value.#value_components;)*
+    }))
+}
+
+/// a variable, or a field or element of one at a variable or literal index, which can be repeated without evaluating anything twice
+fn is_plain_place(expr: &Expr) -> bool {
+    match expr {
+        Expr::Path(path) => path.path.get_ident().is_some(),
+        Expr::Field(field) => is_plain_place(&field.base),
+        Expr::Index(index) => {
+            let plain_index = match &*index.index {
+                Expr::Lit(_) => true,
+                Expr::Path(p) => p.path.get_ident().is_some(),
+                _ => false,
+            };
+            plain_index && is_plain_place(&index.expr)
+        }
+        Expr::Paren(paren) => is_plain_place(&paren.expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    #[test]
+    fn test_methods_become_builtin_calls() {
+        let cases: [(ExprMethodCall, &str); 8] = [
+            (parse_quote!(a.dot(b)), "dot(a, b)"),
+            (parse_quote!(v.inverse_sqrt()), "inverseSqrt(v)"),
+            (
+                parse_quote!((x * 2.0).mul_add(y, z)),
+                "fma((x * 2.0), y, z)",
+            ),
+            (parse_quote!(v.zyx()), "v.zyx"),
+            (parse_quote!(c.set_a(1.0)), "c.a = 1.0"),
+            (
+                parse_quote!(v.set_zx(a.xy())),
+                "{ let swizzle_value = a.xy(); v.z = swizzle_value.x; v.x = swizzle_value.y; }",
+            ),
+            (
+                parse_quote!(cells[i].color.set_gb(a)),
+                "{ let swizzle_value = a; cells[i].color.g = swizzle_value.x; cells[i].color.b = swizzle_value.y; }",
+            ),
+            (
+                parse_quote!(cells[next(i)].set_xy(a)),
+                "{ let swizzle_target = &mut cells[next(i)]; let swizzle_value = a; (*swizzle_target).x = swizzle_value.x; (*swizzle_target).y = swizzle_value.y; }",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                method_call_to_wgsl(&input).to_token_stream().to_string(),
+                expected
+                    .parse::<proc_macro2::TokenStream>()
+                    .unwrap()
+                    .to_string()
+            );
+        }
+    }
+}
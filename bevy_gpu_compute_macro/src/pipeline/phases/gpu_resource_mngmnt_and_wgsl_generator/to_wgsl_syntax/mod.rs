@@ -1,84 +1,98 @@
-use std::collections::HashMap;
-
 use array::ArrayToWgslTransformer;
-use expr::ExprToWgslTransformer;
-use implicit_to_explicit_return::ImplicitToExplicitReturnTransformer;
-use local_var::replace_let_mut_with_var;
+use bevy_gpu_compute_core::wgsl::ast;
+use control_flow::ControlFlowTransformer;
+use lowering::WgslLowering;
+use pointer::PointerToWgslTransformer;
 use proc_macro_error::abort;
 use proc_macro2::{Span, TokenStream};
-use quote::ToTokens;
-use remove_attributes::remove_attributes;
-use remove_pub_from_struct_def::PubRemover;
-use syn::{File, parse, visit::Visit, visit_mut::VisitMut};
+use syn::{File, parse2, visit_mut::VisitMut};
 use r#type::TypeToWgslTransformer;
-use type_def::TypeDefToWgslTransformer;
-use wgsl_builtin_constructors::convert_wgsl_builtin_constructors;
 
 use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
 
 /**
  # Notes about conversions (all syntax not mentioned is either the same or not supported in wgsl)
 
+- Local(Local):
+  `let mut x = a;` becomes `var x = a;`, and the trailing expression of a function becomes a `return`, see `lowering.rs`
+
+- Type aliases (ItemType):
+  `type A = T;` becomes `alias A = T;`, see `type_def.rs`
+
+- Call(ExprCall) of the helper types:
+  `Vec3F32::new(a, b, c)` becomes `vec3<f32>(a, b, c)`, see `expr.rs`
+
 - ForLoop(ExprForLoop):
-  in wgsl, but with javascript style syntax: for (var i = 0; i< 10; i++){}
+  in wgsl, but with javascript style syntax: for (var i = 0; i< 10; i++){}, see `for_loop.rs`
+
+- If(ExprIf) and Block(ExprBlock):
+  when they produce a value they become `select()` or a hidden `var` assigned in each branch, see `control_flow.rs`
 
 - Loop(ExprLoop):
-  supported in wgsl, but with different syntax: `for (;;) {}`
-
-- Reference(ExprReference):
-  support pointer types, but this is something for a future version. Example of pointers in wgsl:
-  ```ignore
-  fn my_function(
-      /* 'ptr<function,i32,read_write>' is the type of a pointer value that references
-         memory for keeping an 'i32' value, using memory locations in the 'function'
-         address space.  Here 'i32' is the store type.
-         The implied access mode is 'read_write'.
-         See "Address Space" section for defaults. */
-      ptr_int: ptr<function,i32>,
-
-      /* 'ptr<private,array<f32,50>,read_write>' is the type of a pointer value that
-       refers to memory for keeping an array of 50 elements of type 'f32', using
-       memory locations in the 'private' address space.
-       Here the store type is 'array<f32,50>'.
-       The implied access mode is 'read_write'.
-       See the "Address space section for defaults.
-      */
-      ptr_array: ptr<private, array<f32, 50>>
-    ) { }
-  ```
+  supported in wgsl with the same syntax, labels and `break value` are lowered away first, see `control_flow.rs`
+
+- Match(ExprMatch):
+  matches on u32 or i32 values become a `switch`, see `match_expr.rs`
+
+- MethodCall(ExprMethodCall):
+  methods of the vector, matrix and scalar types become calls to the matching WGSL builtin, see `method_call.rs`.
+  Methods from `impl` blocks on the module's own types were already turned into free functions like `Position_dist(p, o)`, see `impl_blocks.rs`
+
+- Reference(ExprReference) and Unary(ExprUnary) dereferences:
+  `&mut T` parameters become `ptr<function, T>`, `&mut x` arguments become `&x`, and pointers are dereferenced explicitly, see `pointer.rs`
 
 - Struct(ExprStruct):
-  supported, different syntax. in wgsl it becomes `Point(1,1)`, but we must warn the user that
-  the order that they list the fields in when constructing their struct MUST be the same order
-  that they are listed in when defining the struct type
+  supported, different syntax. in wgsl it becomes `Point(1,1)`, with the fields reordered to match the struct definition, see `struct_literal.rs`
 
-- Array(ExprArray):
-  supported, but with different syntax. in wgsl it becomes `array<f32, 3>`
+- Array(ExprArray) and Repeat(ExprRepeat):
+  become array constructors like `array<f32, 3>(a, b, c)`, and `[T; N]` types become `array<T, N>`, see `array.rs`.
+  On the CPU, array indices are cast to `usize`, see `make_array_indices_usize.rs`
+
+- Tuple(ExprTuple):
+  every tuple type becomes a hidden struct before the conversion, and destructuring becomes field accesses, see `tuples.rs`
+
+- Generic functions:
+  a copy is made for every set of type arguments they are called with, named like `lerp_f32`, see `generics.rs`
+
+- Closure(ExprClosure):
+  closures stored with `let` become functions of the module named like `main_sq`, with the captured variables as extra parameters, see `closures.rs`
 
 - Types:
   - f32, f16, i32, u32, bool, vec2, vec3, vec4, mat2x2, mat3x3, mat4x4
   */
 mod array;
+mod control_flow;
 mod expr;
-mod implicit_to_explicit_return;
-mod local_var;
-pub mod remove_attributes;
-mod remove_pub_from_struct_def;
+mod for_loop;
+mod lowering;
+mod match_expr;
+mod method_call;
+mod pointer;
+mod source;
+mod struct_literal;
 mod r#type;
 mod type_def;
-mod wgsl_builtin_constructors;
 /// called_from is for debug messages
 pub fn convert_file_to_wgsl(
     input: TokenStream,
     custom_types: &Vec<CustomType>,
     called_from: String,
 ) -> String {
-    let unprocessed_string = input.to_string();
-    let input_no_attributes = remove_attributes(unprocessed_string);
-    let processed_stream: TokenStream = input_no_attributes.parse().unwrap();
-    let debug_str = processed_stream.clone().to_string();
+    lower_file_to_wgsl(input, custom_types, called_from)
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    let mut file = if let Ok(f) = parse::<File>(processed_stream.into()) {
+/// the rust items are first rewritten as rust syntax where WGSL has an equivalent construct, and then lowered into the WGSL AST
+pub fn lower_file_to_wgsl(
+    input: TokenStream,
+    custom_types: &Vec<CustomType>,
+    called_from: String,
+) -> Vec<ast::Item> {
+    let debug_str = input.to_string();
+    let mut file = if let Ok(f) = parse2::<File>(input) {
         f
     } else {
         let message = format!(
@@ -88,24 +102,13 @@ pub fn convert_file_to_wgsl(
         abort!(Span::call_site(), message);
     };
 
-    PubRemover {}.visit_file_mut(&mut file);
     TypeToWgslTransformer { custom_types }.visit_file_mut(&mut file);
     ArrayToWgslTransformer {}.visit_file_mut(&mut file);
-    ExprToWgslTransformer {}.visit_file_mut(&mut file);
-    ImplicitToExplicitReturnTransformer {}.visit_file_mut(&mut file);
-    let mut type_def_transformer = TypeDefToWgslTransformer {
-        replacements: HashMap::new(),
-    };
-    type_def_transformer.visit_file(&file);
-    // expressions and type defs have to be transformed differently because they may change the token structure, so we have to transition to strings
-    let mut string_version = file.to_token_stream().to_string();
-
-    type_def_transformer.replacements.iter().for_each(|(k, v)| {
-        string_version = string_version.replace(k, v);
-    });
-    // println!("Final string version: {}", string_version);
-    // transform vec and matrix constructors
-    string_version = convert_wgsl_builtin_constructors(string_version);
-    string_version = replace_let_mut_with_var(&string_version);
-    string_version
+    PointerToWgslTransformer::new().visit_file_mut(&mut file);
+    ControlFlowTransformer::new().visit_file_mut(&mut file);
+    let mut lowering = WgslLowering::new(custom_types);
+    file.items
+        .iter()
+        .map(|item| lowering.lower_item(item))
+        .collect()
 }
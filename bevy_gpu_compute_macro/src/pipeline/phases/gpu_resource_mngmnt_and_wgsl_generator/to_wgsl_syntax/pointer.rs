@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use syn::{Expr, FnArg, Pat, Type, parse_quote, visit_mut::VisitMut};
+
+/**
+ `&mut T` parameters become WGSL pointers to a local variable of the caller:
+ - `fn f(acc: &mut Vec3F32)` => `fn f(acc: ptr<function, vec3<f32>>)`
+ - `f(&mut total)` => `f(&total)`
+ - `acc.x`, `acc[i]` and `acc.length()` => `(*acc).x`, `(*acc)[i]` and `length(*acc)`, since WGSL does not dereference pointers automatically
+
+ The shader module only has constants at module scope, so the address space is always `function`, never `private`.
+ Which `&mut` references are allowed was already checked on the original tree, see `validate_mutable_references.rs`
+*/
+pub struct PointerToWgslTransformer {
+    /// `&mut` parameters of the current function
+    pointer_params: HashSet<String>,
+}
+
+impl PointerToWgslTransformer {
+    pub fn new() -> Self {
+        Self {
+            pointer_params: HashSet::new(),
+        }
+    }
+    fn is_pointer(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .is_some_and(|ident| self.pointer_params.contains(&ident.to_string())),
+            _ => false,
+        }
+    }
+    fn deref(&self, expr: &mut Expr) {
+        if self.is_pointer(expr) {
+            *expr = parse_quote!((*#expr));
+        }
+    }
+}
+
+impl VisitMut for PointerToWgslTransformer {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut syn::ItemFn) {
+        self.pointer_params.clear();
+        for input in item_fn.sig.inputs.iter_mut() {
+            let FnArg::Typed(pat_type) = input else {
+                continue;
+            };
+            let (Pat::Ident(pat_ident), Type::Reference(reference)) =
+                (&*pat_type.pat, &*pat_type.ty)
+            else {
+                continue;
+            };
+            if reference.mutability.is_some() {
+                self.pointer_params.insert(pat_ident.ident.to_string());
+                let elem = &reference.elem;
+                *pat_type.ty = parse_quote!(ptr<function, #elem>);
+            }
+        }
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        match expr {
+            Expr::Reference(reference) => reference.mutability = None,
+            Expr::Field(field) => self.deref(&mut field.base),
+            Expr::Index(index) => self.deref(&mut index.expr),
+            Expr::MethodCall(method_call) => self.deref(&mut method_call.receiver),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    #[test]
+    fn test_mutable_references_become_pointers() {
+        let mut input: syn::ItemFn = parse_quote! {
+            fn accumulate(acc: &mut vec3<f32>, v: vec3<f32>) {
+                acc.x += v.length();
+                *acc = *acc + v;
+                scale(&mut *acc, acc.y);
+            }
+        };
+        PointerToWgslTransformer::new().visit_item_fn_mut(&mut input);
+        assert_eq!(
+            input.to_token_stream().to_string(),
+            "fn accumulate (acc : ptr < function , vec3 < f32 > > , v : vec3 < f32 >) { (* acc) . x += v . length () ; * acc = * acc + v ; scale (& * acc , (* acc) . y) ; }"
+        );
+    }
+}
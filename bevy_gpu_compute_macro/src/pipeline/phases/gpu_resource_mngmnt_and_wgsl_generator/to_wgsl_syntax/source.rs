@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use bevy_gpu_compute_core::wgsl::ast;
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{Attribute, Expr, ExprLit, Lit, Meta};
+
+/**
+ Where the lowered code came from in the rust source, and the comments written next to it.
+
+ Only spans of tokens written by the user have a location. Tokens created by the macro itself have the span of the `#[wgsl_shader_module]` attribute, and tokens parsed from strings (like in unit tests) have no file, so neither are annotated.
+ Plain `//` comments are not part of the token stream, so they are read from the source file between the statements of a block.
+*/
+#[derive(Default)]
+pub struct SourceFiles {
+    /// the lines of each file that was read, `None` if it could not be read
+    files: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl SourceFiles {
+    /// the text of the `//` comments on their own line strictly between the two lines of the file of `span`
+    pub fn comments_between(
+        &mut self,
+        span: Span,
+        after_line: usize,
+        before_line: usize,
+    ) -> Vec<String> {
+        let Some(file) = span.local_file() else {
+            return Vec::new();
+        };
+        if before_line <= after_line + 1 {
+            return Vec::new();
+        }
+        let Some(lines) = self.files.entry(file.clone()).or_insert_with(|| {
+            std::fs::read_to_string(&file)
+                .ok()
+                .map(|s| s.lines().map(str::to_string).collect())
+        }) else {
+            return Vec::new();
+        };
+        // lines of a span start at 1
+        lines
+            .iter()
+            .take(before_line - 1)
+            .skip(after_line)
+            .filter_map(|line| {
+                let comment = line.trim().strip_prefix("//")?;
+                // doc comments are attributes, they are handled with the item they belong to
+                if comment.starts_with('/') || comment.starts_with('!') {
+                    return None;
+                }
+                Some(comment.to_string())
+            })
+            .collect()
+    }
+}
+
+/// `file:line` of the start of `span`, relative to the root of the crate being compiled
+pub fn source_location(span: Span) -> Option<ast::SourceLocation> {
+    let file = span.local_file()?;
+    let line = span.start().line;
+    // tokens created by the macro have the span of the macro call
+    let macro_site = Span::call_site();
+    if line == 0
+        || (macro_site.local_file() == Some(file.clone())
+            && span.start() == macro_site.start()
+            && span.end() == macro_site.end())
+    {
+        return None;
+    }
+    Some(ast::SourceLocation {
+        file: crate_relative_path(&file),
+        line,
+    })
+}
+
+/// the text of the doc comments among the attributes
+pub fn doc_comments(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident("doc") => {
+                match &name_value.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(s), ..
+                    }) => Some(s.value()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// the location of the first token written by the user that is not part of an outer attribute, so that a statement is located at its code instead of its doc comment, and statements rewritten by the macro are located at the code they came from
+pub fn first_code_location(tokens: &impl ToTokens) -> Option<(Span, ast::SourceLocation)> {
+    let mut tokens = tokens.to_token_stream().into_iter().peekable();
+    while let Some(TokenTree::Punct(punct)) = tokens.peek() {
+        if punct.as_char() != '#' {
+            break;
+        }
+        tokens.next();
+        tokens.next();
+    }
+    tokens.find_map(|token| first_located_token(&token))
+}
+
+fn first_located_token(token: &TokenTree) -> Option<(Span, ast::SourceLocation)> {
+    if let Some(location) = source_location(token.span()) {
+        return Some((token.span(), location));
+    }
+    match token {
+        TokenTree::Group(group) => group
+            .stream()
+            .into_iter()
+            .find_map(|token| first_located_token(&token)),
+        _ => None,
+    }
+}
+
+/// the line that the tokens end on, a delimited group ends at its closing delimiter
+pub fn last_line(tokens: &impl ToTokens) -> usize {
+    let stream: TokenStream = tokens.to_token_stream();
+    stream
+        .into_iter()
+        .last()
+        .map_or(0, |token| token.span().end().line)
+}
+
+fn crate_relative_path(file: &Path) -> String {
+    let relative = std::env::var("CARGO_MANIFEST_DIR")
+        .ok()
+        .and_then(|manifest_dir| {
+            let absolute = std::fs::canonicalize(file).ok()?;
+            let manifest_dir = std::fs::canonicalize(manifest_dir).ok()?;
+            absolute
+                .strip_prefix(manifest_dir)
+                .ok()
+                .map(Path::to_path_buf)
+        });
+    relative
+        .as_deref()
+        .unwrap_or(file)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
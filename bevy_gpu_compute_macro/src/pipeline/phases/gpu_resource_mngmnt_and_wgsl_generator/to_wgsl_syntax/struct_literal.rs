@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use proc_macro_error::abort;
+use quote::ToTokens;
+use syn::{
+    Expr, ExprCall, ExprPath, ExprStruct, Ident, ImplItem, ItemImpl, ItemStruct, Member, Stmt,
+    Type, parse_quote, parse2, spanned::Spanned, visit_mut::VisitMut,
+};
+
+use super::{array::ArrayToWgslTransformer, r#type::TypeToWgslTransformer};
+use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
+
+/**
+ Struct literals become WGSL constructor calls, which take the fields in the order that they are defined in, so they are reordered by name.
+ For `struct Foo { a: u32, b: u32 }`:
+ - `Foo { b: 1, a: 2 }` => `Foo(2, 1)`
+ - `Foo { a: 2, ..other }` => `Foo(2, other.b)`
+ - `Foo { a: 2, ..Default::default() }` => `Foo(2, u32())`
+
+ The defaults come from an `impl Default for Foo` in the module whose `default` function returns a struct literal, or otherwise from `#[derive(Default)]`, which gives every field its zero value.
+
+ The field values are not converted here, the caller still has to visit them.
+*/
+pub fn struct_literal_to_wgsl(struct_expr: &ExprStruct, custom_types: &[CustomType]) -> Expr {
+    let Some(struct_name) = struct_expr.path.segments.last().map(|s| &s.ident) else {
+        abort!(struct_expr.span(), "Struct path is empty")
+    };
+    let Some(custom_type) = custom_types.iter().find(|c| c.name.eq(struct_name)) else {
+        abort!(
+            struct_expr.span(),
+            "Struct literals can only be used for structs defined in the shader module, `{}` is not one. Use the `new` method for helper types like Vec3F32",
+            struct_name
+        );
+    };
+    let definition = match parse2::<ItemStruct>(custom_type.rust_code.clone()) {
+        Ok(definition) => definition,
+        Err(_) => abort!(
+            struct_expr.span(),
+            "`{}` is not a struct, so it cannot be constructed with a struct literal",
+            struct_name
+        ),
+    };
+    let mut given: HashMap<String, Expr> = struct_expr
+        .fields
+        .iter()
+        .map(|f| (member_name(&f.member), f.expr.clone()))
+        .collect();
+    let mut defaults: Option<HashMap<String, Expr>> = None;
+    let values = definition.fields.iter().enumerate().map(|(i, field)| {
+        let name = field
+            .ident
+            .as_ref()
+            .map_or_else(|| i.to_string(), |ident| ident.to_string());
+        if let Some(value) = given.remove(&name) {
+            return value;
+        }
+        let Some(rest) = &struct_expr.rest else {
+            abort!(
+                struct_expr.span(),
+                "Missing field `{}` in struct literal of `{}`, all fields must be given, or the rest filled in with `..Default::default()`",
+                name,
+                struct_name
+            );
+        };
+        if is_default_call(rest, struct_name) {
+            let defaults = defaults.get_or_insert_with(|| default_field_values(&definition, custom_type, custom_types));
+            return defaults.get(&name).cloned().unwrap_or_else(|| {
+                abort!(
+                    rest.span(),
+                    "The `Default` implementation of `{}` does not give a value for field `{}`",
+                    struct_name,
+                    name
+                )
+            });
+        }
+        if let Expr::Path(_) = &**rest {
+            let member: Member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(i.into()),
+            };
+            return parse_quote!(#rest.#member);
+        }
+        abort!(
+            rest.span(),
+            "Only `..Default::default()`, `..{}::default()` or `..variable` are supported as the base of a struct literal in WGSL",
+            struct_name
+        );
+    });
+    let values = values.collect();
+    if let Some(unknown) = given.keys().next() {
+        abort!(
+            struct_expr.span(),
+            "`{}` has no field `{}`",
+            struct_name,
+            unknown
+        );
+    }
+    // built directly instead of parsed, since default values may be WGSL that is not valid rust
+    Expr::Call(ExprCall {
+        attrs: Vec::new(),
+        func: Box::new(parse_quote!(#struct_name)),
+        paren_token: Default::default(),
+        args: values,
+    })
+}
+
+fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => ident.to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+/// `Default::default()`, `Foo::default()` or `<Foo as Default>::default()`, where `Foo` is the type of the struct literal
+fn is_default_call(expr: &Expr, struct_name: &Ident) -> bool {
+    let Expr::Call(call) = expr else {
+        return false;
+    };
+    let Expr::Path(func) = &*call.func else {
+        return false;
+    };
+    if !call.args.is_empty() {
+        return false;
+    }
+    let segments: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    match &func.qself {
+        None => {
+            segments == ["Default", "default"]
+                || segments == [struct_name.to_string(), "default".to_string()]
+        }
+        Some(qself) => {
+            matches!(&*qself.ty, Type::Path(t) if t.qself.is_none() && t.path.is_ident(struct_name))
+                && segments == ["Default", "default"]
+        }
+    }
+}
+
+fn default_field_values(
+    definition: &ItemStruct,
+    custom_type: &CustomType,
+    custom_types: &[CustomType],
+) -> HashMap<String, Expr> {
+    if let Some(default_impl) = &custom_type.default_impl {
+        return default_impl_field_values(default_impl.clone(), definition);
+    }
+    let derives_default = definition.attrs.iter().any(|attr| {
+        attr.path().is_ident("derive") && attr.to_token_stream().to_string().contains("Default")
+    });
+    if !derives_default {
+        abort!(
+            definition.ident.span(),
+            "`..Default::default()` needs `{}` to either `#[derive(Default)]` or have an `impl Default` in the shader module",
+            definition.ident
+        );
+    }
+    // WGSL's zero value constructors, like `f32()`, match the values of `#[derive(Default)]`
+    definition
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| i.to_string(), |ident| ident.to_string());
+            let mut field_type = field.ty.clone();
+            wgsl_type(&mut field_type, custom_types);
+            let Type::Path(type_path) = field_type else {
+                abort!(
+                    field.ty.span(),
+                    "Only named types can be defaulted in a struct literal in WGSL"
+                );
+            };
+            let constructor = Expr::Path(ExprPath {
+                attrs: Vec::new(),
+                qself: None,
+                path: type_path.path,
+            });
+            (name, parse_quote!(#constructor()))
+        })
+        .collect()
+}
+
+fn wgsl_type(t: &mut Type, custom_types: &[CustomType]) {
+    let custom_types = custom_types.to_vec();
+    TypeToWgslTransformer {
+        custom_types: &custom_types,
+    }
+    .visit_type_mut(t);
+    ArrayToWgslTransformer {}.visit_type_mut(t);
+}
+
+/// the field values of the struct literal that the `default` function returns
+fn default_impl_field_values(
+    default_impl: proc_macro2::TokenStream,
+    definition: &ItemStruct,
+) -> HashMap<String, Expr> {
+    let default_impl = parse2::<ItemImpl>(default_impl).unwrap();
+    let returned = default_impl.items.iter().find_map(|item| match item {
+        ImplItem::Fn(f) if f.sig.ident == "default" => match f.block.stmts.as_slice() {
+            [Stmt::Expr(Expr::Struct(s), None)] => Some(s.clone()),
+            [Stmt::Expr(Expr::Return(r), _)] => match r.expr.as_deref() {
+                Some(Expr::Struct(s)) => Some(s.clone()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    });
+    let Some(returned) = returned.filter(|s| s.rest.is_none()) else {
+        abort!(
+            default_impl.span(),
+            "To be used in WGSL, the `default` function of `{}` must only return a struct literal with every field",
+            definition.ident
+        );
+    };
+    returned
+        .fields
+        .iter()
+        .map(|f| (member_name(&f.member), f.expr.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::phases::custom_type_collector::custom_type::CustomTypeKind;
+    use quote::quote;
+
+    fn custom_type(rust_code: proc_macro2::TokenStream) -> CustomType {
+        let definition = parse2::<ItemStruct>(rust_code.clone()).unwrap();
+        CustomType::new(
+            &definition.ident,
+            CustomTypeKind::GpuOnlyHelperType,
+            rust_code,
+        )
+    }
+
+    fn convert(expr: ExprStruct, custom_types: &[CustomType]) -> String {
+        struct_literal_to_wgsl(&expr, custom_types)
+            .to_token_stream()
+            .to_string()
+    }
+
+    #[test]
+    fn test_fields_are_reordered() {
+        let types = [custom_type(quote!(
+            struct Foo {
+                a: u32,
+                b: f32,
+            }
+        ))];
+        assert_eq!(
+            convert(parse_quote!(Foo { b: 1.0, a: 2 }), &types),
+            "Foo (2 , 1.0)"
+        );
+        assert_eq!(
+            convert(parse_quote!(Foo { b: 1.0, ..other }), &types),
+            "Foo (other . a , 1.0)"
+        );
+    }
+
+    #[test]
+    fn test_defaults() {
+        let mut derived = custom_type(quote!(
+            #[derive(Default)]
+            struct Foo {
+                a: u32,
+                b: [Vec2F32; 2],
+            }
+        ));
+        assert_eq!(
+            convert(
+                parse_quote!(Foo {
+                    a: 2,
+                    ..Default::default()
+                }),
+                std::slice::from_ref(&derived)
+            ),
+            "Foo (2 , array :: < vec2 < f32 > , 2 > ())"
+        );
+        derived.default_impl = Some(quote!(
+            impl Default for Foo {
+                fn default() -> Self {
+                    Foo { b: [Vec2F32::new(1.0, 1.0); 2], a: 7 }
+                }
+            }
+        ));
+        assert_eq!(
+            convert(
+                parse_quote!(Foo { ..Foo::default() }),
+                std::slice::from_ref(&derived)
+            ),
+            "Foo (7 , [Vec2F32 :: new (1.0 , 1.0) ; 2])"
+        );
+        assert_eq!(
+            convert(
+                parse_quote!(Foo {
+                    ..<Foo as Default>::default()
+                }),
+                &[derived]
+            ),
+            "Foo (7 , [Vec2F32 :: new (1.0 , 1.0) ; 2])"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use proc_macro_error::abort;
 use syn::{PathSegment, parse_quote, visit_mut::VisitMut};
 
-use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
+use crate::pipeline::phases::custom_type_collector::custom_type::{CustomType, CustomTypeKind};
 
 pub struct TypeToWgslTransformer<'a> {
     pub custom_types: &'a Vec<CustomType>,
@@ -21,10 +21,14 @@ pub fn path_type_to_wgsl(type_path: &mut syn::TypePath, custom_types: &[CustomTy
         *segment = new_segment;
     }
 }
-fn convert_path_segment(segment: PathSegment, custom_types: &[CustomType]) -> PathSegment {
+pub fn convert_path_segment(segment: PathSegment, custom_types: &[CustomType]) -> PathSegment {
     let ident = &segment.ident;
     let custom_t = custom_types.iter().find(|t| t.name.eq(ident));
-    if custom_t.is_some() {
+    if let Some(custom_t) = custom_t {
+        if custom_t.kind == CustomTypeKind::Enum {
+            // the variants are `u32` constants, see `enums.rs`
+            return parse_quote!(u32);
+        }
         segment.clone()
     } else {
         match ident.to_string().as_str() {
@@ -40,12 +44,8 @@ fn convert_path_segment(segment: PathSegment, custom_types: &[CustomType]) -> Pa
                     "Standard rust f16s are not \"Pods\", use `PodF16` instead of `f16`. This is because we use `bytemuck` for creating and reading GPU buffers."
                 )
             }
-            "bool" => {
-                abort!(
-                    ident.span(),
-                    "Standard rust bools are not \"Pods\". There are ways we could probably work around this, but currently you should use u32 or i32 instead of bools (0 = false, 1 = true)."
-                )
-            }
+            // the bool fields of transferred types were already changed to u32, see `bools.rs`
+            "bool" => segment.clone(),
             "vec3" => segment.clone(),
             "vec2" => segment.clone(),
             "vec4" => segment.clone(),
@@ -1,32 +1,19 @@
-use std::collections::HashMap;
-
+use bevy_gpu_compute_core::wgsl::ast;
 use proc_macro_error::abort;
-use quote::ToTokens;
-use syn::{ItemType, visit::Visit};
+use syn::ItemType;
 
-pub struct TypeDefToWgslTransformer {
-    pub replacements: HashMap<String, String>,
-}
-
-impl Visit<'_> for TypeDefToWgslTransformer {
-    fn visit_item_type(&mut self, t: &syn::ItemType) {
-        syn::visit::visit_item_type(self, t);
-        // Instead of direct replacement, use placeholder system
-        let new_type_def = type_def_to_wgsl(t);
-        let existing = t.to_token_stream().to_string();
-        // extract everything to the left of the first =
-        let expr = existing.split('=').collect::<Vec<&str>>()[0]
-            .trim()
-            .to_string();
-        self.replacements.insert(expr, new_type_def);
-    }
-}
+use super::{
+    lowering::WgslLowering,
+    source::{doc_comments, source_location},
+};
 
 const UNALLOWED_TYPES_FOR_RENAMING: [&str; 12] = [
     "vec2", "vec3", "vec4", "mat2x2", "mat2x3", "mat2x4", "mat3x2", "mat3x3", "mat3x4", "mat4x2",
     "mat4x3", "mat4x4",
 ]; //
-fn type_def_to_wgsl(type_def: &ItemType) -> String {
+
+/// `type A = T;` => `alias A = T;`
+pub fn type_def_to_wgsl(type_def: &ItemType, lowering: &mut WgslLowering) -> ast::Alias {
     // ensure that the type is not a custom type
     if let syn::Type::Path(p) = *type_def.ty.clone() {
         if let Some(f) = p.path.segments.first() {
@@ -41,6 +28,10 @@ fn type_def_to_wgsl(type_def: &ItemType) -> String {
             }
         }
     }
-    let s = format!("alias {} ", type_def.ident,);
-    s
+    ast::Alias {
+        docs: doc_comments(&type_def.attrs),
+        location: source_location(type_def.type_token.span),
+        name: type_def.ident.to_string(),
+        ty: lowering.lower_type(&type_def.ty),
+    }
 }
@@ -0,0 +1,532 @@
+use std::collections::{HashMap, hash_map::Entry};
+
+use proc_macro_error::abort;
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Expr, FnArg, Ident, ItemMod, Local, Member, Pat, ReturnType, Stmt, Type, parse_quote,
+    spanned::Spanned, visit::Visit, visit_mut::VisitMut,
+};
+
+use crate::pipeline::phases::custom_type_collector::custom_type::{CustomType, CustomTypeKind};
+
+/**
+ WGSL has no tuples, so every distinct tuple type in the module becomes a hidden struct, `(f32, u32)` => `struct Tuple_f32_u32 { _0: f32, _1: u32 }`
+ - `(a, b)` => `Tuple_f32_u32 { _0: a, _1: b }`, when the tuple type is known from the return type of the function, the declared type of the variable or constant, or the parameter type of the called function
+ - `let (d, i) = f(x);` => `let tuple_value_0 = f(x); let d = tuple_value_0._0; let i = tuple_value_0._1;`
+ - `let (a, b) = (x, y);` => `let a = x; let b = y;`, or `let tuple_value_0_0 = x; let tuple_value_0_1 = y; let a = tuple_value_0_0; let b = tuple_value_0_1;` when one of the values uses a variable that is declared by the pattern, like in `let (a, b) = (b, a);`
+ - `t.0` => `t._0`
+
+ Only the module for the GPU is changed, the CPU version of the module keeps the real tuples.
+*/
+pub fn lower_tuples(module: &ItemMod, custom_types: &[CustomType]) -> (ItemMod, Vec<CustomType>) {
+    let mut module = module.clone();
+    let mut tuple_types = TupleTypeTransformer::default();
+    tuple_types.visit_item_mod_mut(&mut module);
+    let mut functions = FunctionParams {
+        tuple_names: &tuple_types.element_types,
+        params: HashMap::new(),
+    };
+    functions.visit_item_mod(&module);
+    let params = functions.params;
+    TupleExprTransformer {
+        tuple_types: &tuple_types.element_types,
+        fn_params: params,
+        return_type: None,
+        var_types: HashMap::new(),
+        hidden_var_count: 0,
+    }
+    .visit_item_mod_mut(&mut module);
+    for name in tuple_types.names.iter() {
+        if custom_types.iter().any(|c| c.name.eq(name)) {
+            abort!(
+                name.span(),
+                "`{}` is the name of the hidden struct of a tuple type, so it cannot be used for a type in the shader module",
+                name
+            );
+        }
+    }
+    let mut custom_types = custom_types.to_vec();
+    custom_types.extend(tuple_types.names.iter().map(|name| {
+        let fields = tuple_types.element_types[&name.to_string()]
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let field = format_ident!("_{}", i);
+                quote!(#field: #t)
+            });
+        CustomType::new(
+            name,
+            CustomTypeKind::GpuOnlyHelperType,
+            quote!(struct #name { #(#fields),* }),
+        )
+    }));
+    (module, custom_types)
+}
+
+/// replaces tuple types with the name of their hidden struct, nested tuples first
+#[derive(Default)]
+struct TupleTypeTransformer {
+    /// in the order they were found, so the output is deterministic
+    names: Vec<Ident>,
+    element_types: HashMap<String, Vec<Type>>,
+}
+
+impl VisitMut for TupleTypeTransformer {
+    fn visit_type_mut(&mut self, t: &mut Type) {
+        syn::visit_mut::visit_type_mut(self, t);
+        if let Type::Tuple(tuple) = t {
+            if tuple.elems.is_empty() {
+                return;
+            }
+            let elements: Vec<Type> = tuple.elems.iter().cloned().collect();
+            let name = tuple_struct_name(&elements);
+            match self.element_types.entry(name.to_string()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(elements);
+                    self.names.push(name.clone());
+                }
+                Entry::Occupied(entry) => {
+                    let same_types = entry.get().len() == elements.len()
+                        && entry.get().iter().zip(elements.iter()).all(|(a, b)| {
+                            a.to_token_stream().to_string() == b.to_token_stream().to_string()
+                        });
+                    if !same_types {
+                        let other = entry.get();
+                        abort!(
+                            tuple.span(),
+                            "The hidden struct of this tuple type would be named `{}`, like the one of the different tuple type `{}`, rename one of the types that they contain",
+                            name,
+                            quote!((#(#other),*))
+                        );
+                    }
+                }
+            }
+            *t = parse_quote!(#name);
+        }
+    }
+}
+
+/// `(f32, [u32; 2])` => `Tuple_f32_u32_2`
+fn tuple_struct_name(elements: &[Type]) -> Ident {
+    let elements: Vec<String> = elements.iter().map(type_name_for_ident).collect();
+    format_ident!("Tuple_{}", elements.join("_"))
+}
+
+/// a type as part of an identifier, `[u32; 2]` => `u32_2`. Different types can have the same name, like `[u32; 2]` and a struct `u32_2`, so the users of the names check for collisions
+pub fn type_name_for_ident(t: &Type) -> String {
+    t.to_token_stream()
+        .to_string()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// the hidden struct of `t`, if it is a tuple type that was already replaced
+fn tuple_struct_of(t: &Type, tuple_types: &HashMap<String, Vec<Type>>) -> Option<Ident> {
+    match t {
+        Type::Path(p) => p
+            .path
+            .get_ident()
+            .filter(|ident| tuple_types.contains_key(&ident.to_string()))
+            .cloned(),
+        Type::Paren(p) => tuple_struct_of(&p.elem, tuple_types),
+        _ => None,
+    }
+}
+
+/// which parameters of each function in the module are tuples
+struct FunctionParams<'a> {
+    tuple_names: &'a HashMap<String, Vec<Type>>,
+    params: HashMap<String, Vec<Option<Ident>>>,
+}
+
+impl Visit<'_> for FunctionParams<'_> {
+    fn visit_item_fn(&mut self, item_fn: &syn::ItemFn) {
+        let params = item_fn
+            .sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Typed(pat_type) => tuple_struct_of(&pat_type.ty, self.tuple_names),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        self.params.insert(item_fn.sig.ident.to_string(), params);
+    }
+}
+
+struct TupleExprTransformer<'a> {
+    tuple_types: &'a HashMap<String, Vec<Type>>,
+    fn_params: HashMap<String, Vec<Option<Ident>>>,
+    /// the hidden struct returned by the current function
+    return_type: Option<Ident>,
+    /// parameters and local variables of the current function that are tuples
+    var_types: HashMap<String, Ident>,
+    hidden_var_count: usize,
+}
+
+impl VisitMut for TupleExprTransformer<'_> {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut syn::ItemFn) {
+        self.return_type = match &item_fn.sig.output {
+            ReturnType::Type(_, t) => tuple_struct_of(t, self.tuple_types),
+            ReturnType::Default => None,
+        };
+        self.var_types.clear();
+        self.hidden_var_count = 0;
+        for input in item_fn.sig.inputs.iter() {
+            let FnArg::Typed(pat_type) = input else {
+                continue;
+            };
+            let Some(tuple_struct) = tuple_struct_of(&pat_type.ty, self.tuple_types) else {
+                continue;
+            };
+            match &*pat_type.pat {
+                Pat::Ident(pat_ident) => {
+                    self.var_types
+                        .insert(pat_ident.ident.to_string(), tuple_struct);
+                }
+                _ => abort!(
+                    pat_type.pat.span(),
+                    "Tuple parameters cannot be destructured in WGSL, destructure them in the function body instead"
+                ),
+            }
+        }
+        if let Some(return_type) = self.return_type.clone() {
+            if let Some(Stmt::Expr(tail, None)) = item_fn.block.stmts.last_mut() {
+                self.construct(tail, &return_type);
+            }
+        }
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+    }
+    fn visit_item_const_mut(&mut self, c: &mut syn::ItemConst) {
+        if let Some(tuple_struct) = tuple_struct_of(&c.ty, self.tuple_types) {
+            self.construct(&mut c.expr, &tuple_struct);
+        }
+        syn::visit_mut::visit_item_const_mut(self, c);
+    }
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        let stmts = std::mem::take(&mut block.stmts);
+        for stmt in stmts {
+            match stmt {
+                Stmt::Local(local) if destructured_tuple(&local.pat).is_some() => {
+                    block.stmts.extend(self.destructure(local));
+                }
+                stmt => block.stmts.push(stmt),
+            }
+        }
+        syn::visit_mut::visit_block_mut(self, block);
+    }
+    fn visit_local_mut(&mut self, local: &mut Local) {
+        if let Pat::Type(pat_type) = &local.pat {
+            if let (Some(tuple_struct), Pat::Ident(pat_ident)) = (
+                tuple_struct_of(&pat_type.ty, self.tuple_types),
+                &*pat_type.pat,
+            ) {
+                self.var_types
+                    .insert(pat_ident.ident.to_string(), tuple_struct.clone());
+                if let Some(init) = &mut local.init {
+                    self.construct(&mut init.expr, &tuple_struct);
+                }
+            }
+        }
+        syn::visit_mut::visit_local_mut(self, local);
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Return(r) => {
+                if let (Some(returned), Some(return_type)) =
+                    (r.expr.as_mut(), self.return_type.clone())
+                {
+                    self.construct(returned, &return_type);
+                }
+            }
+            Expr::Call(call) => {
+                let params = match &*call.func {
+                    Expr::Path(p) => p
+                        .path
+                        .get_ident()
+                        .and_then(|f| self.fn_params.get(&f.to_string()))
+                        .cloned(),
+                    _ => None,
+                };
+                for (arg, param) in call.args.iter_mut().zip(params.unwrap_or_default()) {
+                    if let Some(tuple_struct) = param {
+                        self.construct(arg, &tuple_struct);
+                    }
+                }
+            }
+            Expr::Assign(assign) => {
+                let tuple_struct = match &*assign.left {
+                    Expr::Path(p) => p
+                        .path
+                        .get_ident()
+                        .and_then(|var| self.var_types.get(&var.to_string()))
+                        .cloned(),
+                    _ => None,
+                };
+                if let Some(tuple_struct) = tuple_struct {
+                    self.construct(&mut assign.right, &tuple_struct);
+                }
+            }
+            Expr::Field(field) => {
+                if let Member::Unnamed(index) = &field.member {
+                    field.member = Member::Named(format_ident!("_{}", index.index));
+                }
+            }
+            _ => {}
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Tuple(tuple) = expr {
+            if !tuple.elems.is_empty() {
+                abort!(
+                    tuple.span(),
+                    "The type of this tuple is unknown, in WGSL tuples can only be returned from a function with a tuple return type, assigned to a variable or constant with a declared tuple type, or passed to a function with a tuple parameter"
+                );
+            }
+        }
+    }
+}
+
+/// the elements of `let (a, b)` or `let (a, b): (f32, u32)`
+fn destructured_tuple(pat: &Pat) -> Option<&syn::PatTuple> {
+    match pat {
+        Pat::Tuple(tuple) => Some(tuple),
+        Pat::Type(pat_type) => destructured_tuple(&pat_type.pat),
+        Pat::Paren(paren) => destructured_tuple(&paren.pat),
+        _ => None,
+    }
+}
+
+impl TupleExprTransformer<'_> {
+    /// turns the tuple expressions that `expr` evaluates to into struct literals of `tuple_struct`
+    fn construct(&mut self, expr: &mut Expr, tuple_struct: &Ident) {
+        match expr {
+            Expr::Tuple(tuple) => {
+                let element_types = &self.tuple_types[&tuple_struct.to_string()];
+                if tuple.elems.len() != element_types.len() {
+                    abort!(
+                        tuple.span(),
+                        "Expected a tuple with {} elements",
+                        element_types.len()
+                    );
+                }
+                let fields = tuple
+                    .elems
+                    .iter()
+                    .zip(element_types.clone())
+                    .enumerate()
+                    .map(|(i, (element, element_type))| {
+                        let mut element = element.clone();
+                        if let Some(nested) = tuple_struct_of(&element_type, self.tuple_types) {
+                            self.construct(&mut element, &nested);
+                        }
+                        let field = format_ident!("_{}", i);
+                        quote!(#field: #element)
+                    })
+                    .collect::<Vec<_>>();
+                *expr = parse_quote!(#tuple_struct { #(#fields),* });
+            }
+            Expr::Paren(paren) => self.construct(&mut paren.expr, tuple_struct),
+            Expr::Block(block) => self.construct_block(&mut block.block, tuple_struct),
+            Expr::If(expr_if) => {
+                self.construct_block(&mut expr_if.then_branch, tuple_struct);
+                if let Some((_, else_branch)) = &mut expr_if.else_branch {
+                    self.construct(else_branch, tuple_struct);
+                }
+            }
+            Expr::Match(expr_match) => {
+                for arm in expr_match.arms.iter_mut() {
+                    self.construct(&mut arm.body, tuple_struct);
+                }
+            }
+            _ => {}
+        }
+    }
+    fn construct_block(&mut self, block: &mut syn::Block, tuple_struct: &Ident) {
+        if let Some(Stmt::Expr(tail, None)) = block.stmts.last_mut() {
+            self.construct(tail, tuple_struct);
+        }
+    }
+    fn destructure(&mut self, local: Local) -> Vec<Stmt> {
+        let Some(init) = &local.init else {
+            abort!(
+                local.span(),
+                "A destructured tuple must be initialized where it is declared"
+            );
+        };
+        if let Some((_, diverge)) = &init.diverge {
+            abort!(diverge.span(), "`let ... else` is not supported in WGSL");
+        }
+        let pattern = destructured_tuple(&local.pat).unwrap();
+        let mut names = Vec::new();
+        for element in pattern.elems.iter() {
+            match element {
+                Pat::Ident(pat_ident)
+                    if pat_ident.by_ref.is_none() && pat_ident.subpat.is_none() =>
+                {
+                    names.push(Some(pat_ident.clone()));
+                }
+                Pat::Wild(_) => names.push(None),
+                _ => abort!(
+                    element.span(),
+                    "Only variable names and `_` can be used when destructuring a tuple in WGSL"
+                ),
+            }
+        }
+        let declared_type = match &local.pat {
+            Pat::Type(pat_type) => Some(pat_type.ty.clone()),
+            _ => None,
+        };
+        // without a declared type, a tuple literal is split into one variable per element
+        if let (None, Expr::Tuple(values)) = (&declared_type, strip_parens(&init.expr)) {
+            if values.elems.len() != names.len() {
+                abort!(
+                    values.span(),
+                    "Expected a tuple with {} elements",
+                    names.len()
+                );
+            }
+            let declared: Vec<String> = names
+                .iter()
+                .flatten()
+                .map(|name| name.ident.to_string())
+                .collect();
+            if !values.elems.iter().any(|value| uses_any(value, &declared)) {
+                return names
+                    .into_iter()
+                    .zip(values.elems.iter())
+                    .filter_map(|(name, value)| name.map(|name| parse_quote!(let #name = #value;)))
+                    .collect();
+            }
+            // all values are evaluated before the variables that they use are shadowed
+            let hidden_count = self.hidden_var_count;
+            self.hidden_var_count += 1;
+            let hidden: Vec<Ident> = (0..names.len())
+                .map(|i| format_ident!("tuple_value_{}_{}", hidden_count, i))
+                .collect();
+            let mut stmts: Vec<Stmt> = hidden
+                .iter()
+                .zip(values.elems.iter())
+                .map(|(hidden, value)| parse_quote!(let #hidden = #value;))
+                .collect();
+            for (name, hidden) in names.into_iter().zip(hidden.iter()) {
+                if let Some(name) = name {
+                    stmts.push(parse_quote!(let #name = #hidden;));
+                }
+            }
+            return stmts;
+        }
+        let hidden = format_ident!("tuple_value_{}", self.hidden_var_count);
+        self.hidden_var_count += 1;
+        let value = &init.expr;
+        let mut stmts: Vec<Stmt> = vec![match declared_type {
+            Some(t) => parse_quote!(let #hidden: #t = #value;),
+            None => parse_quote!(let #hidden = #value;),
+        }];
+        for (i, name) in names.into_iter().enumerate() {
+            if let Some(name) = name {
+                let field = format_ident!("_{}", i);
+                stmts.push(parse_quote!(let #name = #hidden.#field;));
+            }
+        }
+        stmts
+    }
+}
+
+/// whether `expr` mentions one of the `names`
+fn uses_any(expr: &Expr, names: &[String]) -> bool {
+    fn tokens_use_any(tokens: proc_macro2::TokenStream, names: &[String]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) => names.contains(&ident.to_string()),
+            proc_macro2::TokenTree::Group(group) => tokens_use_any(group.stream(), names),
+            _ => false,
+        })
+    }
+    tokens_use_any(expr.to_token_stream(), names)
+}
+
+fn strip_parens(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(paren) => strip_parens(&paren.expr),
+        _ => expr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lower(module: ItemMod) -> (String, Vec<String>) {
+        let (module, custom_types) = lower_tuples(&module, &[]);
+        (
+            module.to_token_stream().to_string(),
+            custom_types
+                .iter()
+                .map(|c| c.rust_code.to_string())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_tuple_types_become_structs() {
+        let (module, structs) = lower(parse_quote! {
+            mod m {
+                fn closest(a: Vec2F32) -> (f32, u32) {
+                    if a.x > 0.0 { (a.x, 1) } else { return (a.y, 2); }
+                }
+                fn nested(p: ((f32, u32), [u32; 2])) -> f32 {
+                    p.0.0
+                }
+            }
+        });
+        assert_eq!(
+            structs,
+            [
+                "struct Tuple_f32_u32 { _0 : f32 , _1 : u32 }",
+                "struct Tuple_Tuple_f32_u32_u32_2 { _0 : Tuple_f32_u32 , _1 : [u32 ; 2] }"
+            ]
+        );
+        assert!(module.contains("-> Tuple_f32_u32"));
+        assert!(module.contains("{ Tuple_f32_u32 { _0 : a . x , _1 : 1 } }"));
+        assert!(module.contains("return Tuple_f32_u32 { _0 : a . y , _1 : 2 } ;"));
+        assert!(module.contains("p : Tuple_Tuple_f32_u32_u32_2"));
+        assert!(module.contains("p . _0 . _0"));
+    }
+
+    #[test]
+    fn test_destructuring() {
+        let (module, _) = lower(parse_quote! {
+            mod m {
+                fn main() {
+                    let (d, mut i) = closest(x);
+                    let (_, j): (f32, u32) = (1.0, 2);
+                    let (a, b) = (x, y);
+                }
+            }
+        });
+        assert!(module.contains(
+            "let tuple_value_0 = closest (x) ; let d = tuple_value_0 . _0 ; let mut i = tuple_value_0 . _1 ;"
+        ));
+        assert!(module.contains(
+            "let tuple_value_1 : Tuple_f32_u32 = Tuple_f32_u32 { _0 : 1.0 , _1 : 2 } ; let j = tuple_value_1 . _1 ;"
+        ));
+        assert!(module.contains("let a = x ; let b = y ;"));
+    }
+
+    #[test]
+    fn test_destructuring_swap() {
+        let (module, _) = lower(parse_quote! {
+            mod m {
+                fn main() {
+                    let (x, y) = (y, x + y);
+                }
+            }
+        });
+        assert!(module.contains(
+            "let tuple_value_0_0 = y ; let tuple_value_0_1 = x + y ; let x = tuple_value_0_0 ; let y = tuple_value_0_1 ;"
+        ));
+    }
+}
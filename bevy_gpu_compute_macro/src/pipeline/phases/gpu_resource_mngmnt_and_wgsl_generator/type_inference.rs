@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use syn::{BinOp, Expr, Fields, Item, Lit, Local, Pat, ReturnType, Type, parse_quote};
+
+/// declared types of the module level items, to work out the types of expressions where they have to be known before the shader is generated
+#[derive(Default)]
+pub struct ModuleTypes {
+    pub constants: HashMap<String, Type>,
+    pub struct_fields: HashMap<String, HashMap<String, Type>>,
+    pub fn_returns: HashMap<String, Type>,
+}
+
+impl ModuleTypes {
+    pub fn add(&mut self, item: &Item) {
+        match item {
+            Item::Const(c) => {
+                self.constants.insert(c.ident.to_string(), *c.ty.clone());
+            }
+            Item::Struct(s) => {
+                if let Fields::Named(fields) = &s.fields {
+                    let fields = fields
+                        .named
+                        .iter()
+                        .filter_map(|f| Some((f.ident.as_ref()?.to_string(), f.ty.clone())))
+                        .collect();
+                    self.struct_fields.insert(s.ident.to_string(), fields);
+                }
+            }
+            Item::Fn(f) => {
+                if let ReturnType::Type(_, t) = &f.sig.output {
+                    self.fn_returns.insert(f.sig.ident.to_string(), *t.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// remembers the declared or inferred type of a local variable, or forgets a variable it shadows if the type is not known
+    pub fn add_local(&self, local: &Local, var_types: &mut HashMap<String, Type>) {
+        let (pat, declared) = match &local.pat {
+            Pat::Type(pat_type) => (&*pat_type.pat, Some(*pat_type.ty.clone())),
+            pat => (pat, None),
+        };
+        let Pat::Ident(pat_ident) = pat else {
+            return;
+        };
+        let name = pat_ident.ident.to_string();
+        let t = declared.or_else(|| {
+            local
+                .init
+                .as_ref()
+                .and_then(|init| self.type_of(&init.expr, var_types))
+        });
+        match t {
+            Some(t) => {
+                var_types.insert(name, t);
+            }
+            None => {
+                var_types.remove(&name);
+            }
+        }
+    }
+
+    /// the type of the expression, if it is known
+    pub fn type_of(&self, expr: &Expr, var_types: &HashMap<String, Type>) -> Option<Type> {
+        match expr {
+            Expr::Lit(lit) => match &lit.lit {
+                Lit::Float(f) => match f.suffix() {
+                    "" | "f32" => Some(parse_quote!(f32)),
+                    _ => None,
+                },
+                Lit::Int(i) => match i.suffix() {
+                    "u32" => Some(parse_quote!(u32)),
+                    "" | "i32" => Some(parse_quote!(i32)),
+                    _ => None,
+                },
+                Lit::Bool(_) => Some(parse_quote!(bool)),
+                _ => None,
+            },
+            Expr::Path(path) => {
+                let name = path.path.get_ident()?.to_string();
+                var_types
+                    .get(&name)
+                    .or_else(|| self.constants.get(&name))
+                    .cloned()
+            }
+            Expr::Paren(paren) => self.type_of(&paren.expr, var_types),
+            Expr::Group(group) => self.type_of(&group.expr, var_types),
+            Expr::Cast(cast) => Some(*cast.ty.clone()),
+            Expr::Reference(reference) => {
+                let elem = self.type_of(&reference.expr, var_types)?;
+                Some(match reference.mutability {
+                    Some(_) => parse_quote!(&mut #elem),
+                    None => parse_quote!(&#elem),
+                })
+            }
+            Expr::Unary(unary) => {
+                let inner = self.type_of(&unary.expr, var_types)?;
+                match (&unary.op, inner) {
+                    (syn::UnOp::Deref(_), Type::Reference(r)) => Some(*r.elem),
+                    (syn::UnOp::Deref(_), _) => None,
+                    (_, inner) => Some(inner),
+                }
+            }
+            Expr::Binary(binary) => match binary.op {
+                BinOp::Eq(_)
+                | BinOp::Ne(_)
+                | BinOp::Lt(_)
+                | BinOp::Le(_)
+                | BinOp::Gt(_)
+                | BinOp::Ge(_)
+                | BinOp::And(_)
+                | BinOp::Or(_) => Some(parse_quote!(bool)),
+                _ => {
+                    let left = self.type_of(&binary.left, var_types);
+                    let right = self.type_of(&binary.right, var_types);
+                    // `2.0 * v` is a vector
+                    match (left, right) {
+                        (Some(left), Some(right)) if is_scalar(&left) && !is_scalar(&right) => {
+                            Some(right)
+                        }
+                        (Some(left), _) => Some(left),
+                        (None, right) => right,
+                    }
+                }
+            },
+            Expr::Struct(s) => {
+                let ident = &s.path.segments.last()?.ident;
+                Some(parse_quote!(#ident))
+            }
+            Expr::Call(call) => {
+                let Expr::Path(path) = &*call.func else {
+                    return None;
+                };
+                match path.path.segments.len() {
+                    1 => self
+                        .fn_returns
+                        .get(&path.path.segments[0].ident.to_string())
+                        .cloned(),
+                    // constructors, like `Vec3F32::new(...)`
+                    2 if path.path.segments[1].ident == "new" => {
+                        let ident = &path.path.segments[0].ident;
+                        Some(parse_quote!(#ident))
+                    }
+                    _ => None,
+                }
+            }
+            Expr::Field(field) => {
+                let syn::Member::Named(member) = &field.member else {
+                    return None;
+                };
+                let Type::Path(base) = self.type_of(&field.base, var_types)? else {
+                    return None;
+                };
+                let base = base.path.get_ident()?.to_string();
+                match self.struct_fields.get(&base) {
+                    Some(fields) => fields.get(&member.to_string()).cloned(),
+                    None => vector_component_type(&base),
+                }
+            }
+            Expr::Index(index) => match self.type_of(&index.expr, var_types)? {
+                Type::Array(array) => Some(*array.elem),
+                Type::Path(p) => vector_component_type(&p.path.get_ident()?.to_string()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+fn is_scalar(t: &Type) -> bool {
+    matches!(t, Type::Path(p) if p.path.get_ident().is_some_and(|i| ["f32", "i32", "u32", "PodF16", "bool"].contains(&i.to_string().as_str())))
+}
+
+/// `Vec3F32` => `f32`, the type of `v.x` or `v[0]`
+fn vector_component_type(vector: &str) -> Option<Type> {
+    let component = vector.strip_prefix("Vec")?.strip_prefix(['2', '3', '4'])?;
+    match component {
+        "F32" => Some(parse_quote!(f32)),
+        "I32" => Some(parse_quote!(i32)),
+        "U32" => Some(parse_quote!(u32)),
+        "F16" => Some(parse_quote!(PodF16)),
+        "Bool" => Some(parse_quote!(bool)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    #[test]
+    fn test_vector_component_type() {
+        let component =
+            |vector: &str| vector_component_type(vector).map(|t| t.to_token_stream().to_string());
+        assert_eq!(component("Vec3F32").as_deref(), Some("f32"));
+        assert_eq!(component("Vec2Bool").as_deref(), Some("bool"));
+        assert_eq!(component("Vec"), None);
+        assert_eq!(component("Vec3"), None);
+        assert_eq!(component("Vecé32"), None);
+        assert_eq!(component("Vec5F32"), None);
+    }
+}
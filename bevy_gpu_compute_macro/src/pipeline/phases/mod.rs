@@ -6,3 +6,4 @@ pub mod module_for_rust_usage_cleaner;
 pub mod non_mutating_tree_validation;
 pub mod typesafe_buffer_builders_generator;
 pub mod wgsl_helper_transformer;
+pub mod wgsl_validation;
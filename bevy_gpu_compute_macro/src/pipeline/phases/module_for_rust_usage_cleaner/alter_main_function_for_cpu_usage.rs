@@ -18,8 +18,14 @@ struct MainFunctionMutator<'a> {
 impl VisitMut for MainFunctionMutator<'_> {
     fn visit_item_fn_mut(&mut self, c: &mut syn::ItemFn) {
         syn::visit_mut::visit_item_fn_mut(self, c);
+        // every entry point gets the buffers as parameters, to be callable on the CPU
         let name = c.sig.ident.to_string();
-        if name != "main" {
+        if !self
+            .wgsl_shader_module_parsed
+            .entry_points
+            .iter()
+            .any(|e| e.name() == name)
+        {
             return;
         }
         alter_main_function_for_cpu_usage(self.wgsl_shader_module_parsed, c);
@@ -1,7 +1,14 @@
-use crate::pipeline::{compilation_unit::CompilationUnit, phases::compiler_phase::CompilerPhase};
+use crate::pipeline::{
+    compilation_unit::CompilationUnit,
+    phases::{
+        compiler_phase::CompilerPhase,
+        gpu_resource_mngmnt_and_wgsl_generator::bools::{BoolStorage, store_transferred_bools},
+    },
+};
 
 use super::{
     alter_main_function_for_cpu_usage::mutate_main_function_for_cpu_usage,
+    make_array_indices_usize::make_array_indices_usize, make_enums_pod::make_enums_pod,
     make_types_pod::make_types_pod, make_types_public::make_types_public,
     remove_internal_attributes::remove_internal_attributes,
 };
@@ -14,6 +21,9 @@ impl CompilerPhase for ModuleForRustUsageCleaner {
         let mut m = input.rust_module_for_cpu().clone();
         mutate_main_function_for_cpu_usage(input.wgsl_module_user_portion(), &mut m);
         remove_internal_attributes(&mut m);
+        make_array_indices_usize(&mut m);
+        make_enums_pod(&mut m);
+        store_transferred_bools(&mut m, input.custom_types(), BoolStorage::WgslBool);
         make_types_pod(&mut m);
         make_types_public(&mut m);
         input.set_rust_module_for_cpu(m.clone());
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use syn::{
+    Expr, Fields, FnArg, Item, Lit, Member, Pat, Type, parse_quote, visit::Visit,
+    visit_mut::VisitMut,
+};
+
+/**
+WGSL arrays can be indexed by any `u32` or `i32`, but rust arrays can only be indexed by `usize`, so `arr[i]` becomes `arr[(i) as usize]`.
+
+Only expressions that are known to be arrays are changed, because the vector and matrix types are indexed by `i32`: function parameters, annotated local variables, local variables initialized with an array literal, constants, and fields or elements of any of those. Literal indices are left alone since rust infers their type.
+
+The behavior of out of bounds indices differs between the two: a negative `i32` index wraps around to a huge `usize`, so it panics on the CPU like any other out of bounds index, while on the GPU out of bounds accesses are clamped to the array, so they read or write some element of it instead. Code that has to give the same results on both should keep its indices in bounds.
+*/
+pub fn make_array_indices_usize(input: &mut syn::ItemMod) {
+    let mut module_types = ModuleTypes::default();
+    module_types.visit_item_mod(input);
+    let mut transformer = ArrayIndexTransformer {
+        module_types,
+        var_types: HashMap::new(),
+    };
+    transformer.visit_item_mod_mut(input);
+}
+
+/// the declared types of the module level items
+#[derive(Default)]
+struct ModuleTypes {
+    constants: HashMap<String, Type>,
+    aliases: HashMap<String, Type>,
+    struct_fields: HashMap<String, HashMap<String, Type>>,
+}
+
+impl Visit<'_> for ModuleTypes {
+    fn visit_item(&mut self, item: &Item) {
+        match item {
+            Item::Const(c) => {
+                self.constants.insert(c.ident.to_string(), *c.ty.clone());
+            }
+            Item::Static(s) => {
+                self.constants.insert(s.ident.to_string(), *s.ty.clone());
+            }
+            Item::Type(t) => {
+                self.aliases.insert(t.ident.to_string(), *t.ty.clone());
+            }
+            Item::Struct(s) => {
+                if let Fields::Named(fields) = &s.fields {
+                    let fields = fields
+                        .named
+                        .iter()
+                        .filter_map(|f| Some((f.ident.as_ref()?.to_string(), f.ty.clone())))
+                        .collect();
+                    self.struct_fields.insert(s.ident.to_string(), fields);
+                }
+            }
+            _ => {}
+        }
+        syn::visit::visit_item(self, item);
+    }
+}
+
+struct ArrayIndexTransformer {
+    module_types: ModuleTypes,
+    /// types of the parameters and local variables of the current function
+    var_types: HashMap<String, Type>,
+}
+
+impl VisitMut for ArrayIndexTransformer {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut syn::ItemFn) {
+        self.var_types.clear();
+        for input in item_fn.sig.inputs.iter() {
+            if let FnArg::Typed(pat_type) = input {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    self.var_types
+                        .insert(pat_ident.ident.to_string(), *pat_type.ty.clone());
+                }
+            }
+        }
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+    }
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        syn::visit_mut::visit_local_mut(self, local);
+        match &local.pat {
+            Pat::Type(pat_type) => {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    self.var_types
+                        .insert(pat_ident.ident.to_string(), *pat_type.ty.clone());
+                }
+            }
+            Pat::Ident(pat_ident) => {
+                let name = pat_ident.ident.to_string();
+                match local.init.as_ref().map(|init| &*init.expr) {
+                    Some(Expr::Array(_) | Expr::Repeat(_)) => {
+                        // only the fact that it is an array matters, not the element type
+                        self.var_types.insert(name, parse_quote!([(); 0]));
+                    }
+                    _ => {
+                        // shadowing a variable that was an array
+                        self.var_types.remove(&name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Index(index) = expr {
+            let is_literal = matches!(&*index.index, Expr::Lit(l) if matches!(l.lit, Lit::Int(_)));
+            if !is_literal && self.type_of(&index.expr).is_some_and(|t| self.is_array(&t)) {
+                let i = &index.index;
+                *index.index = parse_quote!((#i) as usize);
+            }
+        }
+    }
+}
+
+impl ArrayIndexTransformer {
+    fn type_of(&self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Path(path) => {
+                let name = path.path.get_ident()?.to_string();
+                self.var_types
+                    .get(&name)
+                    .or_else(|| self.module_types.constants.get(&name))
+                    .cloned()
+            }
+            Expr::Paren(paren) => self.type_of(&paren.expr),
+            Expr::Field(field) => {
+                let Member::Named(member) = &field.member else {
+                    return None;
+                };
+                let Type::Path(struct_type) = self.resolve_alias(self.type_of(&field.base)?) else {
+                    return None;
+                };
+                let struct_name = struct_type.path.segments.last()?.ident.to_string();
+                self.module_types
+                    .struct_fields
+                    .get(&struct_name)?
+                    .get(&member.to_string())
+                    .cloned()
+            }
+            Expr::Index(index) => match self.resolve_alias(self.type_of(&index.expr)?) {
+                Type::Array(array) => Some(*array.elem),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    fn resolve_alias(&self, t: Type) -> Type {
+        match &t {
+            Type::Path(p) => p
+                .path
+                .get_ident()
+                .and_then(|ident| self.module_types.aliases.get(&ident.to_string()))
+                .map(|aliased| self.resolve_alias(aliased.clone()))
+                .unwrap_or(t),
+            Type::Paren(p) => self.resolve_alias(*p.elem.clone()),
+            _ => t,
+        }
+    }
+    fn is_array(&self, t: &Type) -> bool {
+        matches!(self.resolve_alias(t.clone()), Type::Array(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    #[test]
+    fn test_only_array_indices_are_cast() {
+        let mut module: syn::ItemMod = parse_quote! {
+            mod m {
+                const LUT: [f32; 4] = [0.1, 0.2, 0.3, 0.4];
+                type Row = [u32; 2];
+                struct Particle { hist: [Row; 8], v: Vec2F32 }
+                fn f(p: Particle, i: u32) {
+                    let acc = [0u32; 4];
+                    let a = LUT[i] + p.v[i as i32] + p.hist[i][1] + acc[i + 1] + acc[0];
+                }
+            }
+        };
+        make_array_indices_usize(&mut module);
+        let output = module.to_token_stream().to_string();
+        assert!(output.contains("LUT [(i) as usize]"));
+        assert!(output.contains("p . v [i as i32]"));
+        assert!(output.contains("p . hist [(i) as usize] [1]"));
+        assert!(output.contains("acc [(i + 1) as usize] + acc [0]"));
+    }
+}
@@ -0,0 +1,214 @@
+use proc_macro_error::abort;
+use proc_macro2::Literal;
+use quote::{format_ident, quote};
+use syn::{
+    Expr, Item, ItemEnum, Lit, Path, Token, parse_quote, punctuated::Punctuated, spanned::Spanned,
+};
+
+/**
+Enums are not "Pods", since not every u32 is a valid variant, so they could not be used in the input and output types. Like on the GPU, they become a `u32` instead, with a constant per variant:
+```ignore
+#[derive(PartialEq, Eq)]
+pub struct Shape(pub u32);
+impl Shape {
+    pub const Circle: Shape = Shape(0);
+    pub const Square: Shape = Shape(5);
+}
+```
+`Shape::Circle`, `==` and `match` keep working, `make_types_pod` then adds the bytemuck derives. Since any u32 is a valid value, matches need a `_` arm.
+
+The derives of the enum that also work on the struct are kept, `Hash`, `PartialOrd` and `Ord` compare the values of the variants like they do for the enum, and `Default` becomes an `impl Default` that returns the `#[default]` variant. Other derives are rejected.
+
+Discriminants were already validated when the WGSL constants were generated.
+
+The unaltered copy of the module keeps the enums, `add_struct_derives_to_enums` gives them the derives the struct always has, so that `==` compiles there without the user deriving `PartialEq`.
+*/
+pub fn make_enums_pod(input: &mut syn::ItemMod) {
+    let Some((_, items)) = &mut input.content else {
+        return;
+    };
+    *items = std::mem::take(items)
+        .into_iter()
+        .flat_map(|item| match item {
+            Item::Enum(item_enum) => enum_to_struct(item_enum),
+            item => vec![item],
+        })
+        .collect();
+}
+
+/// the derives every struct made from an enum gets, regardless of what the enum derived
+const STRUCT_DERIVES: [&str; 5] = ["Copy", "Clone", "Debug", "PartialEq", "Eq"];
+
+pub fn add_struct_derives_to_enums(input: &mut syn::ItemMod) {
+    let Some((_, items)) = &mut input.content else {
+        return;
+    };
+    for item in items.iter_mut() {
+        let Item::Enum(item_enum) = item else {
+            continue;
+        };
+        let derived: Vec<String> = item_enum
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("derive"))
+            .filter_map(|attr| {
+                attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                    .ok()
+            })
+            .flatten()
+            .filter_map(|path| path.segments.last().map(|s| s.ident.to_string()))
+            .collect();
+        let missing = STRUCT_DERIVES
+            .iter()
+            .filter(|derive| !derived.iter().any(|d| d == *derive))
+            .map(|derive| format_ident!("{}", derive))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            item_enum.attrs.push(parse_quote!(#[derive(#(#missing),*)]));
+        }
+    }
+}
+
+fn enum_to_struct(item_enum: ItemEnum) -> Vec<Item> {
+    let name = &item_enum.ident;
+    let vis = &item_enum.vis;
+    let attrs = item_enum
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("derive") && !attr.path().is_ident("repr"));
+    let mut derives = Vec::new();
+    let mut derives_default = false;
+    for attr in item_enum
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+    {
+        let paths = attr
+            .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+            .unwrap_or_else(|e| abort!(attr.span(), "Invalid derive: {}", e));
+        for path in paths {
+            let derive = path.segments.last().map(|s| s.ident.to_string());
+            match derive.as_deref() {
+                Some(derive) if STRUCT_DERIVES.contains(&derive) => {}
+                Some("Hash" | "PartialOrd" | "Ord") => derives.push(path),
+                Some("Default") => derives_default = true,
+                _ => abort!(
+                    path.span(),
+                    "`{}` cannot be derived for the enum `{}`, since on the CPU enums become a struct holding a `u32`. Only `Clone`, `Copy`, `Debug`, `PartialEq`, `Eq`, `Hash`, `PartialOrd`, `Ord` and `Default` are supported",
+                    path.segments
+                        .iter()
+                        .map(|s| s.ident.to_string())
+                        .collect::<Vec<_>>()
+                        .join("::"),
+                    name
+                ),
+            }
+        }
+    }
+    let default_impl = derives_default.then(|| {
+        let Some(default_variant) = item_enum.variants.iter().find(|variant| {
+            variant
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("default"))
+        }) else {
+            abort!(
+                name.span(),
+                "Mark the default variant of `{}` with `#[default]`",
+                name
+            );
+        };
+        let variant_name = &default_variant.ident;
+        let default_impl: Item = parse_quote! {
+            impl Default for #name {
+                fn default() -> Self {
+                    #name::#variant_name
+                }
+            }
+        };
+        default_impl
+    });
+    let mut next_value: u32 = 0;
+    let variants = item_enum.variants.iter().map(|variant| {
+        if let Some((
+            _,
+            Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            }),
+        )) = &variant.discriminant
+        {
+            next_value = int.base10_parse().unwrap_or_default();
+        }
+        let value = Literal::u32_suffixed(next_value);
+        next_value = next_value.wrapping_add(1);
+        let variant_name = format_ident!("{}", variant.ident);
+        quote!(pub const #variant_name: #name = #name(#value);)
+    });
+    let mut items: Vec<Item> = vec![
+        parse_quote! {
+            #(#attrs)*
+            #[derive(PartialEq, Eq #(, #derives)*)]
+            #vis struct #name(pub u32);
+        },
+        parse_quote! {
+            #[allow(non_upper_case_globals)]
+            impl #name {
+                #(#variants)*
+            }
+        },
+    ];
+    items.extend(default_impl);
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    #[test]
+    fn test_enums_become_u32_structs() {
+        let mut module: syn::ItemMod = parse_quote! {
+            mod m {
+                #[derive(Clone, Copy, PartialEq)]
+                enum Shape { Circle, Square = 5, Triangle }
+            }
+        };
+        make_enums_pod(&mut module);
+        assert_eq!(
+            module.to_token_stream().to_string(),
+            "mod m { # [derive (PartialEq , Eq)] struct Shape (pub u32) ; # [allow (non_upper_case_globals)] impl Shape { pub const Circle : Shape = Shape (0u32) ; pub const Square : Shape = Shape (5u32) ; pub const Triangle : Shape = Shape (6u32) ; } }"
+        );
+    }
+
+    #[test]
+    fn test_missing_struct_derives_are_added_to_enums() {
+        let mut module: syn::ItemMod = parse_quote! {
+            mod m {
+                #[derive(Clone, PartialEq)]
+                enum Shape { Circle, Square }
+                enum Color { Red }
+            }
+        };
+        add_struct_derives_to_enums(&mut module);
+        assert_eq!(
+            module.to_token_stream().to_string(),
+            "mod m { # [derive (Clone , PartialEq)] # [derive (Copy , Debug , Eq)] enum Shape { Circle , Square } # [derive (Copy , Clone , Debug , PartialEq , Eq)] enum Color { Red } }"
+        );
+    }
+
+    #[test]
+    fn test_derives_are_carried_over() {
+        let mut module: syn::ItemMod = parse_quote! {
+            mod m {
+                #[derive(Clone, Copy, Debug, Hash, PartialOrd, Default)]
+                enum Shape { Circle, #[default] Square }
+            }
+        };
+        make_enums_pod(&mut module);
+        assert_eq!(
+            module.to_token_stream().to_string(),
+            "mod m { # [derive (PartialEq , Eq , Hash , PartialOrd)] struct Shape (pub u32) ; # [allow (non_upper_case_globals)] impl Shape { pub const Circle : Shape = Shape (0u32) ; pub const Square : Shape = Shape (1u32) ; } impl Default for Shape { fn default () -> Self { Shape :: Square } } }"
+        );
+    }
+}
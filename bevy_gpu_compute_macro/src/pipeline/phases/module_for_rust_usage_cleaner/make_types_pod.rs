@@ -1,4 +1,4 @@
-use syn::{ItemStruct, parse_quote, visit_mut::VisitMut};
+use syn::{ItemStruct, parse_quote, visit::Visit, visit_mut::VisitMut};
 
 pub fn make_types_pod(input: &mut syn::ItemMod) {
     let mut transformer = MakeTypesPodTransformer;
@@ -9,16 +9,38 @@ pub fn make_types_pod(input: &mut syn::ItemMod) {
 Add the following as attributes:
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+
+Types that are only used on the GPU can contain bools, so they only get `Copy, Clone, Debug`. The bools of the input, output and config types were already replaced, see `bools.rs`
 */
 struct MakeTypesPodTransformer;
 
 impl VisitMut for MakeTypesPodTransformer {
     fn visit_item_struct_mut(&mut self, i: &mut ItemStruct) {
         syn::visit_mut::visit_item_struct_mut(self, i);
-        i.attrs.push(parse_quote! {
-            #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-        });
+        let mut bools = ContainsBools(false);
+        bools.visit_fields(&i.fields);
+        if bools.0 {
+            i.attrs.push(parse_quote! {
+                #[derive(Copy, Clone, Debug)]
+            });
+        } else {
+            i.attrs.push(parse_quote! {
+                #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+            });
+        }
         i.attrs.push(parse_quote! {
         #[repr(C)]});
     }
 }
+
+/// `bool` and the `Vec3Bool` like types are not "Pods"
+struct ContainsBools(bool);
+
+impl<'ast> Visit<'ast> for ContainsBools {
+    fn visit_ident(&mut self, ident: &'ast proc_macro2::Ident) {
+        let name = ident.to_string();
+        if name == "bool" || (name.ends_with("Bool") && name != "WgslBool") {
+            self.0 = true;
+        }
+    }
+}
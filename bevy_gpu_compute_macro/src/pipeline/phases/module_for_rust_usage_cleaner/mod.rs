@@ -1,5 +1,7 @@
 mod alter_main_function_for_cpu_usage;
 pub mod compiler_phase;
+pub mod make_array_indices_usize;
+pub mod make_enums_pod;
 mod make_types_pod;
 mod make_types_public;
 mod remove_internal_attributes;
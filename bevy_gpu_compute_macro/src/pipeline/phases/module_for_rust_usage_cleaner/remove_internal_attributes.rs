@@ -1,6 +1,7 @@
 use syn::visit_mut::VisitMut;
-const INTERNAL_ATTRIBUTE_NAMES: [&str; 4] = [
+const INTERNAL_ATTRIBUTE_NAMES: [&str; 5] = [
     "wgsl_config",
+    "wgsl_entry",
     "wgsl_input_array",
     "wgsl_output_array",
     "wgsl_output_vec",
@@ -65,6 +66,13 @@ impl VisitMut for InternalAttributesRemover {
                         .any(|name| attr.path().is_ident(name))
                 });
             }
+            syn::Item::Use(item_use) => {
+                item_use.attrs.retain(|attr| {
+                    !INTERNAL_ATTRIBUTE_NAMES
+                        .iter()
+                        .any(|name| attr.path().is_ident(name))
+                });
+            }
             syn::Item::Mod(item_mod) => {
                 item_mod.attrs.retain(|attr| {
                     !INTERNAL_ATTRIBUTE_NAMES
@@ -1,7 +1,14 @@
-use crate::pipeline::{compilation_unit::CompilationUnit, phases::compiler_phase::CompilerPhase};
+use crate::pipeline::{
+    compilation_unit::{CompilationUnit, ModuleKind},
+    phases::compiler_phase::CompilerPhase,
+};
 
-use super::validate_no_doc_comments::validate_no_doc_comments;
+use super::validate_library_contents::validate_library_contents;
+use super::validate_match_scrutinees::validate_match_scrutinees;
+use super::validate_method_calls::validate_method_calls;
+use super::validate_mutable_references::validate_mutable_references;
 use super::validate_no_iter_pos_assignments::validate_no_iter_pos_assignments;
+use super::validate_raw_functions::validate_raw_functions;
 use super::validate_use_statements::validate_use_statements;
 
 /// any sort of input validation that can be done on the original tree that doesn't require mutation
@@ -9,8 +16,15 @@ pub struct NonMutatingTreeValidation;
 
 impl CompilerPhase for NonMutatingTreeValidation {
     fn execute(&self, input: &mut CompilationUnit) {
-        validate_no_doc_comments(input.original_rust_module());
         validate_no_iter_pos_assignments(input.original_rust_module());
         validate_use_statements(input.original_rust_module());
+        validate_match_scrutinees(input.original_rust_module());
+        // the methods of imported library types can be called too
+        validate_method_calls(&input.original_rust_module_with_libraries());
+        validate_mutable_references(input.original_rust_module());
+        validate_raw_functions(input.original_rust_module());
+        if input.kind() == ModuleKind::Library {
+            validate_library_contents(input.original_rust_module());
+        }
     }
 }
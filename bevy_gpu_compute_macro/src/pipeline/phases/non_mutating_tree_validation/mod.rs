@@ -1,4 +1,8 @@
 pub mod compiler_phase;
-mod validate_no_doc_comments;
+mod validate_library_contents;
+mod validate_match_scrutinees;
+mod validate_method_calls;
+mod validate_mutable_references;
 mod validate_no_iter_pos_assignments;
+mod validate_raw_functions;
 mod validate_use_statements;
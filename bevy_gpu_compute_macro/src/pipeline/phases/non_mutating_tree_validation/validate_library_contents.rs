@@ -0,0 +1,43 @@
+use proc_macro_error::abort;
+use syn::{Attribute, Item, ItemMod};
+
+use crate::pipeline::entry_points::is_entry_point;
+
+const BUFFER_TYPE_ATTRIBUTES: [&str; 4] = [
+    "wgsl_config",
+    "wgsl_input_array",
+    "wgsl_output_array",
+    "wgsl_output_vec",
+];
+
+/// a `#[wgsl_library]` can only contain what is shared between shader modules, the entry points and the buffers belong to a shader module
+pub fn validate_library_contents(original_rust_module: &ItemMod) {
+    let Some((_, items)) = &original_rust_module.content else {
+        return;
+    };
+    for item in items {
+        match item {
+            Item::Fn(func) if is_entry_point(func) => abort!(
+                func.sig.ident,
+                "A `#[wgsl_library]` cannot have a main function or `#[wgsl_entry]` functions, put them in the `#[wgsl_shader_module]` that imports the library"
+            ),
+            Item::Struct(s) => validate_no_buffer_type_attribute(&s.attrs),
+            Item::Type(t) => validate_no_buffer_type_attribute(&t.attrs),
+            Item::Use(u) => validate_no_buffer_type_attribute(&u.attrs),
+            _ => {}
+        }
+    }
+}
+
+fn validate_no_buffer_type_attribute(attrs: &[Attribute]) {
+    if let Some(attr) = attrs.iter().find(|attr| {
+        BUFFER_TYPE_ATTRIBUTES
+            .iter()
+            .any(|name| attr.path().is_ident(name))
+    }) {
+        abort!(
+            attr,
+            "Input, output and config types cannot be declared in a `#[wgsl_library]`, only helper types. Declare them in the `#[wgsl_shader_module]` that imports the library"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro_error::abort;
+use syn::{Expr, ExprMatch, FnArg, Item, Lit, Pat, Type, spanned::Spanned, visit::Visit};
+
+/// matches become WGSL `switch` statements, which only work on u32 and i32 values, or enums since they become u32 constants. This is checked here, on the original tree, so that the error points at the offending code
+pub fn validate_match_scrutinees(original_rust_module: &syn::ItemMod) {
+    let enums = original_rust_module
+        .content
+        .iter()
+        .flat_map(|(_, items)| items)
+        .filter_map(|item| match item {
+            Item::Enum(item_enum) => Some(item_enum.ident.to_string()),
+            _ => None,
+        })
+        .collect();
+    let mut checker = MatchScrutineeChecker {
+        var_types: HashMap::new(),
+        enums,
+    };
+    checker.visit_item_mod(original_rust_module);
+}
+
+struct MatchScrutineeChecker {
+    /// declared types of the parameters and annotated local variables of the current function
+    var_types: HashMap<String, Type>,
+    /// names of the enums in the module
+    enums: HashSet<String>,
+}
+
+impl<'ast> Visit<'ast> for MatchScrutineeChecker {
+    fn visit_item_fn(&mut self, item_fn: &'ast syn::ItemFn) {
+        self.var_types.clear();
+        for input in item_fn.sig.inputs.iter() {
+            if let FnArg::Typed(pat_type) = input {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    self.var_types
+                        .insert(pat_ident.ident.to_string(), *pat_type.ty.clone());
+                }
+            }
+        }
+        syn::visit::visit_item_fn(self, item_fn);
+    }
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        syn::visit::visit_local(self, local);
+        if let Pat::Type(pat_type) = &local.pat {
+            if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                self.var_types
+                    .insert(pat_ident.ident.to_string(), *pat_type.ty.clone());
+            }
+        }
+    }
+    fn visit_expr_match(&mut self, match_expr: &'ast ExprMatch) {
+        syn::visit::visit_expr_match(self, match_expr);
+        let has_non_integer_pattern = match_expr
+            .arms
+            .iter()
+            .any(|arm| has_non_integer_literal(&arm.pat));
+        if has_non_integer_pattern || !self.is_integer(&match_expr.expr) {
+            abort!(
+                match_expr.expr.span(),
+                "Match expressions in WGSL can only match on u32 or i32 values"
+            );
+        }
+    }
+}
+
+impl MatchScrutineeChecker {
+    /// true unless the expression is known to not be a u32 or i32
+    fn is_integer(&self, expr: &Expr) -> bool {
+        let declared_type = match expr {
+            Expr::Lit(lit) => return matches!(lit.lit, Lit::Int(_)),
+            Expr::Paren(paren) => return self.is_integer(&paren.expr),
+            Expr::Group(group) => return self.is_integer(&group.expr),
+            Expr::Cast(cast) => Some(&*cast.ty),
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .and_then(|ident| self.var_types.get(&ident.to_string())),
+            _ => None,
+        };
+        match declared_type {
+            Some(Type::Path(p)) => {
+                p.path.is_ident("u32")
+                    || p.path.is_ident("i32")
+                    || p.path
+                        .get_ident()
+                        .is_some_and(|ident| self.enums.contains(&ident.to_string()))
+            }
+            Some(_) => false,
+            None => true,
+        }
+    }
+}
+
+fn has_non_integer_literal(pat: &Pat) -> bool {
+    match pat {
+        Pat::Lit(lit) => !matches!(lit.lit, Lit::Int(_)),
+        Pat::Or(or) => or.cases.iter().any(has_non_integer_literal),
+        Pat::Paren(paren) => has_non_integer_literal(&paren.pat),
+        _ => false,
+    }
+}
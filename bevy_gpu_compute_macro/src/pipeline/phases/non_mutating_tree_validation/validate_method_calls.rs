@@ -0,0 +1,178 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro_error::abort;
+use syn::{Expr, ExprMethodCall, FnArg, ImplItem, Item, Pat, Signature, Type, visit::Visit};
+
+use crate::pipeline::{
+    allowed_types::{SCALAR_FLOAT_METHODS_DIFFERENT_FROM_WGSL, WGSL_BUILTIN_METHODS, is_swizzle},
+    phases::gpu_resource_mngmnt_and_wgsl_generator::type_inference::ModuleTypes,
+};
+
+/// method calls are turned into calls to WGSL builtins, so only the methods that have a builtin can be used. This is checked here, on the original tree, so that the error points at the offending code
+pub fn validate_method_calls(original_rust_module: &syn::ItemMod) {
+    let items = original_rust_module
+        .content
+        .iter()
+        .flat_map(|(_, items)| items);
+    let mut module_types = ModuleTypes::default();
+    // methods of the module's own types become free functions, see `impl_blocks.rs`
+    let mut module_methods: HashMap<String, HashSet<String>> = HashMap::new();
+    for item in items {
+        module_types.add(item);
+        if let Item::Impl(item_impl) = item {
+            if item_impl.trait_.is_some() {
+                continue;
+            }
+            let Type::Path(self_type) = &*item_impl.self_ty else {
+                continue;
+            };
+            let Some(type_name) = self_type.path.get_ident() else {
+                continue;
+            };
+            module_methods
+                .entry(type_name.to_string())
+                .or_default()
+                .extend(
+                    item_impl
+                        .items
+                        .iter()
+                        .filter_map(|impl_item| match impl_item {
+                            ImplItem::Fn(method) => Some(method.sig.ident.to_string()),
+                            _ => None,
+                        }),
+                );
+        }
+    }
+    let mut checker = MethodCallChecker {
+        module_types,
+        var_types: HashMap::new(),
+        module_methods,
+        self_type: None,
+    };
+    checker.visit_item_mod(original_rust_module);
+}
+
+struct MethodCallChecker {
+    module_types: ModuleTypes,
+    /// declared or inferred types of the parameters and local variables of the current function
+    var_types: HashMap<String, Type>,
+    /// names of the methods in the `impl` blocks of the module, by the name of their type
+    module_methods: HashMap<String, HashSet<String>>,
+    /// the type of the `impl` block being visited
+    self_type: Option<Type>,
+}
+
+impl<'ast> Visit<'ast> for MethodCallChecker {
+    fn visit_item_fn(&mut self, item_fn: &'ast syn::ItemFn) {
+        self.add_params(&item_fn.sig);
+        syn::visit::visit_item_fn(self, item_fn);
+    }
+    fn visit_item_impl(&mut self, item_impl: &'ast syn::ItemImpl) {
+        self.self_type = Some(*item_impl.self_ty.clone());
+        syn::visit::visit_item_impl(self, item_impl);
+        self.self_type = None;
+    }
+    fn visit_impl_item_fn(&mut self, method: &'ast syn::ImplItemFn) {
+        self.add_params(&method.sig);
+        syn::visit::visit_impl_item_fn(self, method);
+    }
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        syn::visit::visit_local(self, local);
+        self.module_types.add_local(local, &mut self.var_types);
+    }
+    fn visit_expr_for_loop(&mut self, for_loop: &'ast syn::ExprForLoop) {
+        self.visit_for_loop_iterable(&for_loop.expr);
+        self.visit_block(&for_loop.body);
+    }
+    fn visit_expr_method_call(&mut self, method_call: &'ast ExprMethodCall) {
+        syn::visit::visit_expr_method_call(self, method_call);
+        let method = method_call.method.to_string();
+        let receiver_type = self
+            .module_types
+            .type_of(&method_call.receiver, &self.var_types);
+        let receiver_type_name = match &receiver_type {
+            Some(Type::Path(p)) => p.path.get_ident().map(|i| i.to_string()),
+            _ => None,
+        };
+        let is_module_method = match &receiver_type_name {
+            Some(type_name) => self
+                .module_methods
+                .get(type_name)
+                .is_some_and(|methods| methods.contains(&method)),
+            // which method is called is worked out, or reported as ambiguous, when the impl blocks are lowered
+            None => self
+                .module_methods
+                .values()
+                .any(|methods| methods.contains(&method)),
+        };
+        if is_module_method {
+            return;
+        }
+        if is_swizzle(&method, 2, false)
+            || method
+                .strip_prefix("set_")
+                .is_some_and(|components| is_swizzle(components, 1, true))
+        {
+            return;
+        }
+        if !WGSL_BUILTIN_METHODS
+            .iter()
+            .any(|(name, _)| *name == method.as_str())
+        {
+            let supported: Vec<&str> = WGSL_BUILTIN_METHODS.iter().map(|(name, _)| *name).collect();
+            abort!(
+                method_call.method.span(),
+                "Unknown method `{}`, only swizzles like `v.xy()` or `v.set_xy(...)` and methods that have a WGSL builtin equivalent can be used: {}. Functions from `bevy_gpu_compute_core::wgsl_builtins` can also be called directly",
+                method,
+                supported.join(", ")
+            );
+        }
+        if SCALAR_FLOAT_METHODS_DIFFERENT_FROM_WGSL.contains(&method.as_str())
+            && receiver_type_name.is_some_and(|t| t == "f32" || t == "f16" || t == "PodF16")
+        {
+            abort!(
+                method_call.method.span(),
+                "`{}` on an f32 is rust's version, which gives different results than the WGSL builtin, call `{}(x)` from `bevy_gpu_compute_core::wgsl_builtins` instead",
+                method,
+                method
+            );
+        }
+    }
+}
+
+impl MethodCallChecker {
+    fn add_params(&mut self, sig: &Signature) {
+        self.var_types.clear();
+        for input in sig.inputs.iter() {
+            match input {
+                FnArg::Receiver(_) => {
+                    if let Some(self_type) = &self.self_type {
+                        self.var_types.insert("self".to_string(), self_type.clone());
+                    }
+                }
+                FnArg::Typed(pat_type) => {
+                    if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                        let is_self_type =
+                            matches!(&*pat_type.ty, Type::Path(p) if p.path.is_ident("Self"));
+                        let t = match &self.self_type {
+                            Some(self_type) if is_self_type => self_type.clone(),
+                            _ => *pat_type.ty.clone(),
+                        };
+                        self.var_types.insert(pat_ident.ident.to_string(), t);
+                    }
+                }
+            }
+        }
+    }
+    /// `.rev()` and `.step_by(n)` on the range of a for loop are part of the loop, see `for_loop.rs`
+    fn visit_for_loop_iterable(&mut self, expr: &Expr) {
+        match expr {
+            Expr::MethodCall(call) if call.method == "rev" || call.method == "step_by" => {
+                self.visit_for_loop_iterable(&call.receiver);
+                call.args.iter().for_each(|arg| self.visit_expr(arg));
+            }
+            Expr::Paren(paren) => self.visit_for_loop_iterable(&paren.expr),
+            _ => self.visit_expr(expr),
+        }
+    }
+}
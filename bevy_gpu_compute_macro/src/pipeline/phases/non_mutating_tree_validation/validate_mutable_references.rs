@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use proc_macro_error::abort;
+use syn::{Expr, FnArg, Pat, Type, UnOp, spanned::Spanned, visit::Visit};
+
+/// `&mut T` parameters become WGSL pointers into the caller's function scope, so `&mut` can only be a parameter type, and only local variables declared with `let mut` (or other `&mut` parameters) can be passed to them
+pub fn validate_mutable_references(original_rust_module: &syn::ItemMod) {
+    let mut checker = MutableReferenceChecker {
+        pointer_params: HashSet::new(),
+        mutable_locals: HashSet::new(),
+    };
+    checker.visit_item_mod(original_rust_module);
+}
+
+struct MutableReferenceChecker {
+    /// `&mut` parameters of the current function
+    pointer_params: HashSet<String>,
+    /// local variables of the current function declared with `let mut`
+    mutable_locals: HashSet<String>,
+}
+
+impl<'ast> Visit<'ast> for MutableReferenceChecker {
+    fn visit_item_fn(&mut self, item_fn: &'ast syn::ItemFn) {
+        self.visit_function(&item_fn.sig, &item_fn.block);
+    }
+    fn visit_impl_item_fn(&mut self, method: &'ast syn::ImplItemFn) {
+        self.visit_function(&method.sig, &method.block);
+    }
+    fn visit_receiver(&mut self, _receiver: &'ast syn::Receiver) {}
+    fn visit_type_reference(&mut self, reference: &'ast syn::TypeReference) {
+        if reference.mutability.is_some() {
+            abort!(
+                reference.span(),
+                "`&mut` types can only be used for function parameters in WGSL"
+            );
+        }
+        syn::visit::visit_type_reference(self, reference);
+    }
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        syn::visit::visit_local(self, local);
+        let pat = match &local.pat {
+            Pat::Type(pat_type) => &*pat_type.pat,
+            pat => pat,
+        };
+        if let Pat::Ident(pat_ident) = pat {
+            let name = pat_ident.ident.to_string();
+            if pat_ident.mutability.is_some() {
+                self.mutable_locals.insert(name);
+            } else {
+                // shadowing a mutable variable
+                self.mutable_locals.remove(&name);
+            }
+        }
+    }
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        self.visit_expr(&call.func);
+        for arg in call.args.iter() {
+            match arg {
+                Expr::Reference(reference) if reference.mutability.is_some() => {
+                    self.check_referenced(&reference.expr);
+                    self.visit_expr(&reference.expr);
+                }
+                arg => self.visit_expr(arg),
+            }
+        }
+    }
+    fn visit_expr_reference(&mut self, reference: &'ast syn::ExprReference) {
+        if reference.mutability.is_some() {
+            abort!(
+                reference.span(),
+                "`&mut` can only be used directly on a function argument in WGSL, like `accumulate(&mut total)`"
+            );
+        }
+        syn::visit::visit_expr_reference(self, reference);
+    }
+}
+
+impl<'ast> MutableReferenceChecker {
+    fn visit_function(&mut self, sig: &'ast syn::Signature, block: &'ast syn::Block) {
+        self.pointer_params.clear();
+        self.mutable_locals.clear();
+        for input in sig.inputs.iter() {
+            match input {
+                FnArg::Typed(pat_type) => match (&*pat_type.pat, &*pat_type.ty) {
+                    (Pat::Ident(pat_ident), Type::Reference(reference))
+                        if reference.mutability.is_some() =>
+                    {
+                        self.pointer_params.insert(pat_ident.ident.to_string());
+                        self.visit_type(&reference.elem);
+                    }
+                    _ => self.visit_pat_type(pat_type),
+                },
+                FnArg::Receiver(receiver) => {
+                    if receiver.reference.is_some() && receiver.mutability.is_some() {
+                        self.pointer_params.insert("self".to_string());
+                    }
+                }
+            }
+        }
+        self.visit_return_type(&sig.output);
+        self.visit_block(block);
+    }
+    fn check_referenced(&self, expr: &Expr) {
+        let is_local = match expr {
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .is_some_and(|ident| self.mutable_locals.contains(&ident.to_string())),
+            // passing on a pointer that was itself a parameter
+            Expr::Unary(unary) if matches!(unary.op, UnOp::Deref(_)) => match &*unary.expr {
+                Expr::Path(path) => path
+                    .path
+                    .get_ident()
+                    .is_some_and(|ident| self.pointer_params.contains(&ident.to_string())),
+                _ => false,
+            },
+            _ => false,
+        };
+        if !is_local {
+            abort!(
+                expr.span(),
+                "Only local variables declared with `let mut` can be passed as `&mut` in WGSL"
+            );
+        }
+    }
+}
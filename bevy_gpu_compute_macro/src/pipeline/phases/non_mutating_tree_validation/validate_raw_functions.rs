@@ -0,0 +1,48 @@
+use proc_macro_error::abort;
+use syn::{Item, ItemMod, LitStr};
+
+use crate::pipeline::{
+    entry_points::is_entry_point,
+    raw_functions::{extern_attribute, extern_path, raw_body},
+};
+
+/// `#[wgsl_extern]` and `wgsl_raw!` functions are written in WGSL by hand, so the macro cannot make copies of them for generics, and the entry points have to stay in rust since its buffers are generated
+pub fn validate_raw_functions(original_rust_module: &ItemMod) {
+    let Some((_, items)) = &original_rust_module.content else {
+        return;
+    };
+    for item in items {
+        let Item::Fn(func) = item else {
+            continue;
+        };
+        let attr = extern_attribute(&func.attrs);
+        if let Some(Err(_)) = attr.map(extern_path) {
+            abort!(
+                attr.unwrap(),
+                "`#[wgsl_extern]` needs the path of the WGSL file, relative to this file, like `#[wgsl_extern(path = \"noise.wgsl\")]`"
+            );
+        }
+        let mac = raw_body(&func.block);
+        if let Some(Err(_)) = mac.map(|mac| mac.parse_body::<LitStr>()) {
+            abort!(
+                mac.unwrap(),
+                "`wgsl_raw!` takes the WGSL body of the function as a string literal, like `wgsl_raw!(\"return x * 2.0;\")`"
+            );
+        }
+        if attr.is_none() && mac.is_none() {
+            continue;
+        }
+        if is_entry_point(func) {
+            abort!(
+                func.sig.ident,
+                "An entry point function cannot be written in WGSL, since its inputs and outputs are generated. Move the WGSL to a helper function"
+            );
+        }
+        if !func.sig.generics.params.is_empty() {
+            abort!(
+                func.sig.generics,
+                "Functions written in WGSL cannot be generic, since WGSL has no generics"
+            );
+        }
+    }
+}
@@ -2,8 +2,14 @@ use proc_macro_error::abort;
 use quote::ToTokens;
 use syn::{Item, ItemMod, ItemUse, spanned::Spanned, visit::Visit};
 
-const VALID_USE_STATEMENT_PATHS: [&str; 3] =
-    ["wgsl_helpers", "bevy_gpu_compute", "bevy_gpu_compute_macro"];
+use crate::pipeline::library_imports::{library_import_path, type_import_path};
+
+const VALID_USE_STATEMENT_PATHS: [&str; 4] = [
+    "wgsl_helpers",
+    "wgsl_builtins",
+    "bevy_gpu_compute",
+    "bevy_gpu_compute_macro",
+];
 
 pub fn validate_use_statements(original_rust_module: &ItemMod) {
     let mut handler = UseStatementHandler {};
@@ -22,11 +28,14 @@ impl Visit<'_> for UseStatementHandler {
 }
 
 fn validate_use_statement(use_stmt: &ItemUse) {
+    if library_import_path(use_stmt).is_some() || type_import_path(use_stmt).is_some() {
+        return;
+    }
     let mut single_handler = SingleUseStatementHandler { found: false };
     single_handler.visit_item_use(use_stmt);
     if !single_handler.found {
         let message = format!(
-            "Invalid use statement: {:?}. You are only allowed to import from one of these crates: {}",
+            "Invalid use statement: {:?}. You are only allowed to import from one of these crates: {}, all items of a `#[wgsl_library]` like `use crate::path::to::library::*;`, or a struct deriving `WgslType` like `use crate::path::to::MyType;`",
             use_stmt.to_token_stream().to_string(),
             VALID_USE_STATEMENT_PATHS.join(", ")
         );
@@ -1,4 +1,7 @@
-use crate::pipeline::{compilation_unit::CompilationUnit, phases::compiler_phase::CompilerPhase};
+use crate::pipeline::{
+    compilation_unit::{CompilationUnit, ModuleKind},
+    phases::compiler_phase::CompilerPhase,
+};
 use quote::quote;
 
 use super::{
@@ -12,6 +15,11 @@ pub struct TypesafeBufferBuildersGenerator;
 
 impl CompilerPhase for TypesafeBufferBuildersGenerator {
     fn execute(&self, input: &mut CompilationUnit) {
+        // a library has no buffers
+        if input.kind() == ModuleKind::Library {
+            input.set_typesafe_buffer_builders(quote! {});
+            return;
+        }
         let config_input = create_config_input_data_builder(input.custom_types());
         let array_input = create_input_data_builder(input.custom_types());
         let array_output = create_output_data_builder(input.custom_types());
@@ -7,6 +7,7 @@ use crate::pipeline::{
     phases::custom_type_collector::{
         custom_type::CustomTypeKind, custom_type_idents::CustomTypeIdents,
     },
+    phases::gpu_resource_mngmnt_and_wgsl_generator::array_configs::array_config_element,
 };
 
 pub fn create_config_input_data_builder(custom_types: &[CustomType]) -> TokenStream {
@@ -44,9 +45,32 @@ fn get_methods(custom_types: &[CustomType]) -> TokenStream {
     custom_types
         .iter()
         .filter(|c| c.kind == CustomTypeKind::Uniform)
-        .map(|c| single_method(c.name.clone()))
+        .map(|c| match array_config_element(c) {
+            Some(_) => padded_array_method(c.name.clone()),
+            None => single_method(c.name.clone()),
+        })
         .collect()
 }
+/// every element of an array config starts at a multiple of 16 bytes on the GPU, see `array_configs.rs`
+fn padded_array_method(custom_type_name: CustomTypeIdents) -> TokenStream {
+    let method_name: Ident = format_ident!("set_{}", custom_type_name.snake_case);
+    let type_pascal_case: Ident = custom_type_name.name.clone();
+    let string_key: String = format!("{}", custom_type_name.name);
+    quote! {
+        pub fn #method_name(&mut self, data: #type_pascal_case) -> &mut Self {
+            let element_size = std::mem::size_of_val(&data) / data.len().max(1);
+            let stride = element_size.next_multiple_of(16);
+            let mut bytes = vec![0u8; stride * data.len()];
+            for (i, element) in data.iter().enumerate() {
+                bytes[i * stride..i * stride + element_size]
+                    .copy_from_slice(bytemuck::bytes_of(element));
+            }
+            self.bytes_per_wgsl_config_type_name
+                .insert(#string_key.to_string(), bytes);
+            self
+        }
+    }
+}
 fn single_method(custom_type_name: CustomTypeIdents) -> TokenStream {
     let method_name: Ident = format_ident!("set_{}", custom_type_name.snake_case);
     let type_pascal_case: Ident = custom_type_name.name.clone();
@@ -1,3 +1,5 @@
+use proc_macro_error::abort_if_dirty;
+
 use crate::pipeline::{compilation_unit::CompilationUnit, phases::compiler_phase::CompilerPhase};
 
 use super::run::transform_wgsl_helper_methods;
@@ -6,9 +8,12 @@ pub struct WgslHelperTransformer;
 
 impl CompilerPhase for WgslHelperTransformer {
     fn execute(&self, input: &mut CompilationUnit) {
-        let mut mod_for_gpu = input.original_rust_module().clone();
+        // library items are only copied into the module for the GPU, on the CPU they are imported
+        let mut mod_for_gpu = input.original_rust_module_with_libraries();
         let mut mod_for_cpu = input.original_rust_module().clone();
         transform_wgsl_helper_methods(input.custom_types(), &mut mod_for_gpu, false);
+        // helper misuse is reported on the gpu pass, stop before the cpu pass reports it a second time
+        abort_if_dirty();
         transform_wgsl_helper_methods(input.custom_types(), &mut mod_for_cpu, true);
         input.set_rust_module_for_cpu(mod_for_cpu);
         input.set_rust_module_for_gpu(mod_for_gpu);
@@ -1,3 +1,4 @@
+use proc_macro_error::emit_error;
 use syn::{
     Expr,
     visit::{self, Visit},
@@ -29,8 +30,9 @@ impl Visit<'_> for ErroneousUsageFinder {
         visit::visit_expr(self, expr);
         if let Expr::Call(call) = expr {
             let helper_method = parse_possible_wgsl_helper(call, &self.custom_types);
-            if helper_method.is_some() {
-                panic!(
+            if let Some(method) = helper_method {
+                emit_error!(
+                    method.span,
                     "WGSL Helpers (`bevy_gpu_compute_core::wgsl_helpers`) not allowed outside of functions."
                 );
             }
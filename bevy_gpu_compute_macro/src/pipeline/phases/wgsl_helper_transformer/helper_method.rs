@@ -1,3 +1,4 @@
+use proc_macro2::Span;
 use syn::Expr;
 
 use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
@@ -14,4 +15,6 @@ pub struct WgslHelperMethod {
     pub arg1: Option<Expr>,
     pub arg2: Option<Expr>,
     pub method_expander_kind: Option<ToExpandedFormatMethodKind>,
+    /// span of the whole helper call, used for error messages
+    pub span: Span,
 }
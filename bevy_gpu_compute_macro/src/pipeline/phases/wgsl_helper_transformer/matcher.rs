@@ -1,3 +1,5 @@
+use proc_macro_error::emit_error;
+
 use crate::pipeline::phases::{
     custom_type_collector::custom_type::CustomTypeKind,
     wgsl_helper_transformer::to_expanded_format::ToExpandedFormatMethodKind,
@@ -8,89 +10,113 @@ use super::{
     method_name::WgslHelperMethodName,
 };
 
+/// emits an error at `span` if `condition` is false, evaluating to `condition`
+macro_rules! check {
+    ($span:expr, $condition:expr, $($message:tt)+) => {{
+        let condition = $condition;
+        if !condition {
+            emit_error!($span, $($message)+);
+        }
+        condition
+    }};
+}
+
 pub struct WgslHelperMethodMatcher {}
 impl WgslHelperMethodMatcher {
+    /// Errors are emitted rather than aborting so that every misused helper in the module is reported at once,
+    /// a misused helper is left unexpanded
     pub fn choose_expand_format(method: &mut WgslHelperMethod) {
+        let mut valid = true;
+        let mut kind = None;
         match (&method.category, &method.method) {
             (WgslHelperCategory::ConfigInput, WgslHelperMethodName::Get) => {
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.t_def.kind == CustomTypeKind::Uniform,
                     "Expected {} to be an input config type, since WgslConfigInput::get is called, instead found it was of type {:?}. Put #[wgsl_config] above your type declaration to fix this. A given type cannot be used for multiple purposes, for example a type T cannot be both a config and a input array.",
                     method.t_def.name.name,
                     method.t_def.kind
                 );
-                method.method_expander_kind = Some(ToExpandedFormatMethodKind::ConfigGet);
+                kind = Some(ToExpandedFormatMethodKind::ConfigGet);
             }
             (WgslHelperCategory::VecInput, WgslHelperMethodName::VecLen) => {
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.t_def.kind == CustomTypeKind::InputArray,
                     "Expected {} to be an input array type, since WgslVecInput::vec_len is called, instead found it was of type {:?}. Put #[wgsl_input_array] above your type declaration to fix this. A given type cannot be used for multiple purposes, for example a type T cannot be both an input array and an output array.",
                     method.t_def.name.name,
                     method.t_def.kind
                 );
-                method.method_expander_kind = Some(ToExpandedFormatMethodKind::InputLen);
+                kind = Some(ToExpandedFormatMethodKind::InputLen);
             }
             (WgslHelperCategory::VecInput, WgslHelperMethodName::VecVal) => {
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.arg1.is_some(),
                     "Expected an argument for input vec value getter"
                 );
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.t_def.kind == CustomTypeKind::InputArray,
                     "Expected {} to be an input array type, since WgslVecInput::vec_val is called, instead found it was of type {:?}. Put #[wgsl_input_array] above your type declaration to fix this. A given type cannot be used for multiple purposes, for example a type T cannot be both an input array and an output array.",
                     method.t_def.name.name,
                     method.t_def.kind
                 );
-                method.method_expander_kind = Some(ToExpandedFormatMethodKind::InputVal);
+                kind = Some(ToExpandedFormatMethodKind::InputVal);
             }
             (WgslHelperCategory::Output, WgslHelperMethodName::Push) => {
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.arg1.is_some(),
                     "Expected an argument for output push"
                 );
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.t_def.kind == CustomTypeKind::OutputVec,
                     "Expected {} to be an output vec type, since WgslOutput::push is called, instead found it was of type {:?}. Put #[wgsl_output_vec] above your type declaration to fix this. A given type cannot be used for multiple purposes, for example a type T cannot be both an input array and an output array, an output cannot be both a vec and an array.",
                     method.t_def.name.name,
                     method.t_def.kind
                 );
-                method.method_expander_kind = Some(ToExpandedFormatMethodKind::OutputPush);
+                kind = Some(ToExpandedFormatMethodKind::OutputPush);
             }
             (WgslHelperCategory::Output, WgslHelperMethodName::MaxLen) => {
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.t_def.kind == CustomTypeKind::OutputArray
                         || method.t_def.kind == CustomTypeKind::OutputVec,
                     "Expected {} to be an output array or vec type, since WgslOutput::max_len is called, instead found it was of type {:?}. Put #[wgsl_output_array] or #[wgsl_output_vec] above your type declaration to fix this. A given type cannot be used for multiple purposes, for example a type T cannot be both an input array and an output array.",
                     method.t_def.name.name,
                     method.t_def.kind
                 );
-                method.method_expander_kind = Some(ToExpandedFormatMethodKind::OutputMaxLen);
+                kind = Some(ToExpandedFormatMethodKind::OutputMaxLen);
             }
             (WgslHelperCategory::Output, WgslHelperMethodName::Len) => {
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.t_def.kind == CustomTypeKind::OutputVec,
                     "Expected {} to be an output vec type, since WgslOutput::len is called, instead found it was of type {:?}. Put #[wgsl_output_vec] above your type declaration to fix this. A given type cannot be used for multiple purposes, for example a type T cannot be both an input array and an output array.",
                     method.t_def.name.name,
                     method.t_def.kind
                 );
-                method.method_expander_kind = Some(ToExpandedFormatMethodKind::OutputLen);
+                kind = Some(ToExpandedFormatMethodKind::OutputLen);
             }
             (WgslHelperCategory::Output, WgslHelperMethodName::Set) => {
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.t_def.kind == CustomTypeKind::OutputArray,
                     "Expected {} to be an output array type, since WgslOutput::set is called, instead found it was of type {:?}. Put #[wgsl_output_array] above your type declaration to fix this. A given type cannot be used for multiple purposes, for example a type T cannot be both an input array and an output array.",
                     method.t_def.name.name,
                     method.t_def.kind
                 );
-                assert!(
+                valid &= check!(
+                    method.span,
                     method.arg1.is_some() && method.arg2.is_some(),
                     "Expected two arguments for output set"
                 );
-                method.method_expander_kind = Some(ToExpandedFormatMethodKind::OutputSet);
-            }
-            _ => {
-                method.method_expander_kind = None;
+                kind = Some(ToExpandedFormatMethodKind::OutputSet);
             }
+            _ => {}
         }
+        method.method_expander_kind = if valid { kind } else { None };
     }
 }
@@ -1,4 +1,4 @@
-use syn::{Expr, ExprCall, GenericArgument, PathArguments, Type};
+use syn::{Expr, ExprCall, GenericArgument, PathArguments, Type, spanned::Spanned};
 
 use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
 use crate::pipeline::phases::wgsl_helper_transformer::helper_method::WgslHelperMethod;
@@ -59,6 +59,7 @@ pub fn parse_possible_wgsl_helper<'a>(
                     arg1: args.first().cloned(),
                     arg2: args.get(1).cloned(),
                     method_expander_kind: None,
+                    span: call.span(),
                 };
                 WgslHelperMethodMatcher::choose_expand_format(&mut method);
                 if method.method_expander_kind.is_some() {
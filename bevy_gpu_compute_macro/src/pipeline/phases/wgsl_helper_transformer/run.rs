@@ -1,3 +1,4 @@
+use proc_macro_error::emit_error;
 use proc_macro2::TokenStream;
 
 use syn::{
@@ -6,7 +7,9 @@ use syn::{
     visit_mut::{self, VisitMut},
 };
 
-use crate::pipeline::phases::custom_type_collector::custom_type::CustomType;
+use crate::pipeline::{
+    entry_points::is_entry_point, phases::custom_type_collector::custom_type::CustomType,
+};
 
 use super::{
     erroneous_usage_finder::ErroneousUsageFinder, helper_method::WgslHelperMethod,
@@ -37,7 +40,7 @@ struct WgslHelperExpressionConverter {
 
 impl VisitMut for WgslHelperExpressionConverter {
     fn visit_item_fn_mut(&mut self, node: &mut ItemFn) {
-        if node.sig.ident == "main" && self.nesting_level == 0 {
+        if is_entry_point(node) && self.nesting_level == 0 {
             self.in_main_func = true;
             self.nesting_level += 1;
             visit_mut::visit_item_fn_mut(self, node);
@@ -82,15 +85,14 @@ impl WgslHelperExpressionConverter {
 }
 
 fn process_wgsl_helper(helper_method: WgslHelperMethod, in_main_func: bool) -> TokenStream {
-    if !helper_method
+    let valid_outside_main = helper_method
         .method_expander_kind
         .as_ref()
-        .unwrap()
-        .valid_outside_main()
-        && !in_main_func
-    {
-        panic!(
-            "WGSL helpers that read from inputs or write to outputs (`bevy_gpu_compute_core::wgsl_helpers`) can only be used inside the main function. It is technically possible to pass in entire input arrays, configs, or output arrays to helper functions, but considering the performance implications, it is not recommended. Instead interact with your inputs and outputs in the main function and pass in only the necessary data to the helper functions."
+        .is_some_and(|kind| kind.valid_outside_main());
+    if !valid_outside_main && !in_main_func {
+        emit_error!(
+            helper_method.span,
+            "WGSL helpers that read from inputs or write to outputs (`bevy_gpu_compute_core::wgsl_helpers`) can only be used inside the main function and the `#[wgsl_entry]` functions. It is technically possible to pass in entire input arrays, configs, or output arrays to helper functions, but considering the performance implications, it is not recommended. Instead interact with your inputs and outputs in the entry point functions and pass in only the necessary data to the helper functions."
         );
     }
     ToExpandedFormat::run(&helper_method)
@@ -36,29 +36,6 @@ mod tests {
         );
     }
 
-    #[test]
-    #[should_panic(
-        expected = "WGSL helpers that read from inputs or write to outputs (`bevy_gpu_compute_core::wgsl_helpers`) can only be used inside the main function. It is technically possible to pass in entire input arrays, configs, or output arrays to helper functions, but considering the performance implications, it is not recommended. Instead interact with your inputs and outputs in the main function and pass in only the necessary data to the helper functions."
-    )]
-    fn test_vec_val_only_in_main() {
-        let mut input: ItemMod = parse_quote! {
-            mod test {
-
-                fn example() {
-                    let x = WgslVecInput::vec_val::<Radius>(5);
-                }
-            }
-        };
-
-        let custom_types = vec![CustomType::new(
-            &format_ident!("Radius"),
-            CustomTypeKind::InputArray,
-            TokenStream::new(),
-        )];
-
-        transform_wgsl_helper_methods(&custom_types, &mut input, false);
-    }
-
     #[test]
     fn test_vec_val() {
         let mut input: ItemMod = parse_quote! {
@@ -87,27 +64,6 @@ mod tests {
         );
     }
 
-    #[test]
-    #[should_panic(
-        expected = "WGSL helpers that read from inputs or write to outputs (`bevy_gpu_compute_core::wgsl_helpers`) can only be used inside the main function. It is technically possible to pass in entire input arrays, configs, or output arrays to helper functions, but considering the performance implications, it is not recommended. Instead interact with your inputs and outputs in the main function and pass in only the necessary data to the helper functions."
-    )]
-    fn test_push_only_in_main() {
-        let mut input: ItemMod = parse_quote! {
-            mod test {
-                fn example() {
-                    WgslOutput::push::<CollisionResult>(value);
-                }
-            }
-        };
-
-        let custom_types = vec![CustomType::new(
-            &format_ident!("CollisionResult"),
-            CustomTypeKind::OutputVec,
-            TokenStream::new(),
-        )];
-
-        transform_wgsl_helper_methods(&custom_types, &mut input, false);
-    }
     #[test]
     fn test_push() {
         let mut input: ItemMod = parse_quote! {
@@ -194,27 +150,6 @@ mod tests {
         );
     }
 
-    #[test]
-    #[should_panic(
-        expected = "WGSL helpers that read from inputs or write to outputs (`bevy_gpu_compute_core::wgsl_helpers`) can only be used inside the main function. It is technically possible to pass in entire input arrays, configs, or output arrays to helper functions, but considering the performance implications, it is not recommended. Instead interact with your inputs and outputs in the main function and pass in only the necessary data to the helper functions."
-    )]
-    fn test_output_set_not_in_main() {
-        let mut input: ItemMod = parse_quote! {
-            mod test {
-                fn example() {
-                    WgslOutput::set::<CollisionResult>(idx, val);
-                }
-            }
-        };
-
-        let custom_types = vec![CustomType::new(
-            &format_ident!("CollisionResult"),
-            CustomTypeKind::OutputArray,
-            TokenStream::new(),
-        )];
-
-        transform_wgsl_helper_methods(&custom_types, &mut input, false);
-    }
     #[test]
 
     fn test_output_set() {
@@ -245,27 +180,6 @@ mod tests {
         );
     }
     #[test]
-    #[should_panic(
-        expected = "WGSL helpers that read from inputs or write to outputs (`bevy_gpu_compute_core::wgsl_helpers`) can only be used inside the main function. It is technically possible to pass in entire input arrays, configs, or output arrays to helper functions, but considering the performance implications, it is not recommended. Instead interact with your inputs and outputs in the main function and pass in only the necessary data to the helper functions."
-    )]
-    fn test_config_get_outside_main() {
-        let mut input: ItemMod = parse_quote! {
-            mod test {
-                fn example() {
-                    let t = WgslConfigInput::get::<Position>();
-                }
-            }
-        };
-
-        let custom_types = vec![CustomType::new(
-            &format_ident!("Position"),
-            CustomTypeKind::Uniform,
-            TokenStream::new(),
-        )];
-
-        transform_wgsl_helper_methods(&custom_types, &mut input, false);
-    }
-    #[test]
     fn test_config_get() {
         let mut input: ItemMod = parse_quote! {
             mod test {
@@ -1,10 +1,12 @@
 use proc_macro_error::abort;
-use proc_macro2::Span;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use quote::quote;
 
 use super::helper_method::WgslHelperMethod;
+use crate::pipeline::phases::gpu_resource_mngmnt_and_wgsl_generator::array_configs::{
+    array_config_element, from_uniform_function,
+};
 
 pub enum ToExpandedFormatMethodKind {
     ConfigGet,
@@ -35,6 +37,12 @@ impl ToExpandedFormat {
         match method.method_expander_kind {
             Some(ToExpandedFormatMethodKind::ConfigGet) => {
                 let name = method.t_def.name.uniform();
+                if array_config_element(&method.t_def).is_some() {
+                    let from_uniform = from_uniform_function(&method.t_def.name.name);
+                    return quote! {
+                        #from_uniform(#name)
+                    };
+                }
                 quote! {
                     #name
                 }
@@ -47,7 +55,7 @@ impl ToExpandedFormat {
                 let index = if let Some(a1) = &method.arg1 {
                     a1
                 } else {
-                    abort!(Span::call_site(), "arg1 is None for input value method")
+                    abort!(method.span, "arg1 is None for input value method")
                 };
                 quote! {
                     #name [ #index ]
@@ -62,7 +70,7 @@ impl ToExpandedFormat {
                 let value = if let Some(a1) = &method.arg1 {
                     a1
                 } else {
-                    abort!(Span::call_site(), "arg1 is None for output push method")
+                    abort!(method.span, "arg1 is None for output push method")
                 };
                 quote! {
                     {
@@ -88,18 +96,18 @@ impl ToExpandedFormat {
                 let index = if let Some(a1) = &method.arg1 {
                     a1
                 } else {
-                    abort!(Span::call_site(), "arg1 is None for output set method")
+                    abort!(method.span, "arg1 is None for output set method")
                 };
                 let value = if let Some(a2) = &method.arg2 {
                     a2
                 } else {
-                    abort!(Span::call_site(), "arg2 is None for output set method")
+                    abort!(method.span, "arg2 is None for output set method")
                 };
                 quote! {
                     #arr [ #index ] = #value
                 }
             }
-            None => panic!("method_expander_kind is None"),
+            None => abort!(method.span, "method_expander_kind is None"),
         }
     }
 }
@@ -1,5 +1,4 @@
 use proc_macro_error::abort;
-use proc_macro2::Span;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use quote::quote;
@@ -28,7 +27,7 @@ impl ToExpandedFormatForCpu {
                 let index = if let Some(a1) = &method.arg1 {
                     a1
                 } else {
-                    abort!(Span::call_site(), "arg1 is None for input value method")
+                    abort!(method.span, "arg1 is None for input value method")
                 };
                 quote! {
                     #name [ #index as usize ]
@@ -40,7 +39,7 @@ impl ToExpandedFormatForCpu {
                 let value = if let Some(a1) = &method.arg1 {
                     a1
                 } else {
-                    abort!(Span::call_site(), "arg1 is None for output push method")
+                    abort!(method.span, "arg1 is None for output push method")
                 };
                 quote! {#arr .push( #value )}
             }
@@ -62,18 +61,18 @@ impl ToExpandedFormatForCpu {
                 let index = if let Some(a1) = &method.arg1 {
                     a1
                 } else {
-                    abort!(Span::call_site(), "arg1 is None for output set method")
+                    abort!(method.span, "arg1 is None for output set method")
                 };
                 let value = if let Some(a2) = &method.arg2 {
                     a2
                 } else {
-                    abort!(Span::call_site(), "arg2 is None for output set method")
+                    abort!(method.span, "arg2 is None for output set method")
                 };
                 quote! {
                     #arr [ #index as usize ] = #value
                 }
             }
-            None => panic!("method_expander_kind is None"),
+            None => abort!(method.span, "method_expander_kind is None"),
         }
     }
 }
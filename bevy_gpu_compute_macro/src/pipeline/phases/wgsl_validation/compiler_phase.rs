@@ -0,0 +1,16 @@
+use crate::pipeline::{compilation_unit::CompilationUnit, phases::compiler_phase::CompilerPhase};
+
+use super::validate::validate_wgsl;
+
+/// Checks the generated shader with naga, so that invalid WGSL is a compile error instead of a runtime panic when the shader module is created.
+/// Runs after the final structure generator since the complete shader needs the binding numbers assigned there
+pub struct WgslValidation;
+
+impl CompilerPhase for WgslValidation {
+    fn execute(&self, input: &mut CompilationUnit) {
+        validate_wgsl(
+            input.wgsl_module_user_portion(),
+            input.original_rust_module(),
+        );
+    }
+}
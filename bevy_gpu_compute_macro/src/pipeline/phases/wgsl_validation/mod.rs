@@ -0,0 +1,3 @@
+pub mod compiler_phase;
+mod source_map;
+mod validate;
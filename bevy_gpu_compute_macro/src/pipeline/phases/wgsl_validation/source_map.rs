@@ -0,0 +1,212 @@
+use std::ops::Range;
+
+use bevy_gpu_compute_core::wgsl::shader_module::user_defined_portion::WgslShaderModuleUserPortion;
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use regex::Regex;
+use syn::{ImplItem, Item, ItemMod, Type};
+
+/// Idents that show up in both rust and wgsl code without telling us where an error came from
+const IGNORED_IDENTS: [&str; 14] = [
+    "let", "var", "fn", "return", "if", "else", "for", "loop", "while", "const", "struct", "alias",
+    "as", "mut",
+];
+
+/// Remembers which rust item each piece of the assembled WGSL was generated from
+pub struct WgslSourceMap<'a> {
+    sections: Vec<(Range<usize>, String)>,
+    rust_module: &'a ItemMod,
+}
+
+impl<'a> WgslSourceMap<'a> {
+    pub fn new(
+        wgsl: &str,
+        wgsl_module: &WgslShaderModuleUserPortion,
+        rust_module: &'a ItemMod,
+    ) -> Self {
+        let mut named_code: Vec<(String, &String)> = Vec::new();
+        wgsl_module.static_consts.iter().for_each(|c| {
+            named_code.push((const_name(&c.code.wgsl_code), &c.code.wgsl_code));
+        });
+        wgsl_module
+            .helper_types
+            .iter()
+            .chain(wgsl_module.uniforms.iter())
+            .chain(wgsl_module.input_arrays.iter().map(|a| &a.item_type))
+            .chain(wgsl_module.output_arrays.iter().map(|a| &a.item_type))
+            .for_each(|t| named_code.push((t.name.name().to_string(), &t.code.wgsl_code)));
+        wgsl_module
+            .helper_functions
+            .iter()
+            .chain(wgsl_module.entry_points.iter().map(|e| &e.function))
+            .for_each(|f| named_code.push((f.name.clone(), &f.code.wgsl_code)));
+        let sections = named_code
+            .into_iter()
+            .filter(|(_, code)| !code.is_empty())
+            .filter_map(|(name, code)| {
+                wgsl.find(code.as_str())
+                    .map(|start| (start..start + code.len(), name))
+            })
+            .collect();
+        WgslSourceMap {
+            sections,
+            rust_module,
+        }
+    }
+
+    /// Finds the rust span that produced the WGSL in `range`.
+    /// Falls back to the item the code was generated from, and then to the module itself for code the library generated (bindings, array lengths etc.)
+    pub fn rust_span(&self, wgsl: &str, range: &Range<usize>) -> Span {
+        let Some((_, name)) = self
+            .sections
+            .iter()
+            .find(|(section, _)| section.contains(&range.start))
+        else {
+            return self.rust_module.ident.span();
+        };
+        let Some((item_tokens, item_span)) = find_rust_item(self.rust_module, name) else {
+            return self.rust_module.ident.span();
+        };
+        let snippet = wgsl.get(range.clone()).unwrap_or_default();
+        find_snippet_in_tokens(snippet, item_tokens).unwrap_or(item_span)
+    }
+}
+
+fn const_name(wgsl_code: &str) -> String {
+    let re = Regex::new(r"const\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    re.captures(wgsl_code)
+        .map(|c| c[1].to_string())
+        .unwrap_or_default()
+}
+
+/// Exact name matches win, otherwise the longest item name that prefixes the generated name, like `lerp` for `lerp_f32` or `Color` for `Color_Red`
+fn find_rust_item(module: &ItemMod, name: &str) -> Option<(TokenStream, Span)> {
+    let mut candidates: Vec<(String, TokenStream, Span)> = Vec::new();
+    for item in module.content.as_ref().map(|c| &c.1).into_iter().flatten() {
+        match item {
+            Item::Fn(f) => candidates.push(candidate(&f.sig.ident, f)),
+            Item::Struct(s) => candidates.push(candidate(&s.ident, s)),
+            Item::Type(t) => candidates.push(candidate(&t.ident, t)),
+            Item::Const(c) => candidates.push(candidate(&c.ident, c)),
+            Item::Enum(e) => candidates.push(candidate(&e.ident, e)),
+            Item::Impl(i) => {
+                let Type::Path(self_ty) = &*i.self_ty else {
+                    continue;
+                };
+                let Some(self_name) = self_ty.path.segments.last() else {
+                    continue;
+                };
+                for impl_item in &i.items {
+                    if let ImplItem::Fn(f) = impl_item {
+                        let (_, tokens, span) = candidate(&f.sig.ident, f);
+                        candidates.push((
+                            format!("{}_{}", self_name.ident, f.sig.ident),
+                            tokens,
+                            span,
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(index) = candidates.iter().position(|(n, _, _)| n == name) {
+        let (_, tokens, span) = candidates.swap_remove(index);
+        return Some((tokens, span));
+    }
+    candidates
+        .into_iter()
+        .filter(|(n, _, _)| name.starts_with(&format!("{}_", n)))
+        .max_by_key(|(n, _, _)| n.len())
+        .map(|(_, tokens, span)| (tokens, span))
+}
+
+fn candidate(ident: &Ident, item: &impl ToTokens) -> (String, TokenStream, Span) {
+    (ident.to_string(), item.to_token_stream(), ident.span())
+}
+
+/// Looks for the longest run of the snippet's identifiers in the item's identifiers, returning the span of the first one in the run
+fn find_snippet_in_tokens(snippet: &str, item_tokens: TokenStream) -> Option<Span> {
+    let re = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap();
+    let wanted: Vec<&str> = re
+        .find_iter(snippet)
+        .map(|m| m.as_str())
+        .filter(|i| !IGNORED_IDENTS.contains(i))
+        .collect();
+    if wanted.is_empty() {
+        return None;
+    }
+    let mut idents = Vec::new();
+    flatten_idents(item_tokens, &mut idents);
+    longest_run_start(&idents, &wanted).map(|start| idents[start].span())
+}
+
+fn longest_run_start(idents: &[Ident], wanted: &[&str]) -> Option<usize> {
+    let mut best: Option<(usize, usize)> = None;
+    for start in 0..idents.len() {
+        let run = idents[start..]
+            .iter()
+            .zip(wanted.iter())
+            .take_while(|(ident, w)| ident == *w)
+            .count();
+        if run > 0 && best.is_none_or(|(len, _)| run > len) {
+            best = Some((run, start));
+        }
+    }
+    best.map(|(_, start)| start)
+}
+
+fn flatten_idents(tokens: TokenStream, out: &mut Vec<Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) if !IGNORED_IDENTS.contains(&ident.to_string().as_str()) => {
+                out.push(ident)
+            }
+            TokenTree::Group(group) => flatten_idents(group.stream(), out),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_find_rust_item_for_generated_names() {
+        let module: ItemMod = parse_quote! {
+            mod test {
+                struct Particle { speed: f32 }
+                impl Particle {
+                    fn energy(self) -> f32 { return self.speed * self.speed; }
+                }
+                fn lerp<T>(a: T, b: T) -> T { return a; }
+                fn main(iter_pos: WgslIterationPosition) {}
+            }
+        };
+        let (tokens, _) = find_rust_item(&module, "Particle_energy").unwrap();
+        assert!(tokens.to_string().starts_with("fn energy"));
+        let (tokens, _) = find_rust_item(&module, "lerp_f32").unwrap();
+        assert!(tokens.to_string().starts_with("fn lerp"));
+        let (tokens, _) = find_rust_item(&module, "Particle").unwrap();
+        assert!(tokens.to_string().starts_with("struct Particle"));
+        assert!(find_rust_item(&module, "position_input_array").is_none());
+    }
+
+    #[test]
+    fn test_find_snippet_in_tokens_prefers_longest_run() {
+        let item: syn::ItemFn = parse_quote! {
+            fn f(a: f32, b: u32) -> f32 {
+                let c = a * 2.0;
+                return c + a * b;
+            }
+        };
+        let mut idents = Vec::new();
+        flatten_idents(item.to_token_stream(), &mut idents);
+        // `c` and `a` first appear in the `let`, but only the return expression contains `c + a * b`
+        let start = longest_run_start(&idents, &["c", "a", "b"]).unwrap();
+        assert_eq!(start, idents.len() - 3);
+        assert!(find_snippet_in_tokens("1.0 + 2.0", item.to_token_stream()).is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use std::{error::Error, ops::Range};
+
+use bevy_gpu_compute_core::{
+    IterSpaceDimmension,
+    wgsl::shader_module::{
+        complete_shader_module::WgslShaderModule, user_defined_portion::WgslShaderModuleUserPortion,
+    },
+};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use proc_macro_error::emit_error;
+use regex::Regex;
+use syn::ItemMod;
+
+use super::source_map::WgslSourceMap;
+
+struct WgslDiagnostic {
+    message: String,
+    labels: Vec<(Range<usize>, String)>,
+}
+
+/// Runs naga over the complete shader for every iteration space dimmension, since the workgroup size differs between them.
+/// Each distinct diagnostic is reported once, at the rust code it was generated from
+pub fn validate_wgsl(wgsl_module: &WgslShaderModuleUserPortion, rust_module: &ItemMod) {
+    let complete_module = WgslShaderModule::new(wgsl_module.clone());
+    let mut reported: Vec<String> = Vec::new();
+    let uses_f16 = Regex::new(r"\bf16\b").unwrap();
+    for dimmension in [
+        IterSpaceDimmension::OneD,
+        IterSpaceDimmension::TwoD,
+        IterSpaceDimmension::ThreeD,
+    ] {
+        let wgsl = complete_module.wgsl_code(dimmension);
+        // naga does not support `f16` yet, so shaders using it can only be checked when the shader module is created
+        if uses_f16.is_match(&wgsl) {
+            return;
+        }
+        let Some(diagnostic) = diagnose(&wgsl) else {
+            continue;
+        };
+        if reported.contains(&diagnostic.message) {
+            continue;
+        }
+        report(&diagnostic, &wgsl, wgsl_module, rust_module);
+        reported.push(diagnostic.message);
+    }
+}
+
+fn diagnose(wgsl: &str) -> Option<WgslDiagnostic> {
+    let module = match naga::front::wgsl::parse_str(wgsl) {
+        Ok(module) => module,
+        Err(error) => {
+            return Some(WgslDiagnostic {
+                message: error.message().to_string(),
+                labels: error
+                    .labels()
+                    .filter_map(|(span, label)| span.to_range().map(|r| (r, label.to_string())))
+                    .collect(),
+            });
+        }
+    };
+    let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
+    match validator.validate(&module) {
+        Ok(_) => None,
+        Err(error) => {
+            // the top level error is usually just "Function [2] 'foo' is invalid", the cause is further down the chain
+            let mut message = error.as_inner().to_string();
+            let mut source = error.as_inner().source();
+            while let Some(cause) = source {
+                message.push_str(": ");
+                message.push_str(&cause.to_string());
+                source = cause.source();
+            }
+            Some(WgslDiagnostic {
+                message,
+                labels: error
+                    .spans()
+                    .filter_map(|(span, label)| span.to_range().map(|r| (r, label.clone())))
+                    .collect(),
+            })
+        }
+    }
+}
+
+fn report(
+    diagnostic: &WgslDiagnostic,
+    wgsl: &str,
+    wgsl_module: &WgslShaderModuleUserPortion,
+    rust_module: &ItemMod,
+) {
+    let source_map = WgslSourceMap::new(wgsl, wgsl_module, rust_module);
+    // naga lists the most specific span last
+    let span = diagnostic
+        .labels
+        .last()
+        .map(|(range, _)| source_map.rust_span(wgsl, range))
+        .unwrap_or_else(|| rust_module.ident.span());
+    let generated_code: Vec<String> = diagnostic
+        .labels
+        .iter()
+        .map(|(range, label)| {
+            let code = wgsl.get(range.clone()).unwrap_or_default();
+            if label.is_empty() {
+                format!("`{}`", code)
+            } else {
+                format!("{}: `{}`", label, code)
+            }
+        })
+        .collect();
+    emit_error!(
+        span,
+        "Generated WGSL is invalid: {}", diagnostic.message;
+        note = "In the generated WGSL: {}", generated_code.join(", ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_valid_wgsl() {
+        let wgsl = "override LEN: u32;\n@compute @workgroup_size(64, 1, 1)\nfn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { let x = iter_pos.x + LEN; }";
+        assert!(diagnose(wgsl).is_none());
+    }
+
+    #[test]
+    fn test_diagnose_type_error() {
+        let wgsl = "fn double(x: f32) -> f32 { return x * 2u; }";
+        let diagnostic = diagnose(wgsl).unwrap();
+        let (range, _) = diagnostic.labels.last().unwrap();
+        assert!(
+            wgsl[range.clone()].contains("x * 2u"),
+            "{}",
+            diagnostic.message
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Block, Expr, ExprLit, ForeignItemFn, Item, ItemFn, ItemMod, Lit, LitStr, Macro,
+    Meta, MetaNameValue, Stmt, parse_quote,
+};
+
+/// the `#[wgsl_extern(...)]` attribute of a function
+pub fn extern_attribute(attrs: &[Attribute]) -> Option<&Attribute> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("wgsl_extern"))
+}
+
+/// `path = "noise.wgsl"`
+pub fn parse_extern_path(tokens: TokenStream) -> syn::Result<LitStr> {
+    let name_value: MetaNameValue = syn::parse2(tokens)?;
+    match name_value.value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(path),
+            ..
+        }) if name_value.path.is_ident("path") => Ok(path),
+        _ => Err(syn::Error::new_spanned(
+            name_value,
+            "expected `path = \"file.wgsl\"`",
+        )),
+    }
+}
+
+/// the path of the WGSL file of a `#[wgsl_extern(path = "...")]` attribute
+pub fn extern_path(attr: &Attribute) -> syn::Result<LitStr> {
+    match &attr.meta {
+        Meta::List(list) => parse_extern_path(list.tokens.clone()),
+        meta => Err(syn::Error::new_spanned(
+            meta,
+            "expected `path = \"file.wgsl\"`",
+        )),
+    }
+}
+
+/// the `wgsl_raw!("...")` that is the whole body of a function
+pub fn raw_body(block: &Block) -> Option<&Macro> {
+    let mac = match block.stmts.as_slice() {
+        [Stmt::Macro(stmt_macro)] => &stmt_macro.mac,
+        [Stmt::Expr(Expr::Macro(expr_macro), None)] => &expr_macro.mac,
+        _ => return None,
+    };
+    mac.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "wgsl_raw")
+        .then_some(mac)
+}
+
+/// the body of a function without a rust version, `message` says how to give it one
+fn only_in_wgsl(message: String) -> TokenStream {
+    quote!({ panic!(#message) })
+}
+
+/**
+ Functions that are written in WGSL by hand, for what cannot be expressed in the rust subset that the macro translates:
+ - `#[wgsl_extern(path = "noise.wgsl")] fn simplex(p: Vec2F32) -> f32;` uses the functions of a `.wgsl` file, relative to the rust file like with `include_str!`. A rust body can be given for the CPU
+ - `fn simplex(p: Vec2F32) -> f32 { wgsl_raw!("return ...;") }` has a WGSL body, the signature is translated like any other function
+
+ The WGSL of both is added to the helper functions unchanged, see `helper_functions.rs`. On the CPU they panic, unless an extern function has a rust body.
+
+ `#[wgsl_extern] fn f();` is not a valid rust item, so declarations without a body get one that panics, and the rest of the pipeline treats them like any other function
+*/
+pub fn with_extern_bodies(mut module: ItemMod) -> ItemMod {
+    if let Some((_, items)) = &mut module.content {
+        for item in items.iter_mut() {
+            let Item::Verbatim(tokens) = item else {
+                continue;
+            };
+            let Ok(declaration) = syn::parse2::<ForeignItemFn>(tokens.clone()) else {
+                continue;
+            };
+            if extern_attribute(&declaration.attrs).is_some() {
+                *item = Item::Fn(declaration_with_body(declaration));
+            }
+        }
+    }
+    module
+}
+
+fn declaration_with_body(declaration: ForeignItemFn) -> ItemFn {
+    let body = only_in_wgsl(format!(
+        "`{}` is only implemented in WGSL, give `#[wgsl_extern]` a rust body to call it on the CPU",
+        declaration.sig.ident
+    ));
+    ItemFn {
+        attrs: declaration.attrs,
+        vis: declaration.vis,
+        sig: declaration.sig,
+        block: Box::new(parse_quote!(#body)),
+    }
+}
+
+/// `#[wgsl_extern]` on the CPU: the function, with a body if it has none, and an `include_str!` of the file so that the crate is rebuilt when the file changes
+pub fn expand_wgsl_extern(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let path = parse_extern_path(attr)?;
+    let item_fn = match syn::parse2::<ItemFn>(item.clone()) {
+        Ok(item_fn) => item_fn,
+        Err(_) => declaration_with_body(syn::parse2(item)?),
+    };
+    Ok(quote! {
+        #item_fn
+        const _: &str = include_str!(#path);
+    })
+}
+
+/// `wgsl_raw!` on the CPU
+pub fn expand_wgsl_raw(input: TokenStream) -> syn::Result<TokenStream> {
+    syn::parse2::<LitStr>(input)?;
+    Ok(only_in_wgsl(
+        "a function with a `wgsl_raw!` body is only implemented in WGSL, use `#[wgsl_extern]` with a rust body instead to call it on the CPU"
+            .to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    #[test]
+    fn test_extern_declarations_get_a_body() {
+        let module: ItemMod = syn::parse_str(
+            r#"mod m {
+                #[wgsl_extern(path = "noise.wgsl")]
+                fn simplex(p: Vec2F32) -> f32;
+                fn double(x: f32) -> f32 { wgsl_raw!("return x * 2.0;") }
+            }"#,
+        )
+        .unwrap();
+        let module = with_extern_bodies(module);
+        let items = module.content.unwrap().1;
+        let Item::Fn(simplex) = &items[0] else {
+            panic!("expected a function");
+        };
+        assert_eq!(
+            extern_path(extern_attribute(&simplex.attrs).unwrap())
+                .unwrap()
+                .value(),
+            "noise.wgsl"
+        );
+        assert_eq!(
+            simplex.block.to_token_stream().to_string(),
+            "{ panic ! (\"`simplex` is only implemented in WGSL, give `#[wgsl_extern]` a rust body to call it on the CPU\") }"
+        );
+        let Item::Fn(double) = &items[1] else {
+            panic!("expected a function");
+        };
+        assert_eq!(
+            raw_body(&double.block)
+                .unwrap()
+                .parse_body::<LitStr>()
+                .unwrap()
+                .value(),
+            "return x * 2.0;"
+        );
+        assert!(raw_body(&simplex.block).is_none());
+    }
+}
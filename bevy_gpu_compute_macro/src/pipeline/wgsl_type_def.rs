@@ -0,0 +1,323 @@
+use bevy_gpu_compute_core::wgsl::ast;
+use proc_macro_error::abort;
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote, quote_spanned};
+use syn::{
+    Field, Fields, ItemStruct, Token, TypePath,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    visit::Visit,
+    visit_mut::{self, VisitMut},
+};
+
+use super::{
+    allowed_types::WGSL_NATIVE_TYPES,
+    library_imports::type_source_macro,
+    phases::{
+        custom_type_collector::custom_type::{CustomType, CustomTypeKind},
+        gpu_resource_mngmnt_and_wgsl_generator::to_wgsl_syntax::lower_file_to_wgsl,
+    },
+};
+
+/// `#[derive(WgslType)]`: implements `WgslTypeDef` with the WGSL translation of the struct, and exports the source of the struct for the shader modules importing it
+pub fn derive_wgsl_type(item_struct: &ItemStruct) -> TokenStream {
+    validate_wgsl_type(item_struct);
+    let ident = &item_struct.ident;
+    let name = ident.to_string();
+    let custom_types = vec![CustomType::new(
+        ident,
+        CustomTypeKind::GpuOnlyHelperType,
+        item_struct.to_token_stream(),
+    )];
+    let mut wgsl_struct = item_struct.clone();
+    wgsl_struct.attrs.retain(|attr| attr.path().is_ident("doc"));
+    TypePathShortener.visit_item_struct_mut(&mut wgsl_struct);
+    let Some(ast::Item::Struct(lowered)) = lower_file_to_wgsl(
+        wgsl_struct.to_token_stream(),
+        &custom_types,
+        "derive(WgslType)".to_string(),
+    )
+    .pop() else {
+        abort!(ident, "Failed to translate the struct to WGSL");
+    };
+    let wgsl_code = ast::Item::Struct(lowered.clone()).to_string();
+    let Fields::Named(fields) = &item_struct.fields else {
+        unreachable!("only structs with named fields can be lowered to WGSL")
+    };
+    let layout_asserts = wgsl_layout_asserts(item_struct, &fields.named, &lowered.members);
+    let fields = fields
+        .named
+        .iter()
+        .zip(lowered.members.iter())
+        .map(|(field, member)| {
+            let field_ident = field.ident.as_ref().unwrap();
+            let field_name = field_ident.to_string();
+            let field_type = &field.ty;
+            let wgsl_type = member.ty.to_string();
+            quote! {
+                bevy_gpu_compute_core::wgsl::wgsl_type_def::WgslFieldLayout {
+                    name: #field_name.to_string(),
+                    wgsl_type: #wgsl_type.to_string(),
+                    offset: std::mem::offset_of!(#ident, #field_ident),
+                    size: std::mem::size_of::<#field_type>(),
+                },
+            }
+        });
+    let source_macro = type_source_macro(&wgsl_struct);
+    quote! {
+        impl bevy_gpu_compute_core::wgsl::wgsl_type_def::WgslTypeDef for #ident {
+            fn wgsl_type() -> bevy_gpu_compute_core::wgsl::shader_sections::WgslType {
+                bevy_gpu_compute_core::wgsl::shader_sections::WgslType {
+                    name: bevy_gpu_compute_core::wgsl::shader_custom_type_name::ShaderCustomTypeName::new(#name),
+                    code: bevy_gpu_compute_core::wgsl::shader_sections::WgslShaderModuleSectionCode {
+                        wgsl_code: #wgsl_code.to_string(),
+                    },
+                }
+            }
+            fn fields() -> Vec<bevy_gpu_compute_core::wgsl::wgsl_type_def::WgslFieldLayout> {
+                vec![#(#fields)*]
+            }
+        }
+
+        #(#layout_asserts)*
+
+        #source_macro
+    }
+}
+
+/// compile time checks that the rust offsets of the fields and the size of the struct are the ones WGSL gives them, the rust vector types are only aligned to their components, so fields after them can need padding
+fn wgsl_layout_asserts(
+    item_struct: &ItemStruct,
+    fields: &Punctuated<Field, Token![,]>,
+    members: &[ast::Member],
+) -> Vec<TokenStream> {
+    let ident = &item_struct.ident;
+    let mut asserts = Vec::new();
+    let mut offset = 0usize;
+    let mut struct_align = 1;
+    for (field, member) in fields.iter().zip(members.iter()) {
+        let Some((align, size)) = wgsl_layout(&member.ty) else {
+            abort!(
+                field.ty,
+                "The WGSL layout of `{}` is unknown, the fields of a `WgslType` can only be scalars, vectors, matrices and arrays of them with a literal length",
+                member.ty
+            );
+        };
+        offset = offset.next_multiple_of(align);
+        struct_align = struct_align.max(align);
+        let field_ident = field.ident.as_ref().unwrap();
+        let message = format!(
+            "`{}.{}` has to be at byte {} like in WGSL, where `{}` is aligned to {} bytes. Add padding fields before it",
+            ident, field_ident, offset, member.ty, align
+        );
+        asserts.push(quote_spanned! {field.span()=>
+            const _: () = assert!(std::mem::offset_of!(#ident, #field_ident) == #offset, #message);
+        });
+        offset += size;
+    }
+    let size = offset.next_multiple_of(struct_align);
+    let message = format!(
+        "`{}` has to be {} bytes like in WGSL, so that it has the same stride in a buffer. Add padding fields at its end",
+        ident, size
+    );
+    asserts.push(quote_spanned! {ident.span()=>
+        const _: () = assert!(std::mem::size_of::<#ident>() == #size, #message);
+    });
+    asserts
+}
+
+/// the alignment and size in bytes of a scalar, vector, matrix or array in a WGSL storage buffer
+fn wgsl_layout(ty: &ast::Type) -> Option<(usize, usize)> {
+    let element = || match ty.template_args.first() {
+        Some(ast::TemplateArg::Type(element)) => Some(element),
+        _ => None,
+    };
+    // vec2 is aligned to twice its component, vec3 and vec4 to four times
+    let vector = |len: usize, component: &ast::Type| {
+        let (component_size, _) = wgsl_layout(component)?;
+        let align = if len == 2 { 2 } else { 4 } * component_size;
+        Some((align, len * component_size))
+    };
+    match ty.name.as_str() {
+        "f32" | "i32" | "u32" => Some((4, 4)),
+        "f16" => Some((2, 2)),
+        "vec2" | "vec3" | "vec4" => vector(ty.name[3..].parse().ok()?, element()?),
+        // a matrix is an array of its columns
+        name if name.starts_with("mat") => {
+            let (columns, rows) = name[3..].split_once('x')?;
+            let (align, size) = vector(rows.parse().ok()?, element()?)?;
+            Some((
+                align,
+                columns.parse::<usize>().ok()? * size.next_multiple_of(align),
+            ))
+        }
+        "array" => {
+            let (align, size) = wgsl_layout(element()?)?;
+            let Some(ast::TemplateArg::Expr(ast::Expr::Literal(len))) = ty.template_args.get(1)
+            else {
+                return None;
+            };
+            let len: usize = len.trim_end_matches(['u', 'i']).parse().ok()?;
+            Some((align, len * size.next_multiple_of(align)))
+        }
+        _ => None,
+    }
+}
+
+/// `wgsl_helpers::Vec2F32` => `Vec2F32`, the struct is used outside of the shader modules, where the helper types are not always imported
+struct TypePathShortener;
+
+impl VisitMut for TypePathShortener {
+    fn visit_type_path_mut(&mut self, t: &mut TypePath) {
+        visit_mut::visit_type_path_mut(self, t);
+        if t.qself.is_none() {
+            let last = t.path.segments.pop().unwrap().into_value();
+            t.path.leading_colon = None;
+            t.path.segments.clear();
+            t.path.segments.push(last);
+        }
+    }
+}
+
+/// the rust layout of the struct has to match its WGSL layout, so that it can be copied into buffers
+fn validate_wgsl_type(item_struct: &ItemStruct) {
+    if !item_struct.generics.params.is_empty() {
+        abort!(
+            item_struct.generics,
+            "A `WgslType` cannot be generic, WGSL has no generic structs"
+        );
+    }
+    let mut repr_c = false;
+    for attr in item_struct
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        let _ = attr.parse_nested_meta(|meta| {
+            repr_c |= meta.path.is_ident("C");
+            Ok(())
+        });
+    }
+    if !repr_c {
+        abort!(
+            item_struct.ident,
+            "A `WgslType` needs `#[repr(C)]`, so that its fields are laid out in the order they are declared in, like in WGSL"
+        );
+    }
+    let mut bools = BoolFinder(None);
+    bools.visit_fields(&item_struct.fields);
+    if let Some(ident) = bools.0 {
+        abort!(
+            ident,
+            "A `WgslType` cannot contain bools, since bools cannot be stored in GPU buffers. Use a `u32` instead"
+        );
+    }
+}
+
+/// `bool` and the `Vec3Bool` like helper types
+struct BoolFinder(Option<proc_macro2::Ident>);
+
+impl<'ast> Visit<'ast> for BoolFinder {
+    fn visit_ident(&mut self, ident: &'ast proc_macro2::Ident) {
+        let name = ident.to_string();
+        let is_bool = WGSL_NATIVE_TYPES.contains(&name.as_str())
+            && (name == "bool" || name.ends_with("Bool"));
+        if self.0.is_none() && is_bool {
+            self.0 = Some(ident.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_derive_wgsl_type() {
+        let item_struct: ItemStruct = parse_quote! {
+            /// a moving point
+            #[derive(WgslType, Clone, Copy)]
+            #[repr(C)]
+            pub struct Particle {
+                pub position: Vec2F32,
+                pub mass: f32,
+            }
+        };
+        let tokens = derive_wgsl_type(&item_struct).to_string();
+        assert!(tokens.contains(
+            "\"// a moving point\\nstruct Particle {\\n    position: vec2<f32>,\\n    mass: f32,\\n}\""
+        ));
+        assert!(tokens.contains("wgsl_type : \"vec2<f32>\""));
+        assert!(tokens.contains("offset : std :: mem :: offset_of ! (Particle , mass)"));
+        assert!(tokens.contains("macro_rules ! __wgsl_type_source_Particle"));
+        assert!(tokens.contains("offset_of ! (Particle , mass) == 8usize"));
+        assert!(tokens.contains("size_of :: < Particle > () == 16usize"));
+    }
+
+    #[test]
+    fn test_wgsl_layout() {
+        let layout = |ty: syn::Type| {
+            let item: ItemStruct = parse_quote!(
+                struct S {
+                    field: #ty,
+                }
+            );
+            let Some(ast::Item::Struct(lowered)) =
+                lower_file_to_wgsl(item.to_token_stream(), &vec![], "test".to_string()).pop()
+            else {
+                panic!("expected a struct");
+            };
+            wgsl_layout(&lowered.members[0].ty)
+        };
+        assert_eq!(layout(parse_quote!(f32)), Some((4, 4)));
+        assert_eq!(layout(parse_quote!(Vec2F32)), Some((8, 8)));
+        assert_eq!(layout(parse_quote!(Vec3F32)), Some((16, 12)));
+        assert_eq!(layout(parse_quote!(Vec3F16)), Some((8, 6)));
+        assert_eq!(layout(parse_quote!(Mat3x3F32)), Some((16, 48)));
+        assert_eq!(layout(parse_quote!(Mat4x2F32)), Some((8, 32)));
+        assert_eq!(layout(parse_quote!([Vec3F32; 2])), Some((16, 32)));
+        // other structs
+        assert_eq!(
+            wgsl_layout(&ast::Type {
+                name: "Particle".to_string(),
+                template_args: vec![],
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_only_bool_types_are_rejected_as_bools() {
+        let find = |item: ItemStruct| {
+            let mut bools = BoolFinder(None);
+            bools.visit_fields(&item.fields);
+            bools.0.map(|ident| ident.to_string())
+        };
+        assert_eq!(
+            find(parse_quote!(
+                struct S {
+                    a: [Vec3Bool; 2],
+                }
+            )),
+            Some("Vec3Bool".to_string())
+        );
+        assert_eq!(
+            find(parse_quote!(
+                struct S {
+                    a: bool,
+                }
+            )),
+            Some("bool".to_string())
+        );
+        assert_eq!(
+            find(parse_quote!(
+                struct S {
+                    is_bool: u32,
+                    flag: crate::flags::HasBool,
+                }
+            )),
+            None
+        );
+    }
+}
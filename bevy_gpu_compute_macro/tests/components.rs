@@ -3,16 +3,19 @@
 use std::collections::HashMap;
 
 use bevy_gpu_compute_core::{
-    TypeErasedArrayOutputData, TypesSpec,
+    IterSpaceDimmension, TypeErasedArrayOutputData, TypesSpec,
     wgsl::{
         shader_custom_type_name::ShaderCustomTypeName,
-        shader_module::user_defined_portion::WgslShaderModuleUserPortion,
+        shader_module::{
+            complete_shader_module::WgslShaderModule,
+            user_defined_portion::WgslShaderModuleUserPortion,
+        },
         shader_sections::{
-            WgslConstAssignment, WgslFunction, WgslInputArray, WgslOutputArray,
+            WgslConstAssignment, WgslEntryPoint, WgslFunction, WgslInputArray, WgslOutputArray,
             WgslShaderModuleSectionCode, WgslType,
         },
     },
-    wgsl_helpers::WgslIterationPosition,
+    wgsl_helpers::{self, WgslIterationPosition},
 };
 use bevy_gpu_compute_macro::wgsl_shader_module;
 use pretty_assertions::assert_eq;
@@ -34,11 +37,11 @@ fn test_simple_struct() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.helper_types.len() == 1);
     assert_eq!(
-        t2.main_function.unwrap().code.wgsl_code,
-        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) {}"
+        t2.entry_points[0].function.code.wgsl_code,
+        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) {} // tests/components.rs:33"
     );
 }
 
@@ -62,11 +65,11 @@ fn test_struct_creation() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.helper_types.len() == 1);
     assert_eq!(
-        t2.main_function.unwrap().code.wgsl_code,
-        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { let obj = TStruct(1.0, 2.0); }"
+        t2.entry_points[0].function.code.wgsl_code,
+        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { // tests/components.rs:58\n    let obj = TStruct(1.0, 2.0); // tests/components.rs:59\n}"
     );
 }
 
@@ -93,13 +96,13 @@ fn test_struct_creation_with_nested_transforms() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
 
     assert!(t2.helper_types.len() == 1);
     assert_eq!(
-        t2.main_function.unwrap().code.wgsl_code,
-        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>)\n{ let obj = TStruct(1.0,vec3<f32>(2.0, 3.0, 4.0)); }"
+        t2.entry_points[0].function.code.wgsl_code,
+        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { // tests/components.rs:86\n    let obj = TStruct(1.0, vec3<f32>(2.0, 3.0, 4.0)); // tests/components.rs:87\n}"
     );
 }
 #[test]
@@ -116,11 +119,11 @@ fn test_type_alias() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.helper_types.len() == 1);
     assert_eq!(
         t2.helper_types.first().unwrap().code.wgsl_code,
-        "alias MyType  = i32;"
+        "alias MyType = i32; // tests/components.rs:113"
     );
 }
 #[test]
@@ -137,12 +140,12 @@ fn test_consts() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.len() == 1);
     assert!(t2.helper_types.is_empty());
     assert_eq!(
         t2.static_consts.first().unwrap().code.wgsl_code,
-        "const MY_CONST : i32 = 3;"
+        "const MY_CONST: i32 = 3; // tests/components.rs:134"
     );
 }
 
@@ -164,12 +167,12 @@ fn test_output_arrays() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
     assert_eq!(
         t2.output_arrays.first().unwrap().item_type.code.wgsl_code,
-        "struct CollisionResult { entity1 : u32, entity2 : u32, }"
+        "struct CollisionResult { // tests/components.rs:159\n    entity1: u32,\n    entity2: u32,\n}"
     );
 
     assert!(
@@ -198,12 +201,12 @@ fn test_helper_functions() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.len() == 1);
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
     assert_eq!(
         t2.helper_functions.first().unwrap().code.wgsl_code,
-        "fn calculate_distance_squared(p1 : array < f32, 2 > , p2 : array < f32, 2 >)\n-> f32\n{\n    let dx = p1 [0] - p2 [0]; let dy = p1 [1] - p2 [1]; return dx * dx + dy *\n    dy;\n}"
+        "fn calculate_distance_squared(p1: array<f32, 2>, p2: array<f32, 2>) -> f32 { // tests/components.rs:192\n    let dx = p1[0] - p2[0]; // tests/components.rs:193\n    let dy = p1[1] - p2[1]; // tests/components.rs:194\n    return dx * dx + dy * dy; // tests/components.rs:195\n}"
     );
 }
 
@@ -244,11 +247,11 @@ fn test_simple_type_transforms() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.helper_types.len() == 1);
     assert_eq!(
         t2.helper_types.first().unwrap().code.wgsl_code,
-        "struct TStruct { x : f32, y : vec3 < f32 > , }"
+        "struct TStruct { // tests/components.rs:238\n    x: f32,\n    y: vec3<f32>,\n}"
     );
 }
 
@@ -269,7 +272,7 @@ fn test_doc_comments() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.len() == 1);
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
 }
@@ -292,12 +295,12 @@ fn test_type_casting_and_implicit_returns() {
     assert!(t2.output_arrays.is_empty());
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
     assert_eq!(
         t2.helper_functions.first().unwrap().code.wgsl_code,
-        "fn helper() -> f32\n{\n    let x = f32(1); let y = f32(3.5); let z = f32(54.4); if true\n    { return y + z; } else { return x; }\n}"
+        "fn helper() -> f32 { // tests/components.rs:285\n    let x = f32(1); // tests/components.rs:286\n    let y = 3.5f; // tests/components.rs:287\n    let z = 54.4f; // tests/components.rs:288\n    if true { // tests/components.rs:289\n        return y + z; // tests/components.rs:289\n    } else {\n        return x; // tests/components.rs:289\n    }\n}"
     );
 }
 #[test]
@@ -317,12 +320,12 @@ fn test_mutable_variables() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
     assert_eq!(
-        t2.main_function.unwrap().code.wgsl_code,
-        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>)\n{ var x = 1; let x1 = x; x = 2; }"
+        t2.entry_points[0].function.code.wgsl_code,
+        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { // tests/components.rs:312\n    var x = 1; // tests/components.rs:313\n    let x1 = x; // tests/components.rs:314\n    x = 2; // tests/components.rs:315\n}"
     );
 }
 
@@ -343,13 +346,13 @@ fn test_input_arrays() {
     assert!(t2.uniforms.is_empty());
     // type Position = array<f32, 2>;
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
 
     assert_eq!(
         t2.input_arrays.first().unwrap().item_type.code.wgsl_code,
-        "alias Position  = array < f32, 2 > ;"
+        "alias Position = array<f32, 2>; // tests/components.rs:339"
     )
 }
 #[test]
@@ -424,12 +427,12 @@ fn test_output_vec() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.is_empty());
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
     assert_eq!(
         t2.output_arrays.first().unwrap().item_type.code.wgsl_code,
-        "struct CollisionResult { entity1 : u32, entity2 : u32, }"
+        "struct CollisionResult { // tests/components.rs:419\n    entity1: u32,\n    entity2: u32,\n}"
     );
 
     assert!(
@@ -510,8 +513,7 @@ fn test_entire_collision_shader() {
     }
     let t2 = collision_shader::parsed();
 
-    let user_portion = WgslShaderModuleUserPortion { static_consts: vec![WgslConstAssignment { code: WgslShaderModuleSectionCode {  wgsl_code: "const EXAMPLE_MODULE_CONST : u32 = 42;".to_string() } }], helper_types: vec![], uniforms: vec![WgslType { name: ShaderCustomTypeName::new("Uniforms"), code: WgslShaderModuleSectionCode { wgsl_code: "struct Uniforms { time : f32, resolution : vec2 < f32 > , }".to_string() } }], input_arrays: vec![WgslInputArray { item_type: WgslType { name: ShaderCustomTypeName::new("Position"), code: WgslShaderModuleSectionCode {  wgsl_code: "alias Position  = array < f32, 2 > ;".to_string() } } }, WgslInputArray { item_type: WgslType { name: ShaderCustomTypeName::new("Radius") , code: WgslShaderModuleSectionCode {  wgsl_code: "alias Radius  = f32;".to_string() } } }], output_arrays: vec![WgslOutputArray { item_type: WgslType { name: ShaderCustomTypeName::new("CollisionResult"), code: WgslShaderModuleSectionCode { wgsl_code: "struct CollisionResult { entity1 : u32, entity2 : u32, }".to_string() } }, atomic_counter_name: Some("collisionresult_counter".to_string()) }], helper_functions: vec![WgslFunction { name: "calculate_distance_squared".to_string(), code: WgslShaderModuleSectionCode {  wgsl_code: "fn calculate_distance_squared(p1 : array < f32, 2 > , p2 : array < f32, 2 >)\n-> f32\n{\n    let dx = p1 [0] - p2 [0]; let dy = p1 [1] - p2 [1]; return dx * dx + dy *\n    dy;\n}".to_string() } }], main_function: Some(WgslFunction { name: "main".to_owned(), code: WgslShaderModuleSectionCode {  wgsl_code: "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>)\n{\n    let current_entity = iter_pos.x; let other_entity = iter_pos.y; if\n    current_entity >= POSITION_INPUT_ARRAY_LENGTH || other_entity >=\n    POSITION_INPUT_ARRAY_LENGTH || current_entity == other_entity ||\n    current_entity >= other_entity { return; } let current_radius =\n    radius_input_array [current_entity]; let other_radius = radius_input_array\n    [other_entity]; if current_radius <= 0.0 || other_radius <= 0.0\n    { return; } let current_pos = position_input_array [current_entity]; let\n    other_pos = position_input_array [other_entity]; let dist_squared =\n    calculate_distance_squared(current_pos, other_pos); let radius_sum =\n    current_radius + other_radius; if dist_squared < radius_sum * radius_sum\n    {\n        {\n            let collisionresult_output_array_index =\n            atomicAdd(& collisionresult_counter, 1u); if\n            collisionresult_output_array_index <\n            COLLISIONRESULT_OUTPUT_ARRAY_LENGTH\n            {\n                collisionresult_output_array\n                [collisionresult_output_array_index] =
-                CollisionResult(current_entity, other_entity);\n            }\n        };\n    }\n}".to_owned() } }),
+    let user_portion = WgslShaderModuleUserPortion { static_consts: vec![WgslConstAssignment { code: WgslShaderModuleSectionCode {  wgsl_code: "const EXAMPLE_MODULE_CONST: u32 = 42; // tests/components.rs:463".to_string() } }], helper_types: vec![], uniforms: vec![WgslType { name: ShaderCustomTypeName::new("Uniforms"), code: WgslShaderModuleSectionCode { wgsl_code: "struct Uniforms { // tests/components.rs:465\n    time: f32,\n    resolution: vec2<f32>,\n}".to_string() } }], input_arrays: vec![WgslInputArray { item_type: WgslType { name: ShaderCustomTypeName::new("Position"), code: WgslShaderModuleSectionCode {  wgsl_code: "alias Position = array<f32, 2>; // tests/components.rs:470".to_string() } } }, WgslInputArray { item_type: WgslType { name: ShaderCustomTypeName::new("Radius") , code: WgslShaderModuleSectionCode {  wgsl_code: "alias Radius = f32; // tests/components.rs:472".to_string() } } }], output_arrays: vec![WgslOutputArray { item_type: WgslType { name: ShaderCustomTypeName::new("CollisionResult"), code: WgslShaderModuleSectionCode { wgsl_code: "struct CollisionResult { // tests/components.rs:475\n    entity1: u32,\n    entity2: u32,\n}".to_string() } }, atomic_counter_name: Some("collisionresult_counter".to_string()) }], helper_functions: vec![WgslFunction { name: "calculate_distance_squared".to_string(), code: WgslShaderModuleSectionCode {  wgsl_code: "fn calculate_distance_squared(p1: array<f32, 2>, p2: array<f32, 2>) -> f32 { // tests/components.rs:479\n    let dx = p1[0] - p2[0]; // tests/components.rs:480\n    let dy = p1[1] - p2[1]; // tests/components.rs:481\n    return dx * dx + dy * dy; // tests/components.rs:482\n}".to_string() } }], entry_points: vec![WgslEntryPoint { function: WgslFunction { name: "main".to_owned(), code: WgslShaderModuleSectionCode {  wgsl_code: "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { // tests/components.rs:484\n    // * USER GENERATED LOGIC\n    let current_entity = iter_pos.x; // tests/components.rs:486\n    let other_entity = iter_pos.y; // tests/components.rs:487\n    // Early exit if invalid entity or zero radius\n    if current_entity >= POSITION_INPUT_ARRAY_LENGTH || other_entity >= POSITION_INPUT_ARRAY_LENGTH || current_entity == other_entity || current_entity >= other_entity { // tests/components.rs:489\n        return; // tests/components.rs:494\n    }\n    let current_radius = radius_input_array[current_entity]; // tests/components.rs:496\n    let other_radius = radius_input_array[other_entity]; // tests/components.rs:497\n    if current_radius <= 0.0 || other_radius <= 0.0 { // tests/components.rs:498\n        return; // tests/components.rs:499\n    }\n    let current_pos = position_input_array[current_entity]; // tests/components.rs:501\n    let other_pos = position_input_array[other_entity]; // tests/components.rs:502\n    let dist_squared = calculate_distance_squared(current_pos, other_pos); // tests/components.rs:503\n    let radius_sum = current_radius + other_radius; // tests/components.rs:504\n    // Compare squared distances to avoid sqrt\n    if dist_squared < radius_sum * radius_sum { // tests/components.rs:506\n        { // tests/components.rs:507\n            let collisionresult_output_array_index = atomicAdd(&collisionresult_counter, 1u);\n            if collisionresult_output_array_index < COLLISIONRESULT_OUTPUT_ARRAY_LENGTH { // tests/components.rs:507\n                collisionresult_output_array[collisionresult_output_array_index] = CollisionResult(current_entity, other_entity); // tests/components.rs:507\n            }\n        }\n    }\n}".to_owned() } }, workgroup_size: None }],
         binding_numbers_by_variable_name: Some(HashMap::from([
             ("uniforms".to_string(), 1),
             ("position_input_array".to_string(), 2),
@@ -582,12 +584,12 @@ fn test_uniforms() {
     assert!(t2.input_arrays.is_empty());
     assert!(t2.uniforms.len() == 1);
     assert!(t2.helper_functions.is_empty());
-    assert!(t2.main_function.is_some());
+    assert_eq!(t2.entry_points.len(), 1);
     assert!(t2.static_consts.is_empty());
     assert!(t2.helper_types.is_empty());
     assert_eq!(
         t2.uniforms.first().unwrap().code.wgsl_code,
-        "struct Uniforms { time : f32, resolution : vec2 < f32 > , }"
+        "struct Uniforms { // tests/components.rs:574\n    time: f32,\n    resolution: vec2<f32>,\n}"
     );
 }
 
@@ -733,3 +735,1296 @@ fn test_functions_exposed_for_rust() {
         }]
     );
 }
+
+#[test]
+fn test_for_loops() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn sum_to(n: u32) -> u32 {
+            let mut total: u32 = 100;
+            for i in 0..n {
+                total += i;
+            }
+            for i in (0..=n).rev() {
+                total -= i;
+            }
+            total
+        }
+        pub fn sum_all(values: [f32; 4]) -> f32 {
+            let mut total = 0.0;
+            for v in values {
+                total += v;
+            }
+            total
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert!(t2.helper_functions.len() == 2);
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn sum_to(n: u32) -> u32 { // tests/components.rs:744\n    var total: u32 = 100; // tests/components.rs:745\n    for (var i: u32 = 0; i < n; i += 1u) { // tests/components.rs:746\n        total += i; // tests/components.rs:747\n    }\n    { // tests/components.rs:749\n        var i: u32 = n;\n        loop {\n            if i < 0 {\n                break;\n            }\n            total -= i; // tests/components.rs:750\n            continuing {\n                let _last_i = i == 0;\n                i -= 1u;\n                break if _last_i;\n            }\n        }\n    }\n    return total; // tests/components.rs:752\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn sum_all(values: array<f32, 4>) -> f32 { // tests/components.rs:754\n    var total = 0.0; // tests/components.rs:755\n    for (var _idx_v: u32 = 0; _idx_v < 4; _idx_v += 1u) { // tests/components.rs:756\n        let v = values[_idx_v];\n        total += v; // tests/components.rs:757\n    }\n    return total; // tests/components.rs:759\n}"
+    );
+    // the cpu version keeps the original rust loops
+    assert_eq!(test_module::sum_to(4), 96);
+    assert_eq!(test_module::sum_all([1.0, 2.0, 3.0, 4.0]), 10.0);
+}
+
+#[test]
+fn test_loops_and_labeled_breaks() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn next_power_of_two(n: u32) -> u32 {
+            let mut p: u32 = 1;
+            loop {
+                if p >= n {
+                    break p;
+                }
+                p *= 2;
+            }
+        }
+        pub fn find_factors(product: u32) -> u32 {
+            let mut found: u32 = 0;
+            'outer: for i in 2..10 {
+                for j in 2..10 {
+                    if i * j == product {
+                        found = i * 10 + j;
+                        break 'outer;
+                    }
+                }
+            }
+            found
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert!(t2.helper_functions.len() == 2);
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn next_power_of_two(n: u32) -> u32 { // tests/components.rs:783\n    var p: u32 = 1; // tests/components.rs:784\n    var _loop_value_0: u32; // tests/components.rs:783\n    loop { // tests/components.rs:785\n        if p >= n { // tests/components.rs:786\n            _loop_value_0 = p; // tests/components.rs:787\n            break;\n        }\n        p *= 2u; // tests/components.rs:789\n    }\n    return _loop_value_0;\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn find_factors(product: u32) -> u32 { // tests/components.rs:792\n    var found: u32 = 0; // tests/components.rs:793\n    var _break_outer = false;\n    for (var i: u32 = 2; i < 10; i += 1u) { // tests/components.rs:794\n        for (var j: u32 = 2; j < 10; j += 1u) { // tests/components.rs:795\n            if i * j == product { // tests/components.rs:796\n                found = i * 10 + j; // tests/components.rs:797\n                _break_outer = true;\n                break;\n            }\n        }\n        if _break_outer {\n            break;\n        }\n    }\n    return found; // tests/components.rs:802\n}"
+    );
+    assert_eq!(test_module::next_power_of_two(5), 8);
+    assert_eq!(test_module::find_factors(12), 26);
+}
+
+#[test]
+fn test_match_to_switch() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn roughness(material_id: u32) -> f32 {
+            match material_id {
+                0 => 0.1,
+                1 | 2 => 0.5,
+                _ => 1.0,
+            }
+        }
+        pub fn advance(state: u32) -> u32 {
+            let mut next: u32 = state;
+            match state {
+                0 => {
+                    next += 1;
+                }
+                other => next = other + 10,
+            }
+            next
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert!(t2.helper_functions.len() == 2);
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn roughness(material_id: u32) -> f32 { // tests/components.rs:825\n    var _match_value_0: f32; // tests/components.rs:825\n    switch material_id { // tests/components.rs:826\n        case 0u: {\n            _match_value_0 = 0.1; // tests/components.rs:827\n        }\n        case 1u, 2u: {\n            _match_value_0 = 0.5; // tests/components.rs:828\n        }\n        default: {\n            _match_value_0 = 1.0; // tests/components.rs:829\n        }\n    }\n    return _match_value_0;\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn advance(state: u32) -> u32 { // tests/components.rs:832\n    var next: u32 = state; // tests/components.rs:833\n    switch state { // tests/components.rs:834\n        case 0u: {\n            next += 1u; // tests/components.rs:836\n        }\n        default: {\n            let other = state;\n            next = other + 10; // tests/components.rs:838\n        }\n    }\n    return next; // tests/components.rs:840\n}"
+    );
+    assert_eq!(test_module::roughness(2), 0.5);
+    assert_eq!(test_module::advance(0), 1);
+    assert_eq!(test_module::advance(3), 13);
+}
+
+#[test]
+fn test_value_producing_ifs_and_blocks() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn relu(d: f32) -> f32 {
+            let r = if d > 0.0 { d } else { 0.0 };
+            r
+        }
+        pub fn shade(d: f32) -> f32 {
+            let scaled: f32 = if d > 1.0 {
+                let excess = d - 1.0;
+                1.0 + excess * 0.5
+            } else {
+                relu(d)
+            };
+            let doubled: f32 = {
+                let two = 2.0;
+                scaled * two
+            };
+            doubled
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert!(t2.helper_functions.len() == 2);
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn relu(d: f32) -> f32 { // tests/components.rs:864\n    let r = select(0.0, d, d > 0.0); // tests/components.rs:865\n    return r; // tests/components.rs:866\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn shade(d: f32) -> f32 { // tests/components.rs:868\n    var _if_value_0: f32; // tests/components.rs:869\n    if d > 1.0 { // tests/components.rs:869\n        let excess = d - 1.0; // tests/components.rs:870\n        _if_value_0 = 1.0 + excess * 0.5; // tests/components.rs:871\n    } else {\n        _if_value_0 = relu(d); // tests/components.rs:873\n    }\n    let scaled: f32 = _if_value_0; // tests/components.rs:869\n    var _block_value_1: f32; // tests/components.rs:875\n    { // tests/components.rs:875\n        let two = 2.0; // tests/components.rs:876\n        _block_value_1 = scaled * two; // tests/components.rs:877\n    }\n    let doubled: f32 = _block_value_1; // tests/components.rs:875\n    return doubled; // tests/components.rs:879\n}"
+    );
+    assert_eq!(test_module::relu(-2.0), 0.0);
+    assert_eq!(test_module::shade(3.0), 4.0);
+}
+
+#[test]
+fn test_vector_and_matrix_arithmetic() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn transform(m: Mat2x2F32, v: Vec2F32, offset: f32) -> Vec2F32 {
+            let moved = m * v + offset;
+            -(moved * 2.0) / Vec2F32::new(1.0, 4.0)
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn transform(m: mat2x2<f32>, v: vec2<f32>, offset: f32) -> vec2<f32> { // tests/components.rs:902\n    let moved = m * v + offset; // tests/components.rs:903\n    return -(moved * 2.0) / vec2<f32>(1.0, 4.0); // tests/components.rs:904\n}"
+    );
+    let m = wgsl_helpers::Mat2x2F32::new(
+        wgsl_helpers::Vec2F32::new(1.0, 2.0),
+        wgsl_helpers::Vec2F32::new(3.0, 4.0),
+    );
+    assert_eq!(
+        test_module::transform(m, wgsl_helpers::Vec2F32::new(1.0, 1.0), 1.0),
+        wgsl_helpers::Vec2F32::new(-10.0, -3.5)
+    );
+}
+
+#[test]
+fn test_wgsl_builtins() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_builtins;
+        use bevy_gpu_compute_core::wgsl_builtins::*;
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn shade(normal: Vec3F32, light: Vec3F32, bits: u32) -> f32 {
+            let diffuse = max(dot(normalize(normal), normalize(light)), 0.0);
+            let falloff = smoothstep(0.0, 10.0, wgsl_builtins::length(light));
+            let weight = countOneBits(bits) as f32;
+            mix(diffuse, falloff, 0.5) * weight
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn shade(normal: vec3<f32>, light: vec3<f32>, bits: u32) -> f32 { // tests/components.rs:930\n    let diffuse = max(dot(normalize(normal), normalize(light)), 0.0); // tests/components.rs:931\n    let falloff = smoothstep(0.0, 10.0, length(light)); // tests/components.rs:932\n    let weight = f32(countOneBits(bits)); // tests/components.rs:933\n    return mix(diffuse, falloff, 0.5) * weight; // tests/components.rs:934\n}"
+    );
+    let result = test_module::shade(
+        wgsl_helpers::Vec3F32::new(0.0, 0.0, 2.0),
+        wgsl_helpers::Vec3F32::new(0.0, 0.0, 5.0),
+        0b11,
+    );
+    assert_eq!(result, 1.5);
+}
+
+#[test]
+fn test_method_calls_become_builtins() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn reflect_light(light: Vec3F32, normal: Vec3F32, m: Mat2x2F32) -> f32 {
+            let n = normal.normalize();
+            let r = light.reflect(n);
+            let scale = m.transpose().determinant().abs();
+            r.length().powf(2.0) * scale + light.dot(n).min(0.0)
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn reflect_light(light: vec3<f32>, normal: vec3<f32>, m: mat2x2<f32>) -> f32 { // tests/components.rs:956\n    let n = normalize(normal); // tests/components.rs:957\n    let r = reflect(light, n); // tests/components.rs:958\n    let scale = abs(determinant(transpose(m))); // tests/components.rs:959\n    return pow(length(r), 2.0) * scale + min(dot(light, n), 0.0); // tests/components.rs:960\n}"
+    );
+    let result = test_module::reflect_light(
+        wgsl_helpers::Vec3F32::new(3.0, -4.0, 0.0),
+        wgsl_helpers::Vec3F32::new(0.0, 2.0, 0.0),
+        wgsl_helpers::Mat2x2F32::new(
+            wgsl_helpers::Vec2F32::new(1.0, 2.0),
+            wgsl_helpers::Vec2F32::new(3.0, 4.0),
+        ),
+    );
+    assert_eq!(result, 46.0);
+}
+
+#[test]
+fn test_swizzles() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn recolor(color: Vec4F32, offset: Vec2F32) -> Vec4F32 {
+            let mut result = color;
+            result.set_x(offset.y);
+            result.set_zy(color.xy() + offset);
+            result.set_a(color.bgr().dot(Vec3F32::new(1.0, 1.0, 1.0)));
+            return result.wzyx();
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn recolor(color: vec4<f32>, offset: vec2<f32>) -> vec4<f32> { // tests/components.rs:985\n    var result = color; // tests/components.rs:986\n    result.x = offset.y; // tests/components.rs:987\n    { // tests/components.rs:988\n        let swizzle_value = color.xy + offset; // tests/components.rs:988\n        result.z = swizzle_value.x; // tests/components.rs:988\n        result.y = swizzle_value.y; // tests/components.rs:988\n    }\n    result.a = dot(color.bgr, vec3<f32>(1.0, 1.0, 1.0)); // tests/components.rs:989\n    return result.wzyx; // tests/components.rs:990\n}"
+    );
+    let result = test_module::recolor(
+        wgsl_helpers::Vec4F32::new(1.0, 2.0, 3.0, 4.0),
+        wgsl_helpers::Vec2F32::new(10.0, 20.0),
+    );
+    assert_eq!(result, wgsl_helpers::Vec4F32::new(6.0, 11.0, 22.0, 20.0));
+}
+
+#[test]
+fn test_fixed_size_arrays() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        const LUT: [f32; 4] = [0.5, 1.0, 1.5, 2.0];
+        struct Particle {
+            pub hist: [u32; 4],
+        }
+        pub fn weighted_sum(p: Particle) -> f32 {
+            let mut acc = [0.0f32; 4];
+            let fixed: [u32; 2] = [1, 2];
+            for i in 0..4 {
+                acc[i] = LUT[i] * (p.hist[i] + fixed[i % 2]) as f32;
+            }
+            return acc[0] + acc[1] + acc[2] + acc[3];
+        }
+        pub fn pick(p: Particle, i: u32) -> f32 {
+            let local: [f32; 4] = [0.5, 1.0, 1.5, 2.0];
+            local[i] + p.hist[i] as f32
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.static_consts[0].code.wgsl_code,
+        "const LUT: array<f32, 4> = array<f32, 4>(0.5, 1.0, 1.5, 2.0); // tests/components.rs:1011"
+    );
+    assert_eq!(
+        t2.helper_types[0].code.wgsl_code,
+        "struct Particle { // tests/components.rs:1012\n    hist: array<u32, 4>,\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn weighted_sum(p: Particle) -> f32 { // tests/components.rs:1015\n    var acc = array<f32, 4>(); // tests/components.rs:1016\n    let fixed: array<u32, 2> = array<u32, 2>(1, 2); // tests/components.rs:1017\n    for (var i: u32 = 0; i < 4; i += 1u) { // tests/components.rs:1018\n        acc[i] = LUT[i] * f32(p.hist[i] + fixed[i % 2]); // tests/components.rs:1019\n    }\n    return acc[0] + acc[1] + acc[2] + acc[3]; // tests/components.rs:1021\n}"
+    );
+    // a `u32` index is cast to `usize` on the CPU
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn pick(p: Particle, i: u32) -> f32 { // tests/components.rs:1023\n    let local: array<f32, 4> = array<f32, 4>(0.5, 1.0, 1.5, 2.0); // tests/components.rs:1024\n    return local[i] + f32(p.hist[i]); // tests/components.rs:1025\n}"
+    );
+    let result = test_module::weighted_sum(test_module::Particle { hist: [1, 2, 3, 4] });
+    assert_eq!(result, 23.0);
+    let picked = test_module::pick(test_module::Particle { hist: [1, 2, 3, 4] }, 2);
+    assert_eq!(picked, 4.5);
+}
+
+#[test]
+fn test_struct_literals_follow_the_definition_order() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        #[derive(Default)]
+        pub struct Particle {
+            pub position: Vec2F32,
+            pub mass: f32,
+            pub id: u32,
+        }
+        pub struct Settings {
+            pub gravity: f32,
+            pub drag: f32,
+        }
+        impl Default for Settings {
+            fn default() -> Self {
+                Settings {
+                    drag: 0.5,
+                    gravity: 9.8,
+                }
+            }
+        }
+        pub fn spawn(id: u32) -> Particle {
+            let settings = Settings {
+                drag: 0.1,
+                ..Default::default()
+            };
+            let base = Particle {
+                id,
+                mass: settings.gravity,
+                ..Default::default()
+            };
+            return Particle {
+                mass: settings.drag,
+                ..base
+            };
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn spawn(id: u32) -> Particle { // tests/components.rs:1076\n    let settings = Settings(9.8, 0.1); // tests/components.rs:1077\n    let base = Particle(vec2<f32>(), settings.gravity, id); // tests/components.rs:1081\n    return Particle(base.position, settings.drag, base.id); // tests/components.rs:1086\n}"
+    );
+    let particle = test_module::spawn(3);
+    assert_eq!(particle.id, 3);
+    assert_eq!(particle.mass, 0.1);
+    assert_eq!(particle.position, wgsl_helpers::Vec2F32::new(0.0, 0.0));
+}
+
+#[test]
+fn test_tuples_become_hidden_structs() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn closest(a: Vec2F32) -> (f32, u32) {
+            if a.x < a.y { (a.x, 0) } else { (a.y, 1) }
+        }
+        pub fn sum(a: Vec2F32) -> f32 {
+            let (d, i) = closest(a);
+            let pair = closest(a);
+            return d + pair.0 + i as f32;
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(t2.helper_types.len(), 1);
+    assert_eq!(t2.helper_types[0].name.name(), "Tuple_f32_u32");
+    assert_eq!(
+        t2.helper_types[0].code.wgsl_code,
+        "struct Tuple_f32_u32 {\n    _0: f32,\n    _1: u32,\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn closest(a: vec2<f32>) -> Tuple_f32_u32 { // tests/components.rs:1109\n    if a.x < a.y { // tests/components.rs:1110\n        return Tuple_f32_u32(a.x, 0); // tests/components.rs:1110\n    } else {\n        return Tuple_f32_u32(a.y, 1); // tests/components.rs:1110\n    }\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn sum(a: vec2<f32>) -> f32 { // tests/components.rs:1112\n    let tuple_value_0 = closest(a); // tests/components.rs:1113\n    let d = tuple_value_0._0; // tests/components.rs:1113\n    let i = tuple_value_0._1; // tests/components.rs:1113\n    let pair = closest(a); // tests/components.rs:1114\n    return d + pair._0 + f32(i); // tests/components.rs:1115\n}"
+    );
+    assert_eq!(
+        test_module::closest(wgsl_helpers::Vec2F32::new(2.0, 1.0)),
+        (1.0, 1)
+    );
+    assert_eq!(test_module::sum(wgsl_helpers::Vec2F32::new(2.0, 1.0)), 3.0);
+}
+
+#[test]
+fn test_mutable_reference_parameters_become_pointers() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn accumulate(acc: &mut Vec3F32, v: Vec3F32) {
+            acc.x += v.length();
+            *acc = *acc + v;
+        }
+        pub fn total(a: Vec3F32, b: Vec3F32) -> Vec3F32 {
+            let mut sum = Vec3F32::new(0.0, 0.0, 0.0);
+            accumulate(&mut sum, a);
+            accumulate(&mut sum, b);
+            return sum;
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn accumulate(acc: ptr<function, vec3<f32>>, v: vec3<f32>) { // tests/components.rs:1146\n    (*acc).x += length(v); // tests/components.rs:1147\n    *acc = *acc + v; // tests/components.rs:1148\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn total(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> { // tests/components.rs:1150\n    var sum = vec3<f32>(0.0, 0.0, 0.0); // tests/components.rs:1151\n    accumulate(&sum, a); // tests/components.rs:1152\n    accumulate(&sum, b); // tests/components.rs:1153\n    return sum; // tests/components.rs:1154\n}"
+    );
+    let sum = test_module::total(
+        wgsl_helpers::Vec3F32::new(3.0, 4.0, 0.0),
+        wgsl_helpers::Vec3F32::new(0.0, 0.0, 2.0),
+    );
+    assert_eq!(sum, wgsl_helpers::Vec3F32::new(10.0, 4.0, 2.0));
+}
+
+#[test]
+fn test_enums_become_u32_constants() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        use bevy_gpu_compute_macro::wgsl_input_array;
+        #[derive(Clone, Copy)]
+        pub enum Shape {
+            Circle,
+            Square = 5,
+            Triangle,
+        }
+        #[wgsl_input_array]
+        pub struct Body {
+            pub shape: Shape,
+            pub size: f32,
+        }
+        pub fn area(body: Body) -> f32 {
+            if body.shape == Shape::Circle {
+                return 3.14 * body.size * body.size;
+            }
+            match body.shape {
+                Shape::Square => body.size * body.size,
+                _ => 0.5 * body.size * body.size,
+            }
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    let consts: Vec<&str> = t2
+        .static_consts
+        .iter()
+        .map(|c| c.code.wgsl_code.as_str())
+        .collect();
+    assert_eq!(
+        consts,
+        [
+            "const Shape_Circle: u32 = 0;",
+            "const Shape_Square: u32 = 5;",
+            "const Shape_Triangle: u32 = 6;"
+        ]
+    );
+    assert_eq!(
+        t2.input_arrays[0].item_type.code.wgsl_code,
+        "struct Body { // tests/components.rs:1187\n    shape: u32,\n    size: f32,\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn area(body: Body) -> f32 { // tests/components.rs:1191\n    if body.shape == Shape_Circle { // tests/components.rs:1192\n        return 3.14 * body.size * body.size; // tests/components.rs:1193\n    }\n    var _match_value_0: f32; // tests/components.rs:1191\n    switch body.shape { // tests/components.rs:1195\n        case Shape_Square: {\n            _match_value_0 = body.size * body.size; // tests/components.rs:1196\n        }\n        default: {\n            _match_value_0 = 0.5 * body.size * body.size; // tests/components.rs:1197\n        }\n    }\n    return _match_value_0;\n}"
+    );
+    let body = test_module::Body {
+        shape: test_module::Shape::Square,
+        size: 2.0,
+    };
+    assert_eq!(test_module::area(body), 4.0);
+    assert_eq!(bytemuck::bytes_of(&body.shape), &5u32.to_ne_bytes());
+}
+
+#[test]
+fn test_impl_blocks_become_functions() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub struct Position {
+            pub x: f32,
+            pub y: f32,
+        }
+        impl Position {
+            pub fn new(x: f32, y: f32) -> Self {
+                Self { x, y }
+            }
+            pub fn dist(&self, o: Position) -> f32 {
+                let d = Vec2F32::new(self.x - o.x, self.y - o.y);
+                d.length()
+            }
+            pub fn translate(&mut self, by: f32) {
+                self.x += by;
+                self.y += by;
+            }
+        }
+        pub fn moved_dist(a: Position) -> f32 {
+            let mut b = Position::new(a.x, a.y);
+            b.translate(3.0);
+            a.dist(b)
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    let functions: Vec<&str> = t2
+        .helper_functions
+        .iter()
+        .map(|f| f.code.wgsl_code.as_str())
+        .collect();
+    assert_eq!(
+        functions,
+        [
+            "fn moved_dist(a: Position) -> f32 { // tests/components.rs:1254\n    var b = Position_new(a.x, a.y); // tests/components.rs:1255\n    Position_translate(&b, 3.0); // tests/components.rs:1241\n    return Position_dist(a, b); // tests/components.rs:1241\n}",
+            "fn Position_new(x: f32, y: f32) -> Position { // tests/components.rs:1242\n    return Position(x, y); // tests/components.rs:1241\n}",
+            "fn Position_dist(self_: Position, o: Position) -> f32 { // tests/components.rs:1245\n    let d = vec2<f32>(self_.x - o.x, self_.y - o.y); // tests/components.rs:1246\n    return length(d); // tests/components.rs:1247\n}",
+            "fn Position_translate(self_: ptr<function, Position>, by: f32) { // tests/components.rs:1249\n    (*self_).x += by; // tests/components.rs:1250\n    (*self_).y += by; // tests/components.rs:1251\n}"
+        ]
+    );
+    let a = test_module::Position::new(1.0, 1.0);
+    assert_eq!(test_module::moved_dist(a), 18.0f32.sqrt());
+}
+
+#[test]
+fn test_generic_functions_are_monomorphised() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn double<T: Copy + std::ops::Add<Output = T>>(a: T) -> T {
+            a + a
+        }
+        pub fn doubled_sum(v: Vec2F32, s: f32, n: u32) -> f32 {
+            let d = double(v);
+            d.x + d.y + double(s) + double::<u32>(n) as f32
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    let functions: Vec<&str> = t2
+        .helper_functions
+        .iter()
+        .map(|f| f.code.wgsl_code.as_str())
+        .collect();
+    assert_eq!(
+        functions,
+        [
+            "fn doubled_sum(v: vec2<f32>, s: f32, n: u32) -> f32 { // tests/components.rs:1288\n    let d = double_Vec2F32(v); // tests/components.rs:1289\n    return d.x + d.y + double_f32(s) + f32(double_u32(n)); // tests/components.rs:1290\n}",
+            "fn double_Vec2F32(a: vec2<f32>) -> vec2<f32> { // tests/components.rs:1285\n    return a + a; // tests/components.rs:1286\n}",
+            "fn double_f32(a: f32) -> f32 { // tests/components.rs:1285\n    return a + a; // tests/components.rs:1286\n}",
+            "fn double_u32(a: u32) -> u32 { // tests/components.rs:1285\n    return a + a; // tests/components.rs:1286\n}"
+        ]
+    );
+    assert_eq!(
+        test_module::doubled_sum(
+            bevy_gpu_compute_core::wgsl_helpers::Vec2F32::new(1.0, 2.0),
+            3.0,
+            4
+        ),
+        20.0
+    );
+}
+
+#[test]
+fn test_closures_are_lifted_to_functions() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn weighted_sum(v: Vec3F32, weight: f32) -> f32 {
+            let sq = |x: f32| x * x;
+            let weighted = |x: f32| -> f32 { sq(x) * weight };
+            let mut total = 0.0;
+            total += weighted(v.x);
+            total += weighted(v.y) + sq(v.z);
+            total
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    let functions: Vec<&str> = t2
+        .helper_functions
+        .iter()
+        .map(|f| f.code.wgsl_code.as_str())
+        .collect();
+    assert_eq!(
+        functions,
+        [
+            "fn weighted_sum(v: vec3<f32>, weight: f32) -> f32 { // tests/components.rs:1324\n    var total = 0.0; // tests/components.rs:1327\n    total += weighted_sum_weighted(v.x, weight); // tests/components.rs:1328\n    total += weighted_sum_weighted(v.y, weight) + weighted_sum_sq(v.z); // tests/components.rs:1329\n    return total; // tests/components.rs:1330\n}",
+            "fn weighted_sum_sq(x: f32) -> f32 {\n    return x * x; // tests/components.rs:1325\n}",
+            "fn weighted_sum_weighted(x: f32, weight: f32) -> f32 {\n    return weighted_sum_sq(x) * weight; // tests/components.rs:1324\n}"
+        ]
+    );
+    assert_eq!(
+        test_module::weighted_sum(
+            bevy_gpu_compute_core::wgsl_helpers::Vec3F32::new(1.0, 2.0, 3.0),
+            2.0
+        ),
+        19.0
+    );
+}
+
+#[test]
+fn test_bools_are_stored_as_u32_in_transferred_types() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        use bevy_gpu_compute_macro::*;
+        #[wgsl_config]
+        pub struct Settings {
+            pub invert: bool,
+        }
+        #[wgsl_input_array]
+        pub struct Cell {
+            pub alive: bool,
+            pub age: u32,
+        }
+        #[wgsl_output_vec]
+        pub struct Next {
+            pub alive: bool,
+        }
+        pub struct Rule {
+            pub keep: bool,
+        }
+        pub fn step(cell: Cell, settings: Settings) -> Next {
+            let rule = Rule { keep: cell.age < 3 };
+            let mut alive = cell.alive && rule.keep;
+            if settings.invert {
+                alive = !alive;
+            }
+            Next { alive }
+        }
+        fn main(iter_pos: WgslIterationPosition) {
+            let cell = WgslVecInput::vec_val::<Cell>(iter_pos.x);
+            let settings = WgslConfigInput::get::<Settings>();
+            WgslOutput::push::<Next>(step(cell, settings));
+        }
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.uniforms[0].code.wgsl_code,
+        "struct Settings { // tests/components.rs:1364\n    invert: u32,\n}"
+    );
+    assert_eq!(
+        t2.input_arrays[0].item_type.code.wgsl_code,
+        "struct Cell { // tests/components.rs:1368\n    alive: u32,\n    age: u32,\n}"
+    );
+    assert_eq!(
+        t2.output_arrays[0].item_type.code.wgsl_code,
+        "struct Next { // tests/components.rs:1373\n    alive: u32,\n}"
+    );
+    assert_eq!(
+        t2.helper_types[0].code.wgsl_code,
+        "struct Rule { // tests/components.rs:1376\n    keep: bool,\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn step(cell: Cell, settings: Settings) -> Next { // tests/components.rs:1379\n    let rule = Rule(cell.age < 3); // tests/components.rs:1380\n    var alive = cell.alive != 0u && rule.keep; // tests/components.rs:1381\n    if settings.invert != 0u { // tests/components.rs:1382\n        alive = !alive; // tests/components.rs:1383\n    }\n    return Next(u32(alive)); // tests/components.rs:1385\n}"
+    );
+    let cell = test_module::Cell {
+        alive: true.into(),
+        age: 1,
+    };
+    let settings = test_module::Settings {
+        invert: false.into(),
+    };
+    assert_eq!(test_module::step(cell, settings).alive, true);
+    let cell = test_module::Cell {
+        alive: true.into(),
+        age: 5,
+    };
+    assert!(bool::from(test_module::step(cell, settings).alive) == false);
+    assert_eq!(bytemuck::bytes_of(&cell), &[1, 0, 0, 0, 5, 0, 0, 0]);
+}
+
+#[test]
+fn test_comments_and_source_locations() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::{wgsl_builtins::*, wgsl_helpers::*};
+        use bevy_gpu_compute_macro::wgsl_input_array;
+        /// the strength of each light
+        #[wgsl_input_array]
+        pub struct Light {
+            /// in lumen
+            pub intensity: f32,
+        }
+        /// halves the intensity
+        /// for every unit of distance
+        fn falloff(intensity: f32, distance: f32) -> f32 {
+            // distances below one would brighten
+            let d = max(distance, 1.0);
+
+            // inverse linear, not physically correct
+            intensity / d
+        }
+        fn main(iter_pos: WgslIterationPosition) {
+            let light = WgslVecInput::vec_val::<Light>(iter_pos.x);
+            // the value that is never written
+            let _dim = falloff(light.intensity, 2.0);
+        }
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.input_arrays[0].item_type.code.wgsl_code,
+        "// the strength of each light\nstruct Light { // tests/components.rs:1438\n    // in lumen\n    intensity: f32,\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "// halves the intensity\n// for every unit of distance\nfn falloff(intensity: f32, distance: f32) -> f32 { // tests/components.rs:1444\n    // distances below one would brighten\n    let d = max(distance, 1.0); // tests/components.rs:1446\n    // inverse linear, not physically correct\n    return intensity / d; // tests/components.rs:1449\n}"
+    );
+    assert_eq!(
+        t2.entry_points[0].function.code.wgsl_code,
+        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { // tests/components.rs:1451\n    let light = light_input_array[iter_pos.x]; // tests/components.rs:1452\n    // the value that is never written\n    let _dim = falloff(light.intensity, 2.0); // tests/components.rs:1454\n}"
+    );
+    let source_map = WgslShaderModule::new(t2).source_map(IterSpaceDimmension::OneD);
+    let rust_line = |wgsl_line| source_map.rust_location(wgsl_line).map(|l| l.line);
+    // the override and the bindings are not from the rust source
+    assert_eq!(rust_line(1), None);
+    assert_eq!(rust_line(7), None);
+    assert_eq!(rust_line(13), Some(1446));
+    // the comment above the return belongs to the statement before it
+    assert_eq!(rust_line(14), Some(1446));
+    assert_eq!(rust_line(16), Some(1449));
+    assert_eq!(
+        source_map.rust_location(21).unwrap().to_string(),
+        "tests/components.rs:1454"
+    );
+}
+
+#[test]
+fn test_library_imports() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use crate::shared_geometry::*;
+        use crate::shared_math::*;
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        use bevy_gpu_compute_macro::wgsl_output_vec;
+        #[wgsl_output_vec]
+        pub struct Area {
+            pub value: f32,
+        }
+        pub fn scaled(x: f32) -> f32 {
+            x * 3.0
+        }
+        fn main(iter_pos: WgslIterationPosition) {
+            let c = Circle {
+                center: Vec2F32::new(0.0, 0.0),
+                radius: scaled(1.0),
+            };
+            WgslOutput::push::<Area>(Area {
+                value: area(c) + c.diameter(),
+            });
+        }
+    }
+    let library = shared_geometry::parsed();
+    assert!(library.entry_points.is_empty());
+    assert_eq!(
+        library
+            .helper_functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["area", "scaled", "Circle_diameter"]
+    );
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions
+            .iter()
+            .map(|f| f.code.wgsl_code.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "fn scaled(x: f32) -> f32 { // tests/components.rs:1497\n    return x * 3.0; // tests/components.rs:1498\n}",
+            "fn area(c: Circle) -> f32 { // tests/components.rs:1582\n    return scaled(c.radius * c.radius * 3.0); // tests/components.rs:1583\n}",
+            "fn Circle_diameter(self_: Circle) -> f32 { // tests/components.rs:1570\n    return self_.radius * 2.0; // tests/components.rs:1571\n}"
+        ]
+    );
+    assert_eq!(
+        t2.helper_types
+            .iter()
+            .map(|t| t.code.wgsl_code.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "struct Circle { // tests/components.rs:1565\n    center: vec2<f32>,\n    radius: f32,\n}"
+        ]
+    );
+    assert_eq!(
+        t2.static_consts
+            .iter()
+            .map(|c| c.code.wgsl_code.as_str())
+            .collect::<Vec<_>>(),
+        vec!["const SCALE: f32 = 2.0; // tests/components.rs:1564"]
+    );
+    assert_eq!(
+        t2.entry_points[0].function.code.wgsl_code,
+        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { // tests/components.rs:1500\n    let c = Circle(vec2<f32>(0.0, 0.0), scaled(1.0)); // tests/components.rs:1501\n    { // tests/components.rs:1505\n        let area_output_array_index = atomicAdd(&area_counter, 1u);\n        if area_output_array_index < AREA_OUTPUT_ARRAY_LENGTH { // tests/components.rs:1505\n            area_output_array[area_output_array_index] = Area(area(c) + Circle_diameter(c)); // tests/components.rs:1505\n        }\n    }\n}"
+    );
+    // the library types are the same rust types in every module
+    let c = shared_math::Circle {
+        center: wgsl_helpers::Vec2F32::new(0.0, 0.0),
+        radius: 1.0,
+    };
+    assert_eq!(shared_geometry::area(c), 6.0);
+    assert_eq!(test_module::scaled(c.diameter()), 6.0);
+}
+
+#[bevy_gpu_compute_macro::wgsl_library]
+pub mod shared_math {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    pub const SCALE: f32 = 2.0;
+    pub struct Circle {
+        pub center: Vec2F32,
+        pub radius: f32,
+    }
+    impl Circle {
+        pub fn diameter(&self) -> f32 {
+            self.radius * 2.0
+        }
+    }
+    pub fn scaled(x: f32) -> f32 {
+        x * SCALE
+    }
+}
+
+#[bevy_gpu_compute_macro::wgsl_library]
+pub mod shared_geometry {
+    use crate::shared_math::*;
+    pub fn area(c: Circle) -> f32 {
+        scaled(c.radius * c.radius * 3.0)
+    }
+}
+
+#[test]
+fn test_imported_wgsl_types() {
+    use bevy_gpu_compute_core::wgsl::wgsl_type_def::{WgslFieldLayout, WgslTypeDef};
+    #[wgsl_shader_module]
+    pub mod mover {
+        #[wgsl_input_array]
+        use crate::Particle;
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        use bevy_gpu_compute_macro::*;
+        #[wgsl_output_vec]
+        pub struct Moved {
+            pub particle: Particle,
+        }
+        fn main(iter_pos: WgslIterationPosition) {
+            let p = WgslVecInput::vec_val::<Particle>(iter_pos.x);
+            WgslOutput::push::<Moved>(Moved {
+                particle: Particle {
+                    position: p.position + p.velocity,
+                    velocity: p.velocity,
+                },
+            });
+        }
+    }
+    #[wgsl_shader_module]
+    pub mod stopper {
+        use crate::Particle;
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn stopped(p: Particle) -> Particle {
+            Particle {
+                position: p.position,
+                velocity: Vec2F32::new(0.0, 0.0),
+            }
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let particle_wgsl = "// a point moving without friction\nstruct Particle { // tests/components.rs:1664\n    position: vec2<f32>,\n    velocity: vec2<f32>,\n}";
+    assert_eq!(Particle::wgsl_code(), particle_wgsl);
+    assert_eq!(
+        Particle::fields(),
+        vec![
+            WgslFieldLayout {
+                name: "position".to_string(),
+                wgsl_type: "vec2<f32>".to_string(),
+                offset: 0,
+                size: 8,
+            },
+            WgslFieldLayout {
+                name: "velocity".to_string(),
+                wgsl_type: "vec2<f32>".to_string(),
+                offset: 8,
+                size: 8,
+            }
+        ]
+    );
+    assert_eq!(Particle::size(), 16);
+    let t2 = mover::parsed();
+    assert_eq!(t2.input_arrays.len(), 1);
+    assert_eq!(t2.input_arrays[0].item_type, Particle::wgsl_type());
+    assert_eq!(t2.output_arrays[0].item_type.name.name(), "Moved");
+    let t3 = stopper::parsed();
+    assert_eq!(t3.helper_types, vec![Particle::wgsl_type()]);
+    // both modules use the same rust type
+    let p = Particle {
+        position: wgsl_helpers::Vec2F32::new(1.0, 2.0),
+        velocity: wgsl_helpers::Vec2F32::new(3.0, 4.0),
+    };
+    let moved = mover::Moved {
+        particle: stopper::stopped(p),
+    };
+    assert_eq!(moved.particle.velocity.x, 0.0);
+}
+
+/// a point moving without friction
+#[derive(
+    bevy_gpu_compute_macro::WgslType, Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable,
+)]
+#[repr(C)]
+pub struct Particle {
+    pub position: wgsl_helpers::Vec2F32,
+    pub velocity: wgsl_helpers::Vec2F32,
+}
+
+#[test]
+fn test_functions_written_in_wgsl() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        use bevy_gpu_compute_macro::*;
+        #[wgsl_output_array]
+        pub struct Noise {
+            pub value: f32,
+        }
+        #[wgsl_extern(path = "wgsl/noise.wgsl")]
+        fn value_noise(p: Vec2F32) -> f32;
+        #[wgsl_extern(path = "wgsl/noise.wgsl")]
+        pub fn hash(p: Vec2F32) -> f32 {
+            p.x * 0.5
+        }
+        fn fade(t: f32) -> f32 {
+            wgsl_raw!(
+                "
+                let t3 = t * t * t;
+                return t3 * (t * (t * 6.0 - 15.0) + 10.0);
+                "
+            )
+        }
+        fn main(iter_pos: WgslIterationPosition) {
+            let p = Vec2F32::new(iter_pos.x as f32, iter_pos.y as f32);
+            WgslOutput::set::<Noise>(
+                iter_pos.x,
+                Noise {
+                    value: fade(value_noise(p) + hash(p)),
+                },
+            );
+        }
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["value_noise", "fade"]
+    );
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        include_str!("wgsl/noise.wgsl").trim_end()
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn fade(t: f32) -> f32 { // tests/components.rs:1685\n    let t3 = t * t * t;\n    return t3 * (t * (t * 6.0 - 15.0) + 10.0);\n}"
+    );
+    // the rust body of an extern function is used on the CPU
+    assert_eq!(test_module::hash(wgsl_helpers::Vec2F32::new(3.0, 0.0)), 1.5);
+}
+
+#[test]
+fn test_multiple_entry_points() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        use bevy_gpu_compute_macro::*;
+        #[wgsl_input_array]
+        pub type Value = f32;
+        #[wgsl_output_array]
+        pub struct Total {
+            pub value: f32,
+        }
+        #[wgsl_entry]
+        pub fn clear(iter_pos: WgslIterationPosition) {
+            WgslOutput::set::<Total>(iter_pos.x, Total { value: 0.0 });
+        }
+        #[wgsl_entry(workgroup_size = (16, 4))]
+        pub fn accumulate(iter_pos: WgslIterationPosition) {
+            let value = WgslVecInput::vec_val::<Value>(iter_pos.x);
+            WgslOutput::set::<Total>(iter_pos.x, Total { value: value * 2.0 });
+        }
+    }
+    let t2 = test_module::parsed();
+    assert!(t2.helper_functions.is_empty());
+    assert_eq!(
+        t2.entry_points
+            .iter()
+            .map(|e| (e.name(), e.workgroup_size))
+            .collect::<Vec<_>>(),
+        vec![("clear", None), ("accumulate", Some([16, 4, 1]))]
+    );
+    let wgsl = WgslShaderModule::new(t2).wgsl_code(IterSpaceDimmension::OneD);
+    assert!(wgsl.contains(
+        "@compute @workgroup_size(64, 1, 1)\nfn clear(@builtin(global_invocation_id) iter_pos: vec3<u32>) {"
+    ));
+    assert!(wgsl.contains(
+        "@compute @workgroup_size(16, 4, 1)\nfn accumulate(@builtin(global_invocation_id) iter_pos: vec3<u32>) {"
+    ));
+    // every entry point takes the buffers as parameters on the CPU
+    let mut totals = vec![test_module::Total { value: 5.0 }];
+    test_module::clear(
+        WgslIterationPosition { x: 0, y: 0, z: 0 },
+        vec![3.0],
+        &mut totals,
+    );
+    assert_eq!(totals[0].value, 0.0);
+    test_module::accumulate(
+        WgslIterationPosition { x: 0, y: 0, z: 0 },
+        vec![3.0],
+        &mut totals,
+    );
+    assert_eq!(totals[0].value, 6.0);
+}
+
+#[test]
+fn test_swizzle_setter_on_computed_receiver() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn shift(offset: Vec2F32) -> Vec3F32 {
+            let mut points: [Vec3F32; 3] = [Vec3F32::new(0.0, 0.0, 1.0); 3];
+            for i in 0..2 {
+                points[i + 1].set_xy(offset);
+            }
+            points[2]
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    // the receiver is only evaluated once
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn shift(offset: vec2<f32>) -> vec3<f32> { // tests/components.rs:1782\n    let _repeat_value_0 = vec3<f32>(0.0, 0.0, 1.0); // tests/components.rs:1783\n    var points: array<vec3<f32>, 3> = array<vec3<f32>, 3>(_repeat_value_0, _repeat_value_0, _repeat_value_0); // tests/components.rs:1783\n    for (var i: u32 = 0; i < 2; i += 1u) { // tests/components.rs:1784\n        { // tests/components.rs:1785\n            let swizzle_target = &points[i + 1]; // tests/components.rs:1785\n            let swizzle_value = offset; // tests/components.rs:1785\n            (*swizzle_target).x = swizzle_value.x;\n            (*swizzle_target).y = swizzle_value.y;\n        }\n    }\n    return points[2]; // tests/components.rs:1787\n}"
+    );
+    let result = test_module::shift(wgsl_helpers::Vec2F32::new(2.0, 3.0));
+    assert_eq!(result, wgsl_helpers::Vec3F32::new(2.0, 3.0, 1.0));
+}
+
+#[test]
+fn test_destructuring_a_tuple_that_uses_its_own_names() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn swap_difference(a: f32, b: f32) -> f32 {
+            {
+                let (a, b) = (b, a);
+                a - b
+            }
+        }
+        pub fn fibonacci_step(n: u32) -> u32 {
+            let x: u32 = n;
+            let y: u32 = n + 1;
+            {
+                let (x, y) = (y, x + y);
+                x * 10 + y
+            }
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    // every value is evaluated before the names that it uses are shadowed
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn swap_difference(a: f32, b: f32) -> f32 { // tests/components.rs:1806\n    { // tests/components.rs:1807\n        let tuple_value_0_0 = b; // tests/components.rs:1808\n        let tuple_value_0_1 = a; // tests/components.rs:1808\n        let a = tuple_value_0_0; // tests/components.rs:1808\n        let b = tuple_value_0_1; // tests/components.rs:1808\n        return a - b; // tests/components.rs:1809\n    }\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn fibonacci_step(n: u32) -> u32 { // tests/components.rs:1812\n    let x: u32 = n; // tests/components.rs:1813\n    let y: u32 = n + 1; // tests/components.rs:1814\n    { // tests/components.rs:1815\n        let tuple_value_0_0 = y; // tests/components.rs:1816\n        let tuple_value_0_1 = x + y; // tests/components.rs:1816\n        let x = tuple_value_0_0; // tests/components.rs:1816\n        let y = tuple_value_0_1; // tests/components.rs:1816\n        return x * 10 + y; // tests/components.rs:1817\n    }\n}"
+    );
+    assert_eq!(test_module::swap_difference(1.0, 3.0), 2.0);
+    assert_eq!(test_module::fibonacci_step(2), 35);
+}
+
+#[test]
+fn test_enum_derives_are_kept_on_the_cpu() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        pub enum Shape {
+            Circle,
+            #[default]
+            Square = 5,
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    assert!(test_module::Shape::default() == test_module::Shape::Square);
+    assert!(test_module::Shape::Circle < test_module::Shape::Square);
+    let shapes: std::collections::HashSet<test_module::Shape> =
+        [test_module::Shape::Circle, test_module::Shape::Circle].into();
+    assert_eq!(shapes.len(), 1);
+}
+
+#[test]
+fn test_closure_keeps_a_captured_value_that_is_declared_again() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn shadowed() -> f32 {
+            let s = 2.0;
+            let f = |x: f32| x * s;
+            // WGSL does not allow declaring a variable twice in the same block
+            {
+                let s = 5.0;
+                f(1.0) * s
+            }
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn shadowed() -> f32 { // tests/components.rs:1861\n    let s = 2.0; // tests/components.rs:1862\n    let f_captured_s = s; // tests/components.rs:1863\n    // WGSL does not allow declaring a variable twice in the same block\n    { // tests/components.rs:1865\n        let s = 5.0; // tests/components.rs:1866\n        return shadowed_f(1.0, f_captured_s) * s; // tests/components.rs:1861\n    }\n}"
+    );
+    assert_eq!(test_module::shadowed(), 10.0);
+}
+
+#[test]
+fn test_array_config_is_padded() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        use bevy_gpu_compute_macro::*;
+        #[wgsl_config]
+        type Weights = [f32; 3];
+        #[wgsl_output_array]
+        struct Weighted {
+            pub value: f32,
+        }
+        fn main(iter_pos: WgslIterationPosition) {
+            let weights = WgslConfigInput::get::<Weights>();
+            WgslOutput::set::<Weighted>(
+                iter_pos.x,
+                Weighted {
+                    value: weights[1] * 2.0,
+                },
+            );
+        }
+    }
+    let t2 = test_module::parsed();
+    // arrays in uniform buffers need a stride of 16 bytes
+    assert_eq!(
+        t2.uniforms[0].code.wgsl_code,
+        "alias Weights = array<f32, 3>; // tests/components.rs:1887\nstruct Weights_uniform_element {\n    @align(16) value: f32,\n}\nalias Weights_uniform = array<Weights_uniform_element, 3>;\nfn Weights_from_uniform(padded: Weights_uniform) -> Weights {\n    var value: Weights;\n    for (var i = 0u; i < 3; i += 1u) {\n        value[i] = padded[i].value;\n    }\n    return value;\n}"
+    );
+    assert_eq!(t2.uniforms[0].uniform_binding_type(), "Weights_uniform");
+    assert_eq!(
+        t2.entry_points[0].function.code.wgsl_code,
+        "fn main(@builtin(global_invocation_id) iter_pos: vec3<u32>) { // tests/components.rs:1892\n    let weights = Weights_from_uniform(weights); // tests/components.rs:1893\n    weighted_output_array[iter_pos.x] = Weighted(weights[1] * 2.0); // tests/components.rs:1895\n}"
+    );
+    let config = test_module::ConfigInputDataBuilder::new()
+        .set_weights([1.0, 2.0, 3.0])
+        .finish();
+    let bytes = config.get_bytes("Weights").unwrap();
+    assert_eq!(bytes.len(), 48);
+    let values: &[f32] = bytemuck::cast_slice(bytes);
+    assert_eq!(
+        values,
+        [1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0]
+    );
+}
+
+#[test]
+fn test_integer_valued_match() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn cost(id: u32) -> u32 {
+            let x: u32 = match id {
+                0 => 10,
+                _ => 20,
+            };
+            x
+        }
+        pub fn offset(id: u32) -> i32 {
+            let x: i32 = match id {
+                0 => -3,
+                _ => 4,
+            };
+            x
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn cost(id: u32) -> u32 { // tests/components.rs:1930\n    var _match_value_0: u32; // tests/components.rs:1931\n    switch id { // tests/components.rs:1931\n        case 0u: {\n            _match_value_0 = 10u; // tests/components.rs:1932\n        }\n        default: {\n            _match_value_0 = 20u; // tests/components.rs:1933\n        }\n    }\n    let x: u32 = _match_value_0; // tests/components.rs:1931\n    return x; // tests/components.rs:1935\n}"
+    );
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn offset(id: u32) -> i32 { // tests/components.rs:1937\n    var _match_value_0: i32; // tests/components.rs:1938\n    switch id { // tests/components.rs:1938\n        case 0u: {\n            _match_value_0 = -3; // tests/components.rs:1939\n        }\n        default: {\n            _match_value_0 = 4; // tests/components.rs:1940\n        }\n    }\n    let x: i32 = _match_value_0; // tests/components.rs:1938\n    return x; // tests/components.rs:1942\n}"
+    );
+    assert_eq!(test_module::cost(0), 10);
+    assert_eq!(test_module::offset(1), 4);
+}
+
+#[test]
+fn test_integer_valued_if() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        fn fallback(n: u32) -> u32 {
+            n * 2
+        }
+        pub fn pick(c: bool, n: u32) -> u32 {
+            let x: u32 = if c { 7 } else { fallback(n) };
+            x
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn pick(c: bool, n: u32) -> u32 { // tests/components.rs:1967\n    var _if_value_0: u32; // tests/components.rs:1968\n    if c { // tests/components.rs:1968\n        _if_value_0 = 7u; // tests/components.rs:1968\n    } else {\n        _if_value_0 = fallback(n); // tests/components.rs:1968\n    }\n    let x: u32 = _if_value_0; // tests/components.rs:1968\n    return x; // tests/components.rs:1969\n}"
+    );
+    assert_eq!(test_module::pick(true, 1), 7);
+    assert_eq!(test_module::pick(false, 4), 8);
+}
+
+#[test]
+fn test_integer_break_value() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn first_above(limit: u32) -> u32 {
+            let mut n: u32 = 0;
+            let r: u32 = loop {
+                if n > limit {
+                    break 5;
+                }
+                n += 1;
+            };
+            r + n
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[0].code.wgsl_code,
+        "fn first_above(limit: u32) -> u32 { // tests/components.rs:1987\n    var n: u32 = 0; // tests/components.rs:1988\n    var _loop_value_0: u32; // tests/components.rs:1989\n    loop { // tests/components.rs:1989\n        if n > limit { // tests/components.rs:1990\n            _loop_value_0 = 5u; // tests/components.rs:1991\n            break;\n        }\n        n += 1u; // tests/components.rs:1993\n    }\n    let r: u32 = _loop_value_0; // tests/components.rs:1989\n    return r + n; // tests/components.rs:1995\n}"
+    );
+    assert_eq!(test_module::first_above(2), 8);
+}
+
+#[test]
+fn test_loop_bounds_are_evaluated_once() {
+    #[wgsl_shader_module]
+    pub mod test_module {
+        use bevy_gpu_compute_core::wgsl_helpers::*;
+        pub fn limit(n: u32) -> u32 {
+            n * 2 + 1
+        }
+        pub fn sum_stepped(n: u32) -> u32 {
+            let mut total: u32 = 0;
+            for i in (4294967290..limit(n)).step_by(4) {
+                total += i - 4294967290;
+            }
+            total
+        }
+        fn main(iter_pos: WgslIterationPosition) {}
+    }
+    let t2 = test_module::parsed();
+    assert_eq!(
+        t2.helper_functions[1].code.wgsl_code,
+        "fn sum_stepped(n: u32) -> u32 { // tests/components.rs:2015\n    var total: u32 = 0; // tests/components.rs:2016\n    { // tests/components.rs:2017\n        let _end_i: u32 = limit(n);\n        for (var i: u32 = 4294967290; i < _end_i; i += min(4u, _end_i - i)) {\n            total += i - 4294967290; // tests/components.rs:2018\n        }\n    }\n    return total; // tests/components.rs:2020\n}"
+    );
+    assert_eq!(test_module::sum_stepped(2147483647), 4);
+}
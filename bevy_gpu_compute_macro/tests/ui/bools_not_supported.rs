@@ -6,6 +6,9 @@ pub mod test_module {
     #[wgsl_config]
     struct MyConfig {
         value: bool,
+        mask: Vec3Bool,
     }
     fn main(iter_pos: WgslIterationPosition) {}
 }
+
+fn main() {}
@@ -0,0 +1,16 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn count(a: f32) -> f32 {
+        let mut total = 0.0;
+        let mut add = |x: f32| total += x;
+        add(a);
+        add(a);
+        total
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use bevy_gpu_compute_macro::*;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use bevy_gpu_compute_macro::*;
+    #[wgsl_entry(workgroup_size = (8, 0))]
+    fn clear(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    #[repr(u32)]
+    #[derive(Clone, Copy, PartialEq, PartialOrd, bytemuck::NoUninit)]
+    enum Shape {
+        Circle,
+        Square,
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use bevy_gpu_compute_macro::wgsl_extern;
+    #[wgsl_extern(path = "../wgsl/noise.wgsl")]
+    fn value_noise(p: Vec2F32) -> f32;
+    #[wgsl_extern(path = "../wgsl/hash.wgsl")]
+    fn hash(p: Vec2F32) -> f32;
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
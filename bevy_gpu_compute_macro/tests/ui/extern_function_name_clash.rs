@@ -0,0 +1,15 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use bevy_gpu_compute_macro::wgsl_extern;
+    #[wgsl_extern(path = "../wgsl/noise.wgsl")]
+    fn value_noise(p: Vec2F32) -> f32;
+    fn hash(p: Vec2F32) -> f32 {
+        p.x * 0.5
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+#[wgsl_shader_module]
+pub mod test_module {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use bevy_gpu_compute_macro::wgsl_input_array;
+    #[wgsl_input_array]
+    type Position = [f32; 2];
+    const FIRST: Position = WgslVecInput::vec_val::<Position>(0);
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
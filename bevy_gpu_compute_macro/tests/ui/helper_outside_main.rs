@@ -0,0 +1,38 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+#[wgsl_shader_module]
+pub mod test_module {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use bevy_gpu_compute_macro::{
+        wgsl_config, wgsl_input_array, wgsl_output_array, wgsl_output_vec,
+    };
+    #[wgsl_config]
+    struct Settings {
+        scale: f32,
+    }
+    #[wgsl_input_array]
+    type Position = [f32; 2];
+    #[wgsl_output_vec]
+    type Distance = f32;
+    #[wgsl_output_array]
+    type Flag = u32;
+    fn scaled(x: f32) -> f32 {
+        return x * WgslConfigInput::get::<Settings>().scale;
+    }
+    fn flag(index: u32) {
+        WgslOutput::set::<Flag>(index, 1);
+    }
+    fn first_position() -> Position {
+        return WgslVecInput::vec_val::<Position>(0);
+    }
+    fn record(distance: f32) {
+        WgslOutput::push::<Distance>(distance);
+    }
+    fn main(iter_pos: WgslIterationPosition) {
+        let count = WgslVecInput::vec_len::<Position>();
+        let p = first_position();
+        record(scaled(p[0]) + count as f32);
+        flag(iter_pos.x);
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+#[wgsl_shader_module]
+pub mod test_module {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use bevy_gpu_compute_macro::{wgsl_input_array, wgsl_output_array};
+    #[wgsl_input_array]
+    type Position = [f32; 2];
+    #[wgsl_output_array]
+    type Distance = f32;
+    fn main(iter_pos: WgslIterationPosition) {
+        let p = WgslConfigInput::get::<Position>();
+        WgslOutput::push::<Distance>(p[0]);
+        WgslOutput::set::<Distance>(iter_pos.x);
+    }
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    struct Cell {
+        size: f32,
+    }
+    impl Cell {
+        fn round(&self) -> f32 {
+            self.size
+        }
+    }
+    fn area(a: f32, b: f32) -> f32 {
+        let y = a * b;
+        y.round()
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
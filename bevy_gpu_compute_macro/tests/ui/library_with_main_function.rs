@@ -0,0 +1,12 @@
+use bevy_gpu_compute_macro::wgsl_library;
+
+#[wgsl_library]
+mod my_library {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    pub fn double(x: f32) -> f32 {
+        x * 2.0
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
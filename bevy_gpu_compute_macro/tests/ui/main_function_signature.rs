@@ -0,0 +1,10 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+#[wgsl_shader_module]
+pub mod test_module {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn main(position: WgslIterationPosition) -> u32 {
+        return position.x;
+    }
+}
+
+fn main() {}
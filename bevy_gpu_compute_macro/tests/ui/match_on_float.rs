@@ -0,0 +1,15 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn brightness(level: f32) -> f32 {
+        match level {
+            1.0 => 0.5,
+            _ => 1.0,
+        }
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
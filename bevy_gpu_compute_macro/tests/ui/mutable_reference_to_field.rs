@@ -0,0 +1,18 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    struct Body {
+        velocity: Vec3F32,
+    }
+    fn accumulate(acc: &mut Vec3F32, v: Vec3F32) {
+        *acc = *acc + v;
+    }
+    fn push(mut body: Body, v: Vec3F32) {
+        accumulate(&mut body.velocity, v);
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+#[wgsl_shader_module]
+pub mod test_module {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn double(x: f32) -> f32 {
+        return x * 2.0;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+#[wgsl_shader_module]
+pub mod test_module {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn factorial(n: u32) -> u32 {
+        if n <= 1 {
+            return 1;
+        }
+        return n * factorial(n - 1);
+    }
+    fn main(iter_pos: WgslIterationPosition) {
+        let f = factorial(iter_pos.x);
+    }
+}
+
+fn main() {}
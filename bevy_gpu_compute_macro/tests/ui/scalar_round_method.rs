@@ -0,0 +1,12 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn snap(x: f32) -> f32 {
+        x.round()
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
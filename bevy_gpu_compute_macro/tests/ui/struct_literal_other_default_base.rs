@@ -0,0 +1,25 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    #[derive(Default)]
+    struct Foo {
+        a: u32,
+        b: f32,
+    }
+    #[derive(Default)]
+    struct Bar {
+        a: u32,
+        b: f32,
+    }
+    fn make() -> Foo {
+        Foo {
+            a: 1,
+            ..Bar::default()
+        }
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    #[allow(non_camel_case_types)]
+    struct u32_2 {
+        a: u32,
+    }
+    fn first(p: (f32, [u32; 2])) -> f32 {
+        p.0
+    }
+    fn second(p: (f32, u32_2)) -> f32 {
+        p.0
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
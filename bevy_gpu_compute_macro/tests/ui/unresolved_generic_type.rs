@@ -0,0 +1,17 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn zero<T: Default>() -> T {
+        T::default()
+    }
+    fn scale(x: f32) -> f32 {
+        x * 2.0
+    }
+    fn main(iter_pos: WgslIterationPosition) {
+        let x = scale(zero());
+    }
+}
+
+fn main() {}
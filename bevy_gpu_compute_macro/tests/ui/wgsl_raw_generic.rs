@@ -0,0 +1,13 @@
+use bevy_gpu_compute_macro::*;
+
+#[wgsl_shader_module]
+mod my_mod {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    use bevy_gpu_compute_macro::*;
+    fn twice<T>(x: T) -> T {
+        wgsl_raw!("return x * 2;")
+    }
+    fn main(iter_pos: WgslIterationPosition) {}
+}
+
+fn main() {}
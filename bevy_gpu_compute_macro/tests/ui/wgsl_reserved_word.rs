@@ -0,0 +1,13 @@
+use bevy_gpu_compute_macro::wgsl_shader_module;
+#[wgsl_shader_module]
+pub mod test_module {
+    use bevy_gpu_compute_core::wgsl_helpers::*;
+    fn scale(value: f32, target: f32) -> f32 {
+        return value * target;
+    }
+    fn main(iter_pos: WgslIterationPosition) {
+        let s = scale(1.0, 2.0);
+    }
+}
+
+fn main() {}
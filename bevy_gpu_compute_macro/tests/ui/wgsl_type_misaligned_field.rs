@@ -0,0 +1,17 @@
+use bevy_gpu_compute_core::wgsl_helpers::*;
+use bevy_gpu_compute_macro::WgslType;
+
+#[derive(WgslType, Clone, Copy)]
+#[repr(C)]
+pub struct Particle {
+    pub radius: f32,
+    pub position: Vec2F32,
+}
+
+#[derive(WgslType, Clone, Copy)]
+#[repr(C)]
+pub struct Light {
+    pub position: Vec3F32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bevy_gpu_compute_core::wgsl_helpers::*;
+use bevy_gpu_compute_macro::WgslType;
+
+#[derive(WgslType, Clone, Copy)]
+pub struct Particle {
+    pub position: Vec2F32,
+    pub velocity: Vec2F32,
+}
+
+fn main() {}
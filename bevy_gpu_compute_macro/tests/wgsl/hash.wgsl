@@ -0,0 +1,3 @@
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(p.x * 12.9898 + p.y * 78.233) * 43758.5453);
+}